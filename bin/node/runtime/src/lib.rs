@@ -143,10 +143,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to 0. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 268,
+	spec_version: 269,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 2,
+	transaction_version: 3,
 	state_version: 1,
 };
 
@@ -1995,6 +1995,8 @@ type Migrations = (
 	pallet_nomination_pools::migration::v2::MigrateToV2<Runtime>,
 	pallet_alliance::migration::Migration<Runtime>,
 	pallet_contracts::Migration<Runtime>,
+	pallet_election_provider_multi_phase::migrations::v2::MigrateToV2<Runtime>,
+	pallet_bags_list::migrations::AddBagCounts<Runtime, VoterBagsListInstance>,
 );
 
 type EventRecord = frame_system::EventRecord<
@@ -2203,6 +2205,27 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl frame_election_provider_support_runtime_api::ElectionApi<Block> for Runtime {
+		fn dry_run_elect(
+			voters_bound: Option<u32>,
+			targets_bound: Option<u32>,
+			desired_targets: Option<u32>,
+		) -> Result<onchain::DryRunOutcome, onchain::DryRunError> {
+			// by default, the input is bounded as the snapshot of `ElectionProviderMultiPhase` is.
+			onchain::OnChainExecution::<OnChainSeqPhragmen>::dry_run(
+				Some(voters_bound.unwrap_or_else(MaxElectingVoters::get)),
+				Some(targets_bound.unwrap_or_else(|| MaxElectableTargets::get().into())),
+				desired_targets,
+			)
+			.map_err(Into::into)
+		}
+
+		fn election_forecast() -> Result<onchain::ElectionForecast<BlockNumber>, onchain::DryRunError> {
+			ElectionProviderMultiPhase::election_forecast()
+				.map_err(|e| onchain::DryRunError::DataProvider(e.as_bytes().to_vec()))
+		}
+	}
+
 	impl sp_consensus_babe::BabeApi<Block> for Runtime {
 		fn configuration() -> sp_consensus_babe::BabeConfiguration {
 			let epoch_config = Babe::epoch_config().unwrap_or(BABE_GENESIS_EPOCH_CONFIG);
@@ -20,13 +20,24 @@
 
 #![warn(missing_docs)]
 
+use codec::{Decode, Encode};
 use parking_lot::RwLock;
 use std::sync::Arc;
 
-use sc_rpc::SubscriptionTaskExecutor;
-use sp_runtime::traits::Block as BlockT;
+use sc_client_api::BlockBackend;
+use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_consensus_beefy::{
+	crypto::AuthorityId, BeefyApi, MmrRootHash, ValidatorSet, VersionedFinalityProof,
+	BEEFY_ENGINE_ID,
+};
+use sp_core::{crypto::ByteArray, Bytes, ExecutionContext};
+use sp_keystore::KeystorePtr;
+use sp_mmr_primitives::{EncodableOpaqueLeaf, MmrApi, Proof};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
 
-use futures::{task::SpawnError, FutureExt, StreamExt};
+use futures::{future, task::SpawnError, FutureExt, StreamExt};
 use jsonrpsee::{
 	core::{async_trait, Error as JsonRpseeError, RpcResult},
 	proc_macros::rpc,
@@ -34,9 +45,13 @@ use jsonrpsee::{
 	SubscriptionSink,
 };
 use log::warn;
+use serde::{Deserialize, Serialize};
 
-use sc_consensus_beefy::communication::notification::{
-	BeefyBestBlockStream, BeefyVersionedFinalityProofStream,
+use sc_consensus_beefy::{
+	communication::notification::{BeefyBestBlockStream, BeefyVersionedFinalityProofStream},
+	handoff::{self, SetHandoff},
+	justification::BeefyVersionedFinalityProof,
+	key_rotation,
 };
 
 mod notification;
@@ -50,6 +65,27 @@ pub enum Error {
 	/// The BEEFY RPC background task failed to spawn.
 	#[error("BEEFY RPC background task failed to spawn")]
 	RpcTaskFailure(#[from] SpawnError),
+	/// The BEEFY RPC handler was not set up with an [`AncestryProofProvider`].
+	#[error("BEEFY RPC ancestry proofs not available")]
+	AncestryProofsUnavailable,
+	/// The BEEFY RPC handler was not set up with a keystore.
+	#[error("BEEFY RPC key rotation not available")]
+	KeyRotationUnavailable,
+	/// Generating a new BEEFY key failed.
+	#[error("BEEFY key rotation failed: {0}")]
+	KeyRotationFailed(String),
+	/// The BEEFY RPC handler was not set up with a [`BridgeProofProvider`].
+	#[error("BEEFY RPC bridge proofs not available")]
+	BridgeProofsUnavailable,
+	/// Assembling a bridge proof failed.
+	#[error("BEEFY bridge proof not available: {0}")]
+	BridgeProofFailed(String),
+	/// The BEEFY RPC handler was not set up with a [`HandoffProvider`].
+	#[error("BEEFY RPC authority set handoffs not available")]
+	HandoffsUnavailable,
+	/// Exporting the authority set handoffs failed.
+	#[error("BEEFY authority set handoffs not available: {0}")]
+	HandoffsFailed(String),
 }
 
 /// The error codes returned by jsonrpc.
@@ -58,6 +94,20 @@ pub enum ErrorCode {
 	NotReady = 1,
 	/// Returned on BEEFY RPC background task failure.
 	TaskFailure = 2,
+	/// Returned when ancestry proofs are requested, but not available.
+	AncestryProofsUnavailable = 3,
+	/// Returned when key rotation is requested, but not available.
+	KeyRotationUnavailable = 4,
+	/// Returned when generating a new BEEFY key fails.
+	KeyRotationFailed = 5,
+	/// Returned when bridge proofs are requested, but not available.
+	BridgeProofsUnavailable = 6,
+	/// Returned when assembling a bridge proof fails.
+	BridgeProofFailed = 7,
+	/// Returned when authority set handoffs are requested, but not available.
+	HandoffsUnavailable = 8,
+	/// Returned when exporting authority set handoffs fails.
+	HandoffsFailed = 9,
 }
 
 impl From<Error> for ErrorCode {
@@ -65,6 +115,13 @@ impl From<Error> for ErrorCode {
 		match error {
 			Error::EndpointNotReady => ErrorCode::NotReady,
 			Error::RpcTaskFailure(_) => ErrorCode::TaskFailure,
+			Error::AncestryProofsUnavailable => ErrorCode::AncestryProofsUnavailable,
+			Error::KeyRotationUnavailable => ErrorCode::KeyRotationUnavailable,
+			Error::KeyRotationFailed(_) => ErrorCode::KeyRotationFailed,
+			Error::BridgeProofsUnavailable => ErrorCode::BridgeProofsUnavailable,
+			Error::BridgeProofFailed(_) => ErrorCode::BridgeProofFailed,
+			Error::HandoffsUnavailable => ErrorCode::HandoffsUnavailable,
+			Error::HandoffsFailed(_) => ErrorCode::HandoffsFailed,
 		}
 	}
 }
@@ -81,6 +138,205 @@ impl From<Error> for JsonRpseeError {
 	}
 }
 
+/// A BEEFY finality proof, bundled with an MMR proof of the leaves of its ancestors.
+///
+/// `proof` proves `leaves` against the MMR root that `finality_proof` commits to. The leaves are
+/// those of the block finalized by the previous notification of the subscription, or of the
+/// finalized block itself for the first notification.
+#[derive(Debug, Encode, Decode)]
+pub struct FinalityProofWithAncestry<Block: BlockT> {
+	/// The finality proof of the most recently BEEFY finalized block.
+	pub finality_proof: BeefyVersionedFinalityProof<Block>,
+	/// The proven MMR leaves.
+	pub leaves: Vec<EncodableOpaqueLeaf>,
+	/// The MMR proof of `leaves`.
+	pub proof: Proof<MmrRootHash>,
+}
+
+/// Everything a light-client bridge needs to import a BEEFY finalized block.
+///
+/// `proof` proves `leaves`, the MMR leaf of the finalized block, against the MMR root that
+/// `finality_proof` commits to, while `validator_set` is the set that signed it.
+#[derive(Debug, Encode, Decode)]
+pub struct BridgeProof<Block: BlockT> {
+	/// The BEEFY finality proof of the block.
+	pub finality_proof: BeefyVersionedFinalityProof<Block>,
+	/// The validator set which signed `finality_proof`.
+	pub validator_set: ValidatorSet<AuthorityId>,
+	/// The MMR leaf of the block.
+	pub leaves: Vec<EncodableOpaqueLeaf>,
+	/// The MMR proof of `leaves`.
+	pub proof: Proof<MmrRootHash>,
+}
+
+/// Assembles [`BridgeProof`]s of BEEFY finalized blocks.
+pub trait BridgeProofProvider<Block: BlockT>: Send + Sync {
+	/// Assemble the bridge proof of `block`, which must have a BEEFY justification.
+	fn bridge_proof(&self, block: Block::Hash) -> Result<BridgeProof<Block>, String>;
+}
+
+/// Assembles bridge proofs from the BEEFY justifications stored by the client, together with the
+/// [`BeefyApi`] and [`MmrApi`] of the runtime.
+pub struct ClientBridgeProofs<Client> {
+	client: Arc<Client>,
+}
+
+impl<Client> ClientBridgeProofs<Client> {
+	/// Create new `ClientBridgeProofs` with the given reference to the client.
+	pub fn new(client: Arc<Client>) -> Self {
+		Self { client }
+	}
+}
+
+impl<Block, Client> BridgeProofProvider<Block> for ClientBridgeProofs<Client>
+where
+	Block: BlockT,
+	Client: BlockBackend<Block> + ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync,
+	Client::Api: BeefyApi<Block> + MmrApi<Block, MmrRootHash, NumberFor<Block>>,
+{
+	fn bridge_proof(&self, block: Block::Hash) -> Result<BridgeProof<Block>, String> {
+		let encoded = self
+			.client
+			.justifications(block)
+			.map_err(|e| e.to_string())?
+			.and_then(|justifications| justifications.into_justification(BEEFY_ENGINE_ID))
+			.ok_or_else(|| format!("No BEEFY justification for block {:?}", block))?;
+		let finality_proof = BeefyVersionedFinalityProof::<Block>::decode(&mut &encoded[..])
+			.map_err(|e| e.to_string())?;
+		let (number, set_id) = match &finality_proof {
+			VersionedFinalityProof::V1(signed_commitment) => (
+				signed_commitment.commitment.block_number,
+				signed_commitment.commitment.validator_set_id,
+			),
+		};
+
+		let validator_set = self
+			.client
+			.runtime_api()
+			.validator_set(block)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("No BEEFY validator set at block {:?}", block))?;
+		if validator_set.id() != set_id {
+			return Err(format!(
+				"Justification of block {:?} is signed by validator set {}, but {} is active",
+				block,
+				set_id,
+				validator_set.id()
+			))
+		}
+
+		// The MMR root committed to at `number` includes the leaf of `number` itself.
+		let (leaves, proof) =
+			MmrAncestryProofs::new(self.client.clone()).ancestry_proof(number, number)?;
+
+		Ok(BridgeProof { finality_proof, validator_set, leaves, proof })
+	}
+}
+
+/// Exports the [`SetHandoff`]s of BEEFY sessions, see [`handoff::export_handoffs`].
+pub trait HandoffProvider<Block: BlockT>: Send + Sync {
+	/// Export the handoffs of all sessions starting at or after `since`.
+	fn handoffs(&self, since: Block::Hash) -> Result<Vec<SetHandoff<Block>>, String>;
+}
+
+/// Exports handoffs from the BEEFY justifications stored by the client.
+pub struct ClientHandoffs<Client> {
+	client: Arc<Client>,
+}
+
+impl<Client> ClientHandoffs<Client> {
+	/// Create new `ClientHandoffs` with the given reference to the client.
+	pub fn new(client: Arc<Client>) -> Self {
+		Self { client }
+	}
+}
+
+impl<Block, Client> HandoffProvider<Block> for ClientHandoffs<Client>
+where
+	Block: BlockT,
+	Client: BlockBackend<Block> + HeaderBackend<Block> + Send + Sync,
+{
+	fn handoffs(&self, since: Block::Hash) -> Result<Vec<SetHandoff<Block>>, String> {
+		let since = self
+			.client
+			.number(since)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("Unknown block {:?}", since))?;
+		handoff::export_handoffs(&*self.client, since).map_err(|e| e.to_string())
+	}
+}
+
+/// A newly generated BEEFY key, see `beefy_rotateKey`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotatedKey {
+	/// The raw public key, to be placed at the BEEFY position of the session keys.
+	pub public_key: Bytes,
+	/// The SCALE-encoded signature proving possession of the key, to be passed as the `proof` of
+	/// the session `set_keys` call.
+	pub proof_of_possession: Bytes,
+}
+
+impl From<key_rotation::KeyRotation> for RotatedKey {
+	fn from(rotation: key_rotation::KeyRotation) -> Self {
+		Self {
+			public_key: rotation.public.to_raw_vec().into(),
+			proof_of_possession: rotation.proof_of_possession.encode().into(),
+		}
+	}
+}
+
+/// Generates MMR proofs of ancestors of BEEFY finalized blocks.
+pub trait AncestryProofProvider<Block: BlockT>: Send + Sync {
+	/// Prove the MMR leaf of `ancestor` against the MMR root at block `best`.
+	fn ancestry_proof(
+		&self,
+		ancestor: NumberFor<Block>,
+		best: NumberFor<Block>,
+	) -> Result<(Vec<EncodableOpaqueLeaf>, Proof<MmrRootHash>), String>;
+}
+
+/// Generates ancestry proofs through the [`MmrApi`] of the runtime.
+pub struct MmrAncestryProofs<Client> {
+	client: Arc<Client>,
+}
+
+impl<Client> MmrAncestryProofs<Client> {
+	/// Create new `MmrAncestryProofs` with the given reference to the client.
+	pub fn new(client: Arc<Client>) -> Self {
+		Self { client }
+	}
+}
+
+impl<Block, Client> AncestryProofProvider<Block> for MmrAncestryProofs<Client>
+where
+	Block: BlockT,
+	Client: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync,
+	Client::Api: MmrApi<Block, MmrRootHash, NumberFor<Block>>,
+{
+	fn ancestry_proof(
+		&self,
+		ancestor: NumberFor<Block>,
+		best: NumberFor<Block>,
+	) -> Result<(Vec<EncodableOpaqueLeaf>, Proof<MmrRootHash>), String> {
+		let at = self
+			.client
+			.hash(best)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("Unknown block #{:?}", best))?;
+		self.client
+			.runtime_api()
+			.generate_proof_with_context(
+				at,
+				ExecutionContext::OffchainCall(None),
+				vec![ancestor],
+				Some(best),
+			)
+			.map_err(|e| e.to_string())?
+			.map_err(|e| format!("{:?}", e))
+	}
+}
+
 // Provides RPC methods for interacting with BEEFY.
 #[rpc(client, server)]
 pub trait BeefyApi<Notification, Hash> {
@@ -92,6 +348,17 @@ pub trait BeefyApi<Notification, Hash> {
 	)]
 	fn subscribe_justifications(&self);
 
+	/// Returns the block most recently finalized by BEEFY, alongside its justification and an MMR
+	/// proof of the previously finalized block against the MMR root the justification commits to.
+	///
+	/// Items are SCALE-encoded [`FinalityProofWithAncestry`]s.
+	#[subscription(
+		name = "beefy_subscribeJustificationsWithAncestry" => "beefy_justificationsWithAncestry",
+		unsubscribe = "beefy_unsubscribeJustificationsWithAncestry",
+		item = Notification,
+	)]
+	fn subscribe_justifications_with_ancestry(&self);
+
 	/// Returns hash of the latest BEEFY finalized block as seen by this client.
 	///
 	/// The latest BEEFY block might not be available if the BEEFY gadget is not running
@@ -99,12 +366,36 @@ pub trait BeefyApi<Notification, Hash> {
 	/// In such case an error would be returned.
 	#[method(name = "beefy_getFinalizedHead")]
 	async fn latest_finalized(&self) -> RpcResult<Hash>;
+
+	/// Generates a new BEEFY key in the keystore of the node and proves its possession on behalf
+	/// of `owner`, the SCALE-encoded stash or controller account of the validator.
+	///
+	/// The new key only becomes active once registered through the session `set_keys` call.
+	#[method(name = "beefy_rotateKey")]
+	fn rotate_key(&self, owner: Bytes) -> RpcResult<RotatedKey>;
+
+	/// Returns the SCALE-encoded [`BridgeProof`] of `block`, bundling its BEEFY justification,
+	/// the validator set that signed it and the MMR proof of its leaf.
+	#[method(name = "beefy_getBridgeProof")]
+	fn bridge_proof(&self, block: Hash) -> RpcResult<Bytes>;
+
+	/// Returns the SCALE-encoded [`SetHandoff`]s of the BEEFY sessions starting at or after block
+	/// `since`, each with the justification of its mandatory block.
+	///
+	/// At most [`handoff::MAX_EXPORT_RANGE`] blocks are scanned per call. The result can be
+	/// verified and imported by another node through [`handoff::import_handoffs`].
+	#[method(name = "beefy_exportHandoffs")]
+	fn export_handoffs(&self, since: Hash) -> RpcResult<Bytes>;
 }
 
 /// Implements the BeefyApi RPC trait for interacting with BEEFY.
 pub struct Beefy<Block: BlockT> {
 	finality_proof_stream: BeefyVersionedFinalityProofStream<Block>,
 	beefy_best_block: Arc<RwLock<Option<Block::Hash>>>,
+	ancestry_proofs: Option<Arc<dyn AncestryProofProvider<Block>>>,
+	key_rotation: Option<(KeystorePtr, DenyUnsafe)>,
+	bridge_proofs: Option<Arc<dyn BridgeProofProvider<Block>>>,
+	handoffs: Option<(Arc<dyn HandoffProvider<Block>>, DenyUnsafe)>,
 	executor: SubscriptionTaskExecutor,
 }
 
@@ -128,7 +419,48 @@ where
 		});
 
 		executor.spawn("substrate-rpc-subscription", Some("rpc"), future.map(drop).boxed());
-		Ok(Self { finality_proof_stream, beefy_best_block, executor })
+		Ok(Self {
+			finality_proof_stream,
+			beefy_best_block,
+			ancestry_proofs: None,
+			key_rotation: None,
+			bridge_proofs: None,
+			handoffs: None,
+			executor,
+		})
+	}
+
+	/// Enable `beefy_subscribeJustificationsWithAncestry`, using `provider` to generate the
+	/// ancestry proofs.
+	pub fn with_ancestry_proofs(mut self, provider: Arc<dyn AncestryProofProvider<Block>>) -> Self {
+		self.ancestry_proofs = Some(provider);
+		self
+	}
+
+	/// Enable `beefy_rotateKey`, generating new keys in `keystore`.
+	///
+	/// The method is unsafe and only served when `deny_unsafe` allows it.
+	pub fn with_key_rotation(mut self, keystore: KeystorePtr, deny_unsafe: DenyUnsafe) -> Self {
+		self.key_rotation = Some((keystore, deny_unsafe));
+		self
+	}
+
+	/// Enable `beefy_getBridgeProof`, using `provider` to assemble the bridge proofs.
+	pub fn with_bridge_proofs(mut self, provider: Arc<dyn BridgeProofProvider<Block>>) -> Self {
+		self.bridge_proofs = Some(provider);
+		self
+	}
+
+	/// Enable `beefy_exportHandoffs`, using `provider` to export the handoffs.
+	///
+	/// The method is unsafe and only served when `deny_unsafe` allows it.
+	pub fn with_handoffs(
+		mut self,
+		provider: Arc<dyn HandoffProvider<Block>>,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		self.handoffs = Some((provider, deny_unsafe));
+		self
 	}
 }
 
@@ -152,6 +484,53 @@ where
 		Ok(())
 	}
 
+	fn subscribe_justifications_with_ancestry(
+		&self,
+		mut sink: SubscriptionSink,
+	) -> SubscriptionResult {
+		let provider = match self.ancestry_proofs.clone() {
+			Some(provider) => provider,
+			None => {
+				let _ = sink.reject(JsonRpseeError::from(Error::AncestryProofsUnavailable));
+				return Ok(())
+			},
+		};
+
+		let mut previous = None;
+		let stream = self.finality_proof_stream.subscribe(100_000).filter_map(move |vfp| {
+			let best = match &vfp {
+				VersionedFinalityProof::V1(signed_commitment) =>
+					signed_commitment.commitment.block_number,
+			};
+			let ancestor = previous.unwrap_or(best);
+			let item = match provider.ancestry_proof(ancestor, best) {
+				Ok((leaves, proof)) => {
+					previous = Some(best);
+					let bundle = FinalityProofWithAncestry { finality_proof: vfp, leaves, proof };
+					Some(notification::EncodedFinalityProofWithAncestry::new::<Block>(bundle))
+				},
+				Err(e) => {
+					warn!(
+						target: "rpc",
+						"Failed to generate ancestry proof of #{:?} at #{:?}: {}",
+						ancestor,
+						best,
+						e
+					);
+					None
+				},
+			};
+			future::ready(item)
+		});
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+
 	async fn latest_finalized(&self) -> RpcResult<Block::Hash> {
 		self.beefy_best_block
 			.read()
@@ -160,21 +539,50 @@ where
 			.ok_or(Error::EndpointNotReady)
 			.map_err(Into::into)
 	}
+
+	fn rotate_key(&self, owner: Bytes) -> RpcResult<RotatedKey> {
+		let (keystore, deny_unsafe) =
+			self.key_rotation.as_ref().ok_or(Error::KeyRotationUnavailable)?;
+		deny_unsafe.check_if_safe()?;
+
+		key_rotation::rotate_key(keystore, &owner)
+			.map(Into::into)
+			.map_err(|e| Error::KeyRotationFailed(e.to_string()).into())
+	}
+
+	fn bridge_proof(&self, block: Block::Hash) -> RpcResult<Bytes> {
+		let provider = self.bridge_proofs.as_ref().ok_or(Error::BridgeProofsUnavailable)?;
+		provider
+			.bridge_proof(block)
+			.map(|bundle| bundle.encode().into())
+			.map_err(|e| Error::BridgeProofFailed(e).into())
+	}
+
+	fn export_handoffs(&self, since: Block::Hash) -> RpcResult<Bytes> {
+		let (provider, deny_unsafe) = self.handoffs.as_ref().ok_or(Error::HandoffsUnavailable)?;
+		deny_unsafe.check_if_safe()?;
+
+		provider
+			.handoffs(since)
+			.map(|handoffs| handoffs.encode().into())
+			.map_err(|e| Error::HandoffsFailed(e).into())
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 
-	use codec::{Decode, Encode};
 	use jsonrpsee::{types::EmptyServerParams as EmptyParams, RpcModule};
 	use sc_consensus_beefy::{
 		communication::notification::BeefyVersionedFinalityProofSender,
 		justification::BeefyVersionedFinalityProof,
 	};
-	use sp_consensus_beefy::{known_payloads, Payload, SignedCommitment};
-	use sp_runtime::traits::{BlakeTwo256, Hash};
-	use substrate_test_runtime_client::runtime::Block;
+	use sp_consensus_beefy::{known_payloads, Keyring, Payload, SignedCommitment};
+	use sp_core::H256;
+	use sp_keystore::testing::MemoryKeystore;
+	use sp_runtime::traits::{BlakeTwo256, Hash, Header as HeaderT};
+	use substrate_test_runtime_client::runtime::{Block, Header};
 
 	fn setup_io_handler() -> (RpcModule<Beefy<Block>>, BeefyVersionedFinalityProofSender<Block>) {
 		let (_, stream) = BeefyBestBlockStream::<Block>::channel();
@@ -266,18 +674,239 @@ mod tests {
 	}
 
 	fn create_finality_proof() -> BeefyVersionedFinalityProof<Block> {
+		create_finality_proof_at(5)
+	}
+
+	fn create_finality_proof_at(block_number: u64) -> BeefyVersionedFinalityProof<Block> {
 		let payload =
 			Payload::from_single_entry(known_payloads::MMR_ROOT_ID, "Hello World!".encode());
 		BeefyVersionedFinalityProof::<Block>::V1(SignedCommitment {
 			commitment: sp_consensus_beefy::Commitment {
 				payload,
-				block_number: 5,
+				block_number,
 				validator_set_id: 0,
 			},
 			signatures: vec![],
 		})
 	}
 
+	/// Proves a single leaf, containing the ancestor's and the best block's numbers.
+	struct TestAncestryProofs;
+
+	impl AncestryProofProvider<Block> for TestAncestryProofs {
+		fn ancestry_proof(
+			&self,
+			ancestor: u64,
+			best: u64,
+		) -> Result<(Vec<EncodableOpaqueLeaf>, Proof<MmrRootHash>), String> {
+			if best == 13 {
+				return Err("unlucky".into())
+			}
+			let leaf = EncodableOpaqueLeaf((ancestor, best).encode());
+			Ok((
+				vec![leaf],
+				Proof { leaf_indices: vec![ancestor], leaf_count: best, items: vec![] },
+			))
+		}
+	}
+
+	#[tokio::test]
+	async fn rotate_key_requires_keystore_and_unsafe_rpcs() {
+		let (rpc, _) = setup_io_handler();
+		let request = r#"{"jsonrpc":"2.0","method":"beefy_rotateKey","params":["0x0102"],"id":1}"#;
+		let expected = r#"{"jsonrpc":"2.0","error":{"code":4,"message":"BEEFY RPC key rotation not available"},"id":1}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+		assert_eq!(response.result, expected);
+
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (_, finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let rpc = Beefy::<Block>::new(
+			finality_proof_stream,
+			best_block_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.unwrap()
+		.with_key_rotation(MemoryKeystore::new().into(), DenyUnsafe::Yes)
+		.into_rpc();
+		let expected = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"RPC call is unsafe to be called externally"},"id":1}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+		assert_eq!(response.result, expected);
+	}
+
+	#[tokio::test]
+	async fn rotate_key_works() {
+		let keystore: KeystorePtr = MemoryKeystore::new().into();
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (_, finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let rpc = Beefy::<Block>::new(
+			finality_proof_stream,
+			best_block_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.unwrap()
+		.with_key_rotation(keystore.clone(), DenyUnsafe::No)
+		.into_rpc();
+
+		let owner = vec![1u8, 2];
+		let rotated: RotatedKey =
+			rpc.call("beefy_rotateKey", [Bytes(owner.clone())]).await.unwrap();
+
+		let public =
+			sp_consensus_beefy::crypto::AuthorityId::from_slice(&rotated.public_key).unwrap();
+		assert!(keystore.has_keys(&[(rotated.public_key.to_vec(), sp_consensus_beefy::KEY_TYPE)]));
+		let rotation = key_rotation::KeyRotation {
+			public,
+			proof_of_possession: Decode::decode(&mut &rotated.proof_of_possession[..]).unwrap(),
+		};
+		assert!(key_rotation::verify_proof_of_possession(&rotation, &owner));
+	}
+
+	/// Serves bridge proofs of blocks whose hash starts with a non-zero byte.
+	struct TestBridgeProofs;
+
+	impl BridgeProofProvider<Block> for TestBridgeProofs {
+		fn bridge_proof(&self, block: H256) -> Result<BridgeProof<Block>, String> {
+			if block[0] == 0 {
+				return Err("no justification".into())
+			}
+			let validator_set = ValidatorSet::new(vec![Keyring::Alice.public()], 0).unwrap();
+			Ok(BridgeProof {
+				finality_proof: create_finality_proof_at(block[0] as u64),
+				validator_set,
+				leaves: vec![EncodableOpaqueLeaf(block.encode())],
+				proof: Proof { leaf_indices: vec![block[0] as u64], leaf_count: 10, items: vec![] },
+			})
+		}
+	}
+
+	#[tokio::test]
+	async fn bridge_proof_rpc() {
+		let (rpc, _) = setup_io_handler();
+		let request = r#"{"jsonrpc":"2.0","method":"beefy_getBridgeProof","params":["0x0100000000000000000000000000000000000000000000000000000000000000"],"id":1}"#;
+		let expected = r#"{"jsonrpc":"2.0","error":{"code":6,"message":"BEEFY RPC bridge proofs not available"},"id":1}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+		assert_eq!(response.result, expected);
+
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (_, finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let rpc = Beefy::<Block>::new(
+			finality_proof_stream,
+			best_block_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.unwrap()
+		.with_bridge_proofs(Arc::new(TestBridgeProofs))
+		.into_rpc();
+
+		let block = H256::repeat_byte(7);
+		let bytes: Bytes = rpc.call("beefy_getBridgeProof", [block]).await.unwrap();
+		let bundle = BridgeProof::<Block>::decode(&mut &bytes[..]).unwrap();
+		assert_eq!(bundle.finality_proof, create_finality_proof_at(7));
+		assert_eq!(bundle.validator_set.validators(), &[Keyring::Alice.public()]);
+		assert_eq!(bundle.leaves, vec![EncodableOpaqueLeaf(block.encode())]);
+		assert_eq!(bundle.proof.leaf_indices, vec![7]);
+
+		let request = r#"{"jsonrpc":"2.0","method":"beefy_getBridgeProof","params":["0x0000000000000000000000000000000000000000000000000000000000000000"],"id":1}"#;
+		let expected = r#"{"jsonrpc":"2.0","error":{"code":7,"message":"BEEFY bridge proof not available: no justification"},"id":1}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+		assert_eq!(response.result, expected);
+	}
+
+	/// Exports a single handoff after blocks whose hash starts with a non-zero byte.
+	struct TestHandoffs;
+
+	impl HandoffProvider<Block> for TestHandoffs {
+		fn handoffs(&self, since: H256) -> Result<Vec<SetHandoff<Block>>, String> {
+			if since[0] == 0 {
+				return Err("unknown block".into())
+			}
+			let header = Header::new(
+				since[0] as u64,
+				Default::default(),
+				Default::default(),
+				Default::default(),
+				Default::default(),
+			);
+			Ok(vec![SetHandoff {
+				header,
+				justification: create_finality_proof_at(since[0] as u64),
+			}])
+		}
+	}
+
+	#[tokio::test]
+	async fn export_handoffs_is_unsafe() {
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (_, finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let rpc = Beefy::<Block>::new(
+			finality_proof_stream,
+			best_block_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.unwrap()
+		.with_handoffs(Arc::new(TestHandoffs), DenyUnsafe::Yes)
+		.into_rpc();
+		let request = r#"{"jsonrpc":"2.0","method":"beefy_exportHandoffs","params":["0x0100000000000000000000000000000000000000000000000000000000000000"],"id":1}"#;
+		let expected = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"RPC call is unsafe to be called externally"},"id":1}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+		assert_eq!(response.result, expected);
+	}
+
+	#[tokio::test]
+	async fn unsafe_policies_are_per_method() {
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (_, finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let rpc = Beefy::<Block>::new(
+			finality_proof_stream,
+			best_block_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.unwrap()
+		.with_key_rotation(MemoryKeystore::new().into(), DenyUnsafe::Yes)
+		.with_handoffs(Arc::new(TestHandoffs), DenyUnsafe::No)
+		.into_rpc();
+
+		// handoffs being allowed doesn't allow rotating keys.
+		let request = r#"{"jsonrpc":"2.0","method":"beefy_rotateKey","params":["0x0102"],"id":1}"#;
+		let expected = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"RPC call is unsafe to be called externally"},"id":1}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+		assert_eq!(response.result, expected);
+
+		let bytes: Bytes = rpc.call("beefy_exportHandoffs", [H256::repeat_byte(3)]).await.unwrap();
+		assert_eq!(Vec::<SetHandoff<Block>>::decode(&mut &bytes[..]).unwrap().len(), 1);
+	}
+
+	#[tokio::test]
+	async fn export_handoffs_rpc() {
+		let (rpc, _) = setup_io_handler();
+		let request = r#"{"jsonrpc":"2.0","method":"beefy_exportHandoffs","params":["0x0100000000000000000000000000000000000000000000000000000000000000"],"id":1}"#;
+		let expected = r#"{"jsonrpc":"2.0","error":{"code":8,"message":"BEEFY RPC authority set handoffs not available"},"id":1}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+		assert_eq!(response.result, expected);
+
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (_, finality_proof_stream) = BeefyVersionedFinalityProofStream::<Block>::channel();
+		let rpc = Beefy::<Block>::new(
+			finality_proof_stream,
+			best_block_stream,
+			sc_rpc::testing::test_executor(),
+		)
+		.unwrap()
+		.with_handoffs(Arc::new(TestHandoffs), DenyUnsafe::No)
+		.into_rpc();
+
+		let bytes: Bytes = rpc.call("beefy_exportHandoffs", [H256::repeat_byte(3)]).await.unwrap();
+		let handoffs = Vec::<SetHandoff<Block>>::decode(&mut &bytes[..]).unwrap();
+		assert_eq!(handoffs.len(), 1);
+		assert_eq!(handoffs[0].header.number, 3);
+		assert_eq!(handoffs[0].justification, create_finality_proof_at(3));
+
+		let request = r#"{"jsonrpc":"2.0","method":"beefy_exportHandoffs","params":["0x0000000000000000000000000000000000000000000000000000000000000000"],"id":1}"#;
+		let expected = r#"{"jsonrpc":"2.0","error":{"code":9,"message":"BEEFY authority set handoffs not available: unknown block"},"id":1}"#;
+		let (response, _) = rpc.raw_json_request(request).await.unwrap();
+		assert_eq!(response.result, expected);
+	}
+
 	#[tokio::test]
 	async fn subscribe_and_listen_to_one_justification() {
 		let (rpc, finality_proof_sender) = setup_io_handler();
@@ -300,4 +929,56 @@ mod tests {
 		assert_eq!(&recv_sub_id, sub.subscription_id());
 		assert_eq!(recv_finality_proof, finality_proof);
 	}
+
+	#[tokio::test]
+	async fn subscribe_with_ancestry_requires_provider() {
+		let (rpc, _) = setup_io_handler();
+
+		let result = rpc
+			.subscribe("beefy_subscribeJustificationsWithAncestry", EmptyParams::new())
+			.await;
+		assert!(result.is_err());
+	}
+
+	#[tokio::test]
+	async fn subscribe_and_listen_to_justifications_with_ancestry() {
+		let (_, best_block_stream) = BeefyBestBlockStream::<Block>::channel();
+		let (finality_proof_sender, finality_proof_stream) =
+			BeefyVersionedFinalityProofStream::<Block>::channel();
+		let rpc =
+			Beefy::new(finality_proof_stream, best_block_stream, sc_rpc::testing::test_executor())
+				.unwrap()
+				.with_ancestry_proofs(Arc::new(TestAncestryProofs))
+				.into_rpc();
+
+		let mut sub = rpc
+			.subscribe("beefy_subscribeJustificationsWithAncestry", EmptyParams::new())
+			.await
+			.unwrap();
+
+		// proof generation fails at #13, that notification is skipped.
+		for number in [5, 13, 8] {
+			let r: Result<(), ()> =
+				finality_proof_sender.notify(|| Ok(create_finality_proof_at(number)));
+			r.unwrap();
+		}
+
+		let mut received = vec![];
+		for _ in 0..2 {
+			let (bytes, _) = sub.next::<sp_core::Bytes>().await.unwrap().unwrap();
+			received.push(FinalityProofWithAncestry::<Block>::decode(&mut &bytes[..]).unwrap());
+		}
+
+		// the first notification proves the finalized block itself.
+		let first = &received[0];
+		assert_eq!(first.finality_proof, create_finality_proof_at(5));
+		assert_eq!(first.leaves, vec![EncodableOpaqueLeaf((5u64, 5u64).encode())]);
+		assert_eq!(first.proof.leaf_indices, vec![5]);
+
+		// following ones prove the previously finalized block.
+		let second = &received[1];
+		assert_eq!(second.finality_proof, create_finality_proof_at(8));
+		assert_eq!(second.leaves, vec![EncodableOpaqueLeaf((5u64, 8u64).encode())]);
+		assert_eq!(second.proof.leaf_indices, vec![5]);
+	}
 }
@@ -37,3 +37,16 @@ impl EncodedVersionedFinalityProof {
 		EncodedVersionedFinalityProof(finality_proof.encode().into())
 	}
 }
+
+/// An encoded [`crate::FinalityProofWithAncestry`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EncodedFinalityProofWithAncestry(sp_core::Bytes);
+
+impl EncodedFinalityProofWithAncestry {
+	pub fn new<Block>(bundle: crate::FinalityProofWithAncestry<Block>) -> Self
+	where
+		Block: BlockT,
+	{
+		EncodedFinalityProofWithAncestry(bundle.encode().into())
+	}
+}
@@ -18,17 +18,22 @@
 
 //! Schema for BEEFY state persisted in the aux-db.
 
-use crate::{worker::PersistedState, LOG_TARGET};
+use crate::{
+	keystore::SigningRecord,
+	worker::{PersistedState, VoterOracle},
+	LOG_TARGET,
+};
 use codec::{Decode, Encode};
 use log::{info, trace};
 use sc_client_api::{backend::AuxStore, Backend};
 use sp_blockchain::{Error as ClientError, Result as ClientResult};
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
 
 const VERSION_KEY: &[u8] = b"beefy_auxschema_version";
 const WORKER_STATE_KEY: &[u8] = b"beefy_voter_state";
+const SIGNING_RECORD_KEY: &[u8] = b"beefy_signing_record";
 
-const CURRENT_VERSION: u32 = 4;
+const CURRENT_VERSION: u32 = 5;
 
 pub(crate) fn write_current_version<BE: AuxStore>(backend: &BE) -> ClientResult<()> {
 	info!(target: LOG_TARGET, "🥩 write aux schema version {:?}", CURRENT_VERSION);
@@ -44,6 +49,24 @@ pub(crate) fn write_voter_state<B: BlockT, BE: AuxStore>(
 	AuxStore::insert_aux(backend, &[(WORKER_STATE_KEY, state.encode().as_slice())], &[])
 }
 
+/// Write the record of signed commitments.
+pub(crate) fn write_signing_record<B: BlockT, BE: AuxStore>(
+	backend: &BE,
+	record: &SigningRecord<NumberFor<B>>,
+) -> ClientResult<()> {
+	AuxStore::insert_aux(backend, &[(SIGNING_RECORD_KEY, record.encode().as_slice())], &[])
+}
+
+/// Load the record of signed commitments, or an empty one if there is none.
+///
+/// The record is independent of the voter state: it is kept as is across voter state migrations
+/// and resets, since those don't make signing a conflicting commitment any safer.
+pub(crate) fn load_signing_record<B: BlockT, BE: AuxStore>(
+	backend: &BE,
+) -> ClientResult<SigningRecord<NumberFor<B>>> {
+	load_decode(backend, SIGNING_RECORD_KEY).map(Option::unwrap_or_default)
+}
+
 fn load_decode<BE: AuxStore, T: Decode>(backend: &BE, key: &[u8]) -> ClientResult<Option<T>> {
 	match backend.get_aux(key)? {
 		None => Ok(None),
@@ -53,6 +76,27 @@ fn load_decode<BE: AuxStore, T: Decode>(backend: &BE, key: &[u8]) -> ClientResul
 	}
 }
 
+/// Migrate the voter state from version 4, which didn't include the pending justifications.
+fn migrate_from_version4<B, BE>(backend: &BE) -> ClientResult<Option<PersistedState<B>>>
+where
+	B: BlockT,
+	BE: Backend<B>,
+{
+	// version 4 `PersistedState` fields: `best_voted`, `voting_oracle` and `pallet_genesis`.
+	let state =
+		load_decode::<_, (NumberFor<B>, VoterOracle<B>, NumberFor<B>)>(backend, WORKER_STATE_KEY)?
+			.map(|(best_voted, voting_oracle, pallet_genesis)| {
+				PersistedState::from_v4(best_voted, voting_oracle, pallet_genesis)
+			});
+
+	if let Some(state) = &state {
+		write_voter_state(backend, state)?;
+		write_current_version(backend)?;
+		info!(target: LOG_TARGET, "🥩 Migrated BEEFY voter state from version 4");
+	}
+	Ok(state)
+}
+
 /// Load or initialize persistent data from backend.
 pub(crate) fn load_persistent<B, BE>(backend: &BE) -> ClientResult<Option<PersistedState<B>>>
 where
@@ -64,7 +108,8 @@ where
 	match version {
 		None => (),
 		Some(1) | Some(2) | Some(3) => (), // versions 1, 2 & 3 are obsolete and should be ignored
-		Some(4) => return load_decode::<_, PersistedState<B>>(backend, WORKER_STATE_KEY),
+		Some(4) => return migrate_from_version4::<B, BE>(backend),
+		Some(5) => return load_decode::<_, PersistedState<B>>(backend, WORKER_STATE_KEY),
 		other =>
 			return Err(ClientError::Backend(format!("Unsupported BEEFY DB version: {:?}", other))),
 	}
@@ -76,8 +121,14 @@ where
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
-	use crate::tests::BeefyTestNet;
+	use crate::{
+		round::Rounds,
+		tests::{make_beefy_ids, BeefyTestNet},
+	};
 	use sc_network_test::TestNetFactory;
+	use sp_blockchain::HeaderBackend;
+	use sp_consensus_beefy::{Keyring, ValidatorSet};
+	use substrate_test_runtime_client::runtime::Block;
 
 	// also used in tests.rs
 	pub fn verify_persisted_version<B: BlockT, BE: Backend<B>>(backend: &BE) -> bool {
@@ -103,4 +154,54 @@ pub(crate) mod tests {
 
 		// full `PersistedState` load is tested in `tests.rs`.
 	}
+
+	#[tokio::test]
+	async fn should_persist_signing_record() {
+		let mut net = BeefyTestNet::new(1);
+		let backend = net.peer(0).client().as_backend();
+
+		// nothing signed yet.
+		let mut record = load_signing_record::<Block, _>(&*backend).unwrap();
+		assert_eq!(record, Default::default());
+
+		assert_eq!(record.check_and_record(0, 1, [1; 32]), Ok(true));
+		write_signing_record::<Block, _>(&*backend, &record).unwrap();
+
+		let mut loaded = load_signing_record::<Block, _>(&*backend).unwrap();
+		assert_eq!(loaded, record);
+		assert!(loaded.check_and_record(0, 1, [2; 32]).is_err());
+	}
+
+	#[tokio::test]
+	async fn should_migrate_from_version4() {
+		let mut net = BeefyTestNet::new(1);
+		let backend = net.peer(0).client().as_backend();
+		net.peer(0).push_blocks(1, false);
+		let header = backend.blockchain().expect_header(backend.blockchain().info().best_hash);
+		let validator_set = ValidatorSet::new(make_beefy_ids(&[Keyring::Alice]), 0).unwrap();
+		let state = PersistedState::<Block>::checked_new(
+			header.unwrap(),
+			0,
+			vec![Rounds::new(1, validator_set)].into(),
+			1,
+			1,
+		)
+		.unwrap();
+
+		// write the state using the version 4 layout.
+		let v4_state = (0u64, state.voting_oracle(), state.pallet_genesis());
+		AuxStore::insert_aux(
+			&*backend,
+			&[
+				(VERSION_KEY, 4u32.encode().as_slice()),
+				(WORKER_STATE_KEY, v4_state.encode().as_slice()),
+			],
+			&[],
+		)
+		.unwrap();
+
+		assert_eq!(load_persistent(&*backend).unwrap(), Some(state));
+		// the migrated state has been persisted with the current version.
+		assert!(verify_persisted_version(&*backend));
+	}
 }
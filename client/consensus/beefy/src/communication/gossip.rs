@@ -16,9 +16,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use std::{
+	collections::{BTreeMap, HashSet},
+	sync::Arc,
+	time::Duration,
+};
 
-use sc_network::{PeerId, ReputationChange};
+use sc_network::{ObservedRole, PeerId, ProtocolName, ReputationChange};
 use sc_network_gossip::{MessageIntent, ValidationResult, Validator, ValidatorContext};
 use sp_core::hashing::twox_64;
 use sp_runtime::traits::{Block, Hash, Header, NumberFor};
@@ -32,8 +36,9 @@ use wasm_timer::Instant;
 use crate::{
 	communication::{
 		benefit, cost,
-		peers::{KnownPeers, PeerReport},
+		peers::{KnownPeers, PeerReport, VoteCheck},
 	},
+	dual_signing::{SecondarySigner, SecondaryVote},
 	justification::{
 		proof_block_num_and_set_id, verify_with_validator_set, BeefyVersionedFinalityProof,
 	},
@@ -42,7 +47,7 @@ use crate::{
 };
 use sp_consensus_beefy::{
 	crypto::{AuthorityId, Signature},
-	ValidatorSet, ValidatorSetId, VoteMessage,
+	Commitment, SignatureHasher, ValidatorSet, ValidatorSetId, VoteMessage,
 };
 
 // Timeout for rebroadcasting messages.
@@ -51,6 +56,17 @@ const REBROADCAST_AFTER: Duration = Duration::from_secs(60);
 #[cfg(test)]
 const REBROADCAST_AFTER: Duration = Duration::from_secs(5);
 
+/// Number of votes per voter and round any single peer can make us check.
+///
+/// Leaves room for every voter to equivocate once, anything more is considered spam.
+const MAX_VOTES_PER_VOTER: usize = 2;
+
+/// Maximum number of votes in a single [`VoteBatch`], bounding the size of batches to a few KiB.
+pub(crate) const MAX_VOTES_PER_BATCH: usize = 64;
+
+/// How long newly validated votes are held back from re-gossiping, to be batched with others.
+const BATCH_DELAY: Duration = Duration::from_millis(500);
+
 #[derive(Debug, PartialEq)]
 pub(super) enum Action<H> {
 	// repropagate under given topic, to the given peers, applying cost/benefit to originator.
@@ -73,27 +89,74 @@ enum Consider {
 }
 
 /// BEEFY gossip message type that gets encoded and sent on the network.
+///
+/// Peers speaking a legacy version of the gossip protocol only know of votes and finality proofs,
+/// see [`GossipMessage::is_legacy`].
 #[derive(Debug, Encode, Decode)]
 pub(crate) enum GossipMessage<B: Block> {
 	/// BEEFY message with commitment and single signature.
 	Vote(VoteMessage<NumberFor<B>, AuthorityId, Signature>),
 	/// BEEFY justification with commitment and signatures.
 	FinalityProof(BeefyVersionedFinalityProof<B>),
+	/// BEEFY message with commitment and single signature of the secondary crypto scheme, while
+	/// dual signing.
+	SecondaryVote(SecondaryVote<B>),
+	/// BEEFY message with commitment and the signatures of several voters, re-gossiping their
+	/// votes at once.
+	VoteBatch(VoteBatch<B>),
+}
+
+/// Votes of several voters for the same commitment, gossiped as a single message.
+///
+/// Only regular votes are batched, secondary votes are always gossiped on their own.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub(crate) struct VoteBatch<B: Block> {
+	/// The commitment voted for.
+	pub commitment: Commitment<NumberFor<B>>,
+	/// The voters and their signatures of `commitment`.
+	pub votes: Vec<(AuthorityId, Signature)>,
+}
+
+impl<B: Block> VoteBatch<B> {
+	/// Split the batch into the votes it is made of.
+	pub fn into_votes(
+		self,
+	) -> impl Iterator<Item = VoteMessage<NumberFor<B>, AuthorityId, Signature>> {
+		let commitment = self.commitment;
+		self.votes.into_iter().map(move |(id, signature)| VoteMessage {
+			commitment: commitment.clone(),
+			id,
+			signature,
+		})
+	}
 }
 
 impl<B: Block> GossipMessage<B> {
+	/// Return `true` if this message can be sent to peers speaking a legacy version of the gossip
+	/// protocol.
+	pub fn is_legacy(&self) -> bool {
+		match self {
+			GossipMessage::Vote(_) | GossipMessage::FinalityProof(_) => true,
+			GossipMessage::SecondaryVote(_) | GossipMessage::VoteBatch(_) => false,
+		}
+	}
+
 	/// Return inner vote if this message is a Vote.
 	pub fn unwrap_vote(self) -> Option<VoteMessage<NumberFor<B>, AuthorityId, Signature>> {
 		match self {
 			GossipMessage::Vote(vote) => Some(vote),
-			GossipMessage::FinalityProof(_) => None,
+			GossipMessage::FinalityProof(_) |
+			GossipMessage::SecondaryVote(_) |
+			GossipMessage::VoteBatch(_) => None,
 		}
 	}
 
 	/// Return inner finality proof if this message is a FinalityProof.
 	pub fn unwrap_finality_proof(self) -> Option<BeefyVersionedFinalityProof<B>> {
 		match self {
-			GossipMessage::Vote(_) => None,
+			GossipMessage::Vote(_) |
+			GossipMessage::SecondaryVote(_) |
+			GossipMessage::VoteBatch(_) => None,
 			GossipMessage::FinalityProof(proof) => Some(proof),
 		}
 	}
@@ -123,6 +186,7 @@ pub(crate) struct GossipFilterCfg<'a, B: Block> {
 	pub start: NumberFor<B>,
 	pub end: NumberFor<B>,
 	pub validator_set: &'a ValidatorSet<AuthorityId>,
+	pub hasher: SignatureHasher,
 }
 
 #[derive(Clone, Debug)]
@@ -130,32 +194,47 @@ struct FilterInner<B: Block> {
 	pub start: NumberFor<B>,
 	pub end: NumberFor<B>,
 	pub validator_set: ValidatorSet<AuthorityId>,
+	pub hasher: SignatureHasher,
 }
 
+/// The secondary validator set and signer of a dual-signing session.
+pub(crate) type SecondaryFilterCfg = (ValidatorSet<Vec<u8>>, Arc<dyn SecondarySigner>);
+
 struct Filter<B: Block> {
 	inner: Option<FilterInner<B>>,
 	live_votes: BTreeMap<NumberFor<B>, fnv::FnvHashSet<MessageHash>>,
+	/// Known votes that are re-gossiped as part of a [`VoteBatch`], rather than on their own.
+	batched_votes: BTreeMap<NumberFor<B>, fnv::FnvHashSet<MessageHash>>,
+	secondary: Option<SecondaryFilterCfg>,
 }
 
 impl<B: Block> Filter<B> {
 	pub fn new() -> Self {
-		Self { inner: None, live_votes: BTreeMap::new() }
+		Self {
+			inner: None,
+			live_votes: BTreeMap::new(),
+			batched_votes: BTreeMap::new(),
+			secondary: None,
+		}
 	}
 
 	/// Update filter to new `start` and `set_id`.
 	fn update(&mut self, cfg: GossipFilterCfg<B>) {
 		self.live_votes.retain(|&round, _| round >= cfg.start && round <= cfg.end);
+		self.batched_votes.retain(|&round, _| round >= cfg.start && round <= cfg.end);
 		// only clone+overwrite big validator_set if set_id changed
 		match self.inner.as_mut() {
 			Some(f) if f.validator_set.id() == cfg.validator_set.id() => {
 				f.start = cfg.start;
 				f.end = cfg.end;
+				f.hasher = cfg.hasher;
 			},
 			_ =>
 				self.inner = Some(FilterInner {
 					start: cfg.start,
 					end: cfg.end,
 					validator_set: cfg.validator_set.clone(),
+					hasher: cfg.hasher,
 				}),
 		}
 	}
@@ -213,9 +292,103 @@ impl<B: Block> Filter<B> {
 		self.live_votes.get(&round).map(|known| known.contains(hash)).unwrap_or(false)
 	}
 
+	/// Note that the known vote `hash` is re-gossiped as part of a batch.
+	fn add_batched_vote(&mut self, round: NumberFor<B>, hash: MessageHash) {
+		self.batched_votes.entry(round).or_default().insert(hash);
+	}
+
+	/// Check if `hash` is re-gossiped as part of a batch.
+	fn is_batched_vote(&self, round: NumberFor<B>, hash: &MessageHash) -> bool {
+		self.batched_votes
+			.get(&round)
+			.map(|batched| batched.contains(hash))
+			.unwrap_or(false)
+	}
+
+	/// Note that the known vote `hash` is re-gossiped on its own again.
+	fn remove_batched_vote(&mut self, round: NumberFor<B>, hash: &MessageHash) {
+		if let Some(batched) = self.batched_votes.get_mut(&round) {
+			batched.remove(hash);
+		}
+	}
+
 	fn validator_set(&self) -> Option<&ValidatorSet<AuthorityId>> {
 		self.inner.as_ref().map(|f| &f.validator_set)
 	}
+
+	fn hasher(&self) -> SignatureHasher {
+		self.inner.as_ref().map(|f| f.hasher).unwrap_or_default()
+	}
+
+	/// Return the secondary validator set and signer of validator set `set_id`, if dual signing.
+	fn secondary(
+		&self,
+		set_id: ValidatorSetId,
+	) -> Option<(&ValidatorSet<Vec<u8>>, &dyn SecondarySigner)> {
+		self.secondary
+			.as_ref()
+			.filter(|(validator_set, _)| validator_set.id() == set_id)
+			.map(|(validator_set, signer)| (validator_set, &**signer))
+	}
+}
+
+/// Newly validated votes, waiting to be re-gossiped in [`VoteBatch`]es.
+struct PendingBatches<B: Block> {
+	/// When the oldest pending vote was added.
+	since: Option<Instant>,
+	batches: Vec<VoteBatch<B>>,
+	/// Round and message hash of the pending votes.
+	hashes: Vec<(NumberFor<B>, MessageHash)>,
+}
+
+impl<B: Block> PendingBatches<B> {
+	fn new() -> Self {
+		Self { since: None, batches: Vec::new(), hashes: Vec::new() }
+	}
+
+	/// Add `vote`, with message hash `msg_hash`, to the batch of its commitment.
+	fn push(
+		&mut self,
+		vote: VoteMessage<NumberFor<B>, AuthorityId, Signature>,
+		msg_hash: MessageHash,
+	) {
+		self.since.get_or_insert_with(Instant::now);
+		self.hashes.push((vote.commitment.block_number, msg_hash));
+		let VoteMessage { commitment, id, signature } = vote;
+		match self
+			.batches
+			.iter_mut()
+			.find(|batch| batch.commitment == commitment && batch.votes.len() < MAX_VOTES_PER_BATCH)
+		{
+			Some(batch) => batch.votes.push((id, signature)),
+			None => self.batches.push(VoteBatch { commitment, votes: vec![(id, signature)] }),
+		}
+	}
+
+	/// Take the pending batches, if one of them is full or the oldest vote waited long enough.
+	fn take_ready(&mut self, now: Instant) -> Vec<VoteBatch<B>> {
+		let full = self.batches.iter().any(|batch| batch.votes.len() >= MAX_VOTES_PER_BATCH);
+		let expired = matches!(self.since, Some(since) if now >= since + BATCH_DELAY);
+		if full || expired {
+			self.since = None;
+			self.hashes.clear();
+			std::mem::take(&mut self.batches)
+		} else {
+			Vec::new()
+		}
+	}
+
+	/// Drop the pending batches if the oldest vote waited long enough, returning the round and
+	/// message hash of their votes, so they can be gossiped on their own instead.
+	fn take_stale(&mut self, now: Instant) -> Vec<(NumberFor<B>, MessageHash)> {
+		if matches!(self.since, Some(since) if now >= since + BATCH_DELAY) {
+			self.since = None;
+			self.batches.clear();
+			std::mem::take(&mut self.hashes)
+		} else {
+			Vec::new()
+		}
+	}
 }
 
 /// BEEFY gossip validator
@@ -236,6 +409,10 @@ where
 	next_rebroadcast: Mutex<Instant>,
 	known_peers: Arc<Mutex<KnownPeers<B>>>,
 	report_sender: TracingUnboundedSender<PeerReport>,
+	pending_batches: Mutex<PendingBatches<B>>,
+	/// Peers speaking a legacy version of the gossip protocol, which are only sent the messages
+	/// they know of, and votes on their own rather than in batches.
+	legacy_peers: RwLock<HashSet<PeerId>>,
 }
 
 impl<B> GossipValidator<B>
@@ -253,6 +430,8 @@ where
 			next_rebroadcast: Mutex::new(Instant::now() + REBROADCAST_AFTER),
 			known_peers,
 			report_sender: tx,
+			pending_batches: Mutex::new(PendingBatches::new()),
+			legacy_peers: RwLock::new(HashSet::new()),
 		};
 		(val, rx)
 	}
@@ -262,53 +441,109 @@ where
 	/// Only votes for `set_id` and rounds `start <= round <= end` will be accepted.
 	pub(crate) fn update_filter(&self, filter: GossipFilterCfg<B>) {
 		debug!(target: LOG_TARGET, "🥩 New gossip filter {:?}", filter);
+		self.known_peers.lock().forget_votes_before(filter.start);
 		self.gossip_filter.write().update(filter);
 	}
 
+	/// Update the secondary validator set and signer of the gossip validator filter.
+	///
+	/// Secondary votes are only accepted from validators of the active validator set, while it
+	/// is dual signing.
+	pub(crate) fn update_secondary_filter(&self, secondary: Option<SecondaryFilterCfg>) {
+		self.gossip_filter.write().secondary = secondary;
+	}
+
 	fn report(&self, who: PeerId, cost_benefit: ReputationChange) {
 		let _ = self.report_sender.unbounded_send(PeerReport { who, cost_benefit });
 	}
 
-	fn validate_vote(
+	/// Check that a vote of `id`, for `round` of validator set `set_id`, is worth verifying.
+	///
+	/// Return the index of `id` in the validator set and the signature hasher if so, the action
+	/// to take on the vote otherwise.
+	fn check_vote(
 		&self,
-		vote: VoteMessage<NumberFor<B>, AuthorityId, Signature>,
+		round: NumberFor<B>,
+		set_id: ValidatorSetId,
+		id: &AuthorityId,
 		sender: &PeerId,
-		data: &[u8],
-	) -> Action<B::Hash> {
-		let msg_hash = twox_64(data);
-		let round = vote.commitment.block_number;
-		let set_id = vote.commitment.validator_set_id;
+		msg_hash: MessageHash,
+	) -> Result<(usize, SignatureHasher), Action<B::Hash>> {
 		self.known_peers.lock().note_vote_for(*sender, round);
 
 		// Verify general usefulness of the message.
 		// We are going to discard old votes right away (without verification)
 		// Also we keep track of already received votes to avoid verifying duplicates.
-		{
+		let (index, max_votes, hasher) = {
 			let filter = self.gossip_filter.read();
 
 			match filter.consider_vote(round, set_id) {
-				Consider::RejectPast => return Action::Discard(cost::OUTDATED_MESSAGE),
-				Consider::RejectFuture => return Action::Discard(cost::FUTURE_MESSAGE),
-				Consider::RejectOutOfScope => return Action::Discard(cost::OUT_OF_SCOPE_MESSAGE),
+				Consider::RejectPast => return Err(Action::Discard(cost::OUTDATED_MESSAGE)),
+				Consider::RejectFuture => return Err(Action::Discard(cost::FUTURE_MESSAGE)),
+				Consider::RejectOutOfScope =>
+					return Err(Action::Discard(cost::OUT_OF_SCOPE_MESSAGE)),
 				Consider::Accept => {},
 			}
 
 			if filter.is_known_vote(round, &msg_hash) {
-				return Action::Keep(self.votes_topic, benefit::KNOWN_VOTE_MESSAGE)
+				return Err(Action::Keep(self.votes_topic, benefit::KNOWN_VOTE_MESSAGE))
 			}
 
 			// ensure authority is part of the set.
-			if !filter
+			let index = match filter
 				.validator_set()
-				.map(|set| set.validators().contains(&vote.id))
-				.unwrap_or(false)
+				.and_then(|set| set.validators().iter().position(|v| v == id))
 			{
-				debug!(target: LOG_TARGET, "Message from voter not in validator set: {}", vote.id);
-				return Action::Discard(cost::UNKNOWN_VOTER)
-			}
+				Some(index) => index,
+				None => {
+					debug!(target: LOG_TARGET, "Message from voter not in validator set: {}", id);
+					return Err(Action::Discard(cost::UNKNOWN_VOTER))
+				},
+			};
+
+			// dual-signing voters send two votes per round.
+			let votes_per_voter = if filter.secondary(set_id).is_some() {
+				2 * MAX_VOTES_PER_VOTER
+			} else {
+				MAX_VOTES_PER_VOTER
+			};
+			let max_votes =
+				filter.validator_set().map(|set| set.len()).unwrap_or(0) * votes_per_voter;
+			(index, max_votes, filter.hasher())
+		};
+
+		// Don't let a single peer make us verify the same, or too many, signatures.
+		match self.known_peers.lock().note_checked_vote(*sender, round, msg_hash, max_votes) {
+			VoteCheck::New => Ok((index, hasher)),
+			VoteCheck::Duplicate => Err(Action::Discard(cost::DUPLICATE_VOTE)),
+			VoteCheck::TooMany => {
+				debug!(
+					target: LOG_TARGET,
+					"🥩 Too many votes for round {:?} from {:?}",
+					round,
+					sender
+				);
+				Err(Action::Discard(cost::TOO_MANY_VOTES))
+			},
 		}
+	}
 
-		if BeefyKeystore::verify(&vote.id, &vote.signature, &vote.commitment.encode()) {
+	fn validate_vote(
+		&self,
+		vote: VoteMessage<NumberFor<B>, AuthorityId, Signature>,
+		sender: &PeerId,
+		data: &[u8],
+	) -> Action<B::Hash> {
+		let msg_hash = twox_64(data);
+		let round = vote.commitment.block_number;
+		let set_id = vote.commitment.validator_set_id;
+
+		let hasher = match self.check_vote(round, set_id, &vote.id, sender, msg_hash) {
+			Ok((_, hasher)) => hasher,
+			Err(action) => return action,
+		};
+
+		if BeefyKeystore::verify(&vote.id, &vote.signature, &vote.commitment.encode(), hasher) {
 			self.gossip_filter.write().add_known_vote(round, msg_hash);
 			Action::Keep(self.votes_topic, benefit::VOTE_MESSAGE)
 		} else {
@@ -320,6 +555,135 @@ where
 		}
 	}
 
+	fn validate_secondary_vote(
+		&self,
+		vote: SecondaryVote<B>,
+		sender: &PeerId,
+		data: &[u8],
+	) -> Action<B::Hash> {
+		let msg_hash = twox_64(data);
+		let round = vote.commitment.block_number;
+		let set_id = vote.commitment.validator_set_id;
+
+		if self.gossip_filter.read().secondary(set_id).is_none() {
+			return Action::Discard(cost::OUT_OF_SCOPE_MESSAGE)
+		}
+		let (index, hasher) = match self.check_vote(round, set_id, &vote.id, sender, msg_hash) {
+			Ok(checked) => checked,
+			Err(action) => return action,
+		};
+
+		let valid = self.gossip_filter.read().secondary(set_id).map_or(false, |(set, signer)| {
+			set.validators().get(index).map_or(false, |public| {
+				signer.verify(public, &vote.signature, &vote.commitment.encode(), hasher)
+			})
+		});
+		if valid {
+			self.gossip_filter.write().add_known_vote(round, msg_hash);
+			Action::Keep(self.votes_topic, benefit::VOTE_MESSAGE)
+		} else {
+			debug!(
+				target: LOG_TARGET,
+				"🥩 Bad secondary signature on message: {:?}, from: {:?}", vote, sender
+			);
+			Action::Discard(cost::BAD_SIGNATURE)
+		}
+	}
+
+	fn validate_vote_batch(
+		&self,
+		context: &mut dyn ValidatorContext<B>,
+		batch: VoteBatch<B>,
+		sender: &PeerId,
+	) -> Action<B::Hash> {
+		if batch.votes.is_empty() || batch.votes.len() > MAX_VOTES_PER_BATCH {
+			return Action::Discard(cost::BAD_BATCH)
+		}
+		let round = batch.commitment.block_number;
+		let set_id = batch.commitment.validator_set_id;
+		let encoded_commitment = batch.commitment.encode();
+
+		let mut new_votes = Vec::new();
+		for vote in batch.into_votes() {
+			// known votes are the same, whether they were gossiped on their own or batched.
+			let encoded = GossipMessage::<B>::Vote(vote.clone()).encode();
+			let msg_hash = twox_64(&encoded);
+			let hasher = match self.check_vote(round, set_id, &vote.id, sender, msg_hash) {
+				Ok((_, hasher)) => hasher,
+				Err(Action::Keep(..)) => continue,
+				Err(action) => return action,
+			};
+			if !BeefyKeystore::verify(&vote.id, &vote.signature, &encoded_commitment, hasher) {
+				debug!(
+					target: LOG_TARGET,
+					"🥩 Bad signature in vote batch: {:?}, from: {:?}", vote, sender
+				);
+				return Action::Discard(cost::BAD_SIGNATURE)
+			}
+			let mut filter = self.gossip_filter.write();
+			filter.add_known_vote(round, msg_hash);
+			filter.add_batched_vote(round, msg_hash);
+			new_votes.push(encoded);
+		}
+
+		// legacy peers can't decode batches, so they get the new votes on their own.
+		if !self.legacy_peers.read().is_empty() {
+			for vote in &new_votes {
+				context.broadcast_message(self.votes_topic, vote.clone(), false);
+			}
+		}
+
+		if !new_votes.is_empty() {
+			Action::Keep(self.votes_topic, benefit::VOTE_MESSAGE)
+		} else {
+			Action::Keep(self.votes_topic, benefit::KNOWN_VOTE_MESSAGE)
+		}
+	}
+
+	/// Hold back the newly validated `vote` from re-gossiping, to batch it with others.
+	fn batch_vote(
+		&self,
+		vote: VoteMessage<NumberFor<B>, AuthorityId, Signature>,
+		msg_hash: MessageHash,
+	) {
+		self.gossip_filter
+			.write()
+			.add_batched_vote(vote.commitment.block_number, msg_hash);
+		self.pending_batches.lock().push(vote, msg_hash);
+	}
+
+	/// Let held back votes be re-gossiped on their own, if no further message flushed their batch
+	/// in time.
+	///
+	/// Batches are only gossiped on validating a message, while the gossip engine periodically
+	/// propagates the messages it keeps, so this way votes don't wait for further messages.
+	fn release_stale_batches(&self) {
+		let stale = self.pending_batches.lock().take_stale(Instant::now());
+		if stale.is_empty() {
+			return
+		}
+		trace!(target: LOG_TARGET, "🥩 Releasing {} held back votes", stale.len());
+		let mut filter = self.gossip_filter.write();
+		for (round, msg_hash) in stale {
+			filter.remove_batched_vote(round, &msg_hash);
+		}
+	}
+
+	/// Re-gossip the pending vote batches, once ready.
+	fn gossip_batches(&self, context: &mut dyn ValidatorContext<B>) {
+		let batches = self.pending_batches.lock().take_ready(Instant::now());
+		for batch in batches {
+			trace!(
+				target: LOG_TARGET,
+				"🥩 Gossiping batch of {} votes for round #{:?}",
+				batch.votes.len(),
+				batch.commitment.block_number
+			);
+			let encoded = GossipMessage::<B>::VoteBatch(batch).encode();
+			context.broadcast_message(self.votes_topic, encoded, false);
+		}
+	}
+
 	fn validate_finality_proof(
 		&self,
 		proof: BeefyVersionedFinalityProof<B>,
@@ -341,7 +705,7 @@ where
 			.validator_set()
 			.map(|validator_set| {
 				if let Err((_, signatures_checked)) =
-					verify_with_validator_set::<B>(round, validator_set, &proof)
+					verify_with_validator_set::<B>(round, validator_set, &proof, guard.hasher())
 				{
 					debug!(
 						target: LOG_TARGET,
@@ -363,8 +727,23 @@ impl<B> Validator<B> for GossipValidator<B>
 where
 	B: Block,
 {
+	fn new_peer_with_fallback(
+		&self,
+		_context: &mut dyn ValidatorContext<B>,
+		who: &PeerId,
+		_role: ObservedRole,
+		negotiated_fallback: Option<&ProtocolName>,
+	) {
+		// all fallback names are legacy versions of the protocol.
+		if let Some(protocol) = negotiated_fallback {
+			debug!(target: LOG_TARGET, "🥩 Peer {:?} gossips using {}", who, protocol);
+			self.legacy_peers.write().insert(*who);
+		}
+	}
+
 	fn peer_disconnected(&self, _context: &mut dyn ValidatorContext<B>, who: &PeerId) {
 		self.known_peers.lock().remove(who);
+		self.legacy_peers.write().remove(who);
 	}
 
 	fn validate(
@@ -374,9 +753,19 @@ where
 		mut data: &[u8],
 	) -> ValidationResult<B::Hash> {
 		let raw = data;
+		let mut batched = false;
 		let action = match GossipMessage::<B>::decode(&mut data) {
-			Ok(GossipMessage::Vote(msg)) => self.validate_vote(msg, sender, raw),
+			Ok(GossipMessage::Vote(msg)) => {
+				let action = self.validate_vote(msg.clone(), sender, raw);
+				if action == Action::Keep(self.votes_topic, benefit::VOTE_MESSAGE) {
+					self.batch_vote(msg, twox_64(raw));
+					batched = true;
+				}
+				action
+			},
 			Ok(GossipMessage::FinalityProof(proof)) => self.validate_finality_proof(proof, sender),
+			Ok(GossipMessage::SecondaryVote(msg)) => self.validate_secondary_vote(msg, sender, raw),
+			Ok(GossipMessage::VoteBatch(batch)) => self.validate_vote_batch(context, batch, sender),
 			Err(e) => {
 				debug!(target: LOG_TARGET, "Error decoding message: {}", e);
 				let bytes = raw.len().min(i32::MAX as usize) as i32;
@@ -387,25 +776,34 @@ where
 				Action::Discard(cost)
 			},
 		};
-		match action {
+		let result = match action {
 			Action::Keep(topic, cb) => {
 				self.report(*sender, cb);
-				context.broadcast_message(topic, data.to_vec(), false);
+				if !batched {
+					context.broadcast_message(topic, data.to_vec(), false);
+				} else if !self.legacy_peers.read().is_empty() {
+					// legacy peers can't decode batches, so they get batched votes on their own.
+					context.broadcast_message(topic, raw.to_vec(), false);
+				}
 				ValidationResult::ProcessAndKeep(topic)
 			},
 			Action::Discard(cb) => {
 				self.report(*sender, cb);
 				ValidationResult::Discard
 			},
-		}
+		};
+		self.gossip_batches(context);
+		result
 	}
 
 	fn message_expired<'a>(&'a self) -> Box<dyn FnMut(B::Hash, &[u8]) -> bool + 'a> {
 		let filter = self.gossip_filter.read();
 		Box::new(move |_topic, mut data| match GossipMessage::<B>::decode(&mut data) {
-			Ok(GossipMessage::Vote(msg)) => {
-				let round = msg.commitment.block_number;
-				let set_id = msg.commitment.validator_set_id;
+			Ok(GossipMessage::Vote(VoteMessage { commitment, .. })) |
+			Ok(GossipMessage::SecondaryVote(VoteMessage { commitment, .. })) |
+			Ok(GossipMessage::VoteBatch(VoteBatch { commitment, .. })) => {
+				let round = commitment.block_number;
+				let set_id = commitment.validator_set_id;
 				let expired = filter.consider_vote(round, set_id) != Consider::Accept;
 				trace!(target: LOG_TARGET, "🥩 Vote for round #{} expired: {}", round, expired);
 				expired
@@ -439,18 +837,33 @@ where
 				false
 			}
 		};
+		self.release_stale_batches();
 
 		let filter = self.gossip_filter.read();
-		Box::new(move |_who, intent, _topic, mut data| {
+		let legacy_peers = self.legacy_peers.read();
+		Box::new(move |who, intent, _topic, mut data| {
+			let msg_hash = twox_64(data);
+			let message = GossipMessage::<B>::decode(&mut data);
+			let legacy_peer = legacy_peers.contains(who);
+			if legacy_peer && !matches!(&message, Ok(message) if message.is_legacy()) {
+				return false
+			}
+
 			if let MessageIntent::PeriodicRebroadcast = intent {
 				return do_rebroadcast
 			}
 
-			match GossipMessage::<B>::decode(&mut data) {
-				Ok(GossipMessage::Vote(msg)) => {
-					let round = msg.commitment.block_number;
-					let set_id = msg.commitment.validator_set_id;
-					let allowed = filter.consider_vote(round, set_id) == Consider::Accept;
+			match message {
+				Ok(GossipMessage::Vote(VoteMessage { commitment, .. })) |
+				Ok(GossipMessage::SecondaryVote(VoteMessage { commitment, .. })) |
+				Ok(GossipMessage::VoteBatch(VoteBatch { commitment, .. })) => {
+					let round = commitment.block_number;
+					let set_id = commitment.validator_set_id;
+					// batched votes reach peers that don't know them yet through their batch, but
+					// legacy peers can't decode batches.
+					let allowed = filter.consider_vote(round, set_id) == Consider::Accept &&
+						!(intent == MessageIntent::Broadcast &&
+							!legacy_peer && filter.is_batched_vote(round, &msg_hash));
 					trace!(target: LOG_TARGET, "🥩 Vote for round #{} allowed: {}", round, allowed);
 					allowed
 				},
@@ -474,7 +887,7 @@ where
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
-	use crate::keystore::BeefyKeystore;
+	use crate::{dual_signing::KeystoreSigner, keystore::BeefyKeystore};
 	use sc_network_test::Block;
 	use sp_consensus_beefy::{
 		crypto::Signature, known_payloads, Commitment, Keyring, MmrRootHash, Payload,
@@ -503,7 +916,12 @@ pub(crate) mod tests {
 		assert!(filter.inner.is_none());
 		assert_eq!(filter.consider_vote(1, 1), Consider::RejectOutOfScope);
 
-		filter.update(GossipFilterCfg { start: 3, end: 10, validator_set: &validator_set });
+		filter.update(GossipFilterCfg {
+			start: 3,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
 		assert_eq!(filter.live_votes.len(), 1);
 		assert!(filter.live_votes.contains_key(&3));
 		assert_eq!(filter.consider_vote(2, 1), Consider::RejectPast);
@@ -513,7 +931,12 @@ pub(crate) mod tests {
 		assert_eq!(filter.consider_vote(4, 2), Consider::RejectFuture);
 
 		let validator_set = ValidatorSet::<AuthorityId>::new(keys, 2).unwrap();
-		filter.update(GossipFilterCfg { start: 5, end: 10, validator_set: &validator_set });
+		filter.update(GossipFilterCfg {
+			start: 5,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
 		assert!(filter.live_votes.is_empty());
 	}
 
@@ -538,7 +961,9 @@ pub(crate) mod tests {
 		let store = MemoryKeystore::new();
 		store.ecdsa_generate_new(KEY_TYPE, Some(&who.to_seed())).unwrap();
 		let beefy_keystore: BeefyKeystore = Some(store.into()).into();
-		beefy_keystore.sign(&who.public(), &commitment.encode()).unwrap()
+		beefy_keystore
+			.sign(&who.public(), &commitment.encode(), SignatureHasher::Keccak256)
+			.unwrap()
 	}
 
 	fn dummy_vote(block_number: u64) -> VoteMessage<u64, AuthorityId, Signature> {
@@ -572,6 +997,86 @@ pub(crate) mod tests {
 		BeefyVersionedFinalityProof::<Block>::V1(SignedCommitment { commitment, signatures })
 	}
 
+	#[test]
+	fn should_verify_votes_with_filter_hasher() {
+		let validator_set = ValidatorSet::new(vec![Keyring::Alice.public()], 0).unwrap();
+		let (gv, mut report_stream) =
+			GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
+		let sender = PeerId::random();
+		let mut context = TestContext;
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Blake2_256,
+		});
+
+		// vote signed over the Keccak-256 hash of the commitment is rejected.
+		let mut vote = dummy_vote(3);
+		let encoded = GossipMessage::<Block>::Vote(vote.clone()).encode();
+		let res = gv.validate(&mut context, &sender, &encoded);
+		assert!(matches!(res, ValidationResult::Discard));
+		let expected_report = PeerReport { who: sender, cost_benefit: cost::BAD_SIGNATURE };
+		assert_eq!(report_stream.try_recv().unwrap(), expected_report);
+
+		// vote signed over the Blake2-256 hash of the commitment is accepted.
+		let store = MemoryKeystore::new();
+		store.ecdsa_generate_new(KEY_TYPE, Some(&Keyring::Alice.to_seed())).unwrap();
+		let beefy_keystore: BeefyKeystore = Some(store.into()).into();
+		vote.signature = beefy_keystore
+			.sign(&vote.id, &vote.commitment.encode(), SignatureHasher::Blake2_256)
+			.unwrap();
+		let encoded = GossipMessage::<Block>::Vote(vote).encode();
+		let res = gv.validate(&mut context, &sender, &encoded);
+		assert!(matches!(res, ValidationResult::ProcessAndKeep(_)));
+		let expected_report = PeerReport { who: sender, cost_benefit: benefit::VOTE_MESSAGE };
+		assert_eq!(report_stream.try_recv().unwrap(), expected_report);
+	}
+
+	#[test]
+	fn should_validate_secondary_votes() {
+		let validator_set = ValidatorSet::new(vec![Keyring::Alice.public()], 0).unwrap();
+		let secondary = ValidatorSet::new(vec![Keyring::Bob.public().encode()], 0).unwrap();
+		let (gv, mut report_stream) =
+			GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
+		let sender = PeerId::random();
+		let mut context = TestContext;
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
+
+		// Alice's secondary vote, signed with her secondary key, i.e. Bob's key.
+		let vote = dummy_vote(3);
+		let signature = sign_commitment(&Keyring::Bob, &vote.commitment).encode();
+		let mut secondary_vote =
+			VoteMessage { commitment: vote.commitment, id: vote.id, signature };
+		let encoded = GossipMessage::<Block>::SecondaryVote(secondary_vote.clone()).encode();
+
+		// not dual signing.
+		let res = gv.validate(&mut context, &sender, &encoded);
+		assert!(matches!(res, ValidationResult::Discard));
+		let expected_report = PeerReport { who: sender, cost_benefit: cost::OUT_OF_SCOPE_MESSAGE };
+		assert_eq!(report_stream.try_recv().unwrap(), expected_report);
+
+		let signer = Arc::new(KeystoreSigner::<AuthorityId>::from(None));
+		gv.update_secondary_filter(Some((secondary, signer)));
+		let res = gv.validate(&mut context, &sender, &encoded);
+		assert!(matches!(res, ValidationResult::ProcessAndKeep(_)));
+		let expected_report = PeerReport { who: sender, cost_benefit: benefit::VOTE_MESSAGE };
+		assert_eq!(report_stream.try_recv().unwrap(), expected_report);
+
+		// signed with the primary key instead.
+		secondary_vote.signature = vote.signature.encode();
+		let encoded = GossipMessage::<Block>::SecondaryVote(secondary_vote).encode();
+		let res = gv.validate(&mut context, &sender, &encoded);
+		assert!(matches!(res, ValidationResult::Discard));
+		let expected_report = PeerReport { who: sender, cost_benefit: cost::BAD_SIGNATURE };
+		assert_eq!(report_stream.try_recv().unwrap(), expected_report);
+	}
+
 	#[test]
 	fn should_validate_messages() {
 		let keys = vec![Keyring::Alice.public()];
@@ -603,7 +1108,12 @@ pub(crate) mod tests {
 		expected_report.cost_benefit = cost::OUT_OF_SCOPE_MESSAGE;
 		assert_eq!(report_stream.try_recv().unwrap(), expected_report);
 
-		gv.update_filter(GossipFilterCfg { start: 0, end: 10, validator_set: &validator_set });
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
 		// nothing in cache first time
 		let res = gv.validate(&mut context, &sender, &encoded);
 		assert!(matches!(res, ValidationResult::ProcessAndKeep(_)));
@@ -634,7 +1144,12 @@ pub(crate) mod tests {
 		assert_eq!(report_stream.try_recv().unwrap(), expected_report);
 
 		// reject if the round is not GRANDPA finalized
-		gv.update_filter(GossipFilterCfg { start: 1, end: 2, validator_set: &validator_set });
+		gv.update_filter(GossipFilterCfg {
+			start: 1,
+			end: 2,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
 		let number = vote.commitment.block_number;
 		let set_id = vote.commitment.validator_set_id;
 		assert_eq!(gv.gossip_filter.read().consider_vote(number, set_id), Consider::RejectFuture);
@@ -644,7 +1159,12 @@ pub(crate) mod tests {
 		assert_eq!(report_stream.try_recv().unwrap(), expected_report);
 
 		// reject if the round is not live anymore
-		gv.update_filter(GossipFilterCfg { start: 7, end: 10, validator_set: &validator_set });
+		gv.update_filter(GossipFilterCfg {
+			start: 7,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
 		let number = vote.commitment.block_number;
 		let set_id = vote.commitment.validator_set_id;
 		assert_eq!(gv.gossip_filter.read().consider_vote(number, set_id), Consider::RejectPast);
@@ -700,18 +1220,379 @@ pub(crate) mod tests {
 		assert_eq!(report_stream.try_recv().unwrap(), expected_report);
 	}
 
+	#[test]
+	fn should_rate_limit_votes() {
+		let keys = vec![Keyring::Alice.public()];
+		let validator_set = ValidatorSet::<AuthorityId>::new(keys, 0).unwrap();
+		let (gv, mut report_stream) =
+			GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
+		let sender = PeerId::random();
+		let mut context = TestContext;
+
+		// votes for Alice, signed by Bob.
+		let bad_vote = |block_number, payload: &[u8]| {
+			let mut vote = dummy_vote(block_number);
+			vote.commitment.payload =
+				Payload::from_single_entry(known_payloads::MMR_ROOT_ID, payload.encode());
+			vote.signature = sign_commitment(&Keyring::Bob, &vote.commitment);
+			GossipMessage::<Block>::Vote(vote).encode()
+		};
+		let mut expect_discard = |encoded: &[u8], cost| {
+			let res = gv.validate(&mut context, &sender, encoded);
+			assert!(matches!(res, ValidationResult::Discard));
+			let expected_report = PeerReport { who: sender, cost_benefit: cost };
+			assert_eq!(report_stream.try_recv().unwrap(), expected_report);
+		};
+
+		// signature is checked the first time.
+		expect_discard(&bad_vote(3, b"first"), cost::BAD_SIGNATURE);
+		// but not when the same peer sends the same vote again.
+		expect_discard(&bad_vote(3, b"first"), cost::DUPLICATE_VOTE);
+		// up to `MAX_VOTES_PER_VOTER` votes per voter are checked in a round.
+		expect_discard(&bad_vote(3, b"second"), cost::BAD_SIGNATURE);
+		expect_discard(&bad_vote(3, b"third"), cost::TOO_MANY_VOTES);
+		// other rounds are not affected.
+		expect_discard(&bad_vote(4, b"third"), cost::BAD_SIGNATURE);
+
+		// rounds which are no longer live are forgotten.
+		gv.update_filter(GossipFilterCfg {
+			start: 4,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
+		assert_eq!(gv.known_peers.lock().note_checked_vote(sender, 3, [0; 8], 1), VoteCheck::New);
+	}
+
+	fn vote_by(who: Keyring, block_number: u64) -> VoteMessage<u64, AuthorityId, Signature> {
+		let mut vote = dummy_vote(block_number);
+		vote.signature = sign_commitment(&who, &vote.commitment);
+		vote.id = who.public();
+		vote
+	}
+
+	#[test]
+	fn should_validate_vote_batches() {
+		let keys = vec![Keyring::Alice.public(), Keyring::Bob.public(), Keyring::Charlie.public()];
+		let validator_set = ValidatorSet::<AuthorityId>::new(keys, 0).unwrap();
+		let (gv, mut report_stream) =
+			GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
+		let sender = PeerId::random();
+		let mut context = TestContext;
+		let batch = |votes: Vec<VoteMessage<u64, AuthorityId, Signature>>| {
+			let commitment = dummy_vote(3).commitment;
+			let votes = votes.into_iter().map(|vote| (vote.id, vote.signature)).collect();
+			GossipMessage::<Block>::VoteBatch(VoteBatch { commitment, votes }).encode()
+		};
+		let mut expect_report = |encoded: &[u8], cost_benefit| {
+			let res = gv.validate(&mut context, &sender, encoded);
+			let expected_report = PeerReport { who: sender, cost_benefit };
+			assert_eq!(report_stream.try_recv().unwrap(), expected_report);
+			res
+		};
+
+		// bounds of the batch size.
+		let res = expect_report(&batch(vec![]), cost::BAD_BATCH);
+		assert!(matches!(res, ValidationResult::Discard));
+		let oversized = vec![vote_by(Keyring::Alice, 3); MAX_VOTES_PER_BATCH + 1];
+		let res = expect_report(&batch(oversized), cost::BAD_BATCH);
+		assert!(matches!(res, ValidationResult::Discard));
+
+		// a batch with a bad signature is discarded as a whole.
+		let mut bad_vote = vote_by(Keyring::Charlie, 3);
+		bad_vote.signature = vote_by(Keyring::Bob, 3).signature;
+		let res =
+			expect_report(&batch(vec![vote_by(Keyring::Alice, 3), bad_vote]), cost::BAD_SIGNATURE);
+		assert!(matches!(res, ValidationResult::Discard));
+
+		// batched votes become known, whether they come in a batch or on their own.
+		let votes = vec![vote_by(Keyring::Alice, 3), vote_by(Keyring::Bob, 3)];
+		let res = expect_report(&batch(votes), benefit::VOTE_MESSAGE);
+		assert!(matches!(res, ValidationResult::ProcessAndKeep(_)));
+		let single = GossipMessage::<Block>::Vote(vote_by(Keyring::Bob, 3)).encode();
+		let res = expect_report(&single, benefit::KNOWN_VOTE_MESSAGE);
+		assert!(matches!(res, ValidationResult::ProcessAndKeep(_)));
+		let votes = vec![vote_by(Keyring::Alice, 3), vote_by(Keyring::Bob, 3)];
+		let res = expect_report(&batch(votes), benefit::KNOWN_VOTE_MESSAGE);
+		assert!(matches!(res, ValidationResult::ProcessAndKeep(_)));
+	}
+
+	#[derive(Default)]
+	struct RecordingContext(Vec<Vec<u8>>);
+	impl<B: sp_runtime::traits::Block> ValidatorContext<B> for RecordingContext {
+		fn broadcast_topic(&mut self, _topic: B::Hash, _force: bool) {
+			todo!()
+		}
+
+		fn broadcast_message(&mut self, _topic: B::Hash, message: Vec<u8>, _force: bool) {
+			self.0.push(message);
+		}
+
+		fn send_message(&mut self, _who: &sc_network::PeerId, _message: Vec<u8>) {
+			todo!()
+		}
+
+		fn send_topic(&mut self, _who: &sc_network::PeerId, _topic: B::Hash, _force: bool) {
+			todo!()
+		}
+	}
+
+	#[test]
+	fn should_regossip_votes_in_batches() {
+		let keys = vec![Keyring::Alice.public(), Keyring::Bob.public(), Keyring::Charlie.public()];
+		let validator_set = ValidatorSet::<AuthorityId>::new(keys, 0).unwrap();
+		let (gv, _) = GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
+		let sender = PeerId::random();
+		let mut context = RecordingContext::default();
+
+		// new votes are held back.
+		let alice = GossipMessage::<Block>::Vote(vote_by(Keyring::Alice, 3)).encode();
+		let bob = GossipMessage::<Block>::Vote(vote_by(Keyring::Bob, 3)).encode();
+		gv.validate(&mut context, &sender, &alice);
+		gv.validate(&mut context, &sender, &bob);
+		assert!(context.0.is_empty());
+
+		// and not re-gossiped on their own.
+		let topic = Default::default();
+		let mut allowed = gv.message_allowed();
+		assert!(!allowed(&sender, MessageIntent::Broadcast, &topic, &alice));
+		assert!(allowed(&sender, MessageIntent::ForcedBroadcast, &topic, &alice));
+		drop(allowed);
+
+		// once the delay passed, the next vote triggers gossiping the batch.
+		gv.pending_batches.lock().since = Some(Instant::now() - BATCH_DELAY);
+		let charlie = GossipMessage::<Block>::Vote(vote_by(Keyring::Charlie, 3)).encode();
+		gv.validate(&mut context, &sender, &charlie);
+		assert_eq!(context.0.len(), 1);
+		let batch = match GossipMessage::<Block>::decode(&mut &context.0[0][..]).unwrap() {
+			GossipMessage::VoteBatch(batch) => batch,
+			other => panic!("unexpected message: {:?}", other),
+		};
+		assert_eq!(batch.commitment, dummy_vote(3).commitment);
+		let voters: Vec<_> = batch.votes.iter().map(|(id, _)| id.clone()).collect();
+		assert_eq!(
+			voters,
+			vec![Keyring::Alice.public(), Keyring::Bob.public(), Keyring::Charlie.public()]
+		);
+
+		// the batch itself can be gossiped.
+		let mut allowed = gv.message_allowed();
+		assert!(allowed(&sender, MessageIntent::Broadcast, &topic, &context.0[0]));
+		assert!(gv.pending_batches.lock().batches.is_empty());
+	}
+
+	#[test]
+	fn should_release_held_back_votes_without_further_messages() {
+		let keys = vec![Keyring::Alice.public(), Keyring::Bob.public()];
+		let validator_set = ValidatorSet::<AuthorityId>::new(keys, 0).unwrap();
+		let (gv, _) = GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
+		let sender = PeerId::random();
+		let mut context = RecordingContext::default();
+
+		let alice = GossipMessage::<Block>::Vote(vote_by(Keyring::Alice, 3)).encode();
+		let bob = GossipMessage::<Block>::Vote(vote_by(Keyring::Bob, 3)).encode();
+		gv.validate(&mut context, &sender, &alice);
+		gv.validate(&mut context, &sender, &bob);
+		assert!(context.0.is_empty());
+
+		// held back before the delay passed.
+		let topic = Default::default();
+		let mut allowed = gv.message_allowed();
+		assert!(!allowed(&sender, MessageIntent::Broadcast, &topic, &alice));
+		assert!(!allowed(&sender, MessageIntent::Broadcast, &topic, &bob));
+		drop(allowed);
+
+		// no further message arrives, the next propagation lets the votes out on their own.
+		gv.pending_batches.lock().since = Some(Instant::now() - BATCH_DELAY);
+		let mut allowed = gv.message_allowed();
+		assert!(allowed(&sender, MessageIntent::Broadcast, &topic, &alice));
+		assert!(allowed(&sender, MessageIntent::Broadcast, &topic, &bob));
+		drop(allowed);
+		assert!(gv.pending_batches.lock().batches.is_empty());
+
+		// and they are not gossiped again as part of a batch.
+		let alice_next = GossipMessage::<Block>::Vote(vote_by(Keyring::Alice, 4)).encode();
+		gv.validate(&mut context, &sender, &alice_next);
+		gv.pending_batches.lock().since = Some(Instant::now() - BATCH_DELAY);
+		let bob_next = GossipMessage::<Block>::Vote(vote_by(Keyring::Bob, 4)).encode();
+		gv.validate(&mut context, &sender, &bob_next);
+		assert_eq!(context.0.len(), 1);
+		let batch = match GossipMessage::<Block>::decode(&mut &context.0[0][..]).unwrap() {
+			GossipMessage::VoteBatch(batch) => batch,
+			other => panic!("unexpected message: {:?}", other),
+		};
+		assert_eq!(batch.votes.len(), 2);
+		assert_eq!(batch.commitment, dummy_vote(4).commitment);
+	}
+
+	#[test]
+	fn should_gossip_batched_votes_on_their_own_to_legacy_peers() {
+		let keys = vec![Keyring::Alice.public(), Keyring::Bob.public(), Keyring::Charlie.public()];
+		let validator_set = ValidatorSet::<AuthorityId>::new(keys, 0).unwrap();
+		let (gv, _) = GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
+		let (legacy, current) = (PeerId::random(), PeerId::random());
+		let mut context = RecordingContext::default();
+		let legacy_name = ProtocolName::from("/beefy/2");
+		gv.new_peer_with_fallback(
+			&mut context,
+			&legacy,
+			ObservedRole::Authority,
+			Some(&legacy_name),
+		);
+		gv.new_peer_with_fallback(&mut context, &current, ObservedRole::Authority, None);
+		let topic = Default::default();
+
+		// held back votes are still gossiped, but only reach legacy peers.
+		let alice = GossipMessage::<Block>::Vote(vote_by(Keyring::Alice, 3)).encode();
+		gv.validate(&mut context, &current, &alice);
+		assert_eq!(context.0, vec![alice.clone()]);
+		let mut allowed = gv.message_allowed();
+		assert!(allowed(&legacy, MessageIntent::Broadcast, &topic, &alice));
+		assert!(!allowed(&current, MessageIntent::Broadcast, &topic, &alice));
+		drop(allowed);
+
+		// the same goes for the votes of a batch, which only reaches current peers.
+		let bob = vote_by(Keyring::Bob, 3);
+		let batch = GossipMessage::<Block>::VoteBatch(VoteBatch {
+			commitment: bob.commitment.clone(),
+			votes: vec![(bob.id.clone(), bob.signature.clone())],
+		})
+		.encode();
+		let bob = GossipMessage::<Block>::Vote(bob).encode();
+		context.0.clear();
+		gv.validate(&mut context, &current, &batch);
+		assert_eq!(context.0[0], bob);
+		let mut allowed = gv.message_allowed();
+		assert!(allowed(&legacy, MessageIntent::Broadcast, &topic, &bob));
+		assert!(!allowed(&current, MessageIntent::Broadcast, &topic, &bob));
+		assert!(!allowed(&legacy, MessageIntent::Broadcast, &topic, &batch));
+		assert!(allowed(&current, MessageIntent::Broadcast, &topic, &batch));
+		drop(allowed);
+
+		// without legacy peers, votes are held back again.
+		gv.peer_disconnected(&mut context, &legacy);
+		context.0.clear();
+		let charlie = GossipMessage::<Block>::Vote(vote_by(Keyring::Charlie, 3)).encode();
+		gv.validate(&mut context, &current, &charlie);
+		assert!(context.0.is_empty());
+	}
+
+	#[test]
+	fn should_only_gossip_known_messages_to_legacy_peers() {
+		let keys = vec![Keyring::Alice.public(), Keyring::Bob.public()];
+		let validator_set = ValidatorSet::<AuthorityId>::new(keys, 0).unwrap();
+		let (gv, _) = GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
+		let (legacy, current) = (PeerId::random(), PeerId::random());
+		let mut context = RecordingContext::default();
+		let legacy_name = ProtocolName::from("/beefy/2");
+		gv.new_peer_with_fallback(
+			&mut context,
+			&legacy,
+			ObservedRole::Authority,
+			Some(&legacy_name),
+		);
+		gv.new_peer_with_fallback(&mut context, &current, ObservedRole::Authority, None);
+
+		let commitment = dummy_vote(3).commitment;
+		let vote = GossipMessage::<Block>::Vote(vote_by(Keyring::Alice, 3)).encode();
+		let proof = GossipMessage::<Block>::FinalityProof(dummy_proof(3, &validator_set)).encode();
+		let secondary = GossipMessage::<Block>::SecondaryVote(VoteMessage {
+			commitment: commitment.clone(),
+			id: Keyring::Alice.public(),
+			signature: vec![1, 2, 3],
+		})
+		.encode();
+		let batch = GossipMessage::<Block>::VoteBatch(VoteBatch {
+			commitment,
+			votes: vec![(Keyring::Bob.public(), vote_by(Keyring::Bob, 3).signature)],
+		})
+		.encode();
+
+		let topic = Default::default();
+		let mut allowed = gv.message_allowed();
+		for intent in [MessageIntent::Broadcast, MessageIntent::ForcedBroadcast] {
+			for message in [&vote, &proof, &secondary, &batch] {
+				assert!(allowed(&current, intent, &topic, message));
+			}
+			assert!(allowed(&legacy, intent, &topic, &vote));
+			assert!(allowed(&legacy, intent, &topic, &proof));
+			// legacy peers can't decode these.
+			assert!(!allowed(&legacy, intent, &topic, &secondary));
+			assert!(!allowed(&legacy, intent, &topic, &batch));
+		}
+		drop(allowed);
+
+		*gv.next_rebroadcast.lock() = Instant::now();
+		let mut allowed = gv.message_allowed();
+		let intent = MessageIntent::PeriodicRebroadcast;
+		assert!(allowed(&legacy, intent, &topic, &vote));
+		assert!(!allowed(&legacy, intent, &topic, &batch));
+		drop(allowed);
+
+		// the same peer may reconnect with the current version.
+		gv.peer_disconnected(&mut context, &legacy);
+		let mut allowed = gv.message_allowed();
+		assert!(allowed(&legacy, MessageIntent::Broadcast, &topic, &batch));
+	}
+
 	#[test]
 	fn messages_allowed_and_expired() {
 		let keys = vec![Keyring::Alice.public()];
 		let validator_set = ValidatorSet::<AuthorityId>::new(keys.clone(), 0).unwrap();
 		let (gv, _) = GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
-		gv.update_filter(GossipFilterCfg { start: 0, end: 10, validator_set: &validator_set });
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
 		let sender = sc_network::PeerId::random();
 		let topic = Default::default();
 		let intent = MessageIntent::Broadcast;
 
 		// conclude 2
-		gv.update_filter(GossipFilterCfg { start: 2, end: 10, validator_set: &validator_set });
+		gv.update_filter(GossipFilterCfg {
+			start: 2,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
 		let mut allowed = gv.message_allowed();
 		let mut expired = gv.message_expired();
 
@@ -782,7 +1663,12 @@ pub(crate) mod tests {
 		let keys = vec![Keyring::Alice.public()];
 		let validator_set = ValidatorSet::<AuthorityId>::new(keys.clone(), 0).unwrap();
 		let (gv, _) = GossipValidator::<Block>::new(Arc::new(Mutex::new(KnownPeers::new())));
-		gv.update_filter(GossipFilterCfg { start: 0, end: 10, validator_set: &validator_set });
+		gv.update_filter(GossipFilterCfg {
+			start: 0,
+			end: 10,
+			validator_set: &validator_set,
+			hasher: SignatureHasher::Keccak256,
+		});
 		let sender = sc_network::PeerId::random();
 		let topic = Default::default();
 
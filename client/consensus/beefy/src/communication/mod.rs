@@ -29,7 +29,12 @@ pub(crate) mod beefy_protocol_name {
 	use sc_network::ProtocolName;
 
 	/// BEEFY votes gossip protocol name suffix.
-	const GOSSIP_NAME: &str = "/beefy/2";
+	///
+	/// Version 3 adds secondary votes, while dual signing, and vote batches.
+	const GOSSIP_NAME: &str = "/beefy/3";
+	/// Suffixes of the older versions of the votes gossip protocol, still spoken with peers that
+	/// don't support the current one.
+	const LEGACY_GOSSIP_NAMES: [&str; 1] = ["/beefy/2"];
 	/// BEEFY justifications protocol name suffix.
 	const JUSTIFICATIONS_NAME: &str = "/beefy/justifications/1";
 
@@ -48,6 +53,20 @@ pub(crate) mod beefy_protocol_name {
 		}
 	}
 
+	/// Names of the older versions of the votes gossip protocol `gossip_protocol_name`, as returned
+	/// by [`gossip_protocol_name`].
+	pub fn legacy_gossip_protocol_names(gossip_protocol_name: &str) -> Vec<ProtocolName> {
+		gossip_protocol_name
+			.strip_suffix(GOSSIP_NAME)
+			.map(|prefix| {
+				LEGACY_GOSSIP_NAMES
+					.iter()
+					.map(|name| format!("{}{}", prefix, name).into())
+					.collect()
+			})
+			.unwrap_or_default()
+	}
+
 	/// Name of the BEEFY justifications request-response protocol.
 	pub fn justifications_protocol_name<Hash: AsRef<[u8]>>(
 		genesis_hash: Hash,
@@ -65,11 +84,16 @@ pub(crate) mod beefy_protocol_name {
 /// Returns the configuration value to put in
 /// [`sc_network::config::FullNetworkConfiguration`].
 /// For standard protocol name see [`beefy_protocol_name::gossip_protocol_name`].
+///
+/// Peers only supporting an older version of the protocol connect through its legacy names, see
+/// [`beefy_protocol_name::legacy_gossip_protocol_names`].
 pub fn beefy_peers_set_config(
 	gossip_protocol_name: sc_network::ProtocolName,
 ) -> sc_network::config::NonDefaultSetConfig {
+	let fallback_names = beefy_protocol_name::legacy_gossip_protocol_names(&gossip_protocol_name);
 	let mut cfg = sc_network::config::NonDefaultSetConfig::new(gossip_protocol_name, 1024 * 1024);
 	cfg.allow_non_reserved(25, 25);
+	cfg.add_fallback_names(fallback_names);
 	cfg
 }
 
@@ -82,6 +106,12 @@ mod cost {
 	pub(super) const FUTURE_MESSAGE: Rep = Rep::new(-100, "BEEFY: Future message");
 	// Vote message containing bad signature.
 	pub(super) const BAD_SIGNATURE: Rep = Rep::new(-100, "BEEFY: Bad signature");
+	// Vote message already received from the same peer.
+	pub(super) const DUPLICATE_VOTE: Rep = Rep::new(-100, "BEEFY: Duplicate vote");
+	// Vote batch that is empty or exceeds the maximum batch size.
+	pub(super) const BAD_BATCH: Rep = Rep::new(-250, "BEEFY: Bad vote batch");
+	// Vote message exceeding the number of votes accepted from a peer per round.
+	pub(super) const TOO_MANY_VOTES: Rep = Rep::new(-250, "BEEFY: Too many votes");
 	// Message received with vote from voter not in validator set.
 	pub(super) const UNKNOWN_VOTER: Rep = Rep::new(-150, "BEEFY: Unknown voter");
 	// A message received that cannot be evaluated relative to our current state.
@@ -134,7 +164,7 @@ mod tests {
 		];
 		let genesis_hex = "32043c7b3a6ad8f6c2bc8bc121d4caab09377b5e082b0cfbbb39ad13bc4acd93";
 
-		let expected_gossip_name = format!("/{}/beefy/2", genesis_hex);
+		let expected_gossip_name = format!("/{}/beefy/3", genesis_hex);
 		let gossip_proto_name = gossip_protocol_name(&genesis_hash, None);
 		assert_eq!(gossip_proto_name.to_string(), expected_gossip_name);
 
@@ -142,4 +172,23 @@ mod tests {
 		let justif_proto_name = justifications_protocol_name(&genesis_hash, None);
 		assert_eq!(justif_proto_name.to_string(), expected_justif_name);
 	}
+
+	#[test]
+	fn beefy_legacy_gossip_protocol_names() {
+		use beefy_protocol_name::{gossip_protocol_name, legacy_gossip_protocol_names};
+		let genesis_hash = H256::random();
+		let genesis_hex = array_bytes::bytes2hex("", genesis_hash);
+
+		let gossip_proto_name = gossip_protocol_name(&genesis_hash, Some("fork"));
+		assert_eq!(
+			legacy_gossip_protocol_names(&gossip_proto_name),
+			vec![format!("/{}/fork/beefy/2", genesis_hex).into()],
+		);
+
+		let config = beefy_peers_set_config(gossip_protocol_name(&genesis_hash, None));
+		assert_eq!(config.fallback_names, vec![format!("/{}/beefy/2", genesis_hex).into()]);
+
+		// custom protocol names have no known older versions.
+		assert!(legacy_gossip_protocol_names("/custom/beefy").is_empty());
+	}
 }
@@ -20,7 +20,9 @@
 
 use sc_network::{PeerId, ReputationChange};
 use sp_runtime::traits::{Block, NumberFor, Zero};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use crate::communication::gossip::MessageHash;
 
 /// Report specifying a reputation change for a given peer.
 #[derive(Debug, PartialEq)]
@@ -29,13 +31,26 @@ pub(crate) struct PeerReport {
 	pub cost_benefit: ReputationChange,
 }
 
+/// Outcome of [`KnownPeers::note_checked_vote`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VoteCheck {
+	/// The vote was not sent by the peer before, and should be checked.
+	New,
+	/// The peer already sent this vote.
+	Duplicate,
+	/// The peer already sent too many votes for this round.
+	TooMany,
+}
+
 struct PeerData<B: Block> {
 	last_voted_on: NumberFor<B>,
+	/// Hashes of the votes received from the peer which had to be checked, per round.
+	checked_votes: BTreeMap<NumberFor<B>, HashSet<MessageHash>>,
 }
 
 impl<B: Block> Default for PeerData<B> {
 	fn default() -> Self {
-		PeerData { last_voted_on: Zero::zero() }
+		PeerData { last_voted_on: Zero::zero(), checked_votes: BTreeMap::new() }
 	}
 }
 
@@ -56,6 +71,36 @@ impl<B: Block> KnownPeers<B> {
 		data.last_voted_on = round.max(data.last_voted_on);
 	}
 
+	/// Note that the vote with `hash` for `round` was received from `peer`, and is about to have
+	/// its signature checked.
+	///
+	/// Every peer can make us check at most `limit` distinct votes per round, while it should
+	/// never send the same vote twice.
+	pub fn note_checked_vote(
+		&mut self,
+		peer: PeerId,
+		round: NumberFor<B>,
+		hash: MessageHash,
+		limit: usize,
+	) -> VoteCheck {
+		let checked = self.live.entry(peer).or_default().checked_votes.entry(round).or_default();
+		if checked.contains(&hash) {
+			VoteCheck::Duplicate
+		} else if checked.len() >= limit {
+			VoteCheck::TooMany
+		} else {
+			checked.insert(hash);
+			VoteCheck::New
+		}
+	}
+
+	/// Forget about the votes received for rounds before `round`.
+	pub fn forget_votes_before(&mut self, round: NumberFor<B>) {
+		for data in self.live.values_mut() {
+			data.checked_votes = data.checked_votes.split_off(&round);
+		}
+	}
+
 	/// Remove connected `peer`.
 	pub fn remove(&mut self, peer: &PeerId) {
 		self.live.remove(peer);
@@ -124,4 +169,27 @@ mod tests {
 		assert_eq!(further_than_9.len(), 1);
 		assert!(further_than_9.contains(&charlie));
 	}
+
+	#[test]
+	fn should_rate_limit_checked_votes() {
+		let (alice, bob) = (PeerId::random(), PeerId::random());
+		let mut peers = KnownPeers::<sc_network_test::Block>::new();
+
+		assert_eq!(peers.note_checked_vote(alice, 5, [1; 8], 2), VoteCheck::New);
+		// same vote from the same peer is a duplicate.
+		assert_eq!(peers.note_checked_vote(alice, 5, [1; 8], 2), VoteCheck::Duplicate);
+		// but not from another peer, or for another round.
+		assert_eq!(peers.note_checked_vote(bob, 5, [1; 8], 2), VoteCheck::New);
+		assert_eq!(peers.note_checked_vote(alice, 6, [1; 8], 2), VoteCheck::New);
+
+		// at most `limit` votes per round are checked.
+		assert_eq!(peers.note_checked_vote(alice, 5, [2; 8], 2), VoteCheck::New);
+		assert_eq!(peers.note_checked_vote(alice, 5, [3; 8], 2), VoteCheck::TooMany);
+		assert_eq!(peers.note_checked_vote(alice, 5, [2; 8], 2), VoteCheck::Duplicate);
+
+		// old rounds are forgotten.
+		peers.forget_votes_before(6);
+		assert_eq!(peers.note_checked_vote(alice, 5, [3; 8], 2), VoteCheck::New);
+		assert_eq!(peers.note_checked_vote(alice, 6, [1; 8], 2), VoteCheck::Duplicate);
+	}
 }
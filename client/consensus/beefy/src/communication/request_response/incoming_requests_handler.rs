@@ -16,7 +16,7 @@
 
 //! Helper for handling (i.e. answering) BEEFY justifications requests from a remote peer.
 
-use codec::Decode;
+use codec::{Decode, Encode};
 use futures::{channel::oneshot, StreamExt};
 use log::{debug, error, trace};
 use sc_client_api::BlockBackend;
@@ -25,7 +25,10 @@ use sc_network::{
 	ReputationChange,
 };
 use sp_consensus_beefy::BEEFY_ENGINE_ID;
-use sp_runtime::traits::Block;
+use sp_runtime::{
+	traits::{Block, NumberFor, One},
+	Saturating,
+};
 use std::{marker::PhantomData, sync::Arc};
 
 use crate::{
@@ -33,9 +36,11 @@ use crate::{
 		cost,
 		request_response::{
 			on_demand_justifications_protocol_config, Error, JustificationRequest,
-			BEEFY_SYNC_LOG_TARGET,
+			BEEFY_SYNC_LOG_TARGET, MAX_FAST_FORWARD_RANGE, MAX_JUSTIFICATION_RANGE,
+			MAX_RESPONSE_SIZE,
 		},
 	},
+	justification::{proof_block_num_and_set_id, BeefyVersionedFinalityProof},
 	metric_inc,
 	metrics::{register_metrics, OnDemandIncomingRequestsMetrics},
 };
@@ -160,17 +165,100 @@ where
 		self.justif_protocol_name.clone()
 	}
 
-	// Sends back justification response if justification found in client backend.
-	fn handle_request(&self, request: IncomingRequest<B>) -> Result<(), Error> {
-		let mut reputation_changes = vec![];
-		let maybe_encoded_proof = self
-			.client
-			.block_hash(request.payload.begin)
+	// Returns the encoded BEEFY justification of block `hash`, if found in client backend.
+	fn justification(&self, hash: B::Hash) -> Option<Vec<u8>> {
+		self.client
+			.justifications(hash)
 			.ok()
 			.flatten()
-			.and_then(|hash| self.client.justifications(hash).ok().flatten())
 			.and_then(|justifs| justifs.get(BEEFY_ENGINE_ID).cloned())
-			.ok_or_else(|| reputation_changes.push(cost::UNKOWN_PROOF_REQUEST));
+	}
+
+	// Returns the encoded BEEFY justifications of the `count` blocks starting at `begin`,
+	// stopping early at the last known block or when the response would get too large.
+	fn justifications_range(&self, begin: NumberFor<B>, count: u32) -> Vec<Vec<u8>> {
+		let mut justifs = vec![];
+		// leave room for the length prefix of the vector.
+		let mut size = 5;
+		let mut number = begin;
+		for _ in 0..count.min(MAX_JUSTIFICATION_RANGE) {
+			let hash = match self.client.block_hash(number) {
+				Ok(Some(hash)) => hash,
+				_ => break,
+			};
+			if let Some(justif) = self.justification(hash) {
+				size += justif.encoded_size();
+				if size as u64 > MAX_RESPONSE_SIZE {
+					break
+				}
+				justifs.push(justif);
+			}
+			number = number.saturating_add(One::one());
+		}
+		justifs
+	}
+
+	// Returns the encoded BEEFY justifications to fast-forward a node lagging at block `begin`: the
+	// first justification of each validator set, followed by the latest justification.
+	//
+	// Stops early at the last known block, after `MAX_FAST_FORWARD_RANGE` blocks or when the
+	// response would get too large.
+	fn fast_forward_justifications(&self, begin: NumberFor<B>) -> Vec<Vec<u8>> {
+		let mut handoffs = vec![];
+		let mut latest = None;
+		let mut last_set_id = None;
+		// leave room for the length prefix of the vector.
+		let mut size = 5;
+		let mut number = begin;
+		for _ in 0..MAX_FAST_FORWARD_RANGE {
+			let hash = match self.client.block_hash(number) {
+				Ok(Some(hash)) => hash,
+				_ => break,
+			};
+			number = number.saturating_add(One::one());
+			let justif = match self.justification(hash) {
+				Some(justif) => justif,
+				None => continue,
+			};
+			let set_id = match BeefyVersionedFinalityProof::<B>::decode(&mut &justif[..]) {
+				Ok(proof) => proof_block_num_and_set_id::<B>(&proof).1,
+				Err(_) => continue,
+			};
+			if last_set_id.map_or(true, |last| set_id > last) {
+				size += justif.encoded_size();
+				if size as u64 > MAX_RESPONSE_SIZE {
+					break
+				}
+				last_set_id = Some(set_id);
+				handoffs.push(justif);
+				latest = None;
+			} else {
+				latest = Some(justif);
+			}
+		}
+		if let Some(latest) = latest {
+			if (size + latest.encoded_size()) as u64 <= MAX_RESPONSE_SIZE {
+				handoffs.push(latest);
+			}
+		}
+		handoffs
+	}
+
+	// Sends back justification(s) response if justification(s) found in client backend.
+	fn handle_request(&self, request: IncomingRequest<B>) -> Result<(), Error> {
+		let mut reputation_changes = vec![];
+		let JustificationRequest { begin, count } = request.payload;
+		let maybe_encoded_proof = match count {
+			None =>
+				self.client.block_hash(begin).ok().flatten().and_then(|h| self.justification(h)),
+			Some(0) => Some(self.fast_forward_justifications(begin))
+				.filter(|j| !j.is_empty())
+				.map(|j| j.encode()),
+			Some(count) => Some(self.justifications_range(begin, count))
+				.filter(|j| !j.is_empty())
+				.map(|j| j.encode()),
+		}
+		.ok_or_else(|| reputation_changes.push(cost::UNKOWN_PROOF_REQUEST));
 		request
 			.pending_response
 			.send(netconfig::OutgoingResponse {
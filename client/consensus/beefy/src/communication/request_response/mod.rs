@@ -25,7 +25,7 @@ pub use incoming_requests_handler::BeefyJustifsRequestHandler;
 
 use std::time::Duration;
 
-use codec::{Decode, Encode, Error as CodecError};
+use codec::{Decode, Encode, Error as CodecError, Input, Output};
 use sc_network::{config::RequestResponseConfig, PeerId};
 use sp_runtime::traits::{Block, NumberFor};
 
@@ -37,6 +37,10 @@ use incoming_requests_handler::IncomingRequestReceiver;
 const JUSTIF_CHANNEL_SIZE: usize = 10;
 
 const MAX_RESPONSE_SIZE: u64 = 1024 * 1024;
+/// Maximum number of blocks covered by a single range request.
+const MAX_JUSTIFICATION_RANGE: u32 = 1024;
+/// Maximum number of blocks looked through when answering a single fast-forward request.
+const MAX_FAST_FORWARD_RANGE: u32 = 64 * MAX_JUSTIFICATION_RANGE;
 const JUSTIF_REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
 
 const BEEFY_SYNC_LOG_TARGET: &str = "beefy::sync";
@@ -68,10 +72,58 @@ pub(crate) fn on_demand_justifications_protocol_config<Hash: AsRef<[u8]>>(
 }
 
 /// BEEFY justification request.
-#[derive(Debug, Clone, Encode, Decode)]
+///
+/// Requests the justification of block `begin` or, if `count` is set, all justifications of the
+/// `count` blocks starting at `begin`. The response is then a SCALE-encoded `Vec` of encoded
+/// justifications, instead of a single one. `count` is capped at `MAX_JUSTIFICATION_RANGE`.
+///
+/// A `count` of zero requests a fast-forward from `begin` instead, see [`Self::fast_forward`].
+///
+/// `count` is only encoded when set, so that requests without it stay compatible with peers
+/// which don't know about range requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct JustificationRequest<B: Block> {
 	/// Start collecting proofs from this block.
 	pub begin: NumberFor<B>,
+	/// Number of blocks to collect proofs for, if any.
+	pub count: Option<u32>,
+}
+
+impl<B: Block> JustificationRequest<B> {
+	/// Request the justifications needed by a node lagging at block `begin` to catch up at once.
+	///
+	/// The response is a SCALE-encoded `Vec` of encoded justifications, ordered by block number:
+	/// the justification of the first block of each validator set from `begin` on, i.e. the
+	/// validator set handoffs, followed by the latest justification known to the peer. Responses
+	/// may end early, in which case the request can be repeated from the last handoff.
+	pub fn fast_forward(begin: NumberFor<B>) -> Self {
+		Self { begin, count: Some(0) }
+	}
+
+	/// Return `true` if this is a fast-forward request.
+	pub fn is_fast_forward(&self) -> bool {
+		self.count == Some(0)
+	}
+}
+
+impl<B: Block> Encode for JustificationRequest<B> {
+	fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+		self.begin.encode_to(dest);
+		if let Some(count) = self.count {
+			count.encode_to(dest);
+		}
+	}
+}
+
+impl<B: Block> Decode for JustificationRequest<B> {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, CodecError> {
+		let begin = NumberFor::<B>::decode(input)?;
+		let count = match input.remaining_len()? {
+			Some(0) => None,
+			_ => Some(u32::decode(input)?),
+		};
+		Ok(Self { begin, count })
+	}
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -103,3 +155,28 @@ pub(crate) enum Error {
 	#[error("Internal error while getting response.")]
 	ResponseError,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use substrate_test_runtime_client::runtime::Block;
+
+	#[test]
+	fn justification_request_encoding_is_backwards_compatible() {
+		let single = JustificationRequest::<Block> { begin: 42, count: None };
+		assert_eq!(single.encode(), 42u64.encode());
+		assert_eq!(JustificationRequest::<Block>::decode(&mut &single.encode()[..]), Ok(single));
+
+		let range = JustificationRequest::<Block> { begin: 42, count: Some(10) };
+		assert_eq!(range.encode(), (42u64, 10u32).encode());
+		assert_eq!(JustificationRequest::<Block>::decode(&mut &range.encode()[..]), Ok(range));
+
+		let fast_forward = JustificationRequest::<Block>::fast_forward(42);
+		assert!(fast_forward.is_fast_forward() && !range.is_fast_forward());
+		assert_eq!(fast_forward.encode(), (42u64, 0u32).encode());
+
+		// trailing garbage is not a valid count.
+		let bad = [42u64.encode(), vec![1]].concat();
+		assert!(JustificationRequest::<Block>::decode(&mut &bad[..]).is_err());
+	}
+}
@@ -18,7 +18,7 @@
 
 //! Generating request logic for request/response protocol for syncing BEEFY justifications.
 
-use codec::Encode;
+use codec::{Decode, Encode};
 use futures::channel::{oneshot, oneshot::Canceled};
 use log::{debug, warn};
 use parking_lot::Mutex;
@@ -26,7 +26,8 @@ use sc_network::{
 	request_responses::{IfDisconnected, RequestFailure},
 	NetworkRequest, PeerId, ProtocolName,
 };
-use sp_consensus_beefy::{crypto::AuthorityId, ValidatorSet};
+use sp_consensus::Error as ConsensusError;
+use sp_consensus_beefy::{crypto::AuthorityId, SignatureHasher, ValidatorSet};
 use sp_runtime::traits::{Block, NumberFor};
 use std::{collections::VecDeque, result::Result, sync::Arc};
 
@@ -36,7 +37,10 @@ use crate::{
 		peers::PeerReport,
 		request_response::{Error, JustificationRequest, BEEFY_SYNC_LOG_TARGET},
 	},
-	justification::{decode_and_verify_finality_proof, BeefyVersionedFinalityProof},
+	justification::{
+		decode_and_verify_finality_proof, proof_block_num_and_set_id, verify_with_validator_set,
+		BeefyVersionedFinalityProof,
+	},
 	metric_inc,
 	metrics::{register_metrics, OnDemandOutgoingRequestsMetrics},
 	KnownPeers,
@@ -51,6 +55,9 @@ type ResponseReceiver = oneshot::Receiver<Response>;
 struct RequestInfo<B: Block> {
 	block: NumberFor<B>,
 	active_set: ValidatorSet<AuthorityId>,
+	hasher: SignatureHasher,
+	/// Validator sets of all sessions to fast-forward through, if this is a fast-forward request.
+	fast_forward: Option<Vec<ValidatorSet<AuthorityId>>>,
 }
 
 enum State<B: Block> {
@@ -64,6 +71,9 @@ pub(crate) enum ResponseInfo<B: Block> {
 	Pending,
 	/// Valid justification provided alongside peer reputation changes.
 	ValidProof(BeefyVersionedFinalityProof<B>, PeerReport),
+	/// Valid justifications of a fast-forward request, ordered by block number, alongside peer
+	/// reputation changes.
+	ValidProofs(Vec<BeefyVersionedFinalityProof<B>>, PeerReport),
 	/// No justification yet, only peer reputation changes.
 	PeerReport(PeerReport),
 }
@@ -117,7 +127,12 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 			"🥩 requesting justif #{:?} from peer {:?}", req_info.block, peer,
 		);
 
-		let payload = JustificationRequest::<B> { begin: req_info.block }.encode();
+		let payload = if req_info.fast_forward.is_some() {
+			JustificationRequest::<B>::fast_forward(req_info.block)
+		} else {
+			JustificationRequest::<B> { begin: req_info.block, count: None }
+		}
+		.encode();
 
 		let (tx, rx) = oneshot::channel();
 
@@ -134,18 +149,52 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 
 	/// Start new justification request for `block`, if no other request is in progress.
 	///
-	/// `active_set` will be used to verify validity of potential responses.
-	pub fn request(&mut self, block: NumberFor<B>, active_set: ValidatorSet<AuthorityId>) {
+	/// `active_set` and `hasher` will be used to verify validity of potential responses.
+	pub fn request(
+		&mut self,
+		block: NumberFor<B>,
+		active_set: ValidatorSet<AuthorityId>,
+		hasher: SignatureHasher,
+	) {
+		self.start_request(RequestInfo { block, active_set, hasher, fast_forward: None });
+	}
+
+	/// Start new fast-forward request from mandatory block `block`, if no other request is in
+	/// progress.
+	///
+	/// Peers respond with the justifications of the first block of each validator set from
+	/// `block` on, followed by their latest justification, see [`JustificationRequest`].
+	/// `validator_sets` are the validator sets of all sessions starting from `block`, in order;
+	/// they are used to verify validity of potential responses. Justifications of other
+	/// validator sets are ignored.
+	///
+	/// Peers which don't support fast-forward requests are asked for `block` only.
+	pub fn request_fast_forward(
+		&mut self,
+		block: NumberFor<B>,
+		validator_sets: Vec<ValidatorSet<AuthorityId>>,
+		hasher: SignatureHasher,
+	) {
+		let active_set = match validator_sets.first() {
+			Some(active_set) => active_set.clone(),
+			None => return,
+		};
+		let fast_forward = Some(validator_sets);
+		self.start_request(RequestInfo { block, active_set, hasher, fast_forward });
+	}
+
+	fn start_request(&mut self, req_info: RequestInfo<B>) {
 		// ignore new requests while there's already one pending
 		if matches!(self.state, State::AwaitingResponse(_, _, _)) {
 			return
 		}
+		let block = req_info.block;
 		self.reset_peers_cache_for_block(block);
 
 		// Start the requests engine - each unsuccessful received response will automatically
 		// trigger a new request to the next peer in the `peers_cache` until there are none left.
 		if let Some(peer) = self.try_next_peer() {
-			self.request_from_peer(peer, RequestInfo { block, active_set });
+			self.request_from_peer(peer, req_info);
 		} else {
 			metric_inc!(self, beefy_on_demand_justification_no_peer_to_request_from);
 			debug!(
@@ -174,8 +223,8 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 		peer: &PeerId,
 		req_info: &RequestInfo<B>,
 		response: Result<Response, Canceled>,
-	) -> Result<BeefyVersionedFinalityProof<B>, Error> {
-		response
+	) -> Result<Vec<BeefyVersionedFinalityProof<B>>, Error> {
+		let encoded = response
 			.map_err(|e| {
 				debug!(
 					target: BEEFY_SYNC_LOG_TARGET,
@@ -203,26 +252,69 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 						Error::ResponseError
 					},
 				}
-			})
-			.and_then(|encoded| {
-				decode_and_verify_finality_proof::<B>(
-					&encoded[..],
-					req_info.block,
-					&req_info.active_set,
-				)
-				.map_err(|(err, signatures_checked)| {
-					metric_inc!(self, beefy_on_demand_justification_invalid_proof);
-					debug!(
-						target: BEEFY_SYNC_LOG_TARGET,
-						"🥩 for on demand justification #{:?}, peer {:?} responded with invalid proof: {:?}",
-						req_info.block, peer, err
-					);
-					let mut cost = cost::INVALID_PROOF;
-					cost.value +=
-						cost::PER_SIGNATURE_CHECKED.saturating_mul(signatures_checked as i32);
-					Error::InvalidResponse(PeerReport { who: *peer, cost_benefit: cost })
-				})
-			})
+			})?;
+
+		let verified = match &req_info.fast_forward {
+			None => decode_and_verify_finality_proof::<B>(
+				&encoded[..],
+				req_info.block,
+				&req_info.active_set,
+				req_info.hasher,
+			)
+			.map(|proof| vec![proof]),
+			Some(validator_sets) =>
+				Self::decode_and_verify_fast_forward(&encoded[..], validator_sets, req_info.hasher),
+		};
+		verified.map_err(|(err, signatures_checked)| {
+			metric_inc!(self, beefy_on_demand_justification_invalid_proof);
+			debug!(
+				target: BEEFY_SYNC_LOG_TARGET,
+				"🥩 for on demand justification #{:?}, peer {:?} responded with invalid proof: {:?}",
+				req_info.block, peer, err
+			);
+			let mut cost = cost::INVALID_PROOF;
+			cost.value += cost::PER_SIGNATURE_CHECKED.saturating_mul(signatures_checked as i32);
+			Error::InvalidResponse(PeerReport { who: *peer, cost_benefit: cost })
+		})
+	}
+
+	/// Decode the justifications of a fast-forward response and verify them against the known
+	/// `validator_sets`.
+	///
+	/// Justifications have to be ordered by block number. Justifications of unknown validator
+	/// sets, and any following them, are dropped, since they can't be verified.
+	fn decode_and_verify_fast_forward(
+		encoded: &[u8],
+		validator_sets: &[ValidatorSet<AuthorityId>],
+		hasher: SignatureHasher,
+	) -> Result<Vec<BeefyVersionedFinalityProof<B>>, (ConsensusError, u32)> {
+		let invalid = || (ConsensusError::InvalidJustification, 0);
+		let encoded_proofs = <Vec<Vec<u8>>>::decode(&mut &*encoded).map_err(|_| invalid())?;
+
+		let mut proofs: Vec<BeefyVersionedFinalityProof<B>> = vec![];
+		for encoded_proof in encoded_proofs {
+			let proof = <BeefyVersionedFinalityProof<B>>::decode(&mut &encoded_proof[..])
+				.map_err(|_| invalid())?;
+			let (number, set_id) = proof_block_num_and_set_id::<B>(&proof);
+			if proofs
+				.last()
+				.map_or(false, |last| proof_block_num_and_set_id::<B>(last).0 >= number)
+			{
+				return Err(invalid())
+			}
+			let validator_set = match validator_sets.iter().find(|set| set.id() == set_id) {
+				Some(validator_set) => validator_set,
+				None => break,
+			};
+			verify_with_validator_set::<B>(number, validator_set, &proof, hasher)?;
+			proofs.push(proof);
+		}
+
+		if proofs.is_empty() {
+			Err(invalid())
+		} else {
+			Ok(proofs)
+		}
 	}
 
 	pub(crate) async fn next(&mut self) -> ResponseInfo<B> {
@@ -243,8 +335,10 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 		let block = req_info.block;
 		match self.process_response(&peer, &req_info, resp) {
 			Err(err) => {
-				// No valid justification received, try next peer in our set.
+				// No valid justification received, try next peer in our set. The peer may not
+				// support fast-forward requests, so only ask the next one for `block`.
 				if let Some(peer) = self.try_next_peer() {
+					let req_info = RequestInfo { fast_forward: None, ..req_info };
 					self.request_from_peer(peer, req_info);
 				} else {
 					warn!(
@@ -259,14 +353,21 @@ impl<B: Block> OnDemandJustificationsEngine<B> {
 					ResponseInfo::Pending
 				}
 			},
-			Ok(proof) => {
+			Ok(mut proofs) => {
 				metric_inc!(self, beefy_on_demand_justification_good_proof);
 				debug!(
 					target: BEEFY_SYNC_LOG_TARGET,
-					"🥩 received valid on-demand justif #{:?} from {:?}", block, peer
+					"🥩 received {} valid on-demand justif(s) from #{:?} from {:?}",
+					proofs.len(),
+					block,
+					peer
 				);
 				let peer_report = PeerReport { who: peer, cost_benefit: benefit::VALIDATED_PROOF };
-				ResponseInfo::ValidProof(proof, peer_report)
+				if req_info.fast_forward.is_some() {
+					ResponseInfo::ValidProofs(proofs, peer_report)
+				} else {
+					ResponseInfo::ValidProof(proofs.remove(0), peer_report)
+				}
 			},
 		}
 	}
@@ -0,0 +1,374 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Dual signing of BEEFY votes, while migrating BEEFY to another crypto scheme.
+//!
+//! Switching the crypto scheme of BEEFY at once breaks every light client following it. During a
+//! dual-signing window instead, voters sign each commitment with both their regular, primary, key
+//! and their key of the new, secondary, crypto scheme. Secondary signatures are gossiped next to
+//! the votes and, once a round is concluded and enough of them are collected, they make up a
+//! second justification of the block, stored under [`BEEFY_SECONDARY_ENGINE_ID`].
+//!
+//! The secondary keys of the validators are provided by the runtime, see
+//! [`BeefyApi::secondary_validator_set`](sp_consensus_beefy::BeefyApi::secondary_validator_set).
+//!
+//! [`BEEFY_SECONDARY_ENGINE_ID`]: sp_consensus_beefy::BEEFY_SECONDARY_ENGINE_ID
+
+use codec::{Encode, Output};
+use sp_consensus_beefy::{
+	crypto::AuthorityId, Commitment, SignatureHasher, SignedCommitment, ValidatorSet,
+	ValidatorSetId, VersionedFinalityProof, VoteMessage,
+};
+use sp_runtime::traits::{Block, NumberFor};
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::round::threshold;
+
+#[cfg(any(test, feature = "bls-experimental"))]
+use crate::keystore::{AuthorityIdBound, BeefyKeystore};
+#[cfg(any(test, feature = "bls-experimental"))]
+use codec::DecodeAll;
+#[cfg(any(test, feature = "bls-experimental"))]
+use sp_keystore::KeystorePtr;
+
+/// The dual-signing window of a BEEFY voter.
+#[derive(Clone)]
+pub struct DualSigningConfig {
+	/// Id of the first validator set signing with both crypto schemes.
+	pub start_set_id: ValidatorSetId,
+	/// Number of sessions signing with both crypto schemes, from `start_set_id` on.
+	pub sessions: u64,
+	/// Signer of the secondary crypto scheme.
+	pub signer: Arc<dyn SecondarySigner>,
+}
+
+impl DualSigningConfig {
+	/// Return `true` if validator set `set_id` signs with both crypto schemes.
+	pub fn is_active(&self, set_id: ValidatorSetId) -> bool {
+		set_id >= self.start_set_id && set_id - self.start_set_id < self.sessions
+	}
+}
+
+/// Signing and verification of the secondary crypto scheme of a dual-signing window.
+///
+/// Public keys and signatures are SCALE-encoded.
+pub trait SecondarySigner: Send + Sync {
+	/// Sign `message` with the private key of `public`, if found in the local keystore.
+	fn sign(&self, public: &[u8], message: &[u8], hasher: SignatureHasher) -> Option<Vec<u8>>;
+
+	/// Return `true` if `signature` is a valid signature of `message` by `public`.
+	fn verify(
+		&self,
+		public: &[u8],
+		signature: &[u8],
+		message: &[u8],
+		hasher: SignatureHasher,
+	) -> bool;
+}
+
+/// A [`SecondarySigner`] of the `AuthorityId` crypto scheme, backed by a [`BeefyKeystore`].
+#[cfg(any(test, feature = "bls-experimental"))]
+pub(crate) struct KeystoreSigner<AuthorityId>(BeefyKeystore<AuthorityId>);
+
+#[cfg(any(test, feature = "bls-experimental"))]
+impl<AuthorityId> From<Option<KeystorePtr>> for KeystoreSigner<AuthorityId> {
+	fn from(store: Option<KeystorePtr>) -> Self {
+		KeystoreSigner(store.into())
+	}
+}
+
+#[cfg(any(test, feature = "bls-experimental"))]
+impl<AuthorityId: AuthorityIdBound> SecondarySigner for KeystoreSigner<AuthorityId> {
+	fn sign(&self, public: &[u8], message: &[u8], hasher: SignatureHasher) -> Option<Vec<u8>> {
+		let public = AuthorityId::decode_all(&mut &public[..]).ok()?;
+		if !self.0.public_keys().ok()?.contains(&public) {
+			return None
+		}
+		self.0.sign(&public, message, hasher).ok().map(|signature| signature.encode())
+	}
+
+	fn verify(
+		&self,
+		public: &[u8],
+		signature: &[u8],
+		message: &[u8],
+		hasher: SignatureHasher,
+	) -> bool {
+		let public = AuthorityId::decode_all(&mut &public[..]);
+		let signature =
+			<AuthorityId as AuthorityIdBound>::Signature::decode_all(&mut &signature[..]);
+		match (public, signature) {
+			(Ok(public), Ok(signature)) =>
+				BeefyKeystore::verify(&public, &signature, message, hasher),
+			_ => false,
+		}
+	}
+}
+
+/// Return a [`SecondarySigner`] migrating BEEFY to BLS, with the BLS keys found in `keystore`.
+#[cfg(feature = "bls-experimental")]
+pub fn bls_signer(keystore: Option<KeystorePtr>) -> Arc<dyn SecondarySigner> {
+	Arc::new(KeystoreSigner::<sp_consensus_beefy::bls_crypto::AuthorityId>::from(keystore))
+}
+
+/// A SCALE-encoded secondary signature, which encodes as the signature itself.
+///
+/// Secondary justifications thus encode just like justifications of the secondary signature type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct EncodedSignature(pub Vec<u8>);
+
+impl Encode for EncodedSignature {
+	fn size_hint(&self) -> usize {
+		self.0.len()
+	}
+
+	fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+		dest.write(&self.0)
+	}
+}
+
+/// A justification signed with the secondary crypto scheme.
+pub(crate) type SecondaryFinalityProof<B> = VersionedFinalityProof<NumberFor<B>, EncodedSignature>;
+
+/// A vote signed with the secondary crypto scheme, by the validator of primary authority id `id`.
+pub(crate) type SecondaryVote<B> = VoteMessage<NumberFor<B>, AuthorityId, Vec<u8>>;
+
+/// Secondary signatures collected for a round.
+#[derive(Default)]
+struct SecondaryRound {
+	/// Signatures by primary authority id.
+	signatures: BTreeMap<AuthorityId, Vec<u8>>,
+	/// Whether the round was concluded by the primary signatures already.
+	concluded: bool,
+}
+
+/// Keeps track of the secondary signatures of BEEFY rounds during a dual-signing window.
+///
+/// Does not do any validation on signatures, layers above need to handle that (gossip).
+pub(crate) struct SecondaryVotes<B: Block> {
+	/// Secondary validator sets of the sessions signing with both crypto schemes, by set id.
+	validator_sets: BTreeMap<ValidatorSetId, ValidatorSet<Vec<u8>>>,
+	rounds: BTreeMap<Commitment<NumberFor<B>>, SecondaryRound>,
+	best_concluded: Option<NumberFor<B>>,
+}
+
+impl<B: Block> SecondaryVotes<B> {
+	pub(crate) fn new() -> Self {
+		Self { validator_sets: BTreeMap::new(), rounds: BTreeMap::new(), best_concluded: None }
+	}
+
+	/// Add the `secondary` validator set of primary `validator_set`.
+	///
+	/// Return `false` if they don't match.
+	pub(crate) fn add_session(
+		&mut self,
+		validator_set: &ValidatorSet<AuthorityId>,
+		secondary: ValidatorSet<Vec<u8>>,
+	) -> bool {
+		if secondary.id() != validator_set.id() || secondary.len() != validator_set.len() {
+			return false
+		}
+		self.validator_sets.insert(secondary.id(), secondary);
+		true
+	}
+
+	/// Return the secondary validator set of validator set `set_id`, if dual signing.
+	pub(crate) fn validator_set(&self, set_id: ValidatorSetId) -> Option<&ValidatorSet<Vec<u8>>> {
+		self.validator_sets.get(&set_id)
+	}
+
+	/// Return the secondary key of the validator `id` of `validator_set`, if dual signing.
+	pub(crate) fn secondary_key(
+		&self,
+		validator_set: &ValidatorSet<AuthorityId>,
+		id: &AuthorityId,
+	) -> Option<&Vec<u8>> {
+		let index = validator_set.validators().iter().position(|v| v == id)?;
+		self.validator_set(validator_set.id())?.validators().get(index)
+	}
+
+	/// Add `vote` of a validator of `validator_set`.
+	///
+	/// Return the secondary justification of the round of `vote`, if it is now complete.
+	pub(crate) fn add_vote(
+		&mut self,
+		validator_set: &ValidatorSet<AuthorityId>,
+		vote: SecondaryVote<B>,
+	) -> Option<SecondaryFinalityProof<B>> {
+		let commitment = vote.commitment;
+		if commitment.validator_set_id != validator_set.id() ||
+			self.validator_set(commitment.validator_set_id).is_none() ||
+			Some(commitment.block_number) < self.best_concluded
+		{
+			return None
+		}
+		let round = self.rounds.entry(commitment.clone()).or_default();
+		round.signatures.insert(vote.id, vote.signature);
+		self.try_complete(validator_set, &commitment)
+	}
+
+	/// Note that the round of `commitment` was concluded with the primary signatures of
+	/// `validator_set`, and prune older rounds and sessions.
+	///
+	/// Return the secondary justification of the round, if it is complete.
+	pub(crate) fn conclude(
+		&mut self,
+		validator_set: &ValidatorSet<AuthorityId>,
+		commitment: &Commitment<NumberFor<B>>,
+	) -> Option<SecondaryFinalityProof<B>> {
+		let number = commitment.block_number;
+		self.rounds.retain(|c, _| c.block_number >= number);
+		self.validator_sets.retain(|id, _| *id >= commitment.validator_set_id);
+		self.best_concluded = self.best_concluded.max(Some(number));
+		if commitment.validator_set_id != validator_set.id() ||
+			self.validator_set(commitment.validator_set_id).is_none()
+		{
+			return None
+		}
+		self.rounds.entry(commitment.clone()).or_default().concluded = true;
+		self.try_complete(validator_set, commitment)
+	}
+
+	fn try_complete(
+		&mut self,
+		validator_set: &ValidatorSet<AuthorityId>,
+		commitment: &Commitment<NumberFor<B>>,
+	) -> Option<SecondaryFinalityProof<B>> {
+		let round = self.rounds.get(commitment)?;
+		if !round.concluded || round.signatures.len() < threshold(validator_set.len()) {
+			return None
+		}
+		let mut round = self.rounds.remove(commitment)?;
+		let signatures = validator_set
+			.validators()
+			.iter()
+			.map(|id| round.signatures.remove(id).map(EncodedSignature))
+			.collect();
+		Some(VersionedFinalityProof::V1(SignedCommitment {
+			commitment: commitment.clone(),
+			signatures,
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Decode;
+	use sc_network_test::Block;
+	use sp_consensus_beefy::{
+		crypto, known_payloads::MMR_ROOT_ID, Keyring, Payload, SignedCommitment, KEY_TYPE,
+	};
+	use sp_keystore::{testing::MemoryKeystore, Keystore};
+
+	fn commitment_at(block_number: u64) -> Commitment<u64> {
+		let payload = Payload::from_single_entry(MMR_ROOT_ID, vec![block_number as u8]);
+		Commitment { payload, block_number, validator_set_id: 1 }
+	}
+
+	fn vote(keyring: Keyring, commitment: &Commitment<u64>) -> SecondaryVote<Block> {
+		// signatures aren't checked by `SecondaryVotes`.
+		let signature = Keyring::Eve.sign(&commitment.encode()).encode();
+		VoteMessage { commitment: commitment.clone(), id: keyring.public(), signature }
+	}
+
+	#[test]
+	fn dual_signing_window() {
+		let signer = Arc::new(KeystoreSigner::<crypto::AuthorityId>::from(None));
+		let config = DualSigningConfig { start_set_id: 3, sessions: 2, signer };
+
+		assert!(!config.is_active(2));
+		assert!(config.is_active(3));
+		assert!(config.is_active(4));
+		assert!(!config.is_active(5));
+	}
+
+	#[test]
+	fn keystore_signer_signs_and_verifies() {
+		let store = MemoryKeystore::new();
+		store.ecdsa_generate_new(KEY_TYPE, Some(&Keyring::Alice.to_seed())).unwrap();
+		let signer = KeystoreSigner::<crypto::AuthorityId>::from(Some(store.into()));
+		let alice = Keyring::Alice.public().encode();
+		let bob = Keyring::Bob.public().encode();
+		let hasher = SignatureHasher::Keccak256;
+
+		let signature = signer.sign(&alice, b"commitment", hasher).unwrap();
+		assert!(signer.verify(&alice, &signature, b"commitment", hasher));
+		assert!(!signer.verify(&alice, &signature, b"other commitment", hasher));
+		assert!(!signer.verify(&bob, &signature, b"commitment", hasher));
+		assert!(!signer.verify(&alice, &signature[1..], b"commitment", hasher));
+
+		// no private key for bob, or for garbage.
+		assert_eq!(signer.sign(&bob, b"commitment", hasher), None);
+		assert_eq!(signer.sign(&alice[1..], b"commitment", hasher), None);
+	}
+
+	#[test]
+	fn secondary_justification_once_concluded_and_complete() {
+		let keys = vec![Keyring::Alice, Keyring::Bob, Keyring::Charlie, Keyring::Dave];
+		let validator_set = ValidatorSet::new(keys.iter().map(|k| k.public()), 1).unwrap();
+		let secondary_keys = vec![Keyring::Eve, Keyring::Ferdie, Keyring::One, Keyring::Two];
+		let secondary =
+			ValidatorSet::new(secondary_keys.iter().map(|k| k.public().encode()), 1).unwrap();
+		let mut votes = SecondaryVotes::<Block>::new();
+
+		// no secondary validator set, nothing to collect.
+		let commitment = commitment_at(10);
+		assert_eq!(votes.add_vote(&validator_set, vote(Keyring::Alice, &commitment)), None);
+		assert!(votes.rounds.is_empty());
+
+		// secondary validator sets have to match the primary ones.
+		let other = ValidatorSet::new(vec![Keyring::Eve.public().encode()], 1).unwrap();
+		assert!(!votes.add_session(&validator_set, other));
+		assert!(votes.add_session(&validator_set, secondary));
+		assert_eq!(
+			votes.secondary_key(&validator_set, &Keyring::Bob.public()),
+			Some(&Keyring::Ferdie.public().encode())
+		);
+
+		// enough secondary signatures, but the round isn't concluded.
+		for keyring in &keys[..3] {
+			assert_eq!(votes.add_vote(&validator_set, vote(*keyring, &commitment)), None);
+		}
+
+		// concluded with the primary signatures, but missing secondary signatures.
+		let next = commitment_at(11);
+		assert_eq!(votes.add_vote(&validator_set, vote(Keyring::Alice, &next)), None);
+		assert_eq!(votes.conclude(&validator_set, &next), None);
+		// older rounds got pruned, and aren't collected anymore.
+		assert_eq!(votes.rounds.len(), 1);
+		assert_eq!(votes.add_vote(&validator_set, vote(Keyring::Dave, &commitment)), None);
+		assert_eq!(votes.rounds.len(), 1);
+
+		assert_eq!(votes.add_vote(&validator_set, vote(Keyring::Charlie, &next)), None);
+		let proof = votes.add_vote(&validator_set, vote(Keyring::Dave, &next)).unwrap();
+		assert!(votes.rounds.is_empty());
+
+		// secondary justifications decode as justifications of the secondary signature type.
+		let decoded =
+			VersionedFinalityProof::<u64, crypto::Signature>::decode(&mut &proof.encode()[..])
+				.unwrap();
+		let VersionedFinalityProof::V1(SignedCommitment { commitment, signatures }) = decoded;
+		let signature = Keyring::Eve.sign(&next.encode());
+		assert_eq!(commitment, next);
+		assert_eq!(
+			signatures,
+			vec![Some(signature.clone()), None, Some(signature.clone()), Some(signature)]
+		);
+	}
+}
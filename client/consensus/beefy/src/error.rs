@@ -20,6 +20,7 @@
 //!
 //! Used for BEEFY gadget internal error handling only
 
+use sp_core::crypto::KeyTypeId;
 use std::fmt::Debug;
 
 #[derive(Debug, thiserror::Error)]
@@ -27,25 +28,71 @@ pub enum Error {
 	#[error("Backend: {0}")]
 	Backend(String),
 	#[error("Keystore error: {0}")]
-	Keystore(String),
+	Keystore(#[from] KeystoreError),
 	#[error("Runtime api error: {0}")]
 	RuntimeApi(sp_api::ApiError),
-	#[error("Signature error: {0}")]
-	Signature(String),
 	#[error("Session uninitialized")]
 	UninitSession,
 	#[error("pallet-beefy was reset, please restart voter")]
 	ConsensusReset,
 }
 
+/// Failure of a BEEFY keystore operation.
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum KeystoreError {
+	/// The node has no keystore.
+	#[error("no Keystore")]
+	NoKeystore,
+	/// None of the keys of the keystore are in the validator set.
+	#[error("no authority public key found in store")]
+	NoAuthorityKey,
+	/// The private key of the signing public key is not in the keystore.
+	#[error("missing private key for {0}")]
+	MissingKey(String),
+	/// The keystore doesn't support keys of this type.
+	#[error("key type {0:?} not supported")]
+	UnsupportedKey(KeyTypeId),
+	/// The keystore produced invalid signatures.
+	#[error("invalid signature: {0}")]
+	InvalidSignature(String),
+	/// Refused to sign a commitment conflicting with one signed before, see
+	/// [`crate::keystore::SigningRecord`].
+	#[error("conflicting commitment: {0}")]
+	ConflictingCommitment(String),
+	/// The keystore is temporarily unavailable, e.g. a remote signer timed out.
+	#[error("keystore unavailable")]
+	Unavailable,
+	/// Any other keystore failure.
+	#[error("{0}")]
+	Other(String),
+}
+
+impl KeystoreError {
+	/// Return `true` if retrying the same operation may succeed.
+	pub fn is_transient(&self) -> bool {
+		matches!(self, KeystoreError::Unavailable)
+	}
+}
+
+impl From<sp_keystore::Error> for KeystoreError {
+	fn from(e: sp_keystore::Error) -> Self {
+		match e {
+			sp_keystore::Error::KeyNotSupported(key_type) =>
+				KeystoreError::UnsupportedKey(key_type),
+			sp_keystore::Error::ValidationError(msg) => KeystoreError::InvalidSignature(msg),
+			sp_keystore::Error::Unavailable => KeystoreError::Unavailable,
+			sp_keystore::Error::Other(msg) => KeystoreError::Other(msg),
+		}
+	}
+}
+
 #[cfg(test)]
 impl PartialEq for Error {
 	fn eq(&self, other: &Self) -> bool {
 		match (self, other) {
 			(Error::Backend(s1), Error::Backend(s2)) => s1 == s2,
-			(Error::Keystore(s1), Error::Keystore(s2)) => s1 == s2,
+			(Error::Keystore(e1), Error::Keystore(e2)) => e1 == e2,
 			(Error::RuntimeApi(_), Error::RuntimeApi(_)) => true,
-			(Error::Signature(s1), Error::Signature(s2)) => s1 == s2,
 			(Error::UninitSession, Error::UninitSession) => true,
 			(Error::ConsensusReset, Error::ConsensusReset) => true,
 			_ => false,
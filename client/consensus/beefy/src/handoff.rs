@@ -0,0 +1,297 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Export and import of the BEEFY justifications of authority set handoffs.
+//!
+//! Each BEEFY session starts with a mandatory block, whose header announces the new validator set
+//! and which is finalized by a justification of that set. The chain of these blocks is what a
+//! bridge or a freshly synced node needs to follow the BEEFY authority sets, without fetching
+//! every justification: [`export_handoffs`] collects it from a node, and [`import_handoffs`]
+//! verifies and stores it on another one.
+//!
+//! Note that each validator set is trusted as announced in the header of its mandatory block.
+//! [`verify_handoffs`] checks that the chain is consistent, but the headers themselves must be
+//! trusted by other means, e.g. GRANDPA finality, as [`import_handoffs`] does. In particular, a
+//! validator set is not attested by the previous one, so handoffs alone can't bootstrap a light
+//! client.
+
+use codec::{Decode, Encode};
+use log::debug;
+use sc_client_api::{backend::Backend, BlockBackend};
+use sp_blockchain::{Backend as BlockchainBackend, HeaderBackend};
+use sp_consensus_beefy::{crypto::AuthorityId, SignatureHasher, ValidatorSet, BEEFY_ENGINE_ID};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor, One, Saturating};
+
+use crate::{
+	justification::{proof_block_num_and_set_id, verify_with_validator_set},
+	worker::find_authorities_change,
+	LOG_TARGET,
+};
+
+pub use crate::justification::BeefyVersionedFinalityProof;
+
+/// The maximum number of blocks scanned by a single call to [`export_handoffs`].
+pub const MAX_EXPORT_RANGE: u32 = 14_400;
+
+/// The mandatory block of a BEEFY session, with its justification.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct SetHandoff<Block: BlockT> {
+	/// The header of the mandatory block, announcing the validator set of the session.
+	pub header: Block::Header,
+	/// The justification of the mandatory block, signed by the announced validator set.
+	pub justification: BeefyVersionedFinalityProof<Block>,
+}
+
+impl<Block: BlockT> SetHandoff<Block> {
+	/// The validator set announced by the mandatory block, if any.
+	pub fn validator_set(&self) -> Option<ValidatorSet<AuthorityId>> {
+		find_authorities_change::<Block>(&self.header)
+	}
+}
+
+/// Failure to export, verify or import [`SetHandoff`]s.
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum HandoffError {
+	/// The chain of handoffs is empty.
+	#[error("no authority set handoffs")]
+	Empty,
+	/// The block of a handoff doesn't announce a validator set.
+	#[error("block #{0} is not a BEEFY mandatory block")]
+	NotMandatory(String),
+	/// The justification of a handoff is not valid for its block and validator set.
+	#[error("invalid justification for block #{0}")]
+	InvalidJustification(String),
+	/// The handoffs don't follow each other.
+	#[error("validator set {found} follows validator set {previous}")]
+	NotConsecutive {
+		/// The id of the previous validator set.
+		previous: u64,
+		/// The id of the validator set that doesn't follow it.
+		found: u64,
+	},
+	/// The block of a handoff is not in the local chain.
+	#[error("block #{0} is not in the local chain")]
+	UnknownBlock(String),
+	/// Accessing the client failed.
+	#[error("client error: {0}")]
+	Client(String),
+}
+
+/// Collect the handoffs of the BEEFY sessions starting at or after block `since`, up to the
+/// finalized block.
+///
+/// At most [`MAX_EXPORT_RANGE`] blocks are scanned, the following handoffs are exported by
+/// calling again with `since` past the scanned range. The chain stops before the first mandatory
+/// block without a BEEFY justification, usually the one of the current session while it's still
+/// being voted on.
+pub fn export_handoffs<Block, Client>(
+	client: &Client,
+	since: NumberFor<Block>,
+) -> Result<Vec<SetHandoff<Block>>, HandoffError>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + BlockBackend<Block>,
+{
+	let last = since.saturating_add((MAX_EXPORT_RANGE - 1).into());
+	let last = last.min(client.info().finalized_number);
+	let mut handoffs = vec![];
+	let mut number = since;
+	while number <= last {
+		let unknown_block = || HandoffError::UnknownBlock(format!("{:?}", number));
+		let hash = client
+			.hash(number)
+			.map_err(|e| HandoffError::Client(e.to_string()))?
+			.ok_or_else(unknown_block)?;
+		let header = client
+			.header(hash)
+			.map_err(|e| HandoffError::Client(e.to_string()))?
+			.ok_or_else(unknown_block)?;
+		number += One::one();
+
+		if find_authorities_change::<Block>(&header).is_none() {
+			continue
+		}
+		let justification = client
+			.justifications(header.hash())
+			.map_err(|e| HandoffError::Client(e.to_string()))?
+			.and_then(|justifications| justifications.into_justification(BEEFY_ENGINE_ID));
+		let justification = match justification {
+			Some(encoded) => BeefyVersionedFinalityProof::<Block>::decode(&mut &encoded[..])
+				.map_err(|e| HandoffError::Client(e.to_string()))?,
+			None => {
+				debug!(
+					target: LOG_TARGET,
+					"🥩 Mandatory block #{:?} has no justification, handoffs end there.",
+					header.number()
+				);
+				break
+			},
+		};
+		handoffs.push(SetHandoff { header, justification });
+	}
+	Ok(handoffs)
+}
+
+/// Verify that each handoff is justified by the validator set it announces, and that validator
+/// sets follow each other.
+///
+/// Signatures are expected over commitments hashed with `hasher`. Return the validator set of the
+/// last handoff.
+///
+/// Each validator set only justifies its own mandatory block, and is not attested by the previous
+/// set: the headers of the handoffs must be trusted by other means.
+pub fn verify_handoffs<Block: BlockT>(
+	handoffs: &[SetHandoff<Block>],
+	hasher: SignatureHasher,
+) -> Result<ValidatorSet<AuthorityId>, HandoffError> {
+	let mut previous: Option<ValidatorSet<AuthorityId>> = None;
+	for handoff in handoffs {
+		let number = *handoff.header.number();
+		let validator_set = handoff
+			.validator_set()
+			.ok_or_else(|| HandoffError::NotMandatory(format!("{:?}", number)))?;
+		if let Some(previous) = &previous {
+			if validator_set.id() != previous.id() + 1 {
+				return Err(HandoffError::NotConsecutive {
+					previous: previous.id(),
+					found: validator_set.id(),
+				})
+			}
+		}
+		verify_with_validator_set::<Block>(number, &validator_set, &handoff.justification, hasher)
+			.map_err(|_| HandoffError::InvalidJustification(format!("{:?}", number)))?;
+		previous = Some(validator_set);
+	}
+	previous.ok_or(HandoffError::Empty)
+}
+
+/// Verify `handoffs` and store their justifications in `backend`.
+///
+/// All handoffs must be for blocks of the local chain, see [`verify_handoffs`]. Return the number
+/// of justifications that were stored, skipping blocks that already have a BEEFY justification.
+pub fn import_handoffs<Block, BE>(
+	backend: &BE,
+	handoffs: &[SetHandoff<Block>],
+	hasher: SignatureHasher,
+) -> Result<usize, HandoffError>
+where
+	Block: BlockT,
+	BE: Backend<Block>,
+{
+	verify_handoffs(handoffs, hasher)?;
+
+	let blockchain = backend.blockchain();
+	let mut imported = 0;
+	for handoff in handoffs {
+		let hash = handoff.header.hash();
+		let number = *handoff.header.number();
+		if blockchain.hash(number).map_err(|e| HandoffError::Client(e.to_string()))? != Some(hash) {
+			return Err(HandoffError::UnknownBlock(format!("{:?}", number)))
+		}
+		let has_justification = blockchain
+			.justifications(hash)
+			.map_err(|e| HandoffError::Client(e.to_string()))?
+			.and_then(|justifications| justifications.into_justification(BEEFY_ENGINE_ID))
+			.is_some();
+		if has_justification {
+			continue
+		}
+		debug!(
+			target: LOG_TARGET,
+			"🥩 Importing justification of set {} handoff at #{:?}",
+			proof_block_num_and_set_id::<Block>(&handoff.justification).1,
+			number
+		);
+		backend
+			.append_justification(hash, (BEEFY_ENGINE_ID, handoff.justification.encode()))
+			.map_err(|e| HandoffError::Client(e.to_string()))?;
+		imported += 1;
+	}
+	Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{justification::tests::new_finality_proof, tests::make_beefy_ids};
+	use sp_consensus_beefy::{ConsensusLog, Keyring};
+	use sp_runtime::{Digest, DigestItem};
+	use substrate_test_runtime_client::runtime::{Block, Header};
+
+	fn handoff(number: u64, id: u64, keys: &[Keyring], signers: &[Keyring]) -> SetHandoff<Block> {
+		let validator_set = ValidatorSet::new(make_beefy_ids(keys), id).unwrap();
+		let mut header = Header::new(
+			number,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Digest::default(),
+		);
+		header.digest_mut().push(DigestItem::Consensus(
+			BEEFY_ENGINE_ID,
+			ConsensusLog::<AuthorityId>::AuthoritiesChange(validator_set.clone()).encode(),
+		));
+		SetHandoff { header, justification: new_finality_proof(number, &validator_set, signers) }
+	}
+
+	#[test]
+	fn verify_handoffs_works() {
+		let first = &[Keyring::Alice, Keyring::Bob];
+		let second = &[Keyring::Bob, Keyring::Charlie, Keyring::Dave];
+		let handoffs = vec![handoff(10, 3, first, first), handoff(20, 4, second, second)];
+
+		let last = verify_handoffs(&handoffs, SignatureHasher::Keccak256).unwrap();
+		assert_eq!(last, ValidatorSet::new(make_beefy_ids(second), 4).unwrap());
+
+		// a round-trip through the exported encoding changes nothing.
+		let decoded = Vec::<SetHandoff<Block>>::decode(&mut &handoffs.encode()[..]).unwrap();
+		assert_eq!(decoded, handoffs);
+
+		assert_eq!(
+			verify_handoffs::<Block>(&[], SignatureHasher::Keccak256),
+			Err(HandoffError::Empty)
+		);
+	}
+
+	#[test]
+	fn verify_handoffs_rejects_broken_chains() {
+		let keys = &[Keyring::Alice, Keyring::Bob];
+
+		// a set is skipped.
+		let handoffs = vec![handoff(10, 3, keys, keys), handoff(20, 5, keys, keys)];
+		assert_eq!(
+			verify_handoffs(&handoffs, SignatureHasher::Keccak256),
+			Err(HandoffError::NotConsecutive { previous: 3, found: 5 })
+		);
+
+		// the justification is not signed by the announced set.
+		let handoffs = vec![handoff(10, 3, keys, &[Keyring::Charlie, Keyring::Dave])];
+		assert_eq!(
+			verify_handoffs(&handoffs, SignatureHasher::Keccak256),
+			Err(HandoffError::InvalidJustification("10".into()))
+		);
+
+		// the block doesn't announce a set.
+		let mut not_mandatory = handoff(10, 3, keys, keys);
+		not_mandatory.header.digest_mut().logs.clear();
+		assert_eq!(
+			verify_handoffs(&[not_mandatory], SignatureHasher::Keccak256),
+			Err(HandoffError::NotMandatory("10".into()))
+		);
+	}
+}
@@ -16,13 +16,18 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
-use log::debug;
+use codec::{Decode, Encode};
+use log::{debug, warn};
+use parking_lot::Mutex;
 
 use sp_api::{ProvideRuntimeApi, TransactionFor};
+use sp_blockchain::HeaderBackend;
 use sp_consensus::Error as ConsensusError;
-use sp_consensus_beefy::{BeefyApi, BEEFY_ENGINE_ID};
+use sp_consensus_beefy::{
+	crypto::AuthorityId, BeefyApi, SignatureHasher, ValidatorSet, ValidatorSetId, BEEFY_ENGINE_ID,
+};
 use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT, NumberFor},
 	EncodedJustification,
@@ -33,17 +38,33 @@ use sc_consensus::{BlockCheckParams, BlockImport, BlockImportParams, ImportResul
 
 use crate::{
 	communication::notification::BeefyVersionedFinalityProofSender,
-	justification::{decode_and_verify_finality_proof, BeefyVersionedFinalityProof},
+	justification::{
+		proof_block_num_and_set_id, verify_with_validator_set, BeefyVersionedFinalityProof,
+	},
 	metric_inc,
 	metrics::BlockImportMetrics,
+	signature_hasher,
+	worker::{find_authorities_change, find_signature_hasher},
 	LOG_TARGET,
 };
 
+/// Maximum number of validator sets learned from imported headers to keep around.
+const MAX_KNOWN_VALIDATOR_SETS: usize = 64;
+
+/// Validator sets learned from imported headers, with the hash functions of their signatures.
+type KnownValidatorSets = BTreeMap<ValidatorSetId, (ValidatorSet<AuthorityId>, SignatureHasher)>;
+
 /// A block-import handler for BEEFY.
 ///
 /// This scans each imported block for BEEFY justifications and verifies them.
 /// Wraps a `inner: BlockImport` and ultimately defers to it.
 ///
+/// Justifications are verified against the validator sets, and the hash functions of their
+/// signatures, announced in the headers of imported and finalized blocks, falling back to the
+/// runtime. This allows verifying them while syncing blocks without state. Valid justifications of
+/// finalized blocks are stored right away, so that syncing nodes don't end up with gaps in their
+/// stored justifications.
+///
 /// When using BEEFY, the block import worker should be using this block import object.
 pub struct BeefyBlockImport<Block: BlockT, Backend, RuntimeApi, I> {
 	backend: Arc<Backend>,
@@ -51,6 +72,7 @@ pub struct BeefyBlockImport<Block: BlockT, Backend, RuntimeApi, I> {
 	inner: I,
 	justification_sender: BeefyVersionedFinalityProofSender<Block>,
 	metrics: Option<BlockImportMetrics>,
+	known_validator_sets: Arc<Mutex<KnownValidatorSets>>,
 }
 
 impl<Block: BlockT, BE, Runtime, I: Clone> Clone for BeefyBlockImport<Block, BE, Runtime, I> {
@@ -61,6 +83,7 @@ impl<Block: BlockT, BE, Runtime, I: Clone> Clone for BeefyBlockImport<Block, BE,
 			inner: self.inner.clone(),
 			justification_sender: self.justification_sender.clone(),
 			metrics: self.metrics.clone(),
+			known_validator_sets: self.known_validator_sets.clone(),
 		}
 	}
 }
@@ -74,7 +97,28 @@ impl<Block: BlockT, BE, Runtime, I> BeefyBlockImport<Block, BE, Runtime, I> {
 		justification_sender: BeefyVersionedFinalityProofSender<Block>,
 		metrics: Option<BlockImportMetrics>,
 	) -> BeefyBlockImport<Block, BE, Runtime, I> {
-		BeefyBlockImport { backend, runtime, inner, justification_sender, metrics }
+		BeefyBlockImport {
+			backend,
+			runtime,
+			inner,
+			justification_sender,
+			metrics,
+			known_validator_sets: Default::default(),
+		}
+	}
+
+	/// Remember `validator_set` and the `hasher` of its signatures, announced in the header of an
+	/// imported and finalized block.
+	fn note_validator_set(
+		&self,
+		validator_set: ValidatorSet<AuthorityId>,
+		hasher: SignatureHasher,
+	) {
+		let mut known = self.known_validator_sets.lock();
+		known.insert(validator_set.id(), (validator_set, hasher));
+		while known.len() > MAX_KNOWN_VALIDATOR_SETS {
+			known.pop_first();
+		}
 	}
 }
 
@@ -91,6 +135,29 @@ where
 		number: NumberFor<Block>,
 		hash: <Block as BlockT>::Hash,
 	) -> Result<BeefyVersionedFinalityProof<Block>, ConsensusError> {
+		let proof = <BeefyVersionedFinalityProof<Block>>::decode(&mut &encoded[..])
+			.map_err(|_| ConsensusError::InvalidJustification)?;
+		let (_, set_id) = proof_block_num_and_set_id::<Block>(&proof);
+		let known_validator_set = self.known_validator_sets.lock().get(&set_id).cloned();
+		let (validator_set, hasher) = match known_validator_set {
+			Some(known) => known,
+			None => (
+				self.runtime_validator_set(number, hash)?,
+				signature_hasher::<Block, _>(&*self.runtime, hash)
+					.map_err(|e| ConsensusError::ClientImport(e.to_string()))?,
+			),
+		};
+
+		verify_with_validator_set::<Block>(number, &validator_set, &proof, hasher)
+			.map(|_| proof)
+			.map_err(|(err, _)| err)
+	}
+
+	fn runtime_validator_set(
+		&self,
+		number: NumberFor<Block>,
+		hash: <Block as BlockT>::Hash,
+	) -> Result<ValidatorSet<AuthorityId>, ConsensusError> {
 		use ConsensusError::ClientImport as ImportError;
 		let beefy_genesis = self
 			.runtime
@@ -101,15 +168,39 @@ where
 		if number < beefy_genesis {
 			return Err(ImportError("BEEFY genesis is set for future block".to_string()))
 		}
-		let validator_set = self
-			.runtime
+		self.runtime
 			.runtime_api()
 			.validator_set(hash)
 			.map_err(|e| ImportError(e.to_string()))?
-			.ok_or_else(|| ImportError("Unknown validator set".to_string()))?;
+			.ok_or_else(|| ImportError("Unknown validator set".to_string()))
+	}
 
-		decode_and_verify_finality_proof::<Block>(&encoded[..], number, &validator_set)
-			.map_err(|(err, _)| err)
+	/// Return `true` if block `hash` is finalized, i.e. it is the canonical block at `number` and
+	/// `number` is not above the last finalized block.
+	fn is_finalized(&self, number: NumberFor<Block>, hash: <Block as BlockT>::Hash) -> bool {
+		let blockchain = self.backend.blockchain();
+		number <= blockchain.info().finalized_number &&
+			blockchain.hash(number).ok().flatten() == Some(hash)
+	}
+
+	/// Store the `proof` of block `hash`, if that block is already finalized.
+	///
+	/// Otherwise, the voter stores it once it processed the proof.
+	fn store_if_finalized(
+		&self,
+		proof: &BeefyVersionedFinalityProof<Block>,
+		number: NumberFor<Block>,
+		hash: <Block as BlockT>::Hash,
+	) {
+		if number > self.backend.blockchain().info().finalized_number {
+			return
+		}
+		if let Err(e) = self.backend.append_justification(hash, (BEEFY_ENGINE_ID, proof.encode())) {
+			warn!(
+				target: LOG_TARGET,
+				"🥩 Error {:?} on storing justification for block {:?}", e, number
+			);
+		}
 	}
 }
 
@@ -137,10 +228,16 @@ where
 		let hash = block.post_hash();
 		let number = *block.header.number();
 
+		// headers announce the hasher along with the validator set, unless it is the default one.
+		let validator_set = find_authorities_change::<Block>(&block.header).map(|validator_set| {
+			(validator_set, find_signature_hasher::<Block>(&block.header).unwrap_or_default())
+		});
+
 		let beefy_encoded = block.justifications.as_mut().and_then(|just| {
 			let encoded = just.get(BEEFY_ENGINE_ID).cloned();
-			// Remove BEEFY justification from the list before giving to `inner`; we send it to the
-			// voter (beefy-gadget) and it will append it to the backend after block is finalized.
+			// Remove BEEFY justification from the list before giving to `inner`; we store it
+			// ourselves if the block is already finalized, otherwise we send it to the voter
+			// (beefy-gadget) and it will append it to the backend after block is finalized.
 			just.remove(BEEFY_ENGINE_ID);
 			encoded
 		});
@@ -148,6 +245,17 @@ where
 		// Run inner block import.
 		let inner_import_result = self.inner.import_block(block).await?;
 
+		// Only the headers of blocks that were checked by `inner` and finalized are trusted to
+		// announce validator sets, as they are preferred over the runtime to verify
+		// justifications.
+		if let (Some((validator_set, hasher)), ImportResult::Imported(_)) =
+			(validator_set, &inner_import_result)
+		{
+			if self.is_finalized(number, hash) {
+				self.note_validator_set(validator_set, hasher);
+			}
+		}
+
 		match (beefy_encoded, &inner_import_result) {
 			(Some(encoded), ImportResult::Imported(_)) => {
 				match self.decode_and_verify(&encoded, number, hash) {
@@ -157,6 +265,7 @@ where
 							target: LOG_TARGET,
 							"🥩 import justif {:?} for block number {:?}.", proof, number
 						);
+						self.store_if_finalized(&proof, number, hash);
 						// Send the justification to the BEEFY voter for processing.
 						self.justification_sender
 							.notify(|| Ok::<_, ()>(proof))
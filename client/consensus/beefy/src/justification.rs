@@ -21,7 +21,7 @@ use codec::{Decode, Encode};
 use sp_consensus::Error as ConsensusError;
 use sp_consensus_beefy::{
 	crypto::{AuthorityId, Signature},
-	ValidatorSet, ValidatorSetId, VersionedFinalityProof,
+	SignatureHasher, ValidatorSet, ValidatorSetId, VersionedFinalityProof,
 };
 use sp_runtime::traits::{Block as BlockT, NumberFor};
 
@@ -42,19 +42,22 @@ pub(crate) fn decode_and_verify_finality_proof<Block: BlockT>(
 	encoded: &[u8],
 	target_number: NumberFor<Block>,
 	validator_set: &ValidatorSet<AuthorityId>,
+	hasher: SignatureHasher,
 ) -> Result<BeefyVersionedFinalityProof<Block>, (ConsensusError, u32)> {
 	let proof = <BeefyVersionedFinalityProof<Block>>::decode(&mut &*encoded)
 		.map_err(|_| (ConsensusError::InvalidJustification, 0))?;
-	verify_with_validator_set::<Block>(target_number, validator_set, &proof).map(|_| proof)
+	verify_with_validator_set::<Block>(target_number, validator_set, &proof, hasher).map(|_| proof)
 }
 
 /// Verify the Beefy finality proof against the validator set at the block it was generated.
+///
+/// Signatures are expected over the commitment hashed with `hasher`.
 pub(crate) fn verify_with_validator_set<Block: BlockT>(
 	target_number: NumberFor<Block>,
 	validator_set: &ValidatorSet<AuthorityId>,
 	proof: &BeefyVersionedFinalityProof<Block>,
+	hasher: SignatureHasher,
 ) -> Result<(), (ConsensusError, u32)> {
-	let mut signatures_checked = 0u32;
 	match proof {
 		VersionedFinalityProof::V1(signed_commitment) => {
 			if signed_commitment.signatures.len() != validator_set.len() ||
@@ -67,24 +70,18 @@ pub(crate) fn verify_with_validator_set<Block: BlockT>(
 			// Arrangement of signatures in the commitment should be in the same order
 			// as validators for that set.
 			let message = signed_commitment.commitment.encode();
-			let valid_signatures = validator_set
+			let signatures: Vec<_> = validator_set
 				.validators()
-				.into_iter()
+				.iter()
 				.zip(signed_commitment.signatures.iter())
-				.filter(|(id, signature)| {
-					signature
-						.as_ref()
-						.map(|sig| {
-							signatures_checked += 1;
-							BeefyKeystore::verify(id, sig, &message[..])
-						})
-						.unwrap_or(false)
-				})
-				.count();
-			if valid_signatures >= crate::round::threshold(validator_set.len()) {
+				.filter_map(|(id, signature)| signature.as_ref().map(|sig| (id, sig)))
+				.collect();
+			let quorum = crate::round::threshold(validator_set.len());
+			let result = BeefyKeystore::verify_batch(&message[..], &signatures, quorum, hasher);
+			if result.valid >= quorum {
 				Ok(())
 			} else {
-				Err((ConsensusError::InvalidJustification, signatures_checked))
+				Err((ConsensusError::InvalidJustification, result.checked))
 			}
 		},
 	}
@@ -126,11 +123,33 @@ pub(crate) mod tests {
 
 		let good_proof = proof.clone().into();
 		// should verify successfully
-		verify_with_validator_set::<Block>(block_num, &validator_set, &good_proof).unwrap();
+		verify_with_validator_set::<Block>(
+			block_num,
+			&validator_set,
+			&good_proof,
+			SignatureHasher::Keccak256,
+		)
+		.unwrap();
+
+		// signatures hashed differently are rejected.
+		match verify_with_validator_set::<Block>(
+			block_num,
+			&validator_set,
+			&good_proof,
+			SignatureHasher::Blake2_256,
+		) {
+			Err((ConsensusError::InvalidJustification, 3)) => (),
+			_ => assert!(false, "Expected Err((ConsensusError::InvalidJustification, 3))"),
+		};
 
 		// wrong block number -> should fail verification
 		let good_proof = proof.clone().into();
-		match verify_with_validator_set::<Block>(block_num + 1, &validator_set, &good_proof) {
+		match verify_with_validator_set::<Block>(
+			block_num + 1,
+			&validator_set,
+			&good_proof,
+			SignatureHasher::Keccak256,
+		) {
 			Err((ConsensusError::InvalidJustification, 0)) => (),
 			e => assert!(false, "Got unexpected {:?}", e),
 		};
@@ -138,7 +157,12 @@ pub(crate) mod tests {
 		// wrong validator set id -> should fail verification
 		let good_proof = proof.clone().into();
 		let other = ValidatorSet::new(make_beefy_ids(keys), 1).unwrap();
-		match verify_with_validator_set::<Block>(block_num, &other, &good_proof) {
+		match verify_with_validator_set::<Block>(
+			block_num,
+			&other,
+			&good_proof,
+			SignatureHasher::Keccak256,
+		) {
 			Err((ConsensusError::InvalidJustification, 0)) => (),
 			e => assert!(false, "Got unexpected {:?}", e),
 		};
@@ -150,7 +174,12 @@ pub(crate) mod tests {
 			VersionedFinalityProof::V1(ref mut sc) => sc,
 		};
 		bad_signed_commitment.signatures.pop().flatten().unwrap();
-		match verify_with_validator_set::<Block>(block_num + 1, &validator_set, &bad_proof.into()) {
+		match verify_with_validator_set::<Block>(
+			block_num + 1,
+			&validator_set,
+			&bad_proof.into(),
+			SignatureHasher::Keccak256,
+		) {
 			Err((ConsensusError::InvalidJustification, 0)) => (),
 			e => assert!(false, "Got unexpected {:?}", e),
 		};
@@ -162,7 +191,12 @@ pub(crate) mod tests {
 		};
 		// remove a signature (but same length)
 		*bad_signed_commitment.signatures.first_mut().unwrap() = None;
-		match verify_with_validator_set::<Block>(block_num, &validator_set, &bad_proof.into()) {
+		match verify_with_validator_set::<Block>(
+			block_num,
+			&validator_set,
+			&bad_proof.into(),
+			SignatureHasher::Keccak256,
+		) {
 			Err((ConsensusError::InvalidJustification, 2)) => (),
 			e => assert!(false, "Got unexpected {:?}", e),
 		};
@@ -175,7 +209,12 @@ pub(crate) mod tests {
 		// change a signature to a different key
 		*bad_signed_commitment.signatures.first_mut().unwrap() =
 			Some(Keyring::Dave.sign(&bad_signed_commitment.commitment.encode()));
-		match verify_with_validator_set::<Block>(block_num, &validator_set, &bad_proof.into()) {
+		match verify_with_validator_set::<Block>(
+			block_num,
+			&validator_set,
+			&bad_proof.into(),
+			SignatureHasher::Keccak256,
+		) {
 			Err((ConsensusError::InvalidJustification, 3)) => (),
 			e => assert!(false, "Got unexpected {:?}", e),
 		};
@@ -193,8 +232,13 @@ pub(crate) mod tests {
 		let encoded = versioned_proof.encode();
 
 		// should successfully decode and verify
-		let verified =
-			decode_and_verify_finality_proof::<Block>(&encoded, block_num, &validator_set).unwrap();
+		let verified = decode_and_verify_finality_proof::<Block>(
+			&encoded,
+			block_num,
+			&validator_set,
+			SignatureHasher::Keccak256,
+		)
+		.unwrap();
 		assert_eq!(verified, versioned_proof);
 	}
 }
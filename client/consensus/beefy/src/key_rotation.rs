@@ -0,0 +1,132 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Rotation of the local BEEFY key.
+//!
+//! [`rotate_key`] generates a new BEEFY key and proves its possession by signing the account of
+//! the validator who is going to register it. The new public key has to be placed at the BEEFY
+//! position of the validator's session keys, which are then registered, together with the proof
+//! of possession, through the `set_keys` call of the session pallet.
+
+use codec::{Decode, Encode};
+use sp_consensus_beefy::{crypto, SignatureHasher, KEY_TYPE};
+use sp_keystore::{Error, KeystorePtr};
+
+use crate::keystore::{AuthorityIdBound, BeefyKeystore};
+
+#[cfg(feature = "bls-experimental")]
+use sp_consensus_beefy::bls_crypto;
+
+/// Prefix of the message signed by a proof of possession, see [`proof_of_possession_message`].
+pub const PROOF_OF_POSSESSION_TAG: &[u8] = b"BEEFY_POP";
+
+/// Hasher of proofs of possession, which don't depend on the hasher declared by the runtime.
+const PROOF_OF_POSSESSION_HASHER: SignatureHasher = SignatureHasher::Keccak256;
+
+/// A newly generated BEEFY key, together with the proof of its possession.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct KeyRotation {
+	/// The new BEEFY authority id.
+	pub public: crypto::AuthorityId,
+	/// Signature of [`proof_of_possession_message`] by the new key.
+	pub proof_of_possession: crypto::Signature,
+}
+
+/// Return the message signed to prove possession of a BEEFY key on behalf of `owner`, the encoded
+/// stash or controller account of the validator.
+pub fn proof_of_possession_message(owner: &[u8]) -> Vec<u8> {
+	[PROOF_OF_POSSESSION_TAG, owner].concat()
+}
+
+/// Generate a new BEEFY key in `keystore` and prove its possession on behalf of `owner`.
+pub fn rotate_key(keystore: &KeystorePtr, owner: &[u8]) -> Result<KeyRotation, Error> {
+	let public: crypto::AuthorityId = keystore.ecdsa_generate_new(KEY_TYPE, None)?.into();
+	let proof_of_possession = <crypto::AuthorityId as AuthorityIdBound>::sign(
+		keystore,
+		&public,
+		&proof_of_possession_message(owner),
+		PROOF_OF_POSSESSION_HASHER,
+	)
+	.map_err(|e| Error::Other(e.to_string()))?;
+
+	Ok(KeyRotation { public, proof_of_possession })
+}
+
+/// Check that `rotation` proves possession of its key on behalf of `owner`.
+pub fn verify_proof_of_possession(rotation: &KeyRotation, owner: &[u8]) -> bool {
+	BeefyKeystore::<crypto::AuthorityId>::verify(
+		&rotation.public,
+		&rotation.proof_of_possession,
+		&proof_of_possession_message(owner),
+		PROOF_OF_POSSESSION_HASHER,
+	)
+}
+
+/// Prove possession of the BEEFY BLS key `public` found in `keystore`, as needed to register it
+/// for a validator set with aggregatable signatures.
+///
+/// Unlike [`KeyRotation::proof_of_possession`], the proof isn't bound to an owner account.
+#[cfg(feature = "bls-experimental")]
+pub fn bls_proof_of_possession(
+	keystore: &KeystorePtr,
+	public: &bls_crypto::AuthorityId,
+) -> Result<bls_crypto::Signature, Error> {
+	BeefyKeystore::from(Some(keystore.clone()))
+		.sign_proof_of_possession(public)
+		.map_err(|e| Error::Other(e.to_string()))
+}
+
+/// Check that `proof` proves possession of the BEEFY BLS key `public`, see
+/// [`bls_proof_of_possession`].
+#[cfg(feature = "bls-experimental")]
+pub fn verify_bls_proof_of_possession(
+	public: &bls_crypto::AuthorityId,
+	proof: &bls_crypto::Signature,
+) -> bool {
+	BeefyKeystore::verify_proof_of_possession(public, proof)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_application_crypto::RuntimeAppPublic;
+	use sp_consensus_beefy::Keyring;
+	use sp_keystore::testing::MemoryKeystore;
+
+	#[test]
+	fn rotate_key_works() {
+		let keystore: KeystorePtr = MemoryKeystore::new().into();
+		let owner = b"stash account";
+
+		let rotation = rotate_key(&keystore, owner).unwrap();
+		assert!(keystore.has_keys(&[(rotation.public.to_raw_vec(), KEY_TYPE)]));
+		assert!(verify_proof_of_possession(&rotation, owner));
+
+		// proof is bound to the owner account.
+		assert!(!verify_proof_of_possession(&rotation, b"other account"));
+
+		// and to the key that signed it.
+		let other = rotate_key(&keystore, owner).unwrap();
+		assert_ne!(other.public, rotation.public);
+		let forged = KeyRotation {
+			public: Keyring::Alice.public(),
+			proof_of_possession: other.proof_of_possession,
+		};
+		assert!(!verify_proof_of_possession(&forged, owner));
+	}
+}
@@ -16,43 +16,340 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use sp_application_crypto::RuntimeAppPublic;
-use sp_core::keccak_256;
+use codec::{Codec, Decode, Encode};
+use parking_lot::Mutex;
+use sp_core::{crypto::ByteArray, hashing::blake2_256};
 use sp_keystore::KeystorePtr;
+use std::{collections::VecDeque, fmt::Debug, marker::PhantomData};
+
+#[cfg(feature = "parallel-verification")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel-verification")]
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 
 use log::warn;
 
 use sp_consensus_beefy::{
-	crypto::{Public, Signature},
-	BeefyAuthorityId, KEY_TYPE,
+	crypto, BeefyAuthorityIdWithHasher, SignatureHasher, ValidatorSet, ValidatorSetId, KEY_TYPE,
 };
 
-use crate::{error, LOG_TARGET};
+#[cfg(feature = "bls-experimental")]
+use sp_consensus_beefy::bls_crypto;
+#[cfg(feature = "bls-experimental")]
+use sp_core::{bls377, Pair};
+
+use crate::{
+	error::{self, KeystoreError},
+	LOG_TARGET,
+};
+
+/// A BEEFY authority id, together with the way to use its crypto scheme through a
+/// [`sp_keystore::Keystore`].
+///
+/// Implementing this for another authority id type allows [`BeefyKeystore`] to be used with other
+/// signature schemes than ECDSA.
+pub(crate) trait AuthorityIdBound:
+	ByteArray + Codec + Clone + Debug + PartialEq + Send + Sync + 'static
+{
+	/// The signature type of this crypto scheme.
+	type Signature: Codec + Clone + Debug + PartialEq + Send + Sync;
+
+	/// Return all the public keys of this type found in `store`.
+	fn public_keys(store: &KeystorePtr) -> Result<Vec<Self>, error::Error>;
+
+	/// Sign `message` with the private key of `public` found in `store`.
+	///
+	/// Implementations are responsible for hashing `message` with `hasher`, if their signature
+	/// scheme requires it.
+	fn sign(
+		store: &KeystorePtr,
+		public: &Self,
+		message: &[u8],
+		hasher: SignatureHasher,
+	) -> Result<Self::Signature, error::Error>;
+
+	/// Sign all `messages` with the private key of `public` found in `store`, in a single
+	/// keystore call if the signature scheme allows it.
+	///
+	/// Messages are hashed as in [`Self::sign`].
+	fn sign_batch(
+		store: &KeystorePtr,
+		public: &Self,
+		messages: &[&[u8]],
+		hasher: SignatureHasher,
+	) -> Result<Vec<Self::Signature>, error::Error>;
+
+	/// Return `true` if `signature` is a valid signature of `message` by `self`.
+	///
+	/// Messages are hashed as in [`Self::sign`].
+	fn verify_signature(
+		&self,
+		signature: &Self::Signature,
+		message: &[u8],
+		hasher: SignatureHasher,
+	) -> bool;
+}
+
+impl AuthorityIdBound for crypto::AuthorityId {
+	type Signature = crypto::Signature;
+
+	fn public_keys(store: &KeystorePtr) -> Result<Vec<Self>, error::Error> {
+		// unlike `ecdsa_public_keys`, `keys` reports the failures of the store, e.g. of a remote
+		// signer, instead of hiding them behind an empty list.
+		let keys = store.keys(KEY_TYPE).map_err(KeystoreError::from)?;
+		Ok(keys.iter().filter_map(|key| Self::from_slice(key).ok()).collect())
+	}
+
+	fn sign(
+		store: &KeystorePtr,
+		public: &Self,
+		message: &[u8],
+		hasher: SignatureHasher,
+	) -> Result<crypto::Signature, error::Error> {
+		let msg = hasher.hash(message);
+
+		let sig = store
+			.ecdsa_sign_prehashed(KEY_TYPE, public.as_ref(), &msg)
+			.map_err(KeystoreError::from)?
+			.ok_or_else(|| KeystoreError::MissingKey(format!("{:?}", public)))?;
+
+		// check that `sig` has the expected result type
+		let sig = sig.clone().try_into().map_err(|_| {
+			KeystoreError::InvalidSignature(format!("{:?} for key {:?}", sig, public))
+		})?;
+
+		Ok(sig)
+	}
+
+	fn sign_batch(
+		store: &KeystorePtr,
+		public: &Self,
+		messages: &[&[u8]],
+		hasher: SignatureHasher,
+	) -> Result<Vec<crypto::Signature>, error::Error> {
+		let msgs: Vec<[u8; 32]> = messages.iter().map(|msg| hasher.hash(msg)).collect();
+
+		let sigs = store
+			.ecdsa_sign_prehashed_batch(KEY_TYPE, public.as_ref(), &msgs)
+			.map_err(KeystoreError::from)?
+			.ok_or_else(|| KeystoreError::MissingKey(format!("{:?}", public)))?;
+
+		if sigs.len() != messages.len() {
+			return Err(KeystoreError::InvalidSignature(format!(
+				"got {} signatures for {} messages",
+				sigs.len(),
+				messages.len()
+			))
+			.into())
+		}
+
+		Ok(sigs.into_iter().map(Into::into).collect())
+	}
+
+	fn verify_signature(
+		&self,
+		signature: &crypto::Signature,
+		message: &[u8],
+		hasher: SignatureHasher,
+	) -> bool {
+		self.verify_with_hasher(signature, message, hasher)
+	}
+}
+
+#[cfg(feature = "bls-experimental")]
+impl AuthorityIdBound for bls_crypto::AuthorityId {
+	type Signature = bls_crypto::Signature;
+
+	fn public_keys(store: &KeystorePtr) -> Result<Vec<Self>, error::Error> {
+		Ok(store.bls377_public_keys(KEY_TYPE).drain(..).map(Self::from).collect())
+	}
+
+	// BLS hashes messages to the curve itself, `hasher` isn't used.
+	fn sign(
+		store: &KeystorePtr,
+		public: &Self,
+		message: &[u8],
+		_hasher: SignatureHasher,
+	) -> Result<bls_crypto::Signature, error::Error> {
+		let sig = store
+			.bls377_sign(KEY_TYPE, public.as_ref(), message)
+			.map_err(KeystoreError::from)?
+			.ok_or_else(|| KeystoreError::MissingKey(format!("{:?}", public)))?;
+
+		Ok(sig.into())
+	}
+
+	fn sign_batch(
+		store: &KeystorePtr,
+		public: &Self,
+		messages: &[&[u8]],
+		hasher: SignatureHasher,
+	) -> Result<Vec<bls_crypto::Signature>, error::Error> {
+		messages
+			.iter()
+			.map(|message| Self::sign(store, public, message, hasher))
+			.collect()
+	}
 
-/// Hasher used for BEEFY signatures.
-pub(crate) type BeefySignatureHasher = sp_runtime::traits::Keccak256;
+	fn verify_signature(
+		&self,
+		signature: &bls_crypto::Signature,
+		message: &[u8],
+		_hasher: SignatureHasher,
+	) -> bool {
+		<bls377::Pair as Pair>::verify(signature.as_ref(), message, self.as_ref())
+	}
+}
+
+/// Prefix of the message signed by a proof of possession of a BLS key.
+///
+/// The keystore hashes BLS messages to the curve with a fixed domain separation tag, so proofs of
+/// possession are kept apart from commitment signatures by their message: unlike the SCALE
+/// encoding of a commitment, it starts with this tag.
+#[cfg(feature = "bls-experimental")]
+pub(crate) const BLS_PROOF_OF_POSSESSION_TAG: &[u8] = b"BEEFY_BLS_POP";
+
+/// The outcome of [`BeefyKeystore::verify_batch`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct BatchVerification {
+	/// The number of valid signatures found.
+	pub valid: usize,
+	/// The number of signatures that were verified.
+	pub checked: u32,
+}
+
+/// The maximum number of entries kept in a [`SigningRecord`].
+const MAX_SIGNING_RECORD_ENTRIES: usize = 256;
+
+/// A record of the `(validator set id, block number, payload hash)` of the most recent commitments
+/// signed by the local authority.
+///
+/// It is persisted across restarts, and checked before signing, to refuse signing two different
+/// payloads for the same round, which would get the authority slashed. Only the most recent
+/// [`MAX_SIGNING_RECORD_ENTRIES`] entries are kept.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct SigningRecord<N> {
+	entries: VecDeque<(ValidatorSetId, N, [u8; 32])>,
+}
+
+impl<N: Copy + Debug + PartialEq> SigningRecord<N> {
+	/// Record that we are about to sign `payload_hash` for `block_number` of `validator_set_id`.
+	///
+	/// Return `Ok(true)` if this is a new entry, that should be persisted before signing,
+	/// `Ok(false)` if the same payload was already signed, and an error if a different payload was
+	/// signed for the same round.
+	pub fn check_and_record(
+		&mut self,
+		validator_set_id: ValidatorSetId,
+		block_number: N,
+		payload_hash: [u8; 32],
+	) -> Result<bool, KeystoreError> {
+		let signed = self
+			.entries
+			.iter()
+			.find(|(id, number, _)| *id == validator_set_id && *number == block_number);
+		match signed {
+			Some((_, _, hash)) if *hash == payload_hash => Ok(false),
+			Some((_, _, hash)) => Err(KeystoreError::ConflictingCommitment(format!(
+				"already signed {:?} for block #{:?} of set {}, not signing {:?}",
+				sp_core::hexdisplay::HexDisplay::from(hash),
+				block_number,
+				validator_set_id,
+				sp_core::hexdisplay::HexDisplay::from(&payload_hash),
+			))),
+			None => {
+				if self.entries.len() >= MAX_SIGNING_RECORD_ENTRIES {
+					self.entries.pop_front();
+				}
+				self.entries.push_back((validator_set_id, block_number, payload_hash));
+				Ok(true)
+			},
+		}
+	}
+}
+
+/// The number of lookups answered by a [`CachedAuthorityId`] before the keystore is checked again.
+///
+/// Keys can be inserted into, or removed from, the keystore without notice, e.g. through RPC, so
+/// the cached answer is refreshed after this many lookups.
+const AUTHORITY_ID_CACHE_LOOKUPS: u32 = 64;
+
+/// The local authority id found for a validator set, see [`BeefyKeystore::authority_id`].
+struct CachedAuthorityId<AuthorityId> {
+	validator_set_id: ValidatorSetId,
+	/// Hash of the validators of the set.
+	validators_hash: [u8; 32],
+	/// The number of lookups this entry can still answer.
+	lookups_left: u32,
+	authority_id: Option<AuthorityId>,
+}
 
 /// A BEEFY specific keystore implemented as a `Newtype`. This is basically a
 /// wrapper around [`sp_keystore::Keystore`] and allows to customize
 /// common cryptographic functionality.
-pub(crate) struct BeefyKeystore(Option<KeystorePtr>);
-
-impl BeefyKeystore {
+///
+/// The crypto scheme is given by `AuthorityId`, see [`AuthorityIdBound`].
+pub(crate) struct BeefyKeystore<AuthorityId = crypto::AuthorityId>(
+	Option<KeystorePtr>,
+	Mutex<Option<CachedAuthorityId<AuthorityId>>>,
+	PhantomData<AuthorityId>,
+);
+
+impl<AuthorityId: AuthorityIdBound> BeefyKeystore<AuthorityId> {
 	/// Check if the keystore contains a private key for one of the public keys
 	/// contained in `keys`. A public key with a matching private key is known
 	/// as a local authority id.
 	///
 	/// Return the public key for which we also do have a private key. If no
 	/// matching private key is found, `None` will be returned.
-	pub fn authority_id(&self, keys: &[Public]) -> Option<Public> {
-		let store = self.0.clone()?;
+	///
+	/// The result is cached per validator set, and refreshed every
+	/// [`AUTHORITY_ID_CACHE_LOOKUPS`] lookups to notice keys changing in the keystore.
+	pub fn authority_id(&self, validator_set: &ValidatorSet<AuthorityId>) -> Option<AuthorityId> {
+		let store = self.0.as_ref()?;
+		let validators_hash = blake2_256(&validator_set.validators().encode());
+
+		let mut cache = self.1.lock();
+		match &mut *cache {
+			Some(cached)
+				if cached.validator_set_id == validator_set.id() &&
+					cached.validators_hash == validators_hash &&
+					cached.lookups_left > 0 =>
+			{
+				cached.lookups_left -= 1;
+				cached.authority_id.clone()
+			},
+			_ => {
+				let authority_id = match Self::find_authority_id(store, validator_set.validators())
+				{
+					Ok(authority_id) => authority_id,
+					Err(e) => {
+						// not cached, so that the keystore is asked again on the next lookup.
+						warn!(target: LOG_TARGET, "🥩 Failed to look up the local authority id: {}", e);
+						return None
+					},
+				};
+				*cache = Some(CachedAuthorityId {
+					validator_set_id: validator_set.id(),
+					validators_hash,
+					lookups_left: AUTHORITY_ID_CACHE_LOOKUPS,
+					authority_id: authority_id.clone(),
+				});
+				authority_id
+			},
+		}
+	}
+
+	fn find_authority_id(
+		store: &KeystorePtr,
+		keys: &[AuthorityId],
+	) -> Result<Option<AuthorityId>, error::Error> {
+		// the keystore is listed once, rather than asked about every validator, as each query may
+		// be a round-trip to a remote signer.
+		let local = AuthorityId::public_keys(store)?;
 
 		// we do check for multiple private keys as a key store sanity check.
-		let public: Vec<Public> = keys
-			.iter()
-			.filter(|k| store.has_keys(&[(k.to_raw_vec(), KEY_TYPE)]))
-			.cloned()
-			.collect();
+		let public: Vec<AuthorityId> = keys.iter().filter(|k| local.contains(k)).cloned().collect();
 
 		if public.len() > 1 {
 			warn!(
@@ -63,71 +360,229 @@ impl BeefyKeystore {
 			);
 		}
 
-		public.get(0).cloned()
+		Ok(public.first().cloned())
 	}
 
 	/// Sign `message` with the `public` key.
 	///
-	/// Note that `message` usually will be pre-hashed before being signed.
+	/// Note that `message` usually will be pre-hashed with `hasher` before being signed.
 	///
 	/// Return the message signature or an error in case of failure.
-	pub fn sign(&self, public: &Public, message: &[u8]) -> Result<Signature, error::Error> {
-		let store = self.0.clone().ok_or_else(|| error::Error::Keystore("no Keystore".into()))?;
-
-		let msg = keccak_256(message);
-		let public = public.as_ref();
+	pub fn sign(
+		&self,
+		public: &AuthorityId,
+		message: &[u8],
+		hasher: SignatureHasher,
+	) -> Result<<AuthorityId as AuthorityIdBound>::Signature, error::Error> {
+		let store = self.0.clone().ok_or(KeystoreError::NoKeystore)?;
+
+		<AuthorityId as AuthorityIdBound>::sign(&store, public, message, hasher)
+	}
 
-		let sig = store
-			.ecdsa_sign_prehashed(KEY_TYPE, public, &msg)
-			.map_err(|e| error::Error::Keystore(e.to_string()))?
-			.ok_or_else(|| error::Error::Signature("ecdsa_sign_prehashed() failed".to_string()))?;
+	/// Sign all `messages` with the `public` key, in a single keystore round-trip when possible.
+	///
+	/// This is meant for signing many commitments back-to-back, e.g. when catching up on
+	/// mandatory blocks, where the per-call keystore overhead dominates.
+	///
+	/// Return the signatures in the order of `messages`, or an error in case of failure.
+	pub fn sign_batch(
+		&self,
+		public: &AuthorityId,
+		messages: &[&[u8]],
+		hasher: SignatureHasher,
+	) -> Result<Vec<<AuthorityId as AuthorityIdBound>::Signature>, error::Error> {
+		let store = self.0.clone().ok_or(KeystoreError::NoKeystore)?;
+
+		<AuthorityId as AuthorityIdBound>::sign_batch(&store, public, messages, hasher)
+	}
 
-		// check that `sig` has the expected result type
-		let sig = sig.clone().try_into().map_err(|_| {
-			error::Error::Signature(format!("invalid signature {:?} for key {:?}", sig, public))
-		})?;
+	/// Returns a vector of `AuthorityId` keys which are currently supported (i.e. found in the
+	/// keystore).
+	pub fn public_keys(&self) -> Result<Vec<AuthorityId>, error::Error> {
+		let store = self.0.clone().ok_or(KeystoreError::NoKeystore)?;
 
-		Ok(sig)
+		AuthorityId::public_keys(&store)
 	}
 
-	/// Returns a vector of [`sp_consensus_beefy::crypto::Public`] keys which are currently
-	/// supported (i.e. found in the keystore).
-	pub fn public_keys(&self) -> Result<Vec<Public>, error::Error> {
-		let store = self.0.clone().ok_or_else(|| error::Error::Keystore("no Keystore".into()))?;
+	/// Use the `public` key to verify that `sig` is a valid signature for `message`, hashed with
+	/// `hasher`.
+	///
+	/// Return `true` if the signature is authentic, `false` otherwise.
+	pub fn verify(
+		public: &AuthorityId,
+		sig: &<AuthorityId as AuthorityIdBound>::Signature,
+		message: &[u8],
+		hasher: SignatureHasher,
+	) -> bool {
+		public.verify_signature(sig, message, hasher)
+	}
 
-		let pk: Vec<Public> =
-			store.ecdsa_public_keys(KEY_TYPE).drain(..).map(Public::from).collect();
+	/// Use [`Self::verify`] on `signatures` of the same `message`, until `quorum` of them are
+	/// found to be valid.
+	///
+	/// Signatures are verified in parallel when the `parallel-verification` feature is enabled.
+	/// In that case, slightly more than `quorum` signatures may be verified.
+	pub fn verify_batch(
+		message: &[u8],
+		signatures: &[(&AuthorityId, &<AuthorityId as AuthorityIdBound>::Signature)],
+		quorum: usize,
+		hasher: SignatureHasher,
+	) -> BatchVerification {
+		#[cfg(not(feature = "parallel-verification"))]
+		{
+			let mut result = BatchVerification { valid: 0, checked: 0 };
+			for (public, sig) in signatures {
+				if result.valid >= quorum {
+					break
+				}
+				result.checked += 1;
+				if Self::verify(public, sig, message, hasher) {
+					result.valid += 1;
+				}
+			}
+			result
+		}
 
-		Ok(pk)
+		#[cfg(feature = "parallel-verification")]
+		{
+			let (valid, checked) = (AtomicUsize::new(0), AtomicU32::new(0));
+			// `any` stops as soon as one of the workers has seen the quorum reached.
+			signatures.par_iter().any(|(public, sig)| {
+				if valid.load(Ordering::Relaxed) >= quorum {
+					return true
+				}
+				checked.fetch_add(1, Ordering::Relaxed);
+				Self::verify(public, sig, message, hasher) &&
+					valid.fetch_add(1, Ordering::Relaxed) + 1 >= quorum
+			});
+			BatchVerification { valid: valid.into_inner(), checked: checked.into_inner() }
+		}
 	}
+}
 
-	/// Use the `public` key to verify that `sig` is a valid signature for `message`.
+#[cfg(feature = "bls-experimental")]
+impl BeefyKeystore<bls_crypto::AuthorityId> {
+	/// Prove possession of the private key of the BLS `public` key, by signing the key itself.
 	///
-	/// Return `true` if the signature is authentic, `false` otherwise.
-	pub fn verify(public: &Public, sig: &Signature, message: &[u8]) -> bool {
-		BeefyAuthorityId::<BeefySignatureHasher>::verify(public, sig, message)
+	/// Such a proof is required before admitting `public` to a validator set whose signatures get
+	/// aggregated, to prevent rogue key attacks.
+	pub fn sign_proof_of_possession(
+		&self,
+		public: &bls_crypto::AuthorityId,
+	) -> Result<bls_crypto::Signature, error::Error> {
+		let store = self.0.clone().ok_or(KeystoreError::NoKeystore)?;
+		let message = Self::proof_of_possession_message(public);
+
+		// BLS doesn't use the hasher.
+		<bls_crypto::AuthorityId as AuthorityIdBound>::sign(
+			&store,
+			public,
+			&message,
+			SignatureHasher::Keccak256,
+		)
+	}
+
+	/// Return `true` if `proof` proves possession of the private key of `public`, see
+	/// [`Self::sign_proof_of_possession`].
+	pub fn verify_proof_of_possession(
+		public: &bls_crypto::AuthorityId,
+		proof: &bls_crypto::Signature,
+	) -> bool {
+		let message = Self::proof_of_possession_message(public);
+		Self::verify(public, proof, &message, SignatureHasher::Keccak256)
+	}
+
+	fn proof_of_possession_message(public: &bls_crypto::AuthorityId) -> Vec<u8> {
+		[BLS_PROOF_OF_POSSESSION_TAG, public.as_slice()].concat()
 	}
 }
 
-impl From<Option<KeystorePtr>> for BeefyKeystore {
-	fn from(store: Option<KeystorePtr>) -> BeefyKeystore {
-		BeefyKeystore(store)
+impl<AuthorityId> From<Option<KeystorePtr>> for BeefyKeystore<AuthorityId> {
+	fn from(store: Option<KeystorePtr>) -> BeefyKeystore<AuthorityId> {
+		BeefyKeystore(store, Mutex::new(None), PhantomData)
 	}
 }
 
 #[cfg(test)]
 pub mod tests {
 	use sp_consensus_beefy::{crypto, Keyring};
-	use sp_core::{ecdsa, Pair};
+	use sp_core::{ecdsa, keccak_256, Pair};
 	use sp_keystore::testing::MemoryKeystore;
 
 	use super::*;
-	use crate::error::Error;
+	use crate::error::{Error, KeystoreError};
 
 	fn keystore() -> KeystorePtr {
 		MemoryKeystore::new().into()
 	}
 
+	#[cfg(feature = "bls-experimental")]
+	#[test]
+	fn bls_proof_of_possession_works() {
+		let store = keystore();
+		let public: bls_crypto::AuthorityId =
+			store.bls377_generate_new(KEY_TYPE, Some("//Alice")).unwrap().into();
+		let other: bls_crypto::AuthorityId =
+			store.bls377_generate_new(KEY_TYPE, Some("//Bob")).unwrap().into();
+		let store: BeefyKeystore<bls_crypto::AuthorityId> = Some(store).into();
+
+		let proof = store.sign_proof_of_possession(&public).unwrap();
+		assert!(BeefyKeystore::verify_proof_of_possession(&public, &proof));
+		assert!(!BeefyKeystore::verify_proof_of_possession(&other, &proof));
+
+		// a plain signature of the key doesn't prove its possession, and a proof is no plain
+		// signature.
+		let signature = store.sign(&public, public.as_slice(), SignatureHasher::Keccak256).unwrap();
+		assert!(!BeefyKeystore::verify_proof_of_possession(&public, &signature));
+		assert!(!BeefyKeystore::verify(
+			&public,
+			&proof,
+			public.as_slice(),
+			SignatureHasher::Keccak256
+		));
+
+		let no_store: BeefyKeystore<bls_crypto::AuthorityId> = None.into();
+		assert_eq!(
+			no_store.sign_proof_of_possession(&public),
+			Err(Error::Keystore(KeystoreError::NoKeystore))
+		);
+	}
+
+	#[test]
+	fn signing_record_refuses_conflicting_payload() {
+		let mut record = SigningRecord::<u64>::default();
+
+		assert_eq!(record.check_and_record(1, 10, [1; 32]), Ok(true));
+		// signing the same payload again is fine, and needs not be persisted again.
+		assert_eq!(record.check_and_record(1, 10, [1; 32]), Ok(false));
+		assert!(matches!(
+			record.check_and_record(1, 10, [2; 32]),
+			Err(KeystoreError::ConflictingCommitment(_))
+		));
+
+		// other rounds and other sets are independent.
+		assert_eq!(record.check_and_record(1, 11, [2; 32]), Ok(true));
+		assert_eq!(record.check_and_record(2, 10, [2; 32]), Ok(true));
+
+		// the record survives a round-trip through the database.
+		let mut decoded = SigningRecord::<u64>::decode(&mut &record.encode()[..]).unwrap();
+		assert_eq!(decoded, record);
+		assert!(decoded.check_and_record(1, 10, [2; 32]).is_err());
+	}
+
+	#[test]
+	fn signing_record_is_bounded() {
+		let mut record = SigningRecord::<u64>::default();
+		for number in 0..MAX_SIGNING_RECORD_ENTRIES as u64 + 1 {
+			assert_eq!(record.check_and_record(1, number, [1; 32]), Ok(true));
+		}
+		assert_eq!(record.entries.len(), MAX_SIGNING_RECORD_ENTRIES);
+
+		// the oldest entry has been forgotten.
+		assert_eq!(record.check_and_record(1, 0, [2; 32]), Ok(true));
+		assert!(record.check_and_record(1, 2, [2; 32]).is_err());
+	}
+
 	#[test]
 	fn verify_should_work() {
 		let msg = keccak_256(b"I am Alice!");
@@ -206,15 +661,70 @@ pub mod tests {
 
 		let mut keys = vec![bob, charlie];
 
-		let id = store.authority_id(keys.as_slice());
+		let id = store.authority_id(&ValidatorSet::new(keys.clone(), 0).unwrap());
 		assert!(id.is_none());
 
 		keys.push(alice.clone());
 
-		let id = store.authority_id(keys.as_slice()).unwrap();
+		let id = store.authority_id(&ValidatorSet::new(keys, 1).unwrap()).unwrap();
 		assert_eq!(id, alice);
 	}
 
+	#[test]
+	fn authority_id_is_cached_per_validator_set() {
+		let store = keystore();
+		let beefy_store: BeefyKeystore = Some(store.clone()).into();
+
+		let keys: Vec<crypto::Public> =
+			vec![Keyring::Alice.public(), Keyring::Bob.public(), Keyring::Charlie.public()];
+		let validator_set = ValidatorSet::new(keys.clone(), 0).unwrap();
+		assert!(beefy_store.authority_id(&validator_set).is_none());
+
+		// new keys are found once the cached answer is refreshed.
+		store.ecdsa_generate_new(KEY_TYPE, Some(&Keyring::Bob.to_seed())).unwrap();
+		for _ in 0..AUTHORITY_ID_CACHE_LOOKUPS {
+			assert!(beefy_store.authority_id(&validator_set).is_none());
+		}
+		assert_eq!(beefy_store.authority_id(&validator_set), Some(Keyring::Bob.public()));
+
+		// ids outside of the set are never returned, even for the same set id.
+		let other_set = ValidatorSet::new(vec![Keyring::Alice.public()], 0).unwrap();
+		assert!(beefy_store.authority_id(&other_set).is_none());
+		assert_eq!(beefy_store.authority_id(&validator_set), Some(Keyring::Bob.public()));
+
+		// a new set id invalidates the cache.
+		let next_set = ValidatorSet::new(vec![Keyring::Alice.public()], 1).unwrap();
+		assert!(beefy_store.authority_id(&next_set).is_none());
+	}
+
+	#[test]
+	fn verify_batch_works() {
+		let msg = b"I am Alice, Bob or Charlie!";
+		let keys = [Keyring::Alice, Keyring::Bob, Keyring::Charlie];
+		let ids: Vec<crypto::Public> = keys.iter().map(|k| k.public()).collect();
+		let mut sigs: Vec<crypto::Signature> = keys.iter().map(|k| k.sign(msg)).collect();
+		// Dave signs in place of Bob.
+		sigs[1] = Keyring::Dave.sign(msg);
+		let batch: Vec<_> = ids.iter().zip(sigs.iter()).collect();
+
+		let result = BeefyKeystore::verify_batch(msg, &batch, 3, SignatureHasher::Keccak256);
+		assert_eq!(result, BatchVerification { valid: 2, checked: 3 });
+
+		let result = BeefyKeystore::verify_batch(msg, &batch, 2, SignatureHasher::Keccak256);
+		assert_eq!(result.valid, 2);
+		// serial verification stops as soon as the quorum is reached.
+		#[cfg(not(feature = "parallel-verification"))]
+		assert_eq!(result.checked, 3);
+
+		let result = BeefyKeystore::verify_batch(msg, &batch, 1, SignatureHasher::Keccak256);
+		assert!(result.valid >= 1);
+		#[cfg(not(feature = "parallel-verification"))]
+		assert_eq!(result, BatchVerification { valid: 1, checked: 1 });
+
+		let result = BeefyKeystore::verify_batch(msg, &batch[..0], 1, SignatureHasher::Keccak256);
+		assert_eq!(result, BatchVerification { valid: 0, checked: 0 });
+	}
+
 	#[test]
 	fn sign_works() {
 		let store = keystore();
@@ -229,12 +739,41 @@ pub mod tests {
 
 		let msg = b"are you involved or commited?";
 
-		let sig1 = store.sign(&alice, msg).unwrap();
+		let sig1 = store.sign(&alice, msg, SignatureHasher::Keccak256).unwrap();
 		let sig2 = Keyring::Alice.sign(msg);
 
 		assert_eq!(sig1, sig2);
 	}
 
+	#[test]
+	fn sign_batch_works() {
+		let store = keystore();
+
+		let alice: crypto::Public = store
+			.ecdsa_generate_new(KEY_TYPE, Some(&Keyring::Alice.to_seed()))
+			.ok()
+			.unwrap()
+			.into();
+
+		let store: BeefyKeystore = Some(store).into();
+
+		let msgs: [&[u8]; 2] = [b"first commitment", b"second commitment"];
+
+		let sigs = store.sign_batch(&alice, &msgs, SignatureHasher::Keccak256).unwrap();
+		assert_eq!(sigs, vec![Keyring::Alice.sign(msgs[0]), Keyring::Alice.sign(msgs[1])]);
+		assert!(store.sign_batch(&alice, &[], SignatureHasher::Keccak256).unwrap().is_empty());
+
+		// unknown key
+		let bob = Keyring::Bob.public();
+		let err = Error::Keystore(KeystoreError::MissingKey(format!("{:?}", bob)));
+		assert_eq!(store.sign_batch(&bob, &msgs, SignatureHasher::Keccak256).err().unwrap(), err);
+
+		// no keystore
+		let store: BeefyKeystore = None.into();
+		let err = Error::Keystore(KeystoreError::NoKeystore);
+		assert_eq!(store.sign_batch(&alice, &msgs, SignatureHasher::Keccak256).err().unwrap(), err);
+	}
+
 	#[test]
 	fn sign_error() {
 		let store = keystore();
@@ -246,12 +785,23 @@ pub mod tests {
 		let alice = Keyring::Alice.public();
 
 		let msg = b"are you involved or commited?";
-		let sig = store.sign(&alice, msg).err().unwrap();
-		let err = Error::Signature("ecdsa_sign_prehashed() failed".to_string());
+		let sig = store.sign(&alice, msg, SignatureHasher::Keccak256).err().unwrap();
+		let err = Error::Keystore(KeystoreError::MissingKey(format!("{:?}", alice)));
 
 		assert_eq!(sig, err);
 	}
 
+	#[test]
+	fn transient_sign_errors() {
+		let err = Error::from(KeystoreError::from(sp_keystore::Error::Unavailable));
+		assert!(matches!(err, Error::Keystore(e) if e.is_transient()));
+
+		// permanent errors.
+		assert!(!KeystoreError::NoKeystore.is_transient());
+		assert!(!KeystoreError::MissingKey(format!("{:?}", Keyring::Alice.public())).is_transient());
+		assert!(!KeystoreError::from(sp_keystore::Error::KeyNotSupported(KEY_TYPE)).is_transient());
+	}
+
 	#[test]
 	fn sign_no_keystore() {
 		let store: BeefyKeystore = None.into();
@@ -259,8 +809,8 @@ pub mod tests {
 		let alice = Keyring::Alice.public();
 		let msg = b"are you involved or commited";
 
-		let sig = store.sign(&alice, msg).err().unwrap();
-		let err = Error::Keystore("no Keystore".to_string());
+		let sig = store.sign(&alice, msg, SignatureHasher::Keccak256).err().unwrap();
+		let err = Error::Keystore(KeystoreError::NoKeystore);
 		assert_eq!(sig, err);
 	}
 
@@ -278,12 +828,20 @@ pub mod tests {
 
 		// `msg` and `sig` match
 		let msg = b"are you involved or commited?";
-		let sig = store.sign(&alice, msg).unwrap();
-		assert!(BeefyKeystore::verify(&alice, &sig, msg));
+		let sig = store.sign(&alice, msg, SignatureHasher::Keccak256).unwrap();
+		assert!(BeefyKeystore::verify(&alice, &sig, msg, SignatureHasher::Keccak256));
+
+		// `msg` and `sig` match, but `sig` was hashed differently
+		assert!(!BeefyKeystore::verify(&alice, &sig, msg, SignatureHasher::Blake2_256));
+		let sig = store.sign(&alice, msg, SignatureHasher::Blake2_256).unwrap();
+		assert!(BeefyKeystore::verify(&alice, &sig, msg, SignatureHasher::Blake2_256));
+		assert!(!BeefyKeystore::verify(&alice, &sig, msg, SignatureHasher::Keccak256));
+		let sig = store.sign(&alice, msg, SignatureHasher::Sha2_256).unwrap();
+		assert!(BeefyKeystore::verify(&alice, &sig, msg, SignatureHasher::Sha2_256));
 
 		// `msg and `sig` don't match
 		let msg = b"you are just involved";
-		assert!(!BeefyKeystore::verify(&alice, &sig, msg));
+		assert!(!BeefyKeystore::verify(&alice, &sig, msg, SignatureHasher::Sha2_256));
 	}
 
 	// Note that we use keys with and without a seed for this test.
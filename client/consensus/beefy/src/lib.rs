@@ -27,6 +27,7 @@ use crate::{
 			outgoing_requests_engine::OnDemandJustificationsEngine, BeefyJustifsRequestHandler,
 		},
 	},
+	dual_signing::{DualSigningConfig, SecondaryVotes},
 	import::BeefyBlockImport,
 	metrics::register_metrics,
 	round::Rounds,
@@ -40,22 +41,19 @@ use sc_client_api::{Backend, BlockBackend, BlockchainEvents, FinalityNotificatio
 use sc_consensus::BlockImport;
 use sc_network::{NetworkRequest, ProtocolName};
 use sc_network_gossip::{GossipEngine, Network as GossipNetwork, Syncing as GossipSyncing};
-use sp_api::{HeaderT, NumberFor, ProvideRuntimeApi};
+use sp_api::{ApiExt, HeaderT, NumberFor, ProvideRuntimeApi};
 use sp_blockchain::{
 	Backend as BlockchainBackend, Error as ClientError, HeaderBackend, Result as ClientResult,
 };
 use sp_consensus::{Error as ConsensusError, SyncOracle};
 use sp_consensus_beefy::{
-	crypto::AuthorityId, BeefyApi, MmrRootHash, PayloadProvider, ValidatorSet, BEEFY_ENGINE_ID,
+	crypto::AuthorityId, BeefyApi, MmrRootHash, PayloadProvider, SignatureHasher, ValidatorSet,
+	BEEFY_ENGINE_ID,
 };
 use sp_keystore::KeystorePtr;
 use sp_mmr_primitives::MmrApi;
 use sp_runtime::traits::{Block, Zero};
-use std::{
-	collections::{BTreeMap, VecDeque},
-	marker::PhantomData,
-	sync::Arc,
-};
+use std::{collections::VecDeque, marker::PhantomData, sync::Arc, time::Duration};
 
 mod aux_schema;
 mod error;
@@ -65,8 +63,12 @@ mod round;
 mod worker;
 
 pub mod communication;
+pub mod dual_signing;
+pub mod handoff;
 pub mod import;
 pub mod justification;
+pub mod key_rotation;
+pub mod remote_keystore;
 
 pub use communication::beefy_protocol_name::{
 	gossip_protocol_name, justifications_protocol_name as justifs_protocol_name,
@@ -205,12 +207,18 @@ pub struct BeefyParams<B: Block, BE, C, N, P, R, S> {
 	pub network_params: BeefyNetworkParams<B, N, S>,
 	/// Minimal delta between blocks, BEEFY should vote for
 	pub min_block_delta: u32,
+	/// Interval at which the full voter state is checkpointed to the aux-db, on top of persisting
+	/// it on finality and voting. `None` disables the periodic checkpoints.
+	pub checkpoint_interval: Option<Duration>,
 	/// Prometheus metric registry
 	pub prometheus_registry: Option<Registry>,
 	/// Links between the block importer, the background voter and the RPC layer.
 	pub links: BeefyVoterLinks<B>,
 	/// Handler for incoming BEEFY justifications requests from a remote peer.
 	pub on_demand_justifications_handler: BeefyJustifsRequestHandler<B, C>,
+	/// Dual-signing window, while migrating BEEFY to another crypto scheme. `None` disables dual
+	/// signing.
+	pub dual_signing: Option<DualSigningConfig>,
 }
 
 /// Start the BEEFY gadget.
@@ -236,9 +244,11 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 		key_store,
 		network_params,
 		min_block_delta,
+		checkpoint_interval,
 		prometheus_registry,
 		links,
 		on_demand_justifications_handler,
+		dual_signing,
 	} = beefy_params;
 
 	let BeefyNetworkParams {
@@ -278,10 +288,11 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 	let block_import_justif = links.from_block_import_justif_stream.subscribe(100_000).fuse();
 
 	// Wait for BEEFY pallet to be active before starting voter.
-	let persisted_state =
+	let (persisted_state, signature_hasher) =
 		match wait_for_runtime_pallet(&*runtime, &mut gossip_engine, &mut finality_notifications)
 			.await
 			.and_then(|(beefy_genesis, best_grandpa)| {
+				let hasher = signature_hasher::<B, _>(&*runtime, best_grandpa.hash())?;
 				load_or_init_voter_state(
 					&*backend,
 					&*runtime,
@@ -289,6 +300,7 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 					best_grandpa,
 					min_block_delta,
 				)
+				.map(|state| (state, hasher))
 			}) {
 			Ok(state) => state,
 			Err(e) => {
@@ -298,13 +310,21 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 		};
 	// Update the gossip validator with the right starting round and set id.
 	if let Err(e) = persisted_state
-		.gossip_filter_config()
+		.gossip_filter_config(signature_hasher)
 		.map(|f| gossip_validator.update_filter(f))
 	{
 		error!(target: LOG_TARGET, "Error: {:?}. Terminating.", e);
 		return
 	}
 
+	let signing_record = match aux_schema::load_signing_record::<B, _>(&*backend) {
+		Ok(record) => record,
+		Err(e) => {
+			error!(target: LOG_TARGET, "Error: {:?}. Terminating.", e);
+			return
+		},
+	};
+
 	let worker = worker::BeefyWorker {
 		backend,
 		payload_provider,
@@ -317,8 +337,14 @@ pub async fn start_beefy_gadget<B, BE, C, N, P, R, S>(
 		on_demand_justifications,
 		links,
 		metrics,
-		pending_justifications: BTreeMap::new(),
+		checkpoint_interval,
 		persisted_state,
+		voting_target_since: None,
+		signature_hasher,
+		dual_signing,
+		secondary_votes: SecondaryVotes::new(),
+		signing_failures: None,
+		signing_record,
 	};
 
 	futures::future::select(
@@ -468,6 +494,42 @@ where
 	Ok(state)
 }
 
+/// Return the hasher of BEEFY signatures declared by the runtime at block `at`.
+///
+/// Runtimes that predate [`BeefyApi::signature_hasher`] use [`SignatureHasher::Keccak256`].
+pub(crate) fn signature_hasher<B, R>(runtime: &R, at: B::Hash) -> ClientResult<SignatureHasher>
+where
+	B: Block,
+	R: ProvideRuntimeApi<B>,
+	R::Api: BeefyApi<B>,
+{
+	let api = runtime.runtime_api();
+	if api.api_version::<dyn BeefyApi<B>>(at)?.map_or(false, |version| version >= 3) {
+		Ok(api.signature_hasher(at)?)
+	} else {
+		Ok(SignatureHasher::Keccak256)
+	}
+}
+
+/// Return the secondary validator set declared by the runtime at block `at`, if BEEFY is migrating
+/// to another crypto scheme.
+pub(crate) fn secondary_validator_set<B, R>(
+	runtime: &R,
+	at: B::Hash,
+) -> ClientResult<Option<ValidatorSet<Vec<u8>>>>
+where
+	B: Block,
+	R: ProvideRuntimeApi<B>,
+	R::Api: BeefyApi<B>,
+{
+	let api = runtime.runtime_api();
+	if api.api_version::<dyn BeefyApi<B>>(at)?.map_or(false, |version| version >= 4) {
+		Ok(api.secondary_validator_set(at)?)
+	} else {
+		Ok(None)
+	}
+}
+
 /// Wait for BEEFY runtime pallet to be available, return active validator set.
 /// Should be called only once during worker initialization.
 async fn wait_for_runtime_pallet<B, R>(
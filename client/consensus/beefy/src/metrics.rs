@@ -20,7 +20,9 @@
 
 use crate::LOG_TARGET;
 use log::{debug, error};
-use prometheus::{register, Counter, Gauge, PrometheusError, Registry, U64};
+use prometheus::{
+	register, Counter, Gauge, Histogram, HistogramOpts, PrometheusError, Registry, U64,
+};
 
 /// Helper trait for registering BEEFY metrics to Prometheus registry.
 pub(crate) trait PrometheusRegister<T: Sized = Self>: Sized {
@@ -61,6 +63,18 @@ pub struct VoterMetrics {
 	pub beefy_imported_justifications: Counter<U64>,
 	/// Number of justifications dropped due to full buffers
 	pub beefy_buffered_justifications_dropped: Counter<U64>,
+	/// Number of failures to sign a commitment with the local authority key
+	pub beefy_signing_failures: Counter<U64>,
+	/// Number of sessions for which no local authority key was found in store
+	pub beefy_sessions_without_local_key: Counter<U64>,
+	/// Number of rounds concluded without a vote of the local authority
+	pub beefy_rounds_concluded_without_own_vote: Counter<U64>,
+	/// Time between a block becoming the voting target and this node voting on it
+	pub beefy_vote_delay: Histogram,
+	/// Number of blocks the best BEEFY block lags behind the best GRANDPA block
+	pub beefy_justification_lag: Gauge<U64>,
+	/// Whether the local keystore holds no key of the active validator set (1) or does (0)
+	pub beefy_authority_key_missing: Gauge<U64>,
 }
 
 impl PrometheusRegister for VoterMetrics {
@@ -157,6 +171,48 @@ impl PrometheusRegister for VoterMetrics {
 				)?,
 				registry,
 			)?,
+			beefy_signing_failures: register(
+				Counter::new(
+					"substrate_beefy_signing_failures",
+					"Number of failures to sign a commitment with the local authority key",
+				)?,
+				registry,
+			)?,
+			beefy_sessions_without_local_key: register(
+				Counter::new(
+					"substrate_beefy_sessions_without_local_key",
+					"Number of sessions for which no local authority key was found in store",
+				)?,
+				registry,
+			)?,
+			beefy_rounds_concluded_without_own_vote: register(
+				Counter::new(
+					"substrate_beefy_rounds_concluded_without_own_vote",
+					"Number of rounds concluded without a vote of the local authority",
+				)?,
+				registry,
+			)?,
+			beefy_vote_delay: register(
+				Histogram::with_opts(HistogramOpts::new(
+					"substrate_beefy_vote_delay",
+					"Time between a block becoming the voting target and this node voting on it",
+				))?,
+				registry,
+			)?,
+			beefy_justification_lag: register(
+				Gauge::new(
+					"substrate_beefy_justification_lag",
+					"Number of blocks the best BEEFY block lags behind the best GRANDPA block",
+				)?,
+				registry,
+			)?,
+			beefy_authority_key_missing: register(
+				Gauge::new(
+					"substrate_beefy_authority_key_missing",
+					"Whether the local keystore holds no key of the active validator set (1) or does (0)",
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
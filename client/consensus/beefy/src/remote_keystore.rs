@@ -0,0 +1,635 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A [`Keystore`] backend that produces BEEFY signatures through an external signer.
+//!
+//! This allows validators to keep their BEEFY keys outside of the node, e.g. in a HSM or on an
+//! air-gapped machine, next to which runs a JSON-RPC service implementing these methods:
+//!
+//! - `beefy_ecdsaPublicKeys(key_type: String) -> Vec<Bytes>`: returns all the ECDSA public keys of
+//!   the given key type.
+//! - `beefy_ecdsaSignPrehashed(key_type: String, public: Bytes, message: Bytes) -> Option<Bytes>`:
+//!   signs the 32 bytes long `message` with the private key of `public`, returning `None` if no
+//!   such key is known.
+//! - `beefy_ecdsaSignPrehashedBatch(key_type: String, public: Bytes, messages: Vec<Bytes>) ->
+//!   Option<Vec<Bytes>>`: signs all the 32 bytes long `messages` with the private key of `public`,
+//!   returning the signatures in the same order, or `None` if no such key is known.
+//!
+//! The key type is passed as its four character string representation, e.g. `"beef"`. Only the
+//! ECDSA operations needed by the BEEFY voter are supported; all other operations of the
+//! [`Keystore`] fail.
+//!
+//! To use it, pass a [`RemoteKeystore`] as the `key_store` of [`crate::BeefyParams`]. Nodes can
+//! let it be selected on the command line by flattening [`RemoteSignerParams`] into their CLI, and
+//! passing the keystore returned by [`RemoteSignerParams::keystore`] to BEEFY.
+
+use clap::Args;
+use codec::Decode;
+use futures::{
+	channel::{mpsc, oneshot},
+	StreamExt,
+};
+use jsonrpsee::{
+	core::{client::ClientT, params::ArrayParams},
+	http_client::{HttpClient, HttpClientBuilder},
+};
+use log::{debug, warn};
+use sp_core::{
+	crypto::{ByteArray, KeyTypeId},
+	ecdsa, ed25519, sr25519, Bytes,
+};
+use sp_keystore::{Error, Keystore, KeystorePtr};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+#[cfg(feature = "bls-experimental")]
+use sp_core::{bls377, bls381};
+
+use crate::LOG_TARGET;
+
+/// Configuration of a [`RemoteKeystore`].
+#[derive(Clone, Debug)]
+pub struct RemoteSignerConfig {
+	/// The URL of the JSON-RPC endpoint of the remote signer.
+	pub url: String,
+	/// How long to wait for the response to a single request.
+	pub timeout: Duration,
+	/// How many times a request that failed or timed out is retried.
+	pub retries: u32,
+}
+
+/// Parameters of the remote signer, to be flattened into the CLI of a node running BEEFY.
+#[derive(Debug, Clone, Args)]
+pub struct RemoteSignerParams {
+	/// The URL of the JSON-RPC endpoint of a remote signer holding the BEEFY keys.
+	///
+	/// If given, BEEFY signs with the keys of the remote signer instead of those of the local
+	/// keystore.
+	#[arg(long = "beefy-remote-signer", value_name = "URL")]
+	pub url: Option<String>,
+
+	/// How long to wait for the remote signer to answer a single request.
+	#[arg(
+		long = "beefy-remote-signer-timeout",
+		value_name = "MILLISECONDS",
+		default_value_t = 2000
+	)]
+	pub timeout: u64,
+
+	/// How many times a request that failed or timed out is retried.
+	#[arg(long = "beefy-remote-signer-retries", value_name = "COUNT", default_value_t = 2)]
+	pub retries: u32,
+}
+
+impl RemoteSignerParams {
+	/// The configuration of the remote signer, if one was given.
+	pub fn config(&self) -> Option<RemoteSignerConfig> {
+		self.url.clone().map(|url| RemoteSignerConfig {
+			url,
+			timeout: Duration::from_millis(self.timeout),
+			retries: self.retries,
+		})
+	}
+
+	/// The keystore BEEFY should use: a [`RemoteKeystore`] if a remote signer was given, `local`
+	/// otherwise.
+	pub fn keystore(&self, local: KeystorePtr) -> Result<KeystorePtr, Error> {
+		match self.config() {
+			Some(config) => Ok(Arc::new(RemoteKeystore::new(config)?)),
+			None => Ok(local),
+		}
+	}
+}
+
+/// A request to the background worker, which replies with the JSON response.
+struct Request {
+	method: &'static str,
+	params: Vec<serde_json::Value>,
+	reply: oneshot::Sender<Result<serde_json::Value, Error>>,
+}
+
+/// A [`Keystore`] that signs with ECDSA keys held by a remote signer.
+///
+/// See the [module docs](self) for the protocol spoken with the remote signer.
+pub struct RemoteKeystore {
+	retries: u32,
+	to_worker: mpsc::UnboundedSender<Request>,
+}
+
+impl RemoteKeystore {
+	/// Connect to the remote signer described by `config`.
+	///
+	/// Requests are sent from a dedicated background thread, so that the synchronous [`Keystore`]
+	/// interface can be served from within an async context.
+	pub fn new(config: RemoteSignerConfig) -> Result<Self, Error> {
+		let (to_worker, from_keystore) = mpsc::unbounded();
+		let (ready_tx, ready_rx) = oneshot::channel();
+		let (url, timeout) = (config.url.clone(), config.timeout);
+
+		std::thread::Builder::new()
+			.name("beefy-remote-signer".into())
+			.spawn(move || run_worker(url, timeout, from_keystore, ready_tx))
+			.map_err(|e| Error::Other(e.to_string()))?;
+
+		wait(ready_rx)
+			.map_err(|_| Error::Unavailable)?
+			.map_err(|e| Error::Other(format!("Failed to connect to remote signer: {}", e)))?;
+
+		Ok(Self { retries: config.retries, to_worker })
+	}
+
+	/// Call `method` of the remote signer, retrying up to `self.retries` times.
+	///
+	/// Fails with [`Error::Unavailable`] if no response was received in time.
+	fn call<R: serde::de::DeserializeOwned>(
+		&self,
+		method: &'static str,
+		params: Vec<serde_json::Value>,
+	) -> Result<R, Error> {
+		let mut last_error = Error::Unavailable;
+		for attempt in 0..=self.retries {
+			if attempt > 0 {
+				debug!(target: LOG_TARGET, "🥩 Retrying {} ({}): {:?}", method, attempt, last_error);
+			}
+
+			let (reply, response) = oneshot::channel();
+			let request = Request { method, params: params.clone(), reply };
+			if self.to_worker.unbounded_send(request).is_err() {
+				// the worker is gone, no point in retrying.
+				return Err(Error::Unavailable)
+			}
+
+			// the worker answers every request within its timeout.
+			last_error = match wait(response) {
+				Ok(Ok(value)) =>
+					return serde_json::from_value(value).map_err(|e| Error::Other(e.to_string())),
+				Ok(Err(e)) => e,
+				Err(oneshot::Canceled) => Error::Unavailable,
+			};
+		}
+
+		warn!(target: LOG_TARGET, "🥩 Remote signer call {} failed: {:?}", method, last_error);
+		Err(last_error)
+	}
+
+	fn unsupported<T>(key_type: KeyTypeId) -> Result<T, Error> {
+		Err(Error::KeyNotSupported(key_type))
+	}
+}
+
+/// Wait for the worker to send `response`.
+///
+/// When called from a task of a multi-threaded tokio runtime, e.g. by the BEEFY voter, the thread
+/// is handed over to the runtime's other tasks while waiting.
+fn wait<T>(response: oneshot::Receiver<T>) -> Result<T, oneshot::Canceled> {
+	let wait = || futures::executor::block_on(response);
+	match Handle::try_current() {
+		Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread =>
+			tokio::task::block_in_place(wait),
+		_ => wait(),
+	}
+}
+
+/// Serve requests sent to the worker until the [`RemoteKeystore`] is dropped.
+fn run_worker(
+	url: String,
+	timeout: Duration,
+	mut requests: mpsc::UnboundedReceiver<Request>,
+	ready: oneshot::Sender<Result<(), String>>,
+) {
+	let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+		Ok(runtime) => runtime,
+		Err(e) => {
+			let _ = ready.send(Err(e.to_string()));
+			return
+		},
+	};
+
+	runtime.block_on(async move {
+		let client = match HttpClientBuilder::default().build(url) {
+			Ok(client) => {
+				let _ = ready.send(Ok(()));
+				client
+			},
+			Err(e) => {
+				let _ = ready.send(Err(e.to_string()));
+				return
+			},
+		};
+
+		// requests are served concurrently, so that one hanging doesn't hold up the others, e.g.
+		// its own retry.
+		while let Some(request) = requests.next().await {
+			tokio::spawn(serve(client.clone(), timeout, request));
+		}
+	});
+}
+
+/// Send `request` to the remote signer, and reply with its response.
+async fn serve(client: HttpClient, timeout: Duration, request: Request) {
+	let Request { method, params, reply } = request;
+	let mut rpc_params = ArrayParams::new();
+	for param in params {
+		rpc_params.insert(param).expect("json values can always be serialized; qed");
+	}
+
+	// on timeout the request is dropped, which cancels it. This is done here rather than by the
+	// client, so that timeouts are always reported as `Error::Unavailable`.
+	let result = match tokio::time::timeout(
+		timeout,
+		client.request::<serde_json::Value, _>(method, rpc_params),
+	)
+	.await
+	{
+		Ok(result) => result.map_err(|e| Error::Other(e.to_string())),
+		Err(_) => Err(Error::Unavailable),
+	};
+	let _ = reply.send(result);
+}
+
+fn key_type_param(key_type: KeyTypeId) -> serde_json::Value {
+	String::from_utf8_lossy(&key_type.0).into_owned().into()
+}
+
+fn bytes_param(bytes: &[u8]) -> serde_json::Value {
+	serde_json::to_value(Bytes(bytes.to_vec())).expect("bytes can always be serialized; qed")
+}
+
+impl Keystore for RemoteKeystore {
+	fn sr25519_public_keys(&self, _: KeyTypeId) -> Vec<sr25519::Public> {
+		Vec::new()
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		_: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		Self::unsupported(key_type)
+	}
+
+	fn sr25519_sign(
+		&self,
+		key_type: KeyTypeId,
+		_: &sr25519::Public,
+		_: &[u8],
+	) -> Result<Option<sr25519::Signature>, Error> {
+		Self::unsupported(key_type)
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		_: &sr25519::Public,
+		_: &sr25519::vrf::VrfSignData,
+	) -> Result<Option<sr25519::vrf::VrfSignature>, Error> {
+		Self::unsupported(key_type)
+	}
+
+	fn sr25519_vrf_output(
+		&self,
+		key_type: KeyTypeId,
+		_: &sr25519::Public,
+		_: &sr25519::vrf::VrfInput,
+	) -> Result<Option<sr25519::vrf::VrfOutput>, Error> {
+		Self::unsupported(key_type)
+	}
+
+	fn ed25519_public_keys(&self, _: KeyTypeId) -> Vec<ed25519::Public> {
+		Vec::new()
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		_: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		Self::unsupported(key_type)
+	}
+
+	fn ed25519_sign(
+		&self,
+		key_type: KeyTypeId,
+		_: &ed25519::Public,
+		_: &[u8],
+	) -> Result<Option<ed25519::Signature>, Error> {
+		Self::unsupported(key_type)
+	}
+
+	fn ecdsa_public_keys(&self, key_type: KeyTypeId) -> Vec<ecdsa::Public> {
+		// this can't report errors, use `keys` to tell them apart from a missing key.
+		self.keys(key_type)
+			.map(|keys| {
+				keys.into_iter().filter_map(|k| ecdsa::Public::from_slice(&k).ok()).collect()
+			})
+			.unwrap_or_default()
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		_: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		// keys are managed by the remote signer.
+		Self::unsupported(key_type)
+	}
+
+	fn ecdsa_sign(
+		&self,
+		key_type: KeyTypeId,
+		_: &ecdsa::Public,
+		_: &[u8],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		// BEEFY only ever signs pre-hashed messages.
+		Self::unsupported(key_type)
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		key_type: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let params = vec![key_type_param(key_type), bytes_param(public.as_ref()), bytes_param(msg)];
+		self.call::<Option<Bytes>>("beefy_ecdsaSignPrehashed", params)?
+			.map(|sig| {
+				ecdsa::Signature::decode(&mut &sig[..]).map_err(|_| {
+					Error::ValidationError("Invalid signature returned by remote signer".into())
+				})
+			})
+			.transpose()
+	}
+
+	fn ecdsa_sign_prehashed_batch(
+		&self,
+		key_type: KeyTypeId,
+		public: &ecdsa::Public,
+		msgs: &[[u8; 32]],
+	) -> Result<Option<Vec<ecdsa::Signature>>, Error> {
+		let messages = msgs.iter().map(|msg| Bytes(msg.to_vec())).collect::<Vec<_>>();
+		let messages = serde_json::to_value(messages).expect("bytes can always be serialized; qed");
+		let params = vec![key_type_param(key_type), bytes_param(public.as_ref()), messages];
+		let sigs = match self.call::<Option<Vec<Bytes>>>("beefy_ecdsaSignPrehashedBatch", params)? {
+			Some(sigs) => sigs,
+			None => return Ok(None),
+		};
+		if sigs.len() != msgs.len() {
+			return Err(Error::ValidationError(format!(
+				"Remote signer returned {} signatures for {} messages",
+				sigs.len(),
+				msgs.len()
+			)))
+		}
+		sigs.iter()
+			.map(|sig| {
+				ecdsa::Signature::decode(&mut &sig[..]).map_err(|_| {
+					Error::ValidationError("Invalid signature returned by remote signer".into())
+				})
+			})
+			.collect::<Result<Vec<_>, _>>()
+			.map(Some)
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls381_public_keys(&self, _: KeyTypeId) -> Vec<bls381::Public> {
+		Vec::new()
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls377_public_keys(&self, _: KeyTypeId) -> Vec<bls377::Public> {
+		Vec::new()
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls381_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		_: Option<&str>,
+	) -> Result<bls381::Public, Error> {
+		Self::unsupported(key_type)
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls377_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		_: Option<&str>,
+	) -> Result<bls377::Public, Error> {
+		Self::unsupported(key_type)
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls381_sign(
+		&self,
+		key_type: KeyTypeId,
+		_: &bls381::Public,
+		_: &[u8],
+	) -> Result<Option<bls381::Signature>, Error> {
+		Self::unsupported(key_type)
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls377_sign(
+		&self,
+		key_type: KeyTypeId,
+		_: &bls377::Public,
+		_: &[u8],
+	) -> Result<Option<bls377::Signature>, Error> {
+		Self::unsupported(key_type)
+	}
+
+	fn insert(&self, _: KeyTypeId, _: &str, _: &[u8]) -> Result<(), ()> {
+		Err(())
+	}
+
+	fn keys(&self, key_type: KeyTypeId) -> Result<Vec<Vec<u8>>, Error> {
+		let keys =
+			self.call::<Vec<Bytes>>("beefy_ecdsaPublicKeys", vec![key_type_param(key_type)])?;
+		Ok(keys.into_iter().map(|k| k.0).collect())
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		// the keys of each key type are only fetched once.
+		let mut keys = BTreeMap::new();
+		public_keys.iter().all(|(public, key_type)| {
+			let known = keys.entry(*key_type).or_insert_with(|| self.keys(*key_type));
+			matches!(known, Ok(known) if known.contains(public))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpsee::{server::ServerBuilder, RpcModule};
+	use sp_consensus_beefy::{Keyring, KEY_TYPE};
+	use sp_keystore::{testing::MemoryKeystore, KeystorePtr};
+
+	/// Start a remote signer serving the keys of `store`, returning its URL.
+	async fn remote_signer(store: KeystorePtr) -> String {
+		let mut module = RpcModule::new(store);
+		module
+			.register_method("beefy_ecdsaPublicKeys", |params, store| {
+				let key_type: String = params.one()?;
+				let key_type = KeyTypeId::try_from(key_type.as_str()).unwrap();
+				Ok(store
+					.ecdsa_public_keys(key_type)
+					.into_iter()
+					.map(|k| Bytes(k.to_raw_vec()))
+					.collect::<Vec<_>>())
+			})
+			.unwrap();
+		module
+			.register_method("beefy_ecdsaSignPrehashed", |params, store| {
+				let (key_type, public, msg): (String, Bytes, Bytes) = params.parse()?;
+				let key_type = KeyTypeId::try_from(key_type.as_str()).unwrap();
+				let public = ecdsa::Public::from_slice(&public).unwrap();
+				let msg: [u8; 32] = msg.0.try_into().unwrap();
+				let sig = store.ecdsa_sign_prehashed(key_type, &public, &msg).unwrap();
+				Ok(sig.map(|s| Bytes(s.0.to_vec())))
+			})
+			.unwrap();
+		module
+			.register_method("beefy_ecdsaSignPrehashedBatch", |params, store| {
+				let (key_type, public, msgs): (String, Bytes, Vec<Bytes>) = params.parse()?;
+				let key_type = KeyTypeId::try_from(key_type.as_str()).unwrap();
+				let public = ecdsa::Public::from_slice(&public).unwrap();
+				let msgs: Vec<[u8; 32]> =
+					msgs.into_iter().map(|msg| msg.0.try_into().unwrap()).collect();
+				let sigs = store.ecdsa_sign_prehashed_batch(key_type, &public, &msgs).unwrap();
+				Ok(sigs
+					.map(|sigs| sigs.into_iter().map(|s| Bytes(s.0.to_vec())).collect::<Vec<_>>()))
+			})
+			.unwrap();
+
+		let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+		let url = format!("http://{}", server.local_addr().unwrap());
+		// keep the server running for the rest of the test.
+		std::mem::forget(server.start(module).unwrap());
+		url
+	}
+
+	fn config(url: String) -> RemoteSignerConfig {
+		RemoteSignerConfig { url, timeout: Duration::from_secs(5), retries: 1 }
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn remote_signing_works() {
+		let store: KeystorePtr = MemoryKeystore::new().into();
+		let alice = store.ecdsa_generate_new(KEY_TYPE, Some(&Keyring::Alice.to_seed())).unwrap();
+		let remote = RemoteKeystore::new(config(remote_signer(store.clone()).await)).unwrap();
+
+		assert_eq!(remote.ecdsa_public_keys(KEY_TYPE), vec![alice]);
+		assert!(remote.has_keys(&[(alice.to_raw_vec(), KEY_TYPE)]));
+		assert!(!remote.has_keys(&[(Keyring::Bob.public().to_raw_vec(), KEY_TYPE)]));
+
+		let msg = [42u8; 32];
+		assert_eq!(
+			remote.ecdsa_sign_prehashed(KEY_TYPE, &alice, &msg).unwrap(),
+			store.ecdsa_sign_prehashed(KEY_TYPE, &alice, &msg).unwrap(),
+		);
+
+		let msgs = [[1u8; 32], [2u8; 32]];
+		assert_eq!(
+			remote.ecdsa_sign_prehashed_batch(KEY_TYPE, &alice, &msgs).unwrap(),
+			store.ecdsa_sign_prehashed_batch(KEY_TYPE, &alice, &msgs).unwrap(),
+		);
+
+		// unknown key.
+		let bob = Keyring::Bob.public().into();
+		assert_eq!(remote.ecdsa_sign_prehashed(KEY_TYPE, &bob, &msg).unwrap(), None);
+		assert_eq!(remote.ecdsa_sign_prehashed_batch(KEY_TYPE, &bob, &msgs).unwrap(), None);
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn unreachable_remote_signer_is_unavailable() {
+		// nothing listens on this port.
+		let remote = RemoteKeystore::new(config("http://127.0.0.1:1".into())).unwrap();
+
+		let alice = Keyring::Alice.public().into();
+		assert!(matches!(
+			remote.ecdsa_sign_prehashed(KEY_TYPE, &alice, &[0u8; 32]),
+			Err(Error::Other(_))
+		));
+		assert!(matches!(remote.keys(KEY_TYPE), Err(Error::Other(_))));
+		assert!(remote.ecdsa_public_keys(KEY_TYPE).is_empty());
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn hanging_request_times_out_without_holding_up_others() {
+		let store: KeystorePtr = MemoryKeystore::new().into();
+		let alice = store.ecdsa_generate_new(KEY_TYPE, Some(&Keyring::Alice.to_seed())).unwrap();
+
+		let mut module = RpcModule::new(store);
+		module
+			.register_method("beefy_ecdsaPublicKeys", |_, store| {
+				Ok(store
+					.ecdsa_public_keys(KEY_TYPE)
+					.into_iter()
+					.map(|k| Bytes(k.to_raw_vec()))
+					.collect::<Vec<_>>())
+			})
+			.unwrap();
+		module
+			.register_async_method("beefy_ecdsaSignPrehashed", |_, _| async {
+				tokio::time::sleep(Duration::from_secs(60)).await;
+				Ok(None::<Bytes>)
+			})
+			.unwrap();
+		let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+		let url = format!("http://{}", server.local_addr().unwrap());
+		std::mem::forget(server.start(module).unwrap());
+
+		let remote = Arc::new(
+			RemoteKeystore::new(RemoteSignerConfig {
+				url,
+				timeout: Duration::from_millis(200),
+				retries: 1,
+			})
+			.unwrap(),
+		);
+
+		let signing = {
+			let remote = remote.clone();
+			std::thread::spawn(move || remote.ecdsa_sign_prehashed(KEY_TYPE, &alice, &[0u8; 32]))
+		};
+		// served while the signing request hangs.
+		assert_eq!(remote.keys(KEY_TYPE).unwrap(), vec![alice.to_raw_vec()]);
+
+		// both attempts time out.
+		let started = std::time::Instant::now();
+		assert!(matches!(signing.join().unwrap(), Err(Error::Unavailable)));
+		assert!(started.elapsed() < Duration::from_secs(5));
+	}
+
+	#[test]
+	fn remote_signer_params_work() {
+		let local: KeystorePtr = MemoryKeystore::new().into();
+		let params = RemoteSignerParams { url: None, timeout: 500, retries: 3 };
+		assert!(params.config().is_none());
+		assert!(Arc::ptr_eq(&params.keystore(local.clone()).unwrap(), &local));
+
+		let params = RemoteSignerParams { url: Some("http://127.0.0.1:1".into()), ..params };
+		let config = params.config().unwrap();
+		assert_eq!(
+			(config.url.as_str(), config.timeout, config.retries),
+			("http://127.0.0.1:1", Duration::from_millis(500), 3)
+		);
+		assert!(!Arc::ptr_eq(&params.keystore(local.clone()).unwrap(), &local));
+	}
+}
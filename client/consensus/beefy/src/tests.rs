@@ -422,8 +422,10 @@ where
 			network_params,
 			links: beefy_voter_links.unwrap(),
 			min_block_delta,
+			checkpoint_interval: None,
 			prometheus_registry: None,
 			on_demand_justifications_handler: on_demand_justif_handler,
+			dual_signing: None,
 		};
 		let task = crate::start_beefy_gadget::<_, _, _, _, _, _, _>(beefy_params);
 
@@ -829,6 +831,7 @@ async fn beefy_importing_justifications() {
 	let proof = crate::justification::tests::new_finality_proof(block_num, &good_set, keys);
 	let versioned_proof: VersionedFinalityProof<NumberFor<Block>, Signature> = proof.into();
 	let encoded = versioned_proof.encode();
+	let encoded_justif_of3 = encoded.clone();
 	let justif = Some(Justifications::from((BEEFY_ENGINE_ID, encoded)));
 	let mut justif_recv = justif_stream.subscribe(100_000);
 	assert_eq!(
@@ -841,10 +844,9 @@ async fn beefy_importing_justifications() {
 	);
 	// Verify BEEFY justification successfully imported:
 	{
-		// still not in backend (worker is responsible for appending to backend),
-		assert_eq!(backend_justif_for(hashof3), None);
-		// but sent to BEEFY worker
-		// (worker will append it to backend when all previous mandatory justifs are there as well).
+		// stored in backend, since the block was imported as finalized,
+		assert_eq!(backend_justif_for(hashof3), Some(encoded_justif_of3));
+		// and sent to BEEFY worker.
 		poll_fn(move |cx| {
 			match justif_recv.poll_next_unpin(cx) {
 				Poll::Ready(Some(_justification)) => (),
@@ -1401,8 +1403,8 @@ async fn gossipped_finality_proofs() {
 			.filter_map(|notification| async move {
 				GossipMessage::<Block>::decode(&mut &notification.message[..]).ok().and_then(
 					|message| match message {
-						GossipMessage::<Block>::Vote(_) => unreachable!(),
 						GossipMessage::<Block>::FinalityProof(proof) => Some(proof),
+						_ => unreachable!(),
 					},
 				)
 			})
@@ -22,28 +22,34 @@ use crate::{
 		peers::PeerReport,
 		request_response::outgoing_requests_engine::{OnDemandJustificationsEngine, ResponseInfo},
 	},
-	error::Error,
+	dual_signing::{DualSigningConfig, SecondaryFinalityProof, SecondaryVote, SecondaryVotes},
+	error::{Error, KeystoreError},
 	justification::BeefyVersionedFinalityProof,
-	keystore::{BeefyKeystore, BeefySignatureHasher},
+	keystore::{BeefyKeystore, SigningRecord},
 	metric_inc, metric_set,
 	metrics::VoterMetrics,
 	round::{Rounds, VoteImportResult},
-	BeefyVoterLinks, LOG_TARGET,
+	secondary_validator_set, signature_hasher, BeefyVoterLinks, LOG_TARGET,
 };
 use codec::{Codec, Decode, Encode};
-use futures::{stream::Fuse, FutureExt, StreamExt};
+use futures::{
+	future,
+	stream::{self, Fuse},
+	FutureExt, Stream, StreamExt,
+};
 use log::{debug, error, info, log_enabled, trace, warn};
 use sc_client_api::{Backend, FinalityNotification, FinalityNotifications, HeaderBackend};
 use sc_network_gossip::GossipEngine;
 use sc_utils::{mpsc::TracingUnboundedReceiver, notification::NotificationReceiver};
 use sp_api::{BlockId, ProvideRuntimeApi};
 use sp_arithmetic::traits::{AtLeast32Bit, Saturating};
+use sp_blockchain::Backend as BlockchainBackend;
 use sp_consensus::SyncOracle;
 use sp_consensus_beefy::{
-	check_equivocation_proof,
+	check_equivocation_proof_with_hasher,
 	crypto::{AuthorityId, Signature},
-	BeefyApi, Commitment, ConsensusLog, EquivocationProof, PayloadProvider, ValidatorSet,
-	VersionedFinalityProof, VoteMessage, BEEFY_ENGINE_ID,
+	BeefyApi, Commitment, ConsensusLog, EquivocationProof, PayloadProvider, SignatureHasher,
+	ValidatorSet, VersionedFinalityProof, VoteMessage, BEEFY_ENGINE_ID, BEEFY_SECONDARY_ENGINE_ID,
 };
 use sp_runtime::{
 	generic::OpaqueDigestItemId,
@@ -54,12 +60,20 @@ use std::{
 	collections::{BTreeMap, BTreeSet, VecDeque},
 	fmt::Debug,
 	sync::Arc,
+	time::Duration,
 };
+use wasm_timer::Instant;
 
 /// Bound for the number of pending justifications - use 2400 - the max number
 /// of justifications possible in a single session.
 const MAX_BUFFERED_JUSTIFICATIONS: usize = 2400;
 
+/// Delay before voting again after a transient signing failure, e.g. a remote signer timeout.
+const SIGNING_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Max number of times voting on the same block is retried after transient signing failures.
+const MAX_SIGNING_RETRIES: u32 = 10;
+
 pub(crate) enum RoundAction {
 	Drop,
 	Process,
@@ -203,6 +217,11 @@ impl<B: Block> VoterOracle<B> {
 		})
 	}
 
+	/// Return the validator sets of all queued sessions, in order.
+	pub fn queued_validator_sets(&self) -> Vec<ValidatorSet<AuthorityId>> {
+		self.sessions.iter().map(|round| round.validator_set().clone()).collect()
+	}
+
 	/// Return `(A, B)` tuple representing inclusive [A, B] interval of votes to accept.
 	pub fn accepted_interval(&self) -> Result<(NumberFor<B>, NumberFor<B>), Error> {
 		let rounds = self.sessions.front().ok_or(Error::UninitSession)?;
@@ -271,6 +290,8 @@ pub(crate) struct PersistedState<B: Block> {
 	voting_oracle: VoterOracle<B>,
 	/// Pallet-beefy genesis block - block number when BEEFY consensus started for this chain.
 	pallet_genesis: NumberFor<B>,
+	/// Buffer holding justifications for future processing.
+	pending_justifications: BTreeMap<NumberFor<B>, BeefyVersionedFinalityProof<B>>,
 }
 
 impl<B: Block> PersistedState<B> {
@@ -286,10 +307,26 @@ impl<B: Block> PersistedState<B> {
 				best_voted: Zero::zero(),
 				voting_oracle,
 				pallet_genesis,
+				pending_justifications: BTreeMap::new(),
 			},
 		)
 	}
 
+	/// Build the state from the one persisted with aux-db schema version 4, which didn't persist
+	/// pending justifications yet.
+	pub(crate) fn from_v4(
+		best_voted: NumberFor<B>,
+		voting_oracle: VoterOracle<B>,
+		pallet_genesis: NumberFor<B>,
+	) -> Self {
+		PersistedState {
+			best_voted,
+			voting_oracle,
+			pallet_genesis,
+			pending_justifications: BTreeMap::new(),
+		}
+	}
+
 	pub fn pallet_genesis(&self) -> NumberFor<B> {
 		self.pallet_genesis
 	}
@@ -306,10 +343,13 @@ impl<B: Block> PersistedState<B> {
 		self.voting_oracle.best_grandpa_block_header = best_grandpa;
 	}
 
-	pub(crate) fn gossip_filter_config(&self) -> Result<GossipFilterCfg<B>, Error> {
+	pub(crate) fn gossip_filter_config(
+		&self,
+		hasher: SignatureHasher,
+	) -> Result<GossipFilterCfg<B>, Error> {
 		let (start, end) = self.voting_oracle.accepted_interval()?;
 		let validator_set = self.voting_oracle.current_validator_set()?;
-		Ok(GossipFilterCfg { start, end, validator_set })
+		Ok(GossipFilterCfg { start, end, validator_set, hasher })
 	}
 }
 
@@ -335,10 +375,23 @@ pub(crate) struct BeefyWorker<B: Block, BE, P, RuntimeApi, S> {
 	// voter state
 	/// BEEFY client metrics.
 	pub metrics: Option<VoterMetrics>,
-	/// Buffer holding justifications for future processing.
-	pub pending_justifications: BTreeMap<NumberFor<B>, BeefyVersionedFinalityProof<B>>,
+	/// Interval at which the voter state is persisted, on top of persisting it on finality and
+	/// voting. `None` disables the periodic checkpoints.
+	pub checkpoint_interval: Option<Duration>,
 	/// Persisted voter state.
 	pub persisted_state: PersistedState<B>,
+	/// Current voting target and when it was first seen, used to measure the vote delay.
+	pub voting_target_since: Option<(NumberFor<B>, Instant)>,
+	/// Hasher of BEEFY signatures, as declared by the runtime at the best GRANDPA block.
+	pub signature_hasher: SignatureHasher,
+	/// Dual-signing window, while migrating BEEFY to another crypto scheme.
+	pub dual_signing: Option<DualSigningConfig>,
+	/// Secondary signatures of the dual-signing sessions.
+	pub secondary_votes: SecondaryVotes<B>,
+	/// Block we failed to sign a vote for with a transient error, and the number of failures.
+	pub signing_failures: Option<(NumberFor<B>, u32)>,
+	/// Commitments signed by the local authority, to never sign conflicting ones.
+	pub signing_record: SigningRecord<NumberFor<B>>,
 }
 
 impl<B, BE, P, R, S> BeefyWorker<B, BE, P, R, S>
@@ -381,15 +434,36 @@ where
 		let store: BTreeSet<&AuthorityId> = public_keys.iter().collect();
 
 		if store.intersection(&active).count() == 0 {
-			let msg = "no authority public key found in store".to_string();
-			debug!(target: LOG_TARGET, "🥩 for block {:?} {}", block, msg);
+			let err = KeystoreError::NoAuthorityKey;
+			debug!(target: LOG_TARGET, "🥩 for block {:?} {}", block, err);
 			metric_inc!(self, beefy_no_authority_found_in_store);
-			Err(Error::Keystore(msg))
+			Err(err.into())
 		} else {
 			Ok(())
 		}
 	}
 
+	/// Check that the local keystore holds a key of `validator_set`, loudly warning if it doesn't.
+	///
+	/// Nodes without keystore aren't expected to have any key, and aren't checked.
+	fn check_authority_key(&self, validator_set: &ValidatorSet<AuthorityId>) {
+		if self.key_store.public_keys().is_err() {
+			return
+		}
+		if self.key_store.authority_id(validator_set).is_some() {
+			metric_set!(self, beefy_authority_key_missing, 0);
+			return
+		}
+		warn!(
+			target: LOG_TARGET,
+			"🥩 ⚠️ No BEEFY key of validator set {:?} found in the keystore! This node will not \
+			vote on BEEFY commitments until the key of its authority is inserted.",
+			validator_set.id(),
+		);
+		metric_inc!(self, beefy_sessions_without_local_key);
+		metric_set!(self, beefy_authority_key_missing, 1);
+	}
+
 	/// Handle session changes by starting new voting round for mandatory blocks.
 	fn init_session_at(
 		&mut self,
@@ -416,6 +490,8 @@ where
 			let _ = self.verify_validator_set(&new_session_start, &validator_set);
 		}
 
+		self.check_authority_key(&validator_set);
+
 		let id = validator_set.id();
 		self.persisted_state
 			.voting_oracle
@@ -457,6 +533,12 @@ where
 			// update best GRANDPA finalized block we have seen
 			self.persisted_state.set_best_grandpa(header.clone());
 
+			// The runtime may switch signature hashers on upgrades.
+			match signature_hasher::<B, _>(&*self.runtime, header.hash()) {
+				Ok(hasher) => self.signature_hasher = hasher,
+				Err(e) => warn!(target: LOG_TARGET, "🥩 Failed to get signature hasher: {:?}", e),
+			}
+
 			// Check all (newly) finalized blocks for new session(s).
 			let backend = self.backend.clone();
 			for header in notification
@@ -471,6 +553,7 @@ where
 				.chain(std::iter::once(header.clone()))
 			{
 				if let Some(new_validator_set) = find_authorities_change::<B>(&header) {
+					self.init_secondary_session(&new_validator_set, header.hash());
 					self.init_session_at(new_validator_set, *header.number());
 				}
 			}
@@ -478,11 +561,12 @@ where
 			// Update gossip validator votes filter.
 			if let Err(e) = self
 				.persisted_state
-				.gossip_filter_config()
+				.gossip_filter_config(self.signature_hasher)
 				.map(|filter| self.gossip_validator.update_filter(filter))
 			{
 				error!(target: LOG_TARGET, "🥩 Voter error: {:?}", e);
 			}
+			self.update_secondary_filter();
 		}
 
 		Ok(())
@@ -526,8 +610,11 @@ where
 			},
 			RoundAction::Enqueue => {
 				debug!(target: LOG_TARGET, "🥩 Buffer justification for round: {:?}.", block_num);
-				if self.pending_justifications.len() < MAX_BUFFERED_JUSTIFICATIONS {
-					self.pending_justifications.entry(block_num).or_insert(justification);
+				if self.persisted_state.pending_justifications.len() < MAX_BUFFERED_JUSTIFICATIONS {
+					self.persisted_state
+						.pending_justifications
+						.entry(block_num)
+						.or_insert(justification);
 					metric_inc!(self, beefy_buffered_justifications);
 				} else {
 					metric_inc!(self, beefy_buffered_justifications_dropped);
@@ -597,6 +684,18 @@ where
 			VersionedFinalityProof::V1(ref sc) => sc.commitment.block_number,
 		};
 
+		if self.metrics.is_some() && !self.is_signed_by_local_authority(&finality_proof) {
+			metric_inc!(self, beefy_rounds_concluded_without_own_vote);
+		}
+
+		// Conclude the secondary round too, before its session may get pruned.
+		let secondary_proof =
+			match (&finality_proof, self.persisted_state.voting_oracle.active_rounds()) {
+				(VersionedFinalityProof::V1(sc), Ok(rounds)) =>
+					self.secondary_votes.conclude(rounds.validator_set(), &sc.commitment),
+				_ => None,
+			};
+
 		// Finalize inner round and update voting_oracle state.
 		self.persisted_state.voting_oracle.finalize(block_num)?;
 
@@ -606,6 +705,11 @@ where
 			.map_err(|e| Error::Backend(e.to_string()))?;
 
 		metric_set!(self, beefy_best_block, block_num);
+		metric_set!(
+			self,
+			beefy_justification_lag,
+			self.best_grandpa_block().saturating_sub(block_num)
+		);
 
 		self.on_demand_justifications.cancel_requests_older_than(block_num);
 
@@ -619,6 +723,11 @@ where
 					.notify(|| Ok::<_, ()>(hash))
 					.expect("forwards closure result; the closure always returns Ok; qed.");
 
+				// block import already stored justifications of blocks finalized at import.
+				let stored = self.backend.blockchain().justifications(hash)?;
+				if stored.map_or(false, |j| j.get(BEEFY_ENGINE_ID).is_some()) {
+					return Ok(())
+				}
 				self.backend
 					.append_justification(hash, (BEEFY_ENGINE_ID, finality_proof.encode()))
 			}) {
@@ -628,6 +737,10 @@ where
 			);
 		}
 
+		if let Some(secondary_proof) = secondary_proof {
+			self.append_secondary_justification(secondary_proof);
+		}
+
 		self.links
 			.to_rpc_justif_sender
 			.notify(|| Ok::<_, ()>(finality_proof))
@@ -635,24 +748,119 @@ where
 
 		// Update gossip validator votes filter.
 		self.persisted_state
-			.gossip_filter_config()
+			.gossip_filter_config(self.signature_hasher)
 			.map(|filter| self.gossip_validator.update_filter(filter))?;
+		self.update_secondary_filter();
 		Ok(())
 	}
 
+	/// Start collecting secondary signatures for `validator_set`, if it is dual signing, with
+	/// the secondary validator set declared by the runtime at block `at`.
+	fn init_secondary_session(&mut self, validator_set: &ValidatorSet<AuthorityId>, at: B::Hash) {
+		if !self
+			.dual_signing
+			.as_ref()
+			.map_or(false, |dual| dual.is_active(validator_set.id()))
+		{
+			return
+		}
+		match secondary_validator_set::<B, _>(&*self.runtime, at) {
+			Ok(Some(secondary)) if self.secondary_votes.add_session(validator_set, secondary) => {
+				info!(target: LOG_TARGET, "🥩 Dual signing for validator set {:?}", validator_set.id())
+			},
+			Ok(_) => warn!(
+				target: LOG_TARGET,
+				"🥩 No matching secondary validator set for dual-signing validator set {:?}",
+				validator_set.id()
+			),
+			Err(e) =>
+				warn!(target: LOG_TARGET, "🥩 Failed to get secondary validator set: {:?}", e),
+		}
+	}
+
+	/// Update the secondary part of the gossip validator filter to the active validator set.
+	fn update_secondary_filter(&self) {
+		let secondary = self.dual_signing.as_ref().and_then(|dual| {
+			let set_id = self.voting_oracle().current_validator_set().ok()?.id();
+			let validator_set = self.secondary_votes.validator_set(set_id)?;
+			Some((validator_set.clone(), dual.signer.clone()))
+		});
+		self.gossip_validator.update_secondary_filter(secondary);
+	}
+
+	/// Add the secondary `vote`, verified by the gossip validator, and store the secondary
+	/// justification it may complete.
+	fn handle_secondary_vote(&mut self, vote: SecondaryVote<B>) {
+		let secondary_proof = match self.persisted_state.voting_oracle.active_rounds() {
+			Ok(rounds) => self.secondary_votes.add_vote(rounds.validator_set(), vote),
+			Err(_) => None,
+		};
+		if let Some(secondary_proof) = secondary_proof {
+			self.append_secondary_justification(secondary_proof);
+		}
+	}
+
+	/// Store `secondary_proof` next to the regular justification of its block.
+	fn append_secondary_justification(&self, secondary_proof: SecondaryFinalityProof<B>) {
+		let block_num = match secondary_proof {
+			VersionedFinalityProof::V1(ref sc) => sc.commitment.block_number,
+		};
+		if let Err(e) = self
+			.backend
+			.blockchain()
+			.expect_block_hash_from_id(&BlockId::Number(block_num))
+			.and_then(|hash| {
+				self.backend.append_justification(
+					hash,
+					(BEEFY_SECONDARY_ENGINE_ID, secondary_proof.encode()),
+				)
+			}) {
+			error!(
+				target: LOG_TARGET,
+				"🥩 Error {:?} on appending secondary justification: {:?}", e, secondary_proof
+			);
+		}
+	}
+
+	/// Return `false` if we are an authority of the active validator set, but `finality_proof`
+	/// doesn't contain our signature.
+	fn is_signed_by_local_authority(
+		&self,
+		finality_proof: &BeefyVersionedFinalityProof<B>,
+	) -> bool {
+		let signed_commitment = match finality_proof {
+			VersionedFinalityProof::V1(sc) => sc,
+		};
+		let rounds = match self.voting_oracle().active_rounds() {
+			Ok(rounds)
+				if rounds.validator_set_id() == signed_commitment.commitment.validator_set_id =>
+				rounds,
+			_ => return true,
+		};
+		let validator_set = rounds.validator_set();
+		self.key_store
+			.authority_id(validator_set)
+			.and_then(|id| validator_set.validators().iter().position(|v| *v == id))
+			.map_or(true, |index| {
+				signed_commitment.signatures.get(index).map_or(false, |sig| sig.is_some())
+			})
+	}
+
 	/// Handle previously buffered justifications, that now land in the voting interval.
 	fn try_pending_justififactions(&mut self) -> Result<(), Error> {
 		// Interval of blocks for which we can process justifications and votes right now.
 		let (start, end) = self.voting_oracle().accepted_interval()?;
 		// Process pending justifications.
-		if !self.pending_justifications.is_empty() {
+		if !self.persisted_state.pending_justifications.is_empty() {
 			// These are still pending.
-			let still_pending =
-				self.pending_justifications.split_off(&end.saturating_add(1u32.into()));
+			let still_pending = self
+				.persisted_state
+				.pending_justifications
+				.split_off(&end.saturating_add(1u32.into()));
 			// These can be processed.
-			let justifs_to_process = self.pending_justifications.split_off(&start);
+			let justifs_to_process = self.persisted_state.pending_justifications.split_off(&start);
 			// The rest can be dropped.
-			self.pending_justifications = still_pending;
+			self.persisted_state.pending_justifications = still_pending;
 
 			for (num, justification) in justifs_to_process.into_iter() {
 				debug!(target: LOG_TARGET, "🥩 Handle buffered justification for: {:?}.", num);
@@ -661,7 +869,11 @@ where
 					error!(target: LOG_TARGET, "🥩 Error finalizing block: {}", err);
 				}
 			}
-			metric_set!(self, beefy_buffered_justifications, self.pending_justifications.len());
+			metric_set!(
+				self,
+				beefy_buffered_justifications,
+				self.persisted_state.pending_justifications.len()
+			);
 		}
 		Ok(())
 	}
@@ -672,6 +884,9 @@ where
 		if let Some(target) = self.voting_oracle().voting_target() {
 			metric_set!(self, beefy_should_vote_on, target);
 			if target > self.persisted_state.best_voted {
+				if self.voting_target_since.map_or(true, |(current, _)| current != target) {
+					self.voting_target_since = Some((target, Instant::now()));
+				}
 				self.do_vote(target)?;
 			}
 		}
@@ -719,9 +934,9 @@ where
 		};
 
 		let rounds = self.persisted_state.voting_oracle.active_rounds_mut()?;
-		let (validators, validator_set_id) = (rounds.validators(), rounds.validator_set_id());
+		let validator_set_id = rounds.validator_set_id();
 
-		let authority_id = if let Some(id) = self.key_store.authority_id(validators) {
+		let authority_id = if let Some(id) = self.key_store.authority_id(rounds.validator_set()) {
 			debug!(target: LOG_TARGET, "🥩 Local authority id: {:?}", id);
 			id
 		} else {
@@ -735,21 +950,69 @@ where
 		let commitment = Commitment { payload, block_number: target_number, validator_set_id };
 		let encoded_commitment = commitment.encode();
 
-		let signature = match self.key_store.sign(&authority_id, &encoded_commitment) {
-			Ok(sig) => sig,
+		// Record what we sign before signing it, so that no restart can make us sign a
+		// conflicting payload for the same round.
+		let payload_hash = sp_core::hashing::blake2_256(&commitment.payload.encode());
+		match self
+			.signing_record
+			.check_and_record(validator_set_id, target_number, payload_hash)
+		{
+			Ok(true) => crate::aux_schema::write_signing_record::<B, _>(
+				&*self.backend,
+				&self.signing_record,
+			)
+			.map_err(|e| Error::Backend(e.to_string()))?,
+			Ok(false) => (),
 			Err(err) => {
-				warn!(target: LOG_TARGET, "🥩 Error signing commitment: {:?}", err);
+				error!(target: LOG_TARGET, "🥩 Refusing to vote: {}", err);
+				metric_inc!(self, beefy_signing_failures);
 				return Ok(())
 			},
-		};
+		}
+
+		let signature =
+			match self.key_store.sign(&authority_id, &encoded_commitment, self.signature_hasher) {
+				Ok(sig) => sig,
+				Err(Error::Keystore(err)) if err.is_transient() => {
+					let failures = match self.signing_failures {
+						Some((block, failures)) if block == target_number => failures + 1,
+						_ => 1,
+					};
+					warn!(
+						target: LOG_TARGET,
+						"🥩 Transient error signing commitment for block #{:?} ({} failures): {}",
+						target_number,
+						failures,
+						err
+					);
+					metric_inc!(self, beefy_signing_failures);
+					self.signing_failures = Some((target_number, failures));
+					return Ok(())
+				},
+				Err(err) => {
+					warn!(target: LOG_TARGET, "🥩 Error signing commitment: {:?}", err);
+					metric_inc!(self, beefy_signing_failures);
+					return Ok(())
+				},
+			};
+		self.signing_failures = None;
 
 		trace!(
 			target: LOG_TARGET,
 			"🥩 Produced signature using {:?}, is_valid: {:?}",
 			authority_id,
-			BeefyKeystore::verify(&authority_id, &signature, &encoded_commitment)
+			BeefyKeystore::verify(
+				&authority_id,
+				&signature,
+				&encoded_commitment,
+				self.signature_hasher
+			)
 		);
 
+		// Secondary vote first, for it to make it into a secondary justification concluded by
+		// our own primary vote.
+		self.do_secondary_vote(commitment.clone(), authority_id.clone(), &encoded_commitment);
+
 		let vote = VoteMessage { commitment, id: authority_id, signature };
 		if let Some(finality_proof) = self.handle_vote(vote.clone()).map_err(|err| {
 			error!(target: LOG_TARGET, "🥩 Error handling self vote: {}", err);
@@ -764,6 +1027,14 @@ where
 			self.gossip_engine.gossip_message(votes_topic::<B>(), encoded_vote, false);
 		}
 
+		if let (Some(metrics), Some((target, since))) =
+			(self.metrics.as_ref(), self.voting_target_since.take())
+		{
+			if target == target_number {
+				metrics.beefy_vote_delay.observe(since.elapsed().as_secs_f64());
+			}
+		}
+
 		// Persist state after vote to avoid double voting in case of voter restarts.
 		self.persisted_state.best_voted = target_number;
 		metric_set!(self, beefy_best_voted, target_number);
@@ -771,6 +1042,39 @@ where
 			.map_err(|e| Error::Backend(e.to_string()))
 	}
 
+	/// Sign `commitment` with the secondary key of `authority_id`, if dual signing, and gossip it.
+	fn do_secondary_vote(
+		&mut self,
+		commitment: Commitment<NumberFor<B>>,
+		authority_id: AuthorityId,
+		encoded_commitment: &[u8],
+	) {
+		let signer = match &self.dual_signing {
+			Some(dual) => dual.signer.clone(),
+			None => return,
+		};
+		let public = match self.voting_oracle().active_rounds() {
+			Ok(rounds) => self
+				.secondary_votes
+				.secondary_key(rounds.validator_set(), &authority_id)
+				.cloned(),
+			Err(_) => None,
+		};
+		let signature = match public
+			.and_then(|public| signer.sign(&public, encoded_commitment, self.signature_hasher))
+		{
+			Some(signature) => signature,
+			// not dual signing, or missing the secondary key.
+			None => return,
+		};
+
+		let vote = VoteMessage { commitment, id: authority_id, signature };
+		debug!(target: LOG_TARGET, "🥩 Sent secondary vote message: {:?}", vote);
+		let encoded_vote = GossipMessage::<B>::SecondaryVote(vote.clone()).encode();
+		self.gossip_engine.gossip_message(votes_topic::<B>(), encoded_vote, false);
+		self.handle_secondary_vote(vote);
+	}
+
 	fn process_new_state(&mut self) {
 		// Handle pending justifications and/or votes for now GRANDPA finalized blocks.
 		if let Err(err) = self.try_pending_justififactions() {
@@ -786,8 +1090,18 @@ where
 			// If the current target is a mandatory block,
 			// make sure there's also an on-demand justification request out for it.
 			if let Some((block, active)) = self.voting_oracle().mandatory_pending() {
+				let validator_sets = self.voting_oracle().queued_validator_sets();
 				// This only starts new request if there isn't already an active one.
-				self.on_demand_justifications.request(block, active);
+				if validator_sets.len() > 1 {
+					// Lagging multiple sessions behind, fast-forward through all of them at once.
+					self.on_demand_justifications.request_fast_forward(
+						block,
+						validator_sets,
+						self.signature_hasher,
+					);
+				} else {
+					self.on_demand_justifications.request(block, active, self.signature_hasher);
+				}
 			}
 		}
 	}
@@ -806,13 +1120,21 @@ where
 			self.best_grandpa_block()
 		);
 
+		// Dual signing of the active validator set, if any.
+		if let Ok(validator_set) = self.voting_oracle().current_validator_set().cloned() {
+			let best_grandpa = self.persisted_state.voting_oracle.best_grandpa_block_header.hash();
+			self.init_secondary_session(&validator_set, best_grandpa);
+			self.update_secondary_filter();
+		}
+
+		// Both regular and secondary votes.
 		let mut votes = Box::pin(
 			self.gossip_engine
 				.messages_for(votes_topic::<B>())
 				.filter_map(|notification| async move {
 					let vote = GossipMessage::<B>::decode(&mut &notification.message[..])
 						.ok()
-						.and_then(|message| message.unwrap_vote());
+						.filter(|message| !matches!(message, GossipMessage::FinalityProof(_)));
 					trace!(target: LOG_TARGET, "🥩 Got vote message: {:?}", vote);
 					vote
 				})
@@ -830,11 +1152,27 @@ where
 				})
 				.fuse(),
 		);
+		let mut checkpoints: Fuse<std::pin::Pin<Box<dyn Stream<Item = ()> + Send>>> =
+			match self.checkpoint_interval {
+				Some(interval) => Box::pin(wasm_timer::Interval::new(interval)) as _,
+				None => Box::pin(stream::pending()) as _,
+			}
+			.fuse();
 
 		loop {
 			// Act on changed 'state'.
 			self.process_new_state();
 
+			// Vote again later, if the last vote failed to be signed for a transient reason.
+			let mut signing_retry = match self.signing_failures {
+				Some((block, failures))
+					if failures <= MAX_SIGNING_RETRIES &&
+						self.voting_oracle().voting_target() == Some(block) =>
+					future::Either::Left(wasm_timer::Delay::new(SIGNING_RETRY_DELAY)),
+				_ => future::Either::Right(future::pending()),
+			}
+			.fuse();
+
 			// Mutable reference used to drive the gossip engine.
 			let mut gossip_engine = &mut self.gossip_engine;
 			// Use temp val and report after async section,
@@ -869,6 +1207,14 @@ where
 							}
 							gossip_report = Some(peer_report);
 						},
+						ResponseInfo::ValidProofs(justifs, peer_report) => {
+							for justif in justifs {
+								if let Err(err) = self.triage_incoming_justif(justif) {
+									debug!(target: LOG_TARGET, "🥩 {}", err);
+								}
+							}
+							gossip_report = Some(peer_report);
+						},
 						ResponseInfo::PeerReport(peer_report) => gossip_report = Some(peer_report),
 						ResponseInfo::Pending => (),
 					}
@@ -901,10 +1247,20 @@ where
 				},
 				// Finally process incoming votes.
 				vote = votes.next() => {
+					// Votes have already been verified to be valid by the gossip validator.
 					if let Some(vote) = vote {
-						// Votes have already been verified to be valid by the gossip validator.
-						if let Err(err) = self.triage_incoming_vote(vote) {
-							debug!(target: LOG_TARGET, "🥩 {}", err);
+						match vote {
+							GossipMessage::Vote(vote) => if let Err(err) = self.triage_incoming_vote(vote) {
+								debug!(target: LOG_TARGET, "🥩 {}", err);
+							},
+							GossipMessage::SecondaryVote(vote) => self.handle_secondary_vote(vote),
+							GossipMessage::VoteBatch(batch) => for vote in batch.into_votes() {
+								if let Err(err) = self.triage_incoming_vote(vote) {
+									debug!(target: LOG_TARGET, "🥩 {}", err);
+								}
+							},
+							// Filtered out of the votes stream.
+							GossipMessage::FinalityProof(_) => (),
 						}
 					} else {
 						error!(
@@ -918,6 +1274,12 @@ where
 				report = self.gossip_report_stream.next() => {
 					gossip_report = report;
 				},
+				// Periodically persist the full voter state.
+				_ = checkpoints.next() => self.checkpoint(),
+				// Retry voting, the vote itself happens when acting on the 'state' again.
+				_ = signing_retry => {
+					debug!(target: LOG_TARGET, "🥩 Retrying to vote after signing failure");
+				},
 			}
 			if let Some(PeerReport { who, cost_benefit }) = gossip_report {
 				self.gossip_engine.report(who, cost_benefit);
@@ -925,6 +1287,15 @@ where
 		}
 	}
 
+	/// Persist the voter state, including the observed votes and the pending justifications
+	/// which are not persisted on their own.
+	fn checkpoint(&self) {
+		if let Err(e) = crate::aux_schema::write_voter_state(&*self.backend, &self.persisted_state)
+		{
+			warn!(target: LOG_TARGET, "🥩 Failed to checkpoint voter state: {}", e);
+		}
+	}
+
 	/// Report the given equivocation to the BEEFY runtime module. This method
 	/// generates a session membership proof of the offender and then submits an
 	/// extrinsic to report the equivocation. In particular, the session membership
@@ -935,13 +1306,13 @@ where
 		proof: EquivocationProof<NumberFor<B>, AuthorityId, Signature>,
 	) -> Result<(), Error> {
 		let rounds = self.persisted_state.voting_oracle.active_rounds()?;
-		let (validators, validator_set_id) = (rounds.validators(), rounds.validator_set_id());
+		let validator_set_id = rounds.validator_set_id();
 		let offender_id = proof.offender_id().clone();
 
-		if !check_equivocation_proof::<_, _, BeefySignatureHasher>(&proof) {
+		if !check_equivocation_proof_with_hasher(&proof, self.signature_hasher) {
 			debug!(target: LOG_TARGET, "🥩 Skip report for bad equivocation {:?}", proof);
 			return Ok(())
-		} else if let Some(local_id) = self.key_store.authority_id(validators) {
+		} else if let Some(local_id) = self.key_store.authority_id(rounds.validator_set()) {
 			if offender_id == local_id {
 				debug!(target: LOG_TARGET, "🥩 Skip equivocation report for own equivocation");
 				return Ok(())
@@ -1001,6 +1372,21 @@ where
 	header.digest().convert_first(|l| l.try_to(id).and_then(filter))
 }
 
+/// Scan the `header` digest log for the hash function of the signatures of the BEEFY validator set
+/// it announces, if not [`SignatureHasher::Keccak256`].
+pub(crate) fn find_signature_hasher<B>(header: &B::Header) -> Option<SignatureHasher>
+where
+	B: Block,
+{
+	let id = OpaqueDigestItemId::Consensus(&BEEFY_ENGINE_ID);
+
+	let filter = |log: ConsensusLog<AuthorityId>| match log {
+		ConsensusLog::SignatureHasher(hasher) => Some(hasher),
+		_ => None,
+	};
+	header.digest().convert_first(|l| l.try_to(id).and_then(filter))
+}
+
 /// Calculate next block number to vote on.
 ///
 /// Return `None` if there is no voteable target yet.
@@ -1170,8 +1556,14 @@ pub(crate) mod tests {
 			metrics,
 			sync: Arc::new(sync),
 			on_demand_justifications,
-			pending_justifications: BTreeMap::new(),
+			checkpoint_interval: None,
 			persisted_state,
+			voting_target_since: None,
+			signature_hasher: SignatureHasher::Keccak256,
+			dual_signing: None,
+			secondary_votes: SecondaryVotes::new(),
+			signing_failures: None,
+			signing_record: Default::default(),
 		}
 	}
 
@@ -1435,6 +1827,31 @@ pub(crate) mod tests {
 		assert_eq!(extracted, Some(validator_set));
 	}
 
+	#[test]
+	fn extract_signature_hasher_digest() {
+		let mut header = Header::new(
+			1u32.into(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Digest::default(),
+		);
+		let validator_set = ValidatorSet::new(make_beefy_ids(&[Keyring::One]), 42).unwrap();
+		header.digest_mut().push(DigestItem::Consensus(
+			BEEFY_ENGINE_ID,
+			ConsensusLog::<AuthorityId>::AuthoritiesChange(validator_set).encode(),
+		));
+
+		// the default hasher is not announced.
+		assert!(find_signature_hasher::<Block>(&header).is_none());
+
+		header.digest_mut().push(DigestItem::Consensus(
+			BEEFY_ENGINE_ID,
+			ConsensusLog::<AuthorityId>::SignatureHasher(SignatureHasher::Sha2_256).encode(),
+		));
+		assert_eq!(find_signature_hasher::<Block>(&header), Some(SignatureHasher::Sha2_256));
+	}
+
 	#[tokio::test]
 	async fn keystore_vs_validator_set() {
 		let keys = &[Keyring::Alice];
@@ -1448,13 +1865,12 @@ pub(crate) mod tests {
 		// unknown `Bob` key
 		let keys = &[Keyring::Bob];
 		let validator_set = ValidatorSet::new(make_beefy_ids(keys), 0).unwrap();
-		let err_msg = "no authority public key found in store".to_string();
-		let expected = Err(Error::Keystore(err_msg));
+		let expected = Err(Error::Keystore(KeystoreError::NoAuthorityKey));
 		assert_eq!(worker.verify_validator_set(&1, &validator_set), expected);
 
 		// worker has no keystore
 		worker.key_store = None.into();
-		let expected_err = Err(Error::Keystore("no Keystore".into()));
+		let expected_err = Err(Error::Keystore(KeystoreError::NoKeystore));
 		assert_eq!(worker.verify_validator_set(&1, &validator_set), expected_err);
 	}
 
@@ -193,9 +193,20 @@ impl<B: BlockT> Future for GossipEngine<B> {
 
 					match net_event_stream {
 						Poll::Ready(Some(event)) => match event {
-							Event::NotificationStreamOpened { remote, protocol, role, .. } =>
+							Event::NotificationStreamOpened {
+								remote,
+								protocol,
+								role,
+								negotiated_fallback,
+								..
+							} =>
 								if protocol == this.protocol {
-									this.state_machine.new_peer(&mut *this.network, remote, role);
+									this.state_machine.new_peer(
+										&mut *this.network,
+										remote,
+										role,
+										negotiated_fallback,
+									);
 								},
 							Event::NotificationStreamClosed { remote, protocol } => {
 								if protocol == this.protocol {
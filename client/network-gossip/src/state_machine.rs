@@ -190,12 +190,20 @@ impl<B: BlockT> ConsensusGossip<B> {
 		}
 	}
 
-	/// Handle new connected peer.
-	pub fn new_peer(&mut self, network: &mut dyn Network<B>, who: PeerId, role: ObservedRole) {
+	/// Handle new connected peer, using the fallback protocol name `negotiated_fallback` if it
+	/// isn't `None`.
+	pub fn new_peer(
+		&mut self,
+		network: &mut dyn Network<B>,
+		who: PeerId,
+		role: ObservedRole,
+		negotiated_fallback: Option<ProtocolName>,
+	) {
 		tracing::trace!(
 			target:"gossip",
 			%who,
 			protocol = %self.protocol,
+			?negotiated_fallback,
 			?role,
 			"Registering peer",
 		);
@@ -203,7 +211,7 @@ impl<B: BlockT> ConsensusGossip<B> {
 
 		let validator = self.validator.clone();
 		let mut context = NetworkContext { gossip: self, network };
-		validator.new_peer(&mut context, &who, role);
+		validator.new_peer_with_fallback(&mut context, &who, role, negotiated_fallback.as_ref());
 	}
 
 	fn register_message_hashed(
@@ -770,7 +778,7 @@ mod tests {
 		let mut network = NoOpNetwork::default();
 
 		let peer_id = PeerId::random();
-		consensus.new_peer(&mut network, peer_id, ObservedRole::Full);
+		consensus.new_peer(&mut network, peer_id, ObservedRole::Full, None);
 		assert!(consensus.peers.contains_key(&peer_id));
 
 		consensus.peer_disconnected(&mut network, peer_id);
@@ -818,11 +826,11 @@ mod tests {
 		let mut network = NoOpNetwork::default();
 
 		let peer_id = PeerId::random();
-		consensus.new_peer(&mut network, peer_id, ObservedRole::Full);
+		consensus.new_peer(&mut network, peer_id, ObservedRole::Full, None);
 		assert!(consensus.peers.contains_key(&peer_id));
 
 		let peer_id2 = PeerId::random();
-		consensus.new_peer(&mut network, peer_id2, ObservedRole::Full);
+		consensus.new_peer(&mut network, peer_id2, ObservedRole::Full, None);
 		assert!(consensus.peers.contains_key(&peer_id2));
 
 		let message = vec![vec![1, 2, 3]];
@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use libp2p::PeerId;
+use sc_network::types::ProtocolName;
 use sc_network_common::role::ObservedRole;
 use sp_runtime::traits::Block as BlockT;
 
@@ -26,6 +27,21 @@ pub trait Validator<B: BlockT>: Send + Sync {
 	fn new_peer(&self, _context: &mut dyn ValidatorContext<B>, _who: &PeerId, _role: ObservedRole) {
 	}
 
+	/// New peer is connected, using the fallback protocol name `negotiated_fallback` if it isn't
+	/// `None`, i.e. an older version of the protocol.
+	///
+	/// See `sc_network::config::NonDefaultSetConfig::fallback_names`. Defaults to
+	/// [`Validator::new_peer`], for validators that don't tell protocol versions apart.
+	fn new_peer_with_fallback(
+		&self,
+		context: &mut dyn ValidatorContext<B>,
+		who: &PeerId,
+		role: ObservedRole,
+		_negotiated_fallback: Option<&ProtocolName>,
+	) {
+		self.new_peer(context, who, role)
+	}
+
 	/// New connection is dropped.
 	fn peer_disconnected(&self, _context: &mut dyn ValidatorContext<B>, _who: &PeerId) {}
 
@@ -68,18 +68,18 @@ where
 
 		let mut voters_nominator_only = voters
 			.iter()
-			.filter(|(v, _, _)| pallet_staking::Nominators::<Runtime>::contains_key(v))
+			.filter(|voter| pallet_staking::Nominators::<Runtime>::contains_key(&voter.who))
 			.cloned()
 			.collect::<Vec<_>>();
-		voters_nominator_only.sort_by_key(|(_, w, _)| *w);
+		voters_nominator_only.sort_by_key(|voter| voter.weight);
 
 		let currency_unit = currency_unit as f64;
 		let min_voter = voters_nominator_only
 			.first()
-			.map(|(x, y, _)| (x.clone(), *y as f64 / currency_unit));
+			.map(|voter| (voter.who.clone(), voter.weight as f64 / currency_unit));
 		let max_voter = voters_nominator_only
 			.last()
-			.map(|(x, y, _)| (x.clone(), *y as f64 / currency_unit));
+			.map(|voter| (voter.who.clone(), voter.weight as f64 / currency_unit));
 		log::info!(
 			target: crate::LOG_TARGET,
 			"a snapshot with limit {:?} has been created, {} voters are taken. min nominator: {:?}, max: {:?}",
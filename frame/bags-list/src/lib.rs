@@ -53,10 +53,21 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::FullCodec;
-use frame_election_provider_support::{ScoreProvider, SortedListProvider};
+use codec::{Decode, Encode, FullCodec, MaxEncodedLen};
+use frame_election_provider_support::{
+	BatchMode, ListCursor, NodeInfo, ScoreProvider, SortedListProvider,
+};
+use frame_support::{
+	ensure,
+	traits::{Defensive, Get},
+	weights::Weight,
+};
 use frame_system::ensure_signed;
-use sp_runtime::traits::{AtLeast32BitUnsigned, Bounded, StaticLookup};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{AtLeast32BitUnsigned, Bounded, SaturatedConversion, StaticLookup},
+	RuntimeDebug,
+};
 use sp_std::prelude::*;
 
 #[cfg(any(test, feature = "try-runtime", feature = "fuzz"))]
@@ -95,6 +106,23 @@ macro_rules! log {
 
 type AccountIdLookupOf<T> = <<T as frame_system::Config>::Lookup as StaticLookup>::Source;
 
+/// Whether changes to the list are applied right away, see [`SortedListProvider::lock`].
+///
+/// Encoded like a `bool` for the first two variants.
+#[derive(
+	Encode, Decode, MaxEncodedLen, TypeInfo, Clone, Copy, PartialEq, Eq, Default, RuntimeDebug,
+)]
+pub enum LockState {
+	/// Changes are applied right away.
+	#[default]
+	Unlocked,
+	/// Changes are queued until the list is unlocked.
+	Locked,
+	/// The list was unlocked, and the changes queued in the meantime are being applied on idle.
+	/// New changes are queued behind them until they are all applied.
+	Draining,
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -192,6 +220,58 @@ pub mod pallet {
 	pub(crate) type ListBags<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Twox64Concat, T::Score, list::Bag<T, I>>;
 
+	/// The number of nodes in each bag, keyed by the upper score of the bag.
+	///
+	/// This allows [`SortedListProvider::rank`] to be computed without walking the list.
+	#[pallet::storage]
+	pub(crate) type BagCounts<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::Score, u32, ValueQuery>;
+
+	/// The progress of [`migrations::AddBagCounts`], if ongoing: the last node whose bag was
+	/// counted into [`BagCounts`], or `None` if no node was counted yet.
+	///
+	/// While this exists, the list is frozen as if it was locked, and the remaining nodes are
+	/// counted on idle.
+	#[pallet::storage]
+	pub(crate) type BagCountsMigration<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, Option<T::AccountId>, OptionQuery>;
+
+	/// The id after which [`Pallet::do_idle_rebag`] should continue looking for misplaced nodes.
+	///
+	/// If `None`, the next search starts from the head of the list.
+	#[pallet::storage]
+	pub(crate) type IdleRebagCursor<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, T::AccountId, OptionQuery>;
+
+	/// Whether the list is locked, in which case insertions, updates and removals are queued in
+	/// [`PendingInsertions`], [`PendingUpdates`] and [`PendingRemovals`] rather than applied.
+	///
+	/// See [`SortedListProvider::lock`].
+	#[pallet::storage]
+	pub(crate) type Locked<T: Config<I>, I: 'static = ()> = StorageValue<_, LockState, ValueQuery>;
+
+	/// Ids that were inserted while the list was locked, with their score.
+	///
+	/// These are inserted into the list on idle, once it is unlocked. Each id is queued at most
+	/// once in all of the pending maps, so they never hold more ids than the list would.
+	#[pallet::storage]
+	pub(crate) type PendingInsertions<T: Config<I>, I: 'static = ()> =
+		CountedStorageMap<_, Twox64Concat, T::AccountId, T::Score>;
+
+	/// Ids of the list whose score was updated while the list was locked, with their new score.
+	///
+	/// These are rebagged on idle, once the list is unlocked.
+	#[pallet::storage]
+	pub(crate) type PendingUpdates<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::Score>;
+
+	/// Ids of the list that were removed while the list was locked.
+	///
+	/// These are removed from the list on idle, once it is unlocked.
+	#[pallet::storage]
+	pub(crate) type PendingRemovals<T: Config<I>, I: 'static = ()> =
+		CountedStorageMap<_, Twox64Concat, T::AccountId, ()>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(crate) fn deposit_event)]
 	pub enum Event<T: Config<I>, I: 'static = ()> {
@@ -225,12 +305,13 @@ pub mod pallet {
 		/// Will always update the stored score of `dislocated` to the correct score, based on
 		/// `ScoreProvider`.
 		///
-		/// If `dislocated` does not exists, it returns an error.
+		/// If `dislocated` does not exists, or the list is locked, it returns an error.
 		#[pallet::call_index(0)]
 		#[pallet::weight(T::WeightInfo::rebag_non_terminal().max(T::WeightInfo::rebag_terminal()))]
 		pub fn rebag(origin: OriginFor<T>, dislocated: AccountIdLookupOf<T>) -> DispatchResult {
 			ensure_signed(origin)?;
 			let dislocated = T::Lookup::lookup(dislocated)?;
+			Self::ensure_unlocked().map_err::<Error<T, I>, _>(Into::into)?;
 			let current_score = T::ScoreProvider::score(&dislocated);
 			let _ = Pallet::<T, I>::do_rebag(&dislocated, current_score)
 				.map_err::<Error<T, I>, _>(Into::into)?;
@@ -244,7 +325,8 @@ pub mod pallet {
 		///
 		/// Only works if
 		/// - both nodes are within the same bag,
-		/// - and `origin` has a greater `Score` than `lighter`.
+		/// - `origin` has a greater `Score` than `lighter`,
+		/// - and the list is not locked.
 		#[pallet::call_index(1)]
 		#[pallet::weight(T::WeightInfo::put_in_front_of())]
 		pub fn put_in_front_of(
@@ -253,6 +335,7 @@ pub mod pallet {
 		) -> DispatchResult {
 			let heavier = ensure_signed(origin)?;
 			let lighter = T::Lookup::lookup(lighter)?;
+			Self::ensure_unlocked().map_err::<Error<T, I>, _>(Into::into)?;
 			List::<T, I>::put_in_front_of(&lighter, &heavier)
 				.map_err::<Error<T, I>, _>(Into::into)
 				.map_err::<DispatchError, _>(Into::into)
@@ -261,6 +344,10 @@ pub mod pallet {
 
 	#[pallet::hooks]
 	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
+		fn on_idle(_: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			<Self as SortedListProvider<T::AccountId>>::on_idle_rebag(remaining_weight)
+		}
+
 		fn integrity_test() {
 			// ensure they are strictly increasing, this also implies that duplicates are detected.
 			assert!(
@@ -279,6 +366,13 @@ pub mod pallet {
 #[cfg(any(test, feature = "try-runtime", feature = "fuzz"))]
 impl<T: Config<I>, I: 'static> Pallet<T, I> {
 	pub fn do_try_state() -> Result<(), TryRuntimeError> {
+		ensure!(
+			Self::is_queueing() ||
+				(PendingInsertions::<T, I>::count() == 0 &&
+					PendingUpdates::<T, I>::iter_keys().next().is_none() &&
+					PendingRemovals::<T, I>::count() == 0),
+			"pending changes must have been applied before the list is unlocked"
+		);
 		List::<T, I>::do_try_state()
 	}
 }
@@ -301,6 +395,261 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		Ok(maybe_movement)
 	}
 
+	/// Rebag as many misplaced nodes as fit into `budget`, continuing from where the previous call
+	/// stopped.
+	///
+	/// Each visited node is checked against its current score from `T::ScoreProvider`, and moved
+	/// to the correct bag if needed. Once the end of the list is reached, the next call starts
+	/// again from the head.
+	///
+	/// Nothing is rebagged while the list is locked, since moving nodes around would disturb
+	/// whoever is iterating it. Once it is unlocked, the changes queued in the meantime are applied
+	/// first, see [`Self::apply_pending`]. Before any of this, an ongoing
+	/// [`migrations::AddBagCounts`] is continued, see [`Self::count_bags`].
+	///
+	/// Returns the weight consumed.
+	pub fn do_idle_rebag(budget: Weight) -> Weight {
+		// reading the progress of the migration, if any.
+		let migration = T::DbWeight::get().reads(1);
+		if migration.any_gt(budget) {
+			return Weight::zero()
+		}
+		if BagCountsMigration::<T, I>::exists() {
+			return migration.saturating_add(Self::count_bags(budget.saturating_sub(migration)))
+		}
+
+		let per_node = T::WeightInfo::rebag_non_terminal().max(T::WeightInfo::rebag_terminal());
+		// reading the lock, and reading and writing the cursor.
+		let mut consumed = migration.saturating_add(T::DbWeight::get().reads_writes(2, 1));
+		if consumed.saturating_add(per_node).any_gt(budget) {
+			return Weight::zero()
+		}
+		match Locked::<T, I>::get() {
+			LockState::Unlocked => (),
+			LockState::Locked => return T::DbWeight::get().reads(2),
+			LockState::Draining => {
+				consumed.saturating_accrue(Self::apply_pending(budget.saturating_sub(consumed)));
+				if Locked::<T, I>::get() == LockState::Draining {
+					return consumed
+				}
+			},
+		}
+
+		let iter: Box<dyn Iterator<Item = list::Node<T, I>>> = match IdleRebagCursor::<T, I>::take()
+			.and_then(|cursor| List::<T, I>::iter_from(&cursor).ok())
+		{
+			Some(iter) => Box::new(iter),
+			// either starting anew, or the cursor was removed from the list in the meantime.
+			None => Box::new(List::<T, I>::iter()),
+		};
+
+		let (mut visited, mut rebagged) = (0u32, 0u32);
+		let mut last_visited = None;
+		for node in iter {
+			if consumed.saturating_add(per_node).any_gt(budget) {
+				// out of budget, continue right after the last visited node next time.
+				if let Some(last) = last_visited {
+					IdleRebagCursor::<T, I>::put(last);
+				}
+				break
+			}
+			consumed.saturating_accrue(per_node);
+			visited += 1;
+			last_visited = Some(node.id().clone());
+
+			let current_score = T::ScoreProvider::score(node.id());
+			if node.is_misplaced(current_score) && Self::do_rebag(node.id(), current_score).is_ok()
+			{
+				rebagged += 1;
+			}
+		}
+
+		log!(debug, "visited {} nodes and rebagged {} of them on idle", visited, rebagged);
+		consumed
+	}
+
+	/// Ensure that nodes can be moved around, i.e. the list is not locked and no queued changes
+	/// are waiting to be applied.
+	fn ensure_unlocked() -> Result<(), ListError> {
+		if Self::is_queueing() {
+			Err(ListError::Locked)
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Whether `id` is part of the list, including the changes made while it is locked.
+	fn contains_pending(id: &T::AccountId) -> bool {
+		PendingInsertions::<T, I>::contains_key(id) ||
+			(List::<T, I>::contains(id) && !PendingRemovals::<T, I>::contains_key(id))
+	}
+
+	/// Whether changes to the list are queued rather than applied, see [`LockState`] and
+	/// [`BagCountsMigration`].
+	fn is_queueing() -> bool {
+		Locked::<T, I>::get() != LockState::Unlocked || BagCountsMigration::<T, I>::exists()
+	}
+
+	/// Queue the insertion of `id` while the list is locked.
+	fn queue_insert(id: T::AccountId, score: T::Score) -> Result<(), ListError> {
+		ensure!(!Self::contains_pending(&id), ListError::Duplicate);
+		if PendingRemovals::<T, I>::take(&id).is_some() {
+			// `id` is still in the list, so it only needs to be updated.
+			PendingUpdates::<T, I>::insert(id, score);
+		} else {
+			PendingInsertions::<T, I>::insert(id, score);
+		}
+		Ok(())
+	}
+
+	/// Queue an update of the score of `id` while the list is locked.
+	fn queue_update(id: &T::AccountId, score: T::Score) -> Result<(), ListError> {
+		ensure!(Self::contains_pending(id), ListError::NodeNotFound);
+		if PendingInsertions::<T, I>::contains_key(id) {
+			PendingInsertions::<T, I>::insert(id, score);
+		} else {
+			PendingUpdates::<T, I>::insert(id, score);
+		}
+		Ok(())
+	}
+
+	/// Queue the removal of `id` while the list is locked.
+	fn queue_remove(id: &T::AccountId) -> Result<(), ListError> {
+		ensure!(Self::contains_pending(id), ListError::NodeNotFound);
+		if PendingInsertions::<T, I>::take(id).is_none() {
+			PendingUpdates::<T, I>::remove(id);
+			PendingRemovals::<T, I>::insert(id, ());
+		}
+		Ok(())
+	}
+
+	/// Queue many changes while the list is locked, with `queue` being one of the above, handling
+	/// failures according to `mode`.
+	fn queue_many(
+		items: impl IntoIterator<Item = (T::AccountId, T::Score)>,
+		mode: BatchMode,
+		check: impl Fn(&T::AccountId) -> bool,
+		error: ListError,
+		queue: impl Fn(T::AccountId, T::Score) -> Result<(), ListError>,
+	) -> Result<u32, ListError> {
+		let items = items.into_iter().collect::<Vec<_>>();
+		if mode == BatchMode::AllOrNothing {
+			let mut seen = sp_std::collections::btree_set::BTreeSet::new();
+			for (id, _) in items.iter() {
+				ensure!(check(id) && seen.insert(id), error);
+			}
+		}
+		Ok(items
+			.into_iter()
+			.filter(|(id, score)| queue(id.clone(), *score).is_ok())
+			.count() as u32)
+	}
+
+	/// Apply as many of the changes that were made while the list was locked as fit into `budget`,
+	/// marking the list as unlocked once they are all applied.
+	///
+	/// Each id is queued at most once, so the changes can be applied in any order.
+	///
+	/// Returns the weight consumed.
+	fn apply_pending(budget: Weight) -> Weight {
+		// inserting, moving or removing a node is at most as expensive as rebagging it.
+		let per_change = T::WeightInfo::rebag_non_terminal().max(T::WeightInfo::rebag_terminal());
+		let max_changes = budget
+			.checked_div_per_component(&per_change)
+			.map_or(usize::MAX, |max| max.saturated_into::<usize>());
+		let mut applied = 0;
+
+		for (id, _) in PendingRemovals::<T, I>::drain().take(max_changes) {
+			let _ = List::<T, I>::remove(&id).defensive();
+			applied += 1;
+		}
+		for (id, score) in PendingUpdates::<T, I>::drain().take(max_changes - applied) {
+			let _ = Self::do_rebag(&id, score).defensive();
+			applied += 1;
+		}
+		for (id, score) in PendingInsertions::<T, I>::drain().take(max_changes - applied) {
+			let _ = List::<T, I>::insert(id, score).defensive();
+			applied += 1;
+		}
+
+		// one more change would still fit: all of them have been applied.
+		if applied < max_changes {
+			Locked::<T, I>::kill();
+		}
+		log!(debug, "applied {} changes queued while the list was locked", applied);
+		per_change.saturating_mul(applied as u64)
+	}
+
+	/// Count the bags of as many nodes as fit into `budget` into [`BagCounts`], continuing from
+	/// where the previous call stopped, see [`migrations::AddBagCounts`].
+	///
+	/// Once all nodes are counted, the changes queued in the meantime are applied, see
+	/// [`Self::apply_pending`].
+	///
+	/// Returns the weight consumed.
+	fn count_bags(budget: Weight) -> Weight {
+		// reading a node, and at worst writing the count of its bag.
+		let per_node = T::DbWeight::get().reads_writes(1, 1);
+		let max_nodes = budget
+			.checked_div_per_component(&per_node)
+			.map_or(usize::MAX, |max| max.saturated_into::<usize>());
+
+		let iter = match BagCountsMigration::<T, I>::get().flatten() {
+			Some(last) => ListNodes::<T, I>::iter_from(ListNodes::<T, I>::hashed_key_for(&last)),
+			None => ListNodes::<T, I>::iter(),
+		};
+		let mut counts = sp_std::collections::btree_map::BTreeMap::<T::Score, u32>::new();
+		let (mut counted, mut last) = (0, None);
+		for (id, node) in iter.take(max_nodes) {
+			*counts.entry(node.bag_upper).or_default() += 1;
+			counted += 1;
+			last = Some(id);
+		}
+		for (bag_upper, count) in counts {
+			BagCounts::<T, I>::mutate(bag_upper, |c| *c = c.saturating_add(count));
+		}
+
+		if counted < max_nodes {
+			BagCountsMigration::<T, I>::kill();
+			if Locked::<T, I>::get() == LockState::Unlocked {
+				Locked::<T, I>::put(LockState::Draining);
+			}
+			log!(info, "counted the nodes of all bags");
+		} else if let Some(last) = last {
+			BagCountsMigration::<T, I>::put(Some(last));
+		}
+		per_node.saturating_mul(counted as u64)
+	}
+
+	/// Drop all the changes that were made while the list was locked.
+	fn clear_pending() {
+		#[allow(deprecated)]
+		PendingInsertions::<T, I>::remove_all();
+		#[allow(deprecated)]
+		PendingUpdates::<T, I>::remove_all(None);
+		#[allow(deprecated)]
+		PendingRemovals::<T, I>::remove_all();
+	}
+
+	/// Same as [`Self::do_rebag`], but for many accounts at once, depositing an event for each
+	/// updated account.
+	///
+	/// Returns the number of updated accounts.
+	pub fn do_rebag_many(
+		items: impl IntoIterator<Item = (T::AccountId, T::Score)>,
+		mode: BatchMode,
+	) -> Result<u32, ListError> {
+		let updated = List::<T, I>::update_batch(items, mode)?;
+		let count = updated.len() as u32;
+		for (who, new_score, maybe_movement) in updated {
+			if let Some((from, to)) = maybe_movement {
+				Self::deposit_event(Event::<T, I>::Rebagged { who: who.clone(), from, to });
+			};
+			Self::deposit_event(Event::<T, I>::ScoreUpdated { who, new_score });
+		}
+		Ok(count)
+	}
+
 	/// Equivalent to `ListBags::get`, but public. Useful for tests in outside of this crate.
 	#[cfg(feature = "std")]
 	pub fn list_bags_get(score: T::Score) -> Option<list::Bag<T, I>> {
@@ -312,6 +661,9 @@ impl<T: Config<I>, I: 'static> SortedListProvider<T::AccountId> for Pallet<T, I>
 	type Error = ListError;
 	type Score = T::Score;
 
+	// ids are only sorted into bags, their rank doesn't account for the ids of their own bag.
+	const EXACT_RANK: bool = false;
+
 	fn iter() -> Box<dyn Iterator<Item = T::AccountId>> {
 		Box::new(List::<T, I>::iter().map(|n| n.id().clone()))
 	}
@@ -323,30 +675,138 @@ impl<T: Config<I>, I: 'static> SortedListProvider<T::AccountId> for Pallet<T, I>
 		Ok(Box::new(iter.map(|n| n.id().clone())))
 	}
 
+	fn cursor(last: &T::AccountId) -> Result<ListCursor<T::AccountId, T::Score>, ListError> {
+		List::<T, I>::cursor(last)
+	}
+
+	fn iter_resume(
+		cursor: &ListCursor<T::AccountId, T::Score>,
+	) -> Box<dyn Iterator<Item = T::AccountId>> {
+		Box::new(List::<T, I>::iter_resume(cursor).map(|n| n.id().clone()))
+	}
+
+	fn iter_ascending() -> Box<dyn Iterator<Item = T::AccountId>> {
+		Box::new(List::<T, I>::iter_ascending().map(|n| n.id().clone()))
+	}
+
+	fn iter_from_ascending(
+		start: &T::AccountId,
+	) -> Result<Box<dyn Iterator<Item = T::AccountId>>, Self::Error> {
+		let iter = List::<T, I>::iter_from_ascending(start)?;
+		Ok(Box::new(iter.map(|n| n.id().clone())))
+	}
+
+	fn iter_in_score_range(lo: T::Score, hi: T::Score) -> Box<dyn Iterator<Item = T::AccountId>> {
+		Box::new(List::<T, I>::iter_in_score_range(lo, hi).map(|n| n.id().clone()))
+	}
+
 	fn count() -> u32 {
 		ListNodes::<T, I>::count()
+			.saturating_add(PendingInsertions::<T, I>::count())
+			.saturating_sub(PendingRemovals::<T, I>::count())
 	}
 
 	fn contains(id: &T::AccountId) -> bool {
-		List::<T, I>::contains(id)
+		Self::contains_pending(id)
+	}
+
+	fn rank(id: &T::AccountId) -> Result<u32, ListError> {
+		// the counts of the bags are not complete yet.
+		ensure!(!BagCountsMigration::<T, I>::exists(), ListError::Locked);
+		// ids are ranked by their bag, pending insertions by the bag they are going to be put in.
+		let bag_upper = match PendingInsertions::<T, I>::get(id) {
+			Some(score) => notional_bag_for::<T, I>(score),
+			None if PendingRemovals::<T, I>::contains_key(id) =>
+				return Err(ListError::NodeNotFound),
+			None => Node::<T, I>::get(id).ok_or(ListError::NodeNotFound)?.bag_upper,
+		};
+		Ok(List::<T, I>::count_above(bag_upper))
 	}
 
 	fn on_insert(id: T::AccountId, score: T::Score) -> Result<(), ListError> {
+		if Self::is_queueing() {
+			return Self::queue_insert(id, score)
+		}
 		List::<T, I>::insert(id, score)
 	}
 
+	fn on_insert_many(
+		items: impl IntoIterator<Item = (T::AccountId, T::Score)>,
+		mode: BatchMode,
+	) -> Result<u32, ListError> {
+		if Self::is_queueing() {
+			return Self::queue_many(
+				items,
+				mode,
+				|id| !Self::contains_pending(id),
+				ListError::Duplicate,
+				Self::queue_insert,
+			)
+		}
+		List::<T, I>::insert_batch(items, mode)
+	}
+
+	fn on_update_many(
+		items: impl IntoIterator<Item = (T::AccountId, T::Score)>,
+		mode: BatchMode,
+	) -> Result<u32, ListError> {
+		if Self::is_queueing() {
+			return Self::queue_many(
+				items,
+				mode,
+				Self::contains_pending,
+				ListError::NodeNotFound,
+				|id, score| Self::queue_update(&id, score),
+			)
+		}
+		Pallet::<T, I>::do_rebag_many(items, mode)
+	}
+
 	fn get_score(id: &T::AccountId) -> Result<T::Score, ListError> {
+		if let Some(score) =
+			PendingInsertions::<T, I>::get(id).or_else(|| PendingUpdates::<T, I>::get(id))
+		{
+			return Ok(score)
+		}
+		if PendingRemovals::<T, I>::contains_key(id) {
+			return Err(ListError::NodeNotFound)
+		}
 		List::<T, I>::get_score(id)
 	}
 
+	fn node_info(id: &T::AccountId) -> Result<NodeInfo<T::AccountId, T::Score>, ListError> {
+		List::<T, I>::node_info(id)
+	}
+
 	fn on_update(id: &T::AccountId, new_score: T::Score) -> Result<(), ListError> {
+		if Self::is_queueing() {
+			return Self::queue_update(id, new_score)
+		}
 		Pallet::<T, I>::do_rebag(id, new_score).map(|_| ())
 	}
 
 	fn on_remove(id: &T::AccountId) -> Result<(), ListError> {
+		if Self::is_queueing() {
+			return Self::queue_remove(id)
+		}
 		List::<T, I>::remove(id)
 	}
 
+	fn lock() {
+		Locked::<T, I>::put(LockState::Locked);
+	}
+
+	fn unlock() {
+		// the changes made in the meantime are applied on idle, see `Pallet::do_idle_rebag`.
+		if Locked::<T, I>::get() == LockState::Locked {
+			Locked::<T, I>::put(LockState::Draining);
+		}
+	}
+
+	fn is_locked() -> bool {
+		Locked::<T, I>::get() == LockState::Locked
+	}
+
 	fn unsafe_regenerate(
 		all: impl IntoIterator<Item = T::AccountId>,
 		score_of: Box<dyn Fn(&T::AccountId) -> T::Score>,
@@ -354,9 +814,17 @@ impl<T: Config<I>, I: 'static> SortedListProvider<T::AccountId> for Pallet<T, I>
 		// NOTE: This call is unsafe for the same reason as SortedListProvider::unsafe_regenerate.
 		// I.e. because it can lead to many storage accesses.
 		// So it is ok to call it as caller must ensure the conditions.
+		//
+		// This also rewrites the list while it is locked, disturbing whoever is iterating it. The
+		// changes made while locked are dropped, since `all` is the new source of truth anyway.
+		Self::clear_pending();
 		List::<T, I>::unsafe_regenerate(all, score_of)
 	}
 
+	fn on_idle_rebag(budget: Weight) -> Weight {
+		Pallet::<T, I>::do_idle_rebag(budget)
+	}
+
 	#[cfg(feature = "try-runtime")]
 	fn try_state() -> Result<(), TryRuntimeError> {
 		Self::do_try_state()
@@ -366,6 +834,7 @@ impl<T: Config<I>, I: 'static> SortedListProvider<T::AccountId> for Pallet<T, I>
 		// NOTE: This call is unsafe for the same reason as SortedListProvider::unsafe_clear.
 		// I.e. because it can lead to many storage accesses.
 		// So it is ok to call it as caller must ensure the conditions.
+		Self::clear_pending();
 		List::<T, I>::unsafe_clear()
 	}
 
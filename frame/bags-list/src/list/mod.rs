@@ -26,14 +26,14 @@
 
 use crate::Config;
 use codec::{Decode, Encode, MaxEncodedLen};
-use frame_election_provider_support::ScoreProvider;
+use frame_election_provider_support::{BatchMode, ListCursor, NodeInfo, ScoreProvider};
 use frame_support::{
 	defensive, ensure,
 	traits::{Defensive, DefensiveOption, Get},
 	DefaultNoBound, PalletError,
 };
 use scale_info::TypeInfo;
-use sp_runtime::traits::{Bounded, Zero};
+use sp_runtime::traits::{Bounded, Saturating, Zero};
 use sp_std::{
 	boxed::Box,
 	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
@@ -55,6 +55,9 @@ pub enum ListError {
 	NotInSameBag,
 	/// Given node id was not found.
 	NodeNotFound,
+	/// The list is locked, the changes made while it was locked are still being applied, or the
+	/// bags are still being counted, so nodes cannot be moved on request, nor ranked.
+	Locked,
 }
 
 #[cfg(test)]
@@ -100,7 +103,10 @@ impl<T: Config<I>, I: 'static> List<T, I> {
 		#[allow(deprecated)]
 		crate::ListBags::<T, I>::remove_all(None);
 		#[allow(deprecated)]
+		crate::BagCounts::<T, I>::remove_all(None);
+		#[allow(deprecated)]
 		crate::ListNodes::<T, I>::remove_all();
+		crate::BagCountsMigration::<T, I>::kill();
 	}
 
 	/// Regenerate all of the data from the given ids.
@@ -236,11 +242,77 @@ impl<T: Config<I>, I: 'static> List<T, I> {
 		Node::<T, I>::get(id).map(|node| node.score()).ok_or(ListError::NodeNotFound)
 	}
 
+	/// Get information about where `id` sits in the list.
+	pub fn node_info(id: &T::AccountId) -> Result<NodeInfo<T::AccountId, T::Score>, ListError> {
+		let node = Node::<T, I>::get(id).ok_or(ListError::NodeNotFound)?;
+		let bag_uppers = Self::bag_uppers();
+		let idx = bag_uppers.partition_point(|upper| *upper < node.bag_upper);
+		let lower = idx.checked_sub(1).map_or_else(Zero::zero, |prev_idx| bag_uppers[prev_idx]);
+
+		// the id's neighbours might be in other bags.
+		let prev = match node.prev {
+			Some(prev) => Some(prev),
+			None => Self::iter_from_ascending(id)?.next().map(|n| n.id),
+		};
+		let next = match node.next {
+			Some(next) => Some(next),
+			None => Self::iter_from(id)?.next().map(|n| n.id),
+		};
+
+		Ok(NodeInfo {
+			score: node.score,
+			rank_hint: (bag_uppers.len() - 1 - idx) as u32,
+			bag_bounds: (lower, node.bag_upper),
+			prev,
+			next,
+		})
+	}
+
 	/// Iterate over all nodes in all bags in the list.
 	///
 	/// Full iteration can be expensive; it's recommended to limit the number of items with
 	/// `.take(n)`.
 	pub(crate) fn iter() -> impl Iterator<Item = Node<T, I>> {
+		Self::bag_uppers()
+			.into_iter()
+			.rev()
+			.filter_map(Bag::get)
+			.flat_map(|bag| bag.iter())
+	}
+
+	/// Same as `iter`, but iterates from the lowest score to the highest.
+	///
+	/// Both the bags and the nodes within each bag are visited in reverse order, so nothing needs
+	/// to be materialized in advance.
+	pub(crate) fn iter_ascending() -> impl Iterator<Item = Node<T, I>> {
+		Self::bag_uppers()
+			.into_iter()
+			.filter_map(Bag::get)
+			.flat_map(|bag| bag.iter_rev())
+	}
+
+	/// Same as `iter`, but only yields the nodes whose score is within `lo..=hi`.
+	///
+	/// Only the bags that can contain such nodes are visited.
+	pub(crate) fn iter_in_score_range(
+		lo: T::Score,
+		hi: T::Score,
+	) -> impl Iterator<Item = Node<T, I>> {
+		let lo_bag = notional_bag_for::<T, I>(lo);
+		let hi_bag = notional_bag_for::<T, I>(hi);
+
+		Self::bag_uppers()
+			.into_iter()
+			.rev()
+			.skip_while(move |upper| *upper > hi_bag)
+			.take_while(move |upper| *upper >= lo_bag)
+			.filter_map(Bag::get)
+			.flat_map(|bag| bag.iter())
+			.filter(move |node| node.score >= lo && node.score <= hi)
+	}
+
+	/// The upper thresholds of all bags, from the lowest to the highest.
+	fn bag_uppers() -> Vec<T::Score> {
 		// We need a touch of special handling here: because we permit `T::BagThresholds` to
 		// omit the final bound, we need to ensure that we explicitly include that threshold in the
 		// list.
@@ -249,17 +321,24 @@ impl<T: Config<I>, I: 'static> List<T, I> {
 		// easier; they can just configure `type BagThresholds = ()`.
 		let thresholds = T::BagThresholds::get();
 		let iter = thresholds.iter().copied();
-		let iter: Box<dyn Iterator<Item = T::Score>> = if thresholds.last() ==
-			Some(&T::Score::max_value())
-		{
+		if thresholds.last() == Some(&T::Score::max_value()) {
 			// in the event that they included it, we can just pass the iterator through unchanged.
-			Box::new(iter.rev())
+			iter.collect()
 		} else {
 			// otherwise, insert it here.
-			Box::new(iter.chain(iter::once(T::Score::max_value())).rev())
-		};
+			iter.chain(iter::once(T::Score::max_value())).collect()
+		}
+	}
 
-		iter.filter_map(Bag::get).flat_map(|bag| bag.iter())
+	/// The number of ids in the bags above the bag with the given `upper` threshold, i.e. the rank
+	/// of the first id of that bag.
+	pub(crate) fn count_above(upper: T::Score) -> u32 {
+		Self::bag_uppers()
+			.into_iter()
+			.rev()
+			.take_while(|bag_upper| *bag_upper > upper)
+			.map(crate::BagCounts::<T, I>::get)
+			.fold(0, |count, bag_count| count.saturating_add(bag_count))
 	}
 
 	/// Same as `iter`, but we start from a specific node.
@@ -276,15 +355,73 @@ impl<T: Config<I>, I: 'static> List<T, I> {
 		let start_node_upper = start_node.bag_upper;
 		let start_bag = sp_std::iter::successors(start_node.next(), |prev| prev.next());
 
+		Ok(start_bag.chain(Self::iter_bags_below(start_node_upper)))
+	}
+
+	/// Iterate all the nodes in the bags below the bag with the given `upper` threshold.
+	fn iter_bags_below(upper: T::Score) -> impl Iterator<Item = Node<T, I>> {
 		let thresholds = T::BagThresholds::get();
-		let idx = thresholds.partition_point(|&threshold| start_node_upper > threshold);
-		let leftover_bags = thresholds
+		let idx = thresholds.partition_point(|&threshold| upper > threshold);
+		thresholds
 			.into_iter()
 			.take(idx)
 			.copied()
 			.rev()
 			.filter_map(Bag::get)
-			.flat_map(|bag| bag.iter());
+			.flat_map(|bag| bag.iter())
+	}
+
+	/// Create a cursor pointing right after `last`, to be used with [`Self::iter_resume`].
+	pub(crate) fn cursor(
+		last: &T::AccountId,
+	) -> Result<ListCursor<T::AccountId, T::Score>, ListError> {
+		let node = Node::<T, I>::get(last).ok_or(ListError::NodeNotFound)?;
+		Ok(ListCursor { last: last.clone(), next: node.next, bracket: node.bag_upper, part: 0 })
+	}
+
+	/// Same as `iter_from`, but continues from a `cursor` created earlier.
+	///
+	/// If `cursor.last` has since been removed or moved to another bag, iteration continues from
+	/// `cursor.next`. Only if both are gone, the cursor's bag is iterated again from its head,
+	/// since the nodes of a bag are not ordered in any way that would tell where to continue. This
+	/// cannot happen while the list is locked.
+	pub(crate) fn iter_resume(
+		cursor: &ListCursor<T::AccountId, T::Score>,
+	) -> impl Iterator<Item = Node<T, I>> {
+		let bracket = cursor.bracket;
+		let in_bag = |id: &T::AccountId| Node::<T, I>::get(id).filter(|n| n.bag_upper == bracket);
+		let start = match in_bag(&cursor.last) {
+			Some(last) => last.next(),
+			None => cursor
+				.next
+				.as_ref()
+				.and_then(in_bag)
+				.or_else(|| Bag::<T, I>::get(bracket).and_then(|bag| bag.head())),
+		};
+		let start_bag = sp_std::iter::successors(start, |prev| prev.next());
+
+		start_bag.chain(Self::iter_bags_below(bracket))
+	}
+
+	/// Same as `iter_from`, but iterates from the lowest score to the highest.
+	///
+	/// All items after this node, in ascending order, are returned, excluding `start` itself.
+	pub(crate) fn iter_from_ascending(
+		start: &T::AccountId,
+	) -> Result<impl Iterator<Item = Node<T, I>>, ListError> {
+		// We chain two iterators:
+		// 1. from the given `start` till the head of the bag
+		// 2. all the bags that come above `start`'s bag.
+
+		let start_node = Node::<T, I>::get(start).ok_or(ListError::NodeNotFound)?;
+		let start_node_upper = start_node.bag_upper;
+		let start_bag = sp_std::iter::successors(start_node.prev(), |next| next.prev());
+
+		let leftover_bags = Self::bag_uppers()
+			.into_iter()
+			.filter(move |upper| *upper > start_node_upper)
+			.filter_map(Bag::get)
+			.flat_map(|bag| bag.iter_rev());
 
 		Ok(start_bag.chain(leftover_bags))
 	}
@@ -336,6 +473,120 @@ impl<T: Config<I>, I: 'static> List<T, I> {
 		Ok(())
 	}
 
+	/// Insert many ids, each with their score, into the appropriate bags in the list.
+	///
+	/// This is more efficient than repeated calls to `Self::insert`, since each affected bag is
+	/// only read and written once.
+	///
+	/// With [`BatchMode::AllOrNothing`], all ids are checked for duplicates before anything is
+	/// written. Otherwise, duplicates are skipped.
+	///
+	/// Returns the final count of number of ids inserted.
+	pub(crate) fn insert_batch(
+		items: impl IntoIterator<Item = (T::AccountId, T::Score)>,
+		mode: BatchMode,
+	) -> Result<u32, ListError> {
+		let items = items.into_iter().collect::<Vec<_>>();
+		if mode == BatchMode::AllOrNothing {
+			let mut seen = BTreeSet::new();
+			for (id, _) in items.iter() {
+				ensure!(!Self::contains(id) && seen.insert(id), ListError::Duplicate);
+			}
+		}
+
+		let mut bags = BTreeMap::new();
+		let mut count = 0;
+		for (id, score) in items {
+			if Self::contains(&id) {
+				continue
+			}
+
+			let bag_score = notional_bag_for::<T, I>(score);
+			bags.entry(bag_score)
+				.or_insert_with(|| Bag::<T, I>::get_or_make(bag_score))
+				// unchecked insertion is okay; we just got the correct `notional_bag_for`.
+				.insert_unchecked(id, score);
+			count += 1;
+		}
+
+		for (_, bag) in bags {
+			bag.put();
+		}
+
+		crate::log!(
+			debug,
+			"inserted {} ids in a batch, new count is {}",
+			count,
+			crate::ListNodes::<T, I>::count(),
+		);
+
+		Ok(count)
+	}
+
+	/// Update the score, and thus the position, of many nodes in the list.
+	///
+	/// This is more efficient than repeated calls to `Self::update_position_for`, since each
+	/// affected bag is only read and written once.
+	///
+	/// With [`BatchMode::AllOrNothing`], all ids are checked for existence before anything is
+	/// written. Otherwise, non-existent ids are skipped.
+	///
+	/// Returns the id, the new score and the movement, as in `Self::update_position_for`, of each
+	/// updated node.
+	pub(crate) fn update_batch(
+		items: impl IntoIterator<Item = (T::AccountId, T::Score)>,
+		mode: BatchMode,
+	) -> Result<Vec<(T::AccountId, T::Score, Option<(T::Score, T::Score)>)>, ListError> {
+		let items = items.into_iter().collect::<Vec<_>>();
+		if mode == BatchMode::AllOrNothing {
+			for (id, _) in items.iter() {
+				ensure!(Self::contains(id), ListError::NodeNotFound);
+			}
+		}
+
+		let mut bags = BTreeMap::new();
+		let mut updated = Vec::with_capacity(items.len());
+		for (id, new_score) in items {
+			// the node must be read again for every item, since its neighbors might have changed.
+			let mut node = match Node::<T, I>::get(&id) {
+				Some(node) => node,
+				None => continue,
+			};
+
+			node.score = new_score;
+			let movement = if node.is_misplaced(new_score) {
+				let old_bag_upper = node.bag_upper;
+				if !node.is_terminal() {
+					// this node is not a head or a tail, so we can just cut it out of the list.
+					node.excise();
+				} else {
+					// this is a head or tail, so the bag must be updated.
+					bags.entry(old_bag_upper)
+						.or_insert_with(|| Bag::<T, I>::get_or_make(old_bag_upper))
+						.remove_node_unchecked(&node);
+				}
+				Bag::<T, I>::dec_count(old_bag_upper);
+
+				let new_bag_upper = notional_bag_for::<T, I>(new_score);
+				bags.entry(new_bag_upper)
+					.or_insert_with(|| Bag::<T, I>::get_or_make(new_bag_upper))
+					.insert_node_unchecked(node);
+				Some((old_bag_upper, new_bag_upper))
+			} else {
+				// just write the new score.
+				node.put();
+				None
+			};
+			updated.push((id, new_score, movement));
+		}
+
+		for (_, bag) in bags {
+			bag.put();
+		}
+
+		Ok(updated)
+	}
+
 	/// Remove an id from the list, returning an error if `id` does not exists.
 	pub(crate) fn remove(id: &T::AccountId) -> Result<(), ListError> {
 		if !Self::contains(id) {
@@ -372,6 +623,7 @@ impl<T: Config<I>, I: 'static> List<T, I> {
 				// node.bag_upper must be correct, therefore this bag will contain this node.
 				bag.remove_node_unchecked(&node);
 			}
+			Bag::<T, I>::dec_count(node.bag_upper);
 
 			// now get rid of the node itself
 			node.remove_from_storage_unchecked()
@@ -416,6 +668,7 @@ impl<T: Config<I>, I: 'static> List<T, I> {
 					"Node did not have a bag; BagsList is in an inconsistent state"
 				);
 			}
+			Bag::<T, I>::dec_count(old_bag_upper);
 
 			// put the node into the appropriate new bag.
 			let new_bag_upper = notional_bag_for::<T, I>(new_score);
@@ -501,6 +754,7 @@ impl<T: Config<I>, I: 'static> List<T, I> {
 		};
 
 		// write the updated nodes to storage.
+		Bag::<T, I>::inc_count(at.bag_upper);
 		at.put();
 		node.put();
 	}
@@ -545,6 +799,20 @@ impl<T: Config<I>, I: 'static> List<T, I> {
 
 		let _ = active_bags.clone().try_for_each(|b| b.do_try_state())?;
 
+		// the counts are only complete once they are migrated.
+		if !crate::BagCountsMigration::<T, I>::exists() {
+			ensure!(
+				active_bags
+					.clone()
+					.all(|b| crate::BagCounts::<T, I>::get(b.bag_upper) == b.iter().count() as u32),
+				"bag count does not match the nodes of the bag"
+			);
+			ensure!(
+				crate::BagCounts::<T, I>::iter().count() == active_bags.clone().count(),
+				"bag count stored for an empty bag"
+			);
+		}
+
 		let nodes_in_bags_count =
 			active_bags.clone().fold(0u32, |acc, cur| acc + cur.iter().count() as u32);
 		ensure!(nodes_count == nodes_in_bags_count, "stored_count != nodes_in_bags_count");
@@ -660,6 +928,11 @@ impl<T: Config<I>, I: 'static> Bag<T, I> {
 		sp_std::iter::successors(self.head(), |prev| prev.next())
 	}
 
+	/// Iterate over the nodes in this bag, from the tail to the head.
+	pub(crate) fn iter_rev(&self) -> impl Iterator<Item = Node<T, I>> {
+		sp_std::iter::successors(self.tail(), |next| next.prev())
+	}
+
 	/// Insert a new id into this bag.
 	///
 	/// This is private on purpose because it's naive: it doesn't check whether this is the
@@ -722,6 +995,21 @@ impl<T: Config<I>, I: 'static> Bag<T, I> {
 			self.head = Some(id);
 			debug_assert!(self.iter().count() == 1);
 		}
+		Self::inc_count(self.bag_upper);
+	}
+
+	/// Note that a node was added to the bag with the given `bag_upper`, see
+	/// [`crate::BagCounts`].
+	fn inc_count(bag_upper: T::Score) {
+		crate::BagCounts::<T, I>::mutate(bag_upper, |count| count.saturating_inc());
+	}
+
+	/// Note that a node was removed from the bag with the given `bag_upper`, see
+	/// [`crate::BagCounts`].
+	fn dec_count(bag_upper: T::Score) {
+		crate::BagCounts::<T, I>::mutate_exists(bag_upper, |count| {
+			*count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0);
+		});
 	}
 
 	/// Remove a node from this bag.
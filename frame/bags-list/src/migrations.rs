@@ -20,7 +20,7 @@
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
 use frame_election_provider_support::ScoreProvider;
-use frame_support::traits::OnRuntimeUpgrade;
+use frame_support::traits::{Get, OnRuntimeUpgrade};
 
 #[cfg(feature = "try-runtime")]
 use frame_support::ensure;
@@ -139,3 +139,52 @@ impl<T: crate::Config<I>, I: 'static> OnRuntimeUpgrade for AddScore<T, I> {
 		Ok(())
 	}
 }
+
+/// A struct that migrates all bags lists to track the number of nodes in each bag, see
+/// [`crate::BagCounts`].
+///
+/// This only starts the migration: the nodes are then counted on idle, over as many blocks as
+/// needed, see [`crate::Pallet::do_idle_rebag`]. In the meantime, the list is frozen as if it was
+/// locked, and ids cannot be ranked.
+///
+/// Does nothing if the bag counts are already stored, or being counted.
+pub struct AddBagCounts<T: crate::Config<I>, I: 'static = ()>(PhantomData<(T, I)>);
+impl<T: crate::Config<I>, I: 'static> OnRuntimeUpgrade for AddBagCounts<T, I> {
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+		Ok(crate::ListNodes::<T, I>::count().encode())
+	}
+
+	fn on_runtime_upgrade() -> frame_support::weights::Weight {
+		if crate::BagCountsMigration::<T, I>::exists() ||
+			crate::BagCounts::<T, I>::iter_keys().next().is_some()
+		{
+			crate::log!(info, "bag counts already stored, skipping migration");
+			return T::DbWeight::get().reads(2)
+		}
+		if crate::ListNodes::<T, I>::count() == 0 {
+			crate::log!(info, "no nodes to count, skipping migration");
+			return T::DbWeight::get().reads(3)
+		}
+
+		crate::BagCountsMigration::<T, I>::put(None::<T::AccountId>);
+		crate::log!(info, "started counting the nodes of each bag on idle");
+		T::DbWeight::get().reads_writes(3, 1)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(node_count_before: Vec<u8>) -> Result<(), TryRuntimeError> {
+		let node_count_before: u32 = Decode::decode(&mut node_count_before.as_slice())
+			.expect("the state parameter should be something that was generated by pre_upgrade");
+		ensure!(
+			crate::ListNodes::<T, I>::count() == node_count_before,
+			"the migration must not change the nodes"
+		);
+		ensure!(
+			crate::BagCountsMigration::<T, I>::exists() ||
+				crate::BagCounts::<T, I>::iter_values().sum::<u32>() == node_count_before,
+			"the bag counts must either be stored, or being counted"
+		);
+		crate::Pallet::<T, I>::do_try_state()
+	}
+}
@@ -30,6 +30,7 @@ parameter_types! {
 	// Set the vote weight for any id who's weight has _not_ been set with `set_score_of`.
 	pub static NextVoteWeight: VoteWeight = 0;
 	pub static NextVoteWeightMap: HashMap<AccountId, VoteWeight> = Default::default();
+	pub static DbWeight: frame_support::weights::RuntimeDbWeight = Default::default();
 }
 
 pub struct StakingMock;
@@ -61,7 +62,7 @@ impl frame_system::Config for Runtime {
 	type Header = sp_runtime::testing::Header;
 	type RuntimeEvent = RuntimeEvent;
 	type BlockHashCount = ();
-	type DbWeight = ();
+	type DbWeight = DbWeight;
 	type BlockLength = ();
 	type BlockWeights = ();
 	type Version = ();
@@ -149,7 +150,7 @@ impl ExtBuilder {
 	pub fn build_and_execute(self, test: impl FnOnce() -> ()) {
 		self.build().execute_with(|| {
 			test();
-			List::<Runtime>::do_try_state().expect("do_try_state post condition failed")
+			BagsList::do_try_state().expect("do_try_state post condition failed")
 		})
 	}
 
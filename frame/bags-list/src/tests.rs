@@ -15,10 +15,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use frame_support::{assert_noop, assert_ok, assert_storage_noop, traits::IntegrityTest};
+use frame_support::{
+	assert_noop, assert_ok, assert_storage_noop,
+	traits::{IntegrityTest, OnIdle},
+};
 
 use super::*;
-use frame_election_provider_support::{SortedListProvider, VoteWeight};
+use codec::{Decode, Encode};
+use frame_election_provider_support::{
+	BatchMode, DecayedScore, ListCursor, NodeInfo, ScoreDecay, ScoreProvider, SortedListProvider,
+	VoteWeight,
+};
 use list::Bag;
 use mock::{test_utils::*, *};
 
@@ -449,6 +456,92 @@ mod pallet {
 			);
 		});
 	}
+
+	#[test]
+	fn on_idle_rebag_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			let per_node =
+				<() as WeightInfo>::rebag_non_terminal().max(<() as WeightInfo>::rebag_terminal());
+
+			// given 2 and 4 are misplaced.
+			StakingMock::set_score_of(&1, 10);
+			StakingMock::set_score_of(&2, 10);
+			StakingMock::set_score_of(&3, 1_000);
+			StakingMock::set_score_of(&4, 2_000);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			// not even enough weight for a single node.
+			assert_storage_noop!(assert_eq!(
+				BagsList::on_idle(1, per_node - Weight::from_parts(1, 0)),
+				Weight::zero()
+			));
+
+			// when visiting 2 and 3.
+			assert_eq!(BagsList::on_idle(1, per_node * 2), per_node * 2);
+
+			// then 2 is fixed.
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1, 2]), (1_000, vec![3, 4])]);
+			assert_eq!(IdleRebagCursor::<Runtime>::get(), Some(3));
+
+			// when visiting 4 and 1.
+			assert_eq!(BagsList::on_idle(2, per_node * 2), per_node * 2);
+
+			// then 4 is fixed as well.
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 2]), (1_000, vec![3]), (2_000, vec![4])]
+			);
+			assert_eq!(IdleRebagCursor::<Runtime>::get(), Some(1));
+
+			// when visiting the rest of the list.
+			assert_eq!(BagsList::on_idle(3, per_node * 10), per_node);
+
+			// then the next round starts from the head again.
+			assert_eq!(IdleRebagCursor::<Runtime>::get(), None);
+			assert_eq!(BagsList::on_idle(4, per_node * 10), per_node * 4);
+		});
+	}
+
+	#[test]
+	fn on_idle_rebag_with_removed_cursor() {
+		ExtBuilder::default().build_and_execute(|| {
+			let per_node =
+				<() as WeightInfo>::rebag_non_terminal().max(<() as WeightInfo>::rebag_terminal());
+			for id in 1..=4 {
+				StakingMock::set_score_of(&id, BagsList::get_score(&id).unwrap());
+			}
+
+			assert_eq!(BagsList::on_idle(1, per_node), per_node);
+			assert_eq!(IdleRebagCursor::<Runtime>::get(), Some(2));
+
+			// when the cursor is removed from the list.
+			assert_ok!(BagsList::on_remove(&2));
+
+			// then the next call starts from the head again.
+			assert_eq!(BagsList::on_idle(2, per_node * 10), per_node * 3);
+			assert_eq!(IdleRebagCursor::<Runtime>::get(), None);
+		});
+	}
+
+	#[test]
+	fn on_idle_rebag_does_nothing_while_locked() {
+		ExtBuilder::default().build_and_execute(|| {
+			let per_node =
+				<() as WeightInfo>::rebag_non_terminal().max(<() as WeightInfo>::rebag_terminal());
+
+			// given 2 is misplaced, and the list is locked.
+			StakingMock::set_score_of(&2, 10);
+			BagsList::lock();
+
+			// then nothing is rebagged.
+			assert_storage_noop!(BagsList::on_idle(1, per_node * 10));
+
+			// once unlocked, 2 is fixed.
+			BagsList::unlock();
+			BagsList::on_idle(2, per_node * 10);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1, 2]), (1_000, vec![3, 4])]);
+		});
+	}
 }
 
 mod sorted_list_provider {
@@ -485,6 +578,106 @@ mod sorted_list_provider {
 		});
 	}
 
+	#[test]
+	fn iter_resume_works() {
+		ExtBuilder::default().add_ids(vec![(5, 5), (6, 15)]).build_and_execute(|| {
+			// given
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 5]), (20, vec![6]), (1000, vec![2, 3, 4])]
+			);
+
+			// the cursor can be stored.
+			let cursor = BagsList::cursor(&2).unwrap();
+			assert_eq!(cursor, ListCursor { last: 2, next: Some(3), bracket: 1000, part: 0 });
+			assert_eq!(ListCursor::decode(&mut &cursor.encode()[..]).unwrap(), cursor);
+			assert_eq!(BagsList::iter_resume(&cursor).collect::<Vec<_>>(), vec![3, 4, 6, 1, 5]);
+			assert!(BagsList::cursor(&7).is_err());
+
+			// when unrelated parts of the list change.
+			assert_ok!(BagsList::on_insert(7, 15));
+			assert_ok!(BagsList::on_remove(&1));
+
+			// then
+			assert_eq!(BagsList::iter_resume(&cursor).collect::<Vec<_>>(), vec![3, 4, 6, 7, 5]);
+
+			// when the cursor's id is removed, it continues from the next one.
+			assert_ok!(BagsList::on_remove(&2));
+			assert_eq!(BagsList::iter_resume(&cursor).collect::<Vec<_>>(), vec![3, 4, 6, 7, 5]);
+
+			// when the next one moves to another bag as well, the bag is iterated again rather than
+			// skipping any of its nodes.
+			assert_ok!(BagsList::on_insert(8, 1_000));
+			assert_ok!(BagsList::on_update(&3, 2_000));
+			assert_eq!(BagsList::iter_resume(&cursor).collect::<Vec<_>>(), vec![4, 8, 6, 7, 5]);
+
+			// a cursor at the end of a bag continues with the next bag.
+			let cursor = BagsList::cursor(&7).unwrap();
+			assert_eq!(cursor.next, None);
+			assert_eq!(BagsList::iter_resume(&cursor).collect::<Vec<_>>(), vec![5]);
+		});
+	}
+
+	#[test]
+	fn iter_ascending_works() {
+		ExtBuilder::default().add_ids(vec![(5, 5), (6, 15)]).build_and_execute(|| {
+			// given
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 5]), (20, vec![6]), (1000, vec![2, 3, 4])]
+			);
+
+			// then it is exactly the reverse of `iter`.
+			let mut descending = BagsList::iter().collect::<Vec<_>>();
+			descending.reverse();
+			assert_eq!(BagsList::iter_ascending().collect::<Vec<_>>(), vec![5, 1, 6, 4, 3, 2]);
+			assert_eq!(BagsList::iter_ascending().collect::<Vec<_>>(), descending);
+		});
+	}
+
+	#[test]
+	fn iter_from_ascending_works() {
+		ExtBuilder::default().add_ids(vec![(5, 5), (6, 15)]).build_and_execute(|| {
+			let from = |id| BagsList::iter_from_ascending(&id).unwrap().collect::<Vec<_>>();
+
+			assert_eq!(from(5), vec![1, 6, 4, 3, 2]);
+			assert_eq!(from(1), vec![6, 4, 3, 2]);
+			assert_eq!(from(6), vec![4, 3, 2]);
+			assert_eq!(from(3), vec![2]);
+			assert!(from(2).is_empty());
+			assert!(BagsList::iter_from_ascending(&7).is_err());
+
+			assert_storage_noop!(assert!(BagsList::iter_from_ascending(&8).is_err()));
+		});
+	}
+
+	#[test]
+	fn iter_in_score_range_works() {
+		ExtBuilder::default()
+			.add_ids(vec![(5, 5), (6, 15), (7, 1500)])
+			.build_and_execute(|| {
+				// given
+				assert_eq!(
+					List::<Runtime>::get_bags(),
+					vec![(10, vec![1, 5]), (20, vec![6]), (1000, vec![2, 3, 4]), (2000, vec![7])]
+				);
+
+				let range = |lo, hi| BagsList::iter_in_score_range(lo, hi).collect::<Vec<_>>();
+
+				// the entire range is the same as `iter`.
+				assert_eq!(range(0, VoteWeight::MAX), BagsList::iter().collect::<Vec<_>>());
+				// bounds are inclusive, and nodes in the boundary bags are filtered by score.
+				assert_eq!(range(6, 15), vec![6, 1]);
+				assert_eq!(range(1000, 1000), vec![2, 3, 4]);
+				assert_eq!(range(1000, VoteWeight::MAX), vec![7, 2, 3, 4]);
+				// nothing in range.
+				assert!(range(11, 14).is_empty());
+				assert!(range(20, 10).is_empty());
+
+				assert_storage_noop!(assert_eq!(range(0, 5), vec![5]));
+			});
+	}
+
 	#[test]
 	fn count_works() {
 		ExtBuilder::default().build_and_execute(|| {
@@ -550,6 +743,162 @@ mod sorted_list_provider {
 		});
 	}
 
+	#[test]
+	fn on_insert_many_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// when
+			assert_eq!(
+				BagsList::on_insert_many(
+					vec![(6, 1_000), (7, 1_001), (8, 5), (9, 1_001)],
+					BatchMode::AllOrNothing
+				),
+				Ok(4)
+			);
+
+			// then the bags
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 8]), (1_000, vec![2, 3, 4, 6]), (2_000, vec![7, 9])]
+			);
+			// and the list correctly include the new ids,
+			assert_eq!(BagsList::iter().collect::<Vec<_>>(), vec![7, 9, 2, 3, 4, 6, 1, 8]);
+			// and the count is incremented.
+			assert_eq!(BagsList::count(), 8);
+		})
+	}
+
+	#[test]
+	fn on_insert_many_duplicates() {
+		ExtBuilder::default().build_and_execute(|| {
+			// an existing id fails the entire batch.
+			assert_storage_noop!(assert_eq!(
+				BagsList::on_insert_many(vec![(6, 20), (3, 20)], BatchMode::AllOrNothing),
+				Err(ListError::Duplicate)
+			));
+			// so does an id that is duplicate within the batch.
+			assert_storage_noop!(assert_eq!(
+				BagsList::on_insert_many(vec![(6, 20), (6, 30)], BatchMode::AllOrNothing),
+				Err(ListError::Duplicate)
+			));
+
+			// while best effort skips them.
+			assert_eq!(
+				BagsList::on_insert_many(vec![(6, 20), (3, 20), (6, 30)], BatchMode::BestEffort),
+				Ok(1)
+			);
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![6]), (1_000, vec![2, 3, 4])]
+			);
+			assert_eq!(BagsList::count(), 5);
+		})
+	}
+
+	#[test]
+	fn on_update_many_works() {
+		ExtBuilder::default().add_ids(vec![(42, 20)]).build_and_execute(|| {
+			// given
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1]), (20, vec![42]), (1_000, vec![2, 3, 4])]
+			);
+
+			System::set_block_number(1);
+
+			// when moving a head, a middle node and a tail, and updating in place.
+			assert_eq!(
+				BagsList::on_update_many(
+					vec![(2, 5), (3, 2_000), (4, 15), (42, 19), (1, 10)],
+					BatchMode::AllOrNothing
+				),
+				Ok(5)
+			);
+
+			// then
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 2]), (20, vec![42, 4]), (2_000, vec![3])]
+			);
+			assert_eq!(BagsList::get_score(&4), Ok(15));
+			assert_eq!(BagsList::get_score(&42), Ok(19));
+			assert_eq!(BagsList::count(), 5);
+
+			// and an event is deposited for each of them.
+			assert_eq!(
+				System::events()
+					.into_iter()
+					.filter(|r| matches!(r.event, RuntimeEvent::BagsList(Event::Rebagged { .. })))
+					.count(),
+				3
+			);
+			assert_eq!(System::events().len(), 8);
+		})
+	}
+
+	#[test]
+	fn on_update_many_non_existent() {
+		ExtBuilder::default().build_and_execute(|| {
+			// a non-existent id fails the entire batch.
+			assert_storage_noop!(assert_eq!(
+				BagsList::on_update_many(vec![(2, 5), (42, 5)], BatchMode::AllOrNothing),
+				Err(ListError::NodeNotFound)
+			));
+
+			// while best effort skips it.
+			assert_eq!(
+				BagsList::on_update_many(vec![(2, 5), (42, 5)], BatchMode::BestEffort),
+				Ok(1)
+			);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1, 2]), (1_000, vec![3, 4])]);
+		})
+	}
+
+	#[test]
+	fn node_info_works() {
+		ExtBuilder::default().add_ids(vec![(5, 5), (6, 15)]).build_and_execute(|| {
+			// given
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 5]), (20, vec![6]), (1000, vec![2, 3, 4])]
+			);
+
+			// head of the list.
+			assert_eq!(
+				BagsList::node_info(&2).unwrap(),
+				NodeInfo {
+					score: 1000,
+					rank_hint: 3,
+					bag_bounds: (60, 1000),
+					prev: None,
+					next: Some(3)
+				}
+			);
+			// neighbours in other bags.
+			assert_eq!(
+				BagsList::node_info(&6).unwrap(),
+				NodeInfo {
+					score: 15,
+					rank_hint: 8,
+					bag_bounds: (10, 20),
+					prev: Some(4),
+					next: Some(1)
+				}
+			);
+			// tail of the list, in the lowest bag.
+			assert_eq!(
+				BagsList::node_info(&5).unwrap(),
+				NodeInfo { score: 5, rank_hint: 9, bag_bounds: (0, 10), prev: Some(1), next: None }
+			);
+
+			// a node that needs rebagging can be detected.
+			StakingMock::set_score_of(&3, 10);
+			let info = BagsList::node_info(&3).unwrap();
+			assert!(StakingMock::score(&3) <= info.bag_bounds.0);
+
+			assert_eq!(BagsList::node_info(&42), Err(ListError::NodeNotFound));
+		});
+	}
+
 	#[test]
 	fn on_update_works() {
 		ExtBuilder::default().add_ids(vec![(42, 20)]).build_and_execute(|| {
@@ -651,6 +1000,280 @@ mod sorted_list_provider {
 		});
 	}
 
+	#[test]
+	fn lock_defers_insertions_updates_and_removals() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given
+			assert!(!BagsList::is_locked());
+
+			// when
+			BagsList::lock();
+
+			// then
+			assert!(BagsList::is_locked());
+			assert_ok!(BagsList::on_insert(5, 10));
+			assert_eq!(BagsList::on_insert(5, 10), Err(ListError::Duplicate));
+			assert_eq!(
+				BagsList::on_insert_many(vec![(6, 20), (2, 20)], BatchMode::AllOrNothing),
+				Err(ListError::Duplicate)
+			);
+			assert_eq!(
+				BagsList::on_insert_many(vec![(6, 20), (2, 20)], BatchMode::BestEffort),
+				Ok(1)
+			);
+			StakingMock::set_score_of(&1, 2_000);
+			assert_ok!(BagsList::on_update(&1, 2_000));
+			assert_ok!(BagsList::on_remove(&3));
+			assert_ok!(BagsList::on_remove(&6));
+			assert_eq!(BagsList::on_remove(&3), Err(ListError::NodeNotFound));
+
+			// the changes are visible through the queries..
+			assert!(BagsList::contains(&5));
+			assert!(!BagsList::contains(&3));
+			assert!(!BagsList::contains(&6));
+			assert_eq!(BagsList::count(), 4);
+			assert_eq!(BagsList::get_score(&1), Ok(2_000));
+			assert_eq!(BagsList::get_score(&5), Ok(10));
+			assert_eq!(BagsList::get_score(&3), Err(ListError::NodeNotFound));
+
+			// but the list itself has not changed.
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+
+			// when
+			BagsList::unlock();
+
+			// then the changes are only applied on idle.
+			assert!(!BagsList::is_locked());
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1]), (1_000, vec![2, 3, 4])]);
+			BagsList::on_idle(1, Weight::MAX);
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![5]), (1_000, vec![2, 4]), (2_000, vec![1])]
+			);
+			assert_eq!(BagsList::count(), 4);
+			assert_eq!(PendingInsertions::<Runtime>::count(), 0);
+			assert_eq!(PendingRemovals::<Runtime>::count(), 0);
+			assert_eq!(Locked::<Runtime>::get(), LockState::Unlocked);
+		});
+	}
+
+	#[test]
+	fn unlock_applies_pending_changes_within_the_idle_budget() {
+		ExtBuilder::default().build_and_execute(|| {
+			let per_node =
+				<() as WeightInfo>::rebag_non_terminal().max(<() as WeightInfo>::rebag_terminal());
+
+			// given a removal, an update and an insertion made while locked.
+			BagsList::lock();
+			StakingMock::set_score_of(&1, 2_000);
+			assert_ok!(BagsList::on_remove(&3));
+			assert_ok!(BagsList::on_update(&1, 2_000));
+			assert_ok!(BagsList::on_insert(5, 10));
+			BagsList::unlock();
+			assert_eq!(Locked::<Runtime>::get(), LockState::Draining);
+
+			// when there is only room for two changes.
+			assert_eq!(BagsList::on_idle(1, per_node * 2), per_node * 2);
+
+			// then one change is left, and new changes are still queued behind it.
+			assert_eq!(List::<Runtime>::get_bags(), vec![(1_000, vec![2, 4]), (2_000, vec![1])]);
+			assert_eq!(Locked::<Runtime>::get(), LockState::Draining);
+			assert_ok!(BagsList::on_remove(&4));
+			assert_eq!(List::<Runtime>::get_bags(), vec![(1_000, vec![2, 4]), (2_000, vec![1])]);
+
+			// when
+			BagsList::on_idle(2, per_node * 10);
+
+			// then
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![5]), (1_000, vec![2]), (2_000, vec![1])]
+			);
+			assert_eq!(Locked::<Runtime>::get(), LockState::Unlocked);
+		});
+	}
+
+	#[test]
+	fn lock_reinsertion_is_an_update() {
+		ExtBuilder::default().build_and_execute(|| {
+			BagsList::lock();
+
+			// when 2 is removed and inserted again with another score.
+			StakingMock::set_score_of(&2, 10);
+			assert_ok!(BagsList::on_remove(&2));
+			assert_ok!(BagsList::on_insert(2, 10));
+			assert_eq!(BagsList::count(), 4);
+
+			// then, once unlocked, it is moved rather than inserted twice.
+			BagsList::unlock();
+			BagsList::on_idle(1, Weight::MAX);
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1, 2]), (1_000, vec![3, 4])]);
+		});
+	}
+
+	#[test]
+	fn lock_prevents_moving_nodes() {
+		ExtBuilder::default().build_and_execute(|| {
+			// given 2 is misplaced, and 3 is lighter than 4.
+			StakingMock::set_score_of(&2, 10);
+			StakingMock::set_score_of(&3, 999);
+			BagsList::lock();
+
+			// then neither can be fixed while the list is locked.
+			assert_noop!(
+				BagsList::rebag(RuntimeOrigin::signed(0), 2),
+				crate::pallet::Error::<Runtime>::List(ListError::Locked)
+			);
+			assert_noop!(
+				BagsList::put_in_front_of(RuntimeOrigin::signed(4), 3),
+				crate::pallet::Error::<Runtime>::List(ListError::Locked)
+			);
+
+			// and neither once unlocked, until the queued changes are applied.
+			BagsList::unlock();
+			assert_noop!(
+				BagsList::rebag(RuntimeOrigin::signed(0), 2),
+				crate::pallet::Error::<Runtime>::List(ListError::Locked)
+			);
+			BagsList::on_idle(1, Weight::MAX);
+			assert_ok!(BagsList::rebag(RuntimeOrigin::signed(0), 2));
+			assert_ok!(BagsList::put_in_front_of(RuntimeOrigin::signed(4), 3));
+			assert_eq!(List::<Runtime>::get_bags(), vec![(10, vec![1, 2]), (1_000, vec![4, 3])]);
+		});
+	}
+
+	#[test]
+	fn scores_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_eq!(
+				<BagsList as ScoreProvider<_>>::scores(vec![4, 1, 42].into_iter()),
+				vec![(4, 1_000), (1, 10), (42, 0)]
+			);
+			assert!(<BagsList as ScoreProvider<_>>::scores(sp_std::iter::empty()).is_empty());
+		});
+	}
+
+	#[test]
+	fn decayed_score_works() {
+		/// Halves the score of 3.
+		struct HalveThree;
+		impl ScoreDecay<AccountId, VoteWeight> for HalveThree {
+			fn decay(who: &AccountId, score: VoteWeight) -> VoteWeight {
+				if *who == 3 {
+					score / 2
+				} else {
+					score
+				}
+			}
+		}
+		type Decayed = DecayedScore<BagsList, HalveThree>;
+
+		ExtBuilder::default().build_and_execute(|| {
+			// reads are decayed, but the order of the list is not.
+			assert_eq!(Decayed::get_score(&3), Ok(500));
+			assert_eq!(Decayed::node_info(&3).unwrap().score, 500);
+			assert_eq!(Decayed::iter().collect::<Vec<_>>(), vec![2, 3, 4, 1]);
+			assert_eq!(Decayed::iter_in_score_range(600, 1_000).collect::<Vec<_>>(), vec![2, 4]);
+
+			// writes are applied to the stored score.
+			assert_ok!(Decayed::on_increase(&3, 100));
+			assert_eq!(BagsList::get_score(&3), Ok(1_100));
+			assert_eq!(Decayed::get_score(&3), Ok(550));
+		});
+	}
+
+	#[test]
+	fn rank_and_in_top_work() {
+		ExtBuilder::default().add_ids(vec![(5, 5), (6, 15)]).build_and_execute(|| {
+			// given
+			assert_eq!(
+				List::<Runtime>::get_bags(),
+				vec![(10, vec![1, 5]), (20, vec![6]), (1000, vec![2, 3, 4])]
+			);
+
+			// ids are ranked by the number of ids in the bags above theirs.
+			assert_eq!(BagsList::rank(&2), Ok(0));
+			assert_eq!(BagsList::rank(&4), Ok(0));
+			assert_eq!(BagsList::rank(&6), Ok(3));
+			assert_eq!(BagsList::rank(&1), Ok(4));
+			assert_eq!(BagsList::rank(&5), Ok(4));
+			assert_eq!(BagsList::rank(&42), Err(ListError::NodeNotFound));
+
+			assert!(BagsList::in_top(&6, 4));
+			assert!(!BagsList::in_top(&6, 3));
+			assert!(!BagsList::in_top(&2, 0));
+			assert!(!BagsList::in_top(&42, 10));
+
+			// the counts follow the nodes moving between bags.
+			assert_ok!(BagsList::on_update(&1, 2_000));
+			assert_eq!(BagsList::rank(&6), Ok(4));
+			assert_eq!(BagsList::rank(&5), Ok(5));
+
+			// pending changes are ranked by the bag they are going to be put in.
+			BagsList::lock();
+			assert_ok!(BagsList::on_insert(7, 15));
+			assert_ok!(BagsList::on_remove(&6));
+			assert_eq!(BagsList::rank(&7), Ok(4));
+			assert_eq!(BagsList::rank(&6), Err(ListError::NodeNotFound));
+			BagsList::unlock();
+			assert_eq!(BagsList::rank(&5), Ok(5));
+		});
+	}
+
+	#[test]
+	fn add_bag_counts_migration_works() {
+		use frame_support::{traits::OnRuntimeUpgrade, weights::RuntimeDbWeight};
+
+		ExtBuilder::default().add_ids(vec![(5, 5), (6, 15)]).build_and_execute(|| {
+			// given the counts are not stored yet.
+			#[allow(deprecated)]
+			crate::BagCounts::<Runtime>::remove_all(None);
+			assert!(BagsList::do_try_state().is_err());
+			DbWeight::set(RuntimeDbWeight { read: 1, write: 1 });
+
+			// when
+			crate::migrations::AddBagCounts::<Runtime>::on_runtime_upgrade();
+
+			// then the list is frozen until all nodes are counted.
+			assert!(BagCountsMigration::<Runtime>::exists());
+			assert_ok!(BagsList::do_try_state());
+			assert_eq!(BagsList::rank(&5), Err(ListError::Locked));
+			assert_ok!(BagsList::on_remove(&6));
+			assert_eq!(BagsList::count(), 5);
+			assert_noop!(
+				BagsList::rebag(RuntimeOrigin::signed(0), 2),
+				crate::pallet::Error::<Runtime>::List(ListError::Locked)
+			);
+
+			// when there is only room to count 4 nodes, after reading the progress.
+			assert_eq!(BagsList::on_idle(1, Weight::from_parts(9, 0)), Weight::from_parts(9, 0));
+
+			// then 2 nodes are left.
+			assert_eq!(BagCounts::<Runtime>::iter_values().sum::<u32>(), 4);
+			assert!(BagCountsMigration::<Runtime>::exists());
+
+			// when
+			BagsList::on_idle(2, Weight::MAX);
+
+			// then all nodes are counted, and the queued changes are applied next.
+			assert!(!BagCountsMigration::<Runtime>::exists());
+			let mut counts = crate::BagCounts::<Runtime>::iter().collect::<Vec<_>>();
+			counts.sort();
+			assert_eq!(counts, vec![(10, 2), (20, 1), (1_000, 3)]);
+			assert_ok!(BagsList::do_try_state());
+
+			BagsList::on_idle(3, Weight::MAX);
+			let mut counts = crate::BagCounts::<Runtime>::iter().collect::<Vec<_>>();
+			counts.sort();
+			assert_eq!(counts, vec![(10, 2), (1_000, 3)]);
+			assert_eq!(BagsList::rank(&5), Ok(3));
+			assert_eq!(Locked::<Runtime>::get(), LockState::Unlocked);
+
+			// and running it again does nothing.
+			assert_storage_noop!(crate::migrations::AddBagCounts::<Runtime>::on_runtime_upgrade());
+		});
+	}
+
 	#[test]
 	fn contains_works() {
 		ExtBuilder::default().build_and_execute(|| {
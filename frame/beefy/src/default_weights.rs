@@ -52,4 +52,13 @@ impl crate::WeightInfo for () {
 			// fetching set id -> session index mappings
 			.saturating_add(DbWeight::get().reads(2))
 	}
+
+	fn set_secondary_keys(key_count: u32) -> Weight {
+		// storing the keys
+		Weight::from_parts(10u64 * WEIGHT_REF_TIME_PER_MICROS, 0)
+			.saturating_add(
+				Weight::from_parts(WEIGHT_REF_TIME_PER_MICROS, 0).saturating_mul(key_count as u64),
+			)
+			.saturating_add(DbWeight::get().writes(1))
+	}
 }
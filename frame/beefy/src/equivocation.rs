@@ -209,7 +209,10 @@ where
 			.ok_or(Error::<T>::InvalidKeyOwnershipProof)?;
 
 		// Validate equivocation proof (check votes are different and signatures are valid).
-		if !sp_consensus_beefy::check_equivocation_proof(&equivocation_proof) {
+		if !sp_consensus_beefy::check_equivocation_proof_with_hasher(
+			&equivocation_proof,
+			T::SignatureHasher::get(),
+		) {
 			return Err(Error::<T>::InvalidEquivocationProof.into())
 		}
 
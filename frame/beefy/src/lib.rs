@@ -20,7 +20,7 @@
 use codec::{Encode, MaxEncodedLen};
 
 use frame_support::{
-	dispatch::{DispatchResultWithPostInfo, Pays},
+	dispatch::{DispatchResult, DispatchResultWithPostInfo, Pays},
 	log,
 	pallet_prelude::*,
 	traits::{Get, OneSessionHandler},
@@ -28,7 +28,7 @@ use frame_support::{
 	BoundedSlice, BoundedVec, Parameter,
 };
 use frame_system::{
-	ensure_none, ensure_signed,
+	ensure_none, ensure_root, ensure_signed,
 	pallet_prelude::{BlockNumberFor, OriginFor},
 };
 use sp_runtime::{
@@ -41,8 +41,8 @@ use sp_staking::{offence::OffenceReportSystem, SessionIndex};
 use sp_std::prelude::*;
 
 use sp_consensus_beefy::{
-	AuthorityIndex, BeefyAuthorityId, ConsensusLog, EquivocationProof, OnNewValidatorSet,
-	ValidatorSet, BEEFY_ENGINE_ID, GENESIS_AUTHORITY_SET_ID,
+	AuthorityIndex, BeefyAuthorityIdWithHasher, ConsensusLog, EquivocationProof, OnNewValidatorSet,
+	SignatureHasher, ValidatorSet, BEEFY_ENGINE_ID, GENESIS_AUTHORITY_SET_ID,
 };
 
 mod default_weights;
@@ -57,6 +57,10 @@ pub use pallet::*;
 
 use crate::equivocation::EquivocationEvidenceFor;
 
+/// An authority and its SCALE-encoded key of the crypto scheme BEEFY is migrating to.
+pub type SecondaryKeyOf<T> =
+	(<T as Config>::BeefyId, BoundedVec<u8, <T as Config>::MaxSecondaryKeyLength>);
+
 const LOG_TARGET: &str = "runtime::beefy";
 
 #[frame_support::pallet]
@@ -69,8 +73,7 @@ pub mod pallet {
 		/// Authority identifier type
 		type BeefyId: Member
 			+ Parameter
-			// todo: use custom signature hashing type instead of hardcoded `Keccak256`
-			+ BeefyAuthorityId<sp_runtime::traits::Keccak256>
+			+ BeefyAuthorityIdWithHasher
 			+ MaybeSerializeDeserialize
 			+ MaxEncodedLen;
 
@@ -78,6 +81,19 @@ pub mod pallet {
 		#[pallet::constant]
 		type MaxAuthorities: Get<u32>;
 
+		/// The hash function BEEFY messages are hashed with before being signed.
+		///
+		/// Unless it is [`SignatureHasher::Keccak256`], it is announced along with every new
+		/// validator set, so that light clients and syncing nodes can verify BEEFY justifications
+		/// without state.
+		#[pallet::constant]
+		type SignatureHasher: Get<SignatureHasher>;
+
+		/// The maximum length of the SCALE-encoded keys of the crypto scheme BEEFY is migrating
+		/// to, see [`Pallet::secondary_validator_set`].
+		#[pallet::constant]
+		type MaxSecondaryKeyLength: Get<u32>;
+
 		/// The maximum number of entries to keep in the set id to session index mapping.
 		///
 		/// Since the `SetIdSession` map is only used for validating equivocations this
@@ -155,6 +171,14 @@ pub mod pallet {
 	pub(super) type GenesisBlock<T: Config> =
 		StorageValue<_, Option<BlockNumberFor<T>>, ValueQuery>;
 
+	/// The keys of the crypto scheme BEEFY is migrating to, SCALE-encoded, by authority.
+	///
+	/// Empty unless a migration is in progress.
+	#[pallet::storage]
+	#[pallet::getter(fn secondary_keys)]
+	pub(super) type SecondaryKeys<T: Config> =
+		StorageValue<_, BoundedVec<SecondaryKeyOf<T>, T::MaxAuthorities>, ValueQuery>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		/// Initial set of BEEFY authorities.
@@ -255,6 +279,24 @@ pub mod pallet {
 			)?;
 			Ok(Pays::No.into())
 		}
+
+		/// Set the keys of the crypto scheme BEEFY is migrating to, replacing the previous ones.
+		///
+		/// While every current authority has one, voters can sign commitments with both their
+		/// current and their secondary key, see [`Pallet::secondary_validator_set`]. Setting no
+		/// keys ends the migration.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::set_secondary_keys(keys.len() as u32))]
+		pub fn set_secondary_keys(
+			origin: OriginFor<T>,
+			keys: BoundedVec<SecondaryKeyOf<T>, T::MaxAuthorities>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			<SecondaryKeys<T>>::put(keys);
+			Ok(())
+		}
 	}
 
 	#[pallet::validate_unsigned]
@@ -279,6 +321,27 @@ impl<T: Config> Pallet<T> {
 		ValidatorSet::<T::BeefyId>::new(validators, id)
 	}
 
+	/// Return the hash function BEEFY messages are hashed with before being signed.
+	pub fn signature_hasher() -> SignatureHasher {
+		T::SignatureHasher::get()
+	}
+
+	/// Return the current active BEEFY validator set with the keys of the crypto scheme BEEFY is
+	/// migrating to, in the same order as [`Self::validator_set`].
+	///
+	/// Returns `None` unless every current authority has a secondary key.
+	pub fn secondary_validator_set() -> Option<ValidatorSet<Vec<u8>>> {
+		let keys = Self::secondary_keys();
+		if keys.is_empty() {
+			return None
+		}
+		let validators = Self::authorities()
+			.iter()
+			.map(|id| keys.iter().find(|(key_id, _)| key_id == id).map(|(_, key)| key.to_vec()))
+			.collect::<Option<Vec<_>>>()?;
+		ValidatorSet::new(validators, Self::validator_set_id())
+	}
+
 	/// Submits an extrinsic to report an equivocation. This method will create
 	/// an unsigned extrinsic with a call to `report_equivocation_unsigned` and
 	/// will push the transaction to the pool. Only useful in an offchain context.
@@ -311,6 +374,15 @@ impl<T: Config> Pallet<T> {
 			);
 			<frame_system::Pallet<T>>::deposit_log(log);
 
+			let hasher = T::SignatureHasher::get();
+			if hasher != SignatureHasher::default() {
+				let log = DigestItem::Consensus(
+					BEEFY_ENGINE_ID,
+					ConsensusLog::<T::BeefyId>::SignatureHasher(hasher).encode(),
+				);
+				<frame_system::Pallet<T>>::deposit_log(log);
+			}
+
 			let next_id = new_id + 1;
 			if let Some(next_validator_set) = ValidatorSet::<T::BeefyId>::new(queued, next_id) {
 				<T::OnNewValidatorSet as OnNewValidatorSet<_>>::on_new_validator_set(
@@ -442,4 +514,5 @@ impl<T: Config> IsMember<T::BeefyId> for Pallet<T> {
 
 pub trait WeightInfo {
 	fn report_equivocation(validator_count: u32) -> Weight;
+	fn set_secondary_keys(key_count: u32) -> Weight;
 }
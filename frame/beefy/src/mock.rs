@@ -42,7 +42,7 @@ use crate as pallet_beefy;
 
 pub use sp_consensus_beefy::{
 	crypto::{AuthorityId as BeefyId, AuthoritySignature as BeefySignature},
-	ConsensusLog, EquivocationProof, BEEFY_ENGINE_ID,
+	ConsensusLog, EquivocationProof, SignatureHasher, BEEFY_ENGINE_ID,
 };
 
 impl_opaque_keys! {
@@ -112,11 +112,14 @@ parameter_types! {
 	pub const ReportLongevity: u64 =
 		BondingDuration::get() as u64 * SessionsPerEra::get() as u64 * Period::get();
 	pub const MaxSetIdSessionEntries: u32 = BondingDuration::get() * SessionsPerEra::get();
+	pub static BeefySignatureHasher: SignatureHasher = SignatureHasher::Keccak256;
 }
 
 impl pallet_beefy::Config for Test {
 	type BeefyId = BeefyId;
 	type MaxAuthorities = ConstU32<100>;
+	type SignatureHasher = BeefySignatureHasher;
+	type MaxSecondaryKeyLength = ConstU32<256>;
 	type MaxSetIdSessionEntries = MaxSetIdSessionEntries;
 	type OnNewValidatorSet = ();
 	type WeightInfo = ();
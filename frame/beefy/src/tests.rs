@@ -19,14 +19,14 @@ use std::vec;
 
 use codec::Encode;
 use sp_consensus_beefy::{
-	check_equivocation_proof, generate_equivocation_proof, known_payloads::MMR_ROOT_ID,
-	Keyring as BeefyKeyring, Payload, ValidatorSet,
+	check_equivocation_proof, generate_equivocation_proof, generate_equivocation_proof_with_hasher,
+	known_payloads::MMR_ROOT_ID, Keyring as BeefyKeyring, Payload, ValidatorSet,
 };
 
-use sp_runtime::DigestItem;
+use sp_runtime::{traits::BadOrigin, DigestItem};
 
 use frame_support::{
-	assert_err, assert_ok,
+	assert_err, assert_noop, assert_ok,
 	dispatch::{GetDispatchInfo, Pays},
 	traits::{Currency, KeyOwnerProofSystem, OnInitialize},
 };
@@ -96,6 +96,25 @@ fn session_change_updates_authorities() {
 	});
 }
 
+#[test]
+fn session_change_announces_signature_hasher() {
+	BeefySignatureHasher::set(SignatureHasher::Blake2_256);
+
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		assert_eq!(Beefy::signature_hasher(), SignatureHasher::Blake2_256);
+
+		init_block(1);
+
+		let want = vec![
+			beefy_log(ConsensusLog::AuthoritiesChange(
+				ValidatorSet::new(mock_authorities(vec![1, 2, 3, 4]), 1).unwrap(),
+			)),
+			beefy_log(ConsensusLog::SignatureHasher(SignatureHasher::Blake2_256)),
+		];
+		assert_eq!(want, System::digest().logs);
+	});
+}
+
 #[test]
 fn session_change_updates_next_authorities() {
 	let want = vec![mock_beefy_id(1), mock_beefy_id(2), mock_beefy_id(3), mock_beefy_id(4)];
@@ -162,6 +181,44 @@ fn validator_set_updates_work() {
 	});
 }
 
+#[test]
+fn secondary_validator_set_follows_the_authorities() {
+	let secondary_key = |id: u8| vec![id; 48];
+	let keys = |ids: Vec<u8>| {
+		let keys: Vec<_> = ids
+			.into_iter()
+			.map(|id| (mock_beefy_id(id), secondary_key(id).try_into().unwrap()))
+			.collect();
+		keys.try_into().unwrap()
+	};
+
+	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
+		// no migration in progress.
+		assert_eq!(Beefy::secondary_validator_set(), None);
+
+		assert_noop!(Beefy::set_secondary_keys(RuntimeOrigin::signed(1), keys(vec![1])), BadOrigin);
+
+		// the key of an authority is missing.
+		assert_ok!(Beefy::set_secondary_keys(RuntimeOrigin::root(), keys(vec![4, 1, 3])));
+		assert_eq!(Beefy::secondary_validator_set(), None);
+
+		// secondary keys are in the order of the authorities.
+		assert_ok!(Beefy::set_secondary_keys(RuntimeOrigin::root(), keys(vec![4, 3, 2, 1])));
+		let want = ValidatorSet::new((1..=4).map(secondary_key), 0).unwrap();
+		assert_eq!(Beefy::secondary_validator_set(), Some(want));
+
+		init_block(1);
+		init_block(2);
+
+		let want = ValidatorSet::new(vec![secondary_key(2), secondary_key(4)], 2).unwrap();
+		assert_eq!(Beefy::secondary_validator_set(), Some(want));
+
+		// setting no keys ends the migration.
+		assert_ok!(Beefy::set_secondary_keys(RuntimeOrigin::root(), Default::default()));
+		assert_eq!(Beefy::secondary_validator_set(), None);
+	});
+}
+
 #[test]
 fn cleans_up_old_set_id_session_mappings() {
 	new_test_ext(vec![1, 2, 3, 4]).execute_with(|| {
@@ -620,6 +677,52 @@ fn report_equivocation_invalid_equivocation_proof() {
 	});
 }
 
+#[test]
+fn report_equivocation_checks_signatures_with_signature_hasher() {
+	let authorities = test_authorities();
+	BeefySignatureHasher::set(SignatureHasher::Blake2_256);
+
+	new_test_ext_raw_authorities(authorities).execute_with(|| {
+		start_era(1);
+
+		let block_num = System::block_number();
+		let validator_set = Beefy::validator_set().unwrap();
+		let set_id = validator_set.id();
+		let equivocation_key = &validator_set.validators()[0];
+		let equivocation_keyring = BeefyKeyring::from_public(equivocation_key).unwrap();
+		let key_owner_proof =
+			Historical::prove((sp_consensus_beefy::KEY_TYPE, &equivocation_key)).unwrap();
+
+		let payload1 = Payload::from_single_entry(MMR_ROOT_ID, vec![42]);
+		let payload2 = Payload::from_single_entry(MMR_ROOT_ID, vec![128]);
+
+		// votes signed over Keccak hashes are not valid with another hasher.
+		let equivocation_proof = generate_equivocation_proof(
+			(block_num, payload1.clone(), set_id, &equivocation_keyring),
+			(block_num, payload2.clone(), set_id, &equivocation_keyring),
+		);
+		assert_err!(
+			Beefy::report_equivocation_unsigned(
+				RuntimeOrigin::none(),
+				Box::new(equivocation_proof),
+				key_owner_proof.clone(),
+			),
+			Error::<Test>::InvalidEquivocationProof,
+		);
+
+		let equivocation_proof = generate_equivocation_proof_with_hasher(
+			(block_num, payload1, set_id, &equivocation_keyring),
+			(block_num, payload2, set_id, &equivocation_keyring),
+			SignatureHasher::Blake2_256,
+		);
+		assert_ok!(Beefy::report_equivocation_unsigned(
+			RuntimeOrigin::none(),
+			Box::new(equivocation_proof),
+			key_owner_proof,
+		));
+	});
+}
+
 #[test]
 fn report_equivocation_validate_unsigned_prevents_duplicates() {
 	use sp_runtime::transaction_validity::{
@@ -20,6 +20,7 @@
 use super::*;
 use crate::{unsigned::IndexAssignmentOf, Pallet as MultiPhase};
 use frame_benchmarking::account;
+use frame_election_provider_support::Voter;
 use frame_support::{
 	assert_ok,
 	traits::{Hooks, TryCollect},
@@ -28,6 +29,7 @@ use frame_support::{
 use frame_system::RawOrigin;
 use rand::{prelude::SliceRandom, rngs::SmallRng, SeedableRng};
 use sp_arithmetic::{per_things::Percent, traits::One};
+use sp_npos_elections::VoteWeight;
 use sp_runtime::InnerOf;
 
 const SEED: u32 = 999;
@@ -79,7 +81,7 @@ fn solution_with_size<T: Config>(
 				.try_collect()
 				.expect("<SolutionOf<T::MinerConfig>>::LIMIT is the correct bound; qed.");
 			let voter = frame_benchmarking::account::<T::AccountId>("Voter", i, SEED);
-			(voter, stake, winner_votes)
+			Voter::new(voter, stake, winner_votes)
 		})
 		.collect::<Vec<_>>();
 
@@ -97,7 +99,7 @@ fn solution_with_size<T: Config>(
 				.try_collect()
 				.expect("<SolutionOf<T::MinerConfig>>::LIMIT is the correct bound; qed.");
 			let voter = frame_benchmarking::account::<T::AccountId>("Voter", i, SEED);
-			(voter, stake, votes)
+			Voter::new(voter, stake, votes)
 		})
 		.collect::<Vec<_>>();
 
@@ -114,6 +116,12 @@ fn solution_with_size<T: Config>(
 		targets: targets.len() as u32,
 	});
 	<DesiredTargets<T>>::put(desired_targets);
+	let snapshot_fingerprint = frame_election_provider_support::snapshot_fingerprint(
+		&all_voters,
+		&targets,
+		desired_targets,
+	);
+	<SnapshotFingerprint<T>>::put(snapshot_fingerprint);
 	<Snapshot<T>>::put(RoundSnapshot { voters: all_voters.clone(), targets: targets.clone() });
 
 	// write the snapshot to staking or whoever is the data provider, in case it is needed further
@@ -129,7 +137,7 @@ fn solution_with_size<T: Config>(
 
 	let assignments = active_voters
 		.iter()
-		.map(|(voter, _stake, votes)| {
+		.map(|Voter { who: voter, votes, .. }| {
 			let percent_per_edge: InnerOf<SolutionAccuracyOf<T>> =
 				(100 / votes.len()).try_into().unwrap_or_else(|_| panic!("failed to convert"));
 			crate::unsigned::Assignment::<T> {
@@ -152,7 +160,7 @@ fn solution_with_size<T: Config>(
 		score.minimal_stake > 0,
 		"score is zero, this probably means that the stakes are not set."
 	);
-	Ok(RawSolution { solution, score, round })
+	Ok(RawSolution { solution, score, round, snapshot_fingerprint })
 }
 
 fn set_up_data_provider<T: Config>(v: u32, t: u32) {
@@ -275,9 +283,17 @@ frame_benchmarking::benchmarks! {
 	}: {
 		<MultiPhase::<T>>::create_snapshot_internal(targets, voters, desired_targets)
 	} verify {
-		assert!(<MultiPhase<T>>::snapshot().is_some());
+		let snapshot = <MultiPhase<T>>::snapshot().ok_or("snapshot missing")?;
 		assert_eq!(<MultiPhase<T>>::snapshot_metadata().ok_or("metadata missing")?.voters, v + t);
 		assert_eq!(<MultiPhase<T>>::snapshot_metadata().ok_or("metadata missing")?.targets, t);
+		assert_eq!(
+			<MultiPhase<T>>::snapshot_fingerprint(),
+			Some(frame_election_provider_support::snapshot_fingerprint(
+				&snapshot.voters,
+				&snapshot.targets,
+				desired_targets,
+			))
+		);
 	}
 
 	// a call to `<Pallet as ElectionProvider>::elect` where we only return the queued solution.
@@ -306,6 +322,7 @@ frame_benchmarking::benchmarks! {
 		assert!(<DesiredTargets<T>>::get().is_some());
 		assert!(<Snapshot<T>>::get().is_some());
 		assert!(<SnapshotMetadata<T>>::get().is_some());
+		assert!(<SnapshotFingerprint<T>>::get().is_some());
 	}: {
 		assert_ok!(<MultiPhase<T> as ElectionProvider>::elect());
 	} verify {
@@ -313,6 +330,7 @@ frame_benchmarking::benchmarks! {
 		assert!(<DesiredTargets<T>>::get().is_none());
 		assert!(<Snapshot<T>>::get().is_none());
 		assert!(<SnapshotMetadata<T>>::get().is_none());
+		assert!(<SnapshotFingerprint<T>>::get().is_none());
 		assert_eq!(<CurrentPhase<T>>::get(), <Phase<T::BlockNumber>>::Off);
 	}
 
@@ -488,10 +506,7 @@ frame_benchmarking::benchmarks! {
 
 		// sort assignments by decreasing voter stake
 		assignments.sort_by_key(|crate::unsigned::Assignment::<T> { who, .. }| {
-			let stake = cache.get(who).map(|idx| {
-				let (_, stake, _) = voters[*idx];
-				stake
-			}).unwrap_or_default();
+			let stake = cache.get(who).map(|idx| voters[*idx].weight).unwrap_or_default();
 			sp_std::cmp::Reverse(stake)
 		});
 
@@ -231,11 +231,12 @@
 
 use codec::{Decode, Encode};
 use frame_election_provider_support::{
-	BoundedSupportsOf, ElectionDataProvider, ElectionProvider, ElectionProviderBase,
-	InstantElectionProvider, NposSolution,
+	data_provider, onchain::ElectionForecast, BoundedSupportsOf, ElectionDataProvider,
+	ElectionProvider, ElectionProviderBase, EmergencyElection, InstantElectionProvider,
+	NposSolution,
 };
 use frame_support::{
-	dispatch::DispatchClass,
+	dispatch::{DispatchClass, DispatchResult},
 	ensure,
 	traits::{Currency, DefensiveResult, Get, OnUnbalanced, ReservableCurrency},
 	weights::Weight,
@@ -247,13 +248,13 @@ use sp_arithmetic::{
 	traits::{CheckedAdd, Zero},
 	UpperOf,
 };
-use sp_npos_elections::{BoundedSupports, ElectionScore, IdentifierT, Supports, VoteWeight};
+use sp_npos_elections::{BoundedSupports, ElectionScore, IdentifierT, Supports};
 use sp_runtime::{
 	transaction_validity::{
 		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
 		TransactionValidityError, ValidTransaction,
 	},
-	DispatchError, ModuleError, PerThing, Perbill, RuntimeDebug, SaturatedConversion,
+	DispatchError, ModuleError, PerThing, Perbill, RuntimeDebug, SaturatedConversion, Saturating,
 };
 use sp_std::prelude::*;
 
@@ -276,6 +277,7 @@ pub mod weights;
 use unsigned::VoterOf;
 pub use weights::WeightInfo;
 
+pub use frame_election_provider_support::miner::SolutionOrSnapshotSize;
 pub use signed::{
 	BalanceOf, NegativeImbalanceOf, PositiveImbalanceOf, SignedSubmission, SignedSubmissionOf,
 	SignedSubmissions, SubmissionIndicesOf,
@@ -410,12 +412,19 @@ pub struct RawSolution<S> {
 	pub score: ElectionScore,
 	/// The round at which this solution should be submitted.
 	pub round: u32,
+	/// The [`SnapshotFingerprint`] of the snapshot that this solution was computed against.
+	pub snapshot_fingerprint: [u8; 32],
 }
 
 impl<C: Default> Default for RawSolution<C> {
 	fn default() -> Self {
 		// Round 0 is always invalid, only set this to 1.
-		Self { round: 1, solution: Default::default(), score: Default::default() }
+		Self {
+			round: 1,
+			solution: Default::default(),
+			score: Default::default(),
+			snapshot_fingerprint: Default::default(),
+		}
 	}
 }
 
@@ -462,21 +471,6 @@ pub struct RoundSnapshot<AccountId, DataProvider> {
 	pub targets: Vec<AccountId>,
 }
 
-/// Encodes the length of a solution or a snapshot.
-///
-/// This is stored automatically on-chain, and it contains the **size of the entire snapshot**.
-/// This is also used in dispatchables as weight witness data and should **only contain the size of
-/// the presented solution**, not the entire snapshot.
-#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, Debug, Default, TypeInfo)]
-pub struct SolutionOrSnapshotSize {
-	/// The length of voters.
-	#[codec(compact)]
-	pub voters: u32,
-	/// The length of targets.
-	#[codec(compact)]
-	pub targets: u32,
-}
-
 /// Internal errors of the pallet.
 ///
 /// Note that this is different from [`pallet::Error`].
@@ -493,6 +487,8 @@ pub enum ElectionError<T: Config> {
 	Fallback(FallbackErrorOf<T>),
 	/// No solution has been queued.
 	NothingQueued,
+	/// The election cannot be deferred anymore.
+	CannotDefer,
 }
 
 // NOTE: we have to do this manually because of the additional where clause needed on
@@ -509,6 +505,7 @@ where
 			(Miner(x), Miner(y)) if x == y => true,
 			(DataProvider(x), DataProvider(y)) if x == y => true,
 			(Fallback(x), Fallback(y)) if x == y => true,
+			(CannotDefer, CannotDefer) => true,
 			_ => false,
 		}
 	}
@@ -547,6 +544,8 @@ pub enum FeasibilityError {
 	InvalidScore,
 	/// The provided round is incorrect.
 	InvalidRound,
+	/// The solution was computed against a different snapshot.
+	StaleSnapshot,
 	/// Comparison against `MinimumUntrustedScore` failed.
 	UntrustedScoreTooLow,
 	/// Data Provider returned too many desired targets
@@ -748,7 +747,9 @@ pub mod pallet {
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
 		fn on_initialize(now: T::BlockNumber) -> Weight {
-			let next_election = T::DataProvider::next_election_prediction(now).max(now);
+			let next_election = T::DataProvider::next_election_prediction(now)
+				.max(Self::deferred_until().unwrap_or(now))
+				.max(now);
 
 			let signed_deadline = T::SignedPhase::get() + T::UnsignedPhase::get();
 			let unsigned_deadline = T::UnsignedPhase::get();
@@ -764,6 +765,17 @@ pub mod pallet {
 				Self::snapshot_metadata()
 			);
 			match current_phase {
+				Phase::Off
+					if remaining <= signed_deadline &&
+						remaining > Zero::zero() &&
+						!T::DataProvider::ready_for_election() =>
+				{
+					// the data provider is not ready yet, hold off from taking a snapshot. It is
+					// expected to defer the election in the meantime, which pushes the phases
+					// back through `DeferredUntil`.
+					log!(debug, "data provider not ready, not starting an election");
+					T::WeightInfo::on_initialize_nothing()
+				},
 				Phase::Off if remaining <= signed_deadline && remaining > unsigned_deadline => {
 					// NOTE: if signed-phase length is zero, second part of the if-condition fails.
 					match Self::create_snapshot() {
@@ -984,27 +996,7 @@ pub mod pallet {
 			supports: Supports<T::AccountId>,
 		) -> DispatchResult {
 			T::ForceOrigin::ensure_origin(origin)?;
-			ensure!(Self::current_phase().is_emergency(), <Error<T>>::CallNotAllowed);
-
-			// bound supports with T::MaxWinners
-			let supports = supports.try_into().map_err(|_| Error::<T>::TooManyWinners)?;
-
-			// Note: we don't `rotate_round` at this point; the next call to
-			// `ElectionProvider::elect` will succeed and take care of that.
-			let solution = ReadySolution {
-				supports,
-				score: Default::default(),
-				compute: ElectionCompute::Emergency,
-			};
-
-			Self::deposit_event(Event::SolutionStored {
-				compute: ElectionCompute::Emergency,
-				origin: None,
-				prev_ejected: QueuedSolution::<T>::exists(),
-			});
-
-			<QueuedSolution<T>>::put(solution);
-			Ok(())
+			<Self as EmergencyElection>::set_emergency_result(supports)
 		}
 
 		/// Submit a solution for the signed phase.
@@ -1259,6 +1251,14 @@ pub mod pallet {
 	#[pallet::getter(fn current_phase)]
 	pub type CurrentPhase<T: Config> = StorageValue<_, Phase<T::BlockNumber>, ValueQuery>;
 
+	/// The block until which the current election was deferred, see [`ElectionProvider::defer`].
+	///
+	/// The phases are scheduled as if the election was predicted at this block, if it is later
+	/// than the prediction of the data provider. Cleared at the end of each round.
+	#[pallet::storage]
+	#[pallet::getter(fn deferred_until)]
+	pub type DeferredUntil<T: Config> = StorageValue<_, T::BlockNumber>;
+
 	/// Current best solution, signed or unsigned, queued to be returned upon `elect`.
 	///
 	/// Always sorted by score.
@@ -1288,6 +1288,14 @@ pub mod pallet {
 	#[pallet::getter(fn snapshot_metadata)]
 	pub type SnapshotMetadata<T: Config> = StorageValue<_, SolutionOrSnapshotSize>;
 
+	/// The fingerprint of the [`RoundSnapshot`] and [`DesiredTargets`], as computed by
+	/// [`frame_election_provider_support::snapshot_fingerprint`].
+	///
+	/// Only exists when [`Snapshot`] is present.
+	#[pallet::storage]
+	#[pallet::getter(fn snapshot_fingerprint)]
+	pub type SnapshotFingerprint<T: Config> = StorageValue<_, [u8; 32]>;
+
 	// The following storage items collectively comprise `SignedSubmissions<T>`, and should never be
 	// accessed independently. Instead, get `Self::signed_submissions()`, modify it as desired, and
 	// then do `signed_submissions.put()` when you're done with it.
@@ -1338,7 +1346,7 @@ pub mod pallet {
 	/// The current storage version.
 	///
 	/// v1: https://github.com/paritytech/substrate/pull/12237/
-	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
 	#[pallet::pallet]
 	#[pallet::without_storage_info]
@@ -1402,19 +1410,26 @@ impl<T: Config> Pallet<T> {
 
 		// instead of using storage APIs, we do a manual encoding into a fixed-size buffer.
 		// `encoded_size` encodes it without storing it anywhere, this should not cause any
-		// allocation.
+		// allocation. The buffer has room for `desired_targets` as well, see below.
 		let snapshot = RoundSnapshot::<T::AccountId, VoterOf<T>> { voters, targets };
 		let size = snapshot.encoded_size();
 		log!(debug, "snapshot pre-calculated size {:?}", size);
-		let mut buffer = Vec::with_capacity(size);
+		let mut buffer = Vec::with_capacity(size + desired_targets.encoded_size());
 		snapshot.encode_to(&mut buffer);
 
 		// do some checks.
 		debug_assert_eq!(buffer, snapshot.encode());
-		// buffer should have not re-allocated since.
-		debug_assert!(buffer.len() == size && size == buffer.capacity());
+		debug_assert!(buffer.len() == size);
 
 		sp_io::storage::set(&<Snapshot<T>>::hashed_key(), &buffer);
+
+		// the snapshot is encoded like `(voters, targets)`, so appending `desired_targets` gives
+		// the data of `frame_election_provider_support::snapshot_fingerprint` without encoding
+		// the snapshot again.
+		desired_targets.encode_to(&mut buffer);
+		// buffer should have not re-allocated since.
+		debug_assert!(buffer.len() == buffer.capacity());
+		<SnapshotFingerprint<T>>::put(sp_io::hashing::blake2_256(&buffer));
 	}
 
 	/// Parts of [`create_snapshot`] that happen outside of this pallet.
@@ -1454,13 +1469,43 @@ impl<T: Config> Pallet<T> {
 		Ok((targets, voters, desired_targets))
 	}
 
+	/// Forecast the next election: when it will happen, and the size of its snapshot.
+	///
+	/// The size is the one of the current [`Snapshot`], as per [`SnapshotMetadata`], if any. Else,
+	/// it is the size it would have if it was created now, which reads the whole snapshot from
+	/// the data provider.
+	pub fn election_forecast(
+	) -> data_provider::Result<ElectionForecast<<T as frame_system::Config>::BlockNumber>> {
+		let voter_limit = T::MaxElectingVoters::get().saturated_into::<usize>();
+		let target_limit = T::MaxElectableTargets::get().saturated_into::<usize>();
+
+		let SolutionOrSnapshotSize { voters, targets } = match Self::snapshot_metadata() {
+			Some(metadata) => metadata,
+			None => SolutionOrSnapshotSize {
+				voters: T::DataProvider::electing_voters(Some(voter_limit))?.len() as u32,
+				targets: T::DataProvider::electable_targets(Some(target_limit))?.len() as u32,
+			},
+		};
+
+		let now = <frame_system::Pallet<T>>::block_number();
+		Ok(ElectionForecast {
+			next_election: T::DataProvider::next_election_prediction(now),
+			voters,
+			targets,
+			voters_bound: voter_limit.saturated_into(),
+			targets_bound: target_limit.saturated_into(),
+		})
+	}
+
 	/// Creates the snapshot. Writes new data to:
 	///
 	/// 1. [`SnapshotMetadata`]
 	/// 2. [`RoundSnapshot`]
 	/// 3. [`DesiredTargets`]
+	/// 4. [`SnapshotFingerprint`]
 	///
-	/// Returns `Ok(())` if operation is okay.
+	/// Returns `Ok(())` if operation is okay. The voters are then kept in order through
+	/// [`ElectionDataProvider::lock_voters`] until the snapshot is killed.
 	///
 	/// This is a *self-weighing* function, it will register its own extra weight as
 	/// [`DispatchClass::Mandatory`] with the system pallet.
@@ -1473,6 +1518,7 @@ impl<T: Config> Pallet<T> {
 			T::WeightInfo::create_snapshot_internal(voters.len() as u32, targets.len() as u32);
 		Self::create_snapshot_internal(targets, voters, desired_targets);
 		Self::register_weight(internal_weight);
+		T::DataProvider::lock_voters();
 		Ok(())
 	}
 
@@ -1488,9 +1534,11 @@ impl<T: Config> Pallet<T> {
 
 	/// Kill everything created by [`Pallet::create_snapshot`].
 	pub fn kill_snapshot() {
+		T::DataProvider::unlock_voters();
 		<Snapshot<T>>::kill();
 		<SnapshotMetadata<T>>::kill();
 		<DesiredTargets<T>>::kill();
+		<SnapshotFingerprint<T>>::kill();
 	}
 
 	/// Checks the feasibility of a solution.
@@ -1502,6 +1550,8 @@ impl<T: Config> Pallet<T> {
 			Self::desired_targets().ok_or(FeasibilityError::SnapshotUnavailable)?;
 
 		let snapshot = Self::snapshot().ok_or(FeasibilityError::SnapshotUnavailable)?;
+		let snapshot_fingerprint =
+			Self::snapshot_fingerprint().ok_or(FeasibilityError::SnapshotUnavailable)?;
 		let round = Self::round();
 		let minimum_untrusted_score = Self::minimum_untrusted_score();
 
@@ -1510,6 +1560,7 @@ impl<T: Config> Pallet<T> {
 			compute,
 			desired_targets,
 			snapshot,
+			snapshot_fingerprint,
 			round,
 			minimum_untrusted_score,
 		)
@@ -1527,6 +1578,9 @@ impl<T: Config> Pallet<T> {
 		// Phase is off now.
 		Self::phase_transition(Phase::Off);
 
+		// Any deferral was for this round only.
+		<DeferredUntil<T>>::kill();
+
 		// Kill snapshots.
 		Self::kill_snapshot();
 	}
@@ -1585,7 +1639,8 @@ impl<T: Config> Pallet<T> {
 	fn do_try_state() -> Result<(), TryRuntimeError> {
 		Self::try_state_snapshot()?;
 		Self::try_state_signed_submissions_map()?;
-		Self::try_state_phase_off()
+		Self::try_state_phase_off()?;
+		Self::try_state_fallbacks()
 	}
 
 	// [`Snapshot`] state check. Invariants:
@@ -1594,16 +1649,18 @@ impl<T: Config> Pallet<T> {
 	fn try_state_snapshot() -> Result<(), TryRuntimeError> {
 		if <Snapshot<T>>::exists() &&
 			<SnapshotMetadata<T>>::exists() &&
-			<DesiredTargets<T>>::exists()
+			<DesiredTargets<T>>::exists() &&
+			<SnapshotFingerprint<T>>::exists()
 		{
 			Ok(())
 		} else if !<Snapshot<T>>::exists() &&
 			!<SnapshotMetadata<T>>::exists() &&
-			!<DesiredTargets<T>>::exists()
+			!<DesiredTargets<T>>::exists() &&
+			!<SnapshotFingerprint<T>>::exists()
 		{
 			Ok(())
 		} else {
-			Err("If snapshot exists, metadata and desired targets should be set too. Otherwise, none should be set.".into())
+			Err("If snapshot exists, metadata, desired targets and fingerprint should be set too. Otherwise, none should be set.".into())
 		}
 	}
 
@@ -1668,6 +1725,13 @@ impl<T: Config> Pallet<T> {
 				},
 		}
 	}
+
+	// [`Config::Fallback`] and [`Config::GovernanceFallback`] state checks, as they will be used
+	// if no solution is queued.
+	fn try_state_fallbacks() -> Result<(), TryRuntimeError> {
+		T::Fallback::try_state()?;
+		T::GovernanceFallback::try_state()
+	}
 }
 
 impl<T: Config> ElectionProviderBase for Pallet<T> {
@@ -1686,6 +1750,24 @@ impl<T: Config> ElectionProvider for Pallet<T> {
 		}
 	}
 
+	fn defer(requested_delay: Self::BlockNumber) -> Result<Self::BlockNumber, Self::Error> {
+		// once an election has failed, only governance can get us out of the emergency phase.
+		if Self::current_phase().is_emergency() {
+			return Err(ElectionError::CannotDefer)
+		}
+
+		// the phases are scheduled against the later of `DeferredUntil` and the prediction of the
+		// data provider. An ongoing phase stays open, and keeps its snapshot, until the new
+		// deadline. An earlier deferral is never brought forward.
+		let now = <frame_system::Pallet<T>>::block_number();
+		let until = now.saturating_add(requested_delay).max(Self::deferred_until().unwrap_or(now));
+		<DeferredUntil<T>>::put(until);
+
+		let granted = until.saturating_sub(now);
+		log!(info, "election deferred by {:?} blocks, until {:?}", granted, until);
+		Ok(granted)
+	}
+
 	fn elect() -> Result<BoundedSupportsOf<Self>, Self::Error> {
 		match Self::do_elect() {
 			Ok(supports) => {
@@ -1703,6 +1785,36 @@ impl<T: Config> ElectionProvider for Pallet<T> {
 	}
 }
 
+impl<T: Config> EmergencyElection for Pallet<T> {
+	fn set_emergency_result(supports: Supports<T::AccountId>) -> DispatchResult {
+		ensure!(Self::current_phase().is_emergency(), <Error<T>>::CallNotAllowed);
+
+		// bound supports with T::MaxWinners
+		let supports = supports.try_into().map_err(|_| Error::<T>::TooManyWinners)?;
+
+		// Note: we don't `rotate_round` at this point; the next call to
+		// `ElectionProvider::elect` will succeed and take care of that.
+		let solution = ReadySolution {
+			supports,
+			score: Default::default(),
+			compute: ElectionCompute::Emergency,
+		};
+
+		Self::deposit_event(Event::SolutionStored {
+			compute: ElectionCompute::Emergency,
+			origin: None,
+			prev_ejected: QueuedSolution::<T>::exists(),
+		});
+
+		<QueuedSolution<T>>::put(solution);
+		Ok(())
+	}
+
+	fn emergency_result_pending() -> bool {
+		Self::current_phase().is_emergency() && <QueuedSolution<T>>::exists()
+	}
+}
+
 /// convert a DispatchError to a custom InvalidTransaction with the inner code being the error
 /// number.
 pub fn dispatch_error_to_invalid(error: DispatchError) -> InvalidTransaction {
@@ -1722,7 +1834,7 @@ mod feasibility_check {
 	use super::*;
 	use crate::mock::{
 		raw_solution, roll_to, EpochLength, ExtBuilder, MultiPhase, Runtime, SignedPhase,
-		TargetIndex, UnsignedPhase, VoterIndex,
+		StakingMock, TargetIndex, UnsignedPhase, VoterIndex,
 	};
 	use frame_support::{assert_noop, assert_ok};
 
@@ -1743,10 +1855,11 @@ mod feasibility_check {
 				FeasibilityError::SnapshotUnavailable
 			);
 
-			// kill also `SnapshotMetadata` and `DesiredTargets` for the storage state to be
-			// consistent for the try_state checks to pass.
+			// kill also `SnapshotMetadata`, `DesiredTargets` and `SnapshotFingerprint` for the
+			// storage state to be consistent for the try_state checks to pass.
 			<SnapshotMetadata<Runtime>>::kill();
 			<DesiredTargets<Runtime>>::kill();
+			<SnapshotFingerprint<Runtime>>::kill();
 		})
 	}
 
@@ -1765,6 +1878,40 @@ mod feasibility_check {
 		})
 	}
 
+	#[test]
+	fn stale_snapshot() {
+		ExtBuilder::default().build_and_execute(|| {
+			roll_to(<EpochLength>::get() - <SignedPhase>::get() - <UnsignedPhase>::get());
+			assert!(MultiPhase::current_phase().is_signed());
+
+			let snapshot = MultiPhase::snapshot().unwrap();
+			assert_eq!(
+				MultiPhase::snapshot_fingerprint(),
+				Some(frame_election_provider_support::snapshot_fingerprint(
+					&snapshot.voters,
+					&snapshot.targets,
+					MultiPhase::desired_targets().unwrap(),
+				))
+			);
+			// which is what the data provider expects for the same limits.
+			assert_eq!(
+				MultiPhase::snapshot_fingerprint(),
+				StakingMock::snapshot_fingerprint(
+					<Runtime as Config>::MaxElectingVoters::get() as usize,
+					<Runtime as Config>::MaxElectableTargets::get() as usize,
+				)
+				.ok()
+			);
+
+			let mut solution = raw_solution();
+			solution.snapshot_fingerprint = [1; 32];
+			assert_noop!(
+				MultiPhase::feasibility_check(solution, COMPUTE),
+				FeasibilityError::StaleSnapshot
+			);
+		})
+	}
+
 	#[test]
 	fn desired_targets_gets_capped() {
 		ExtBuilder::default().desired_targets(8).build_and_execute(|| {
@@ -1921,8 +2068,8 @@ mod tests {
 	use crate::{
 		mock::{
 			multi_phase_events, raw_solution, roll_to, roll_to_signed, roll_to_unsigned, AccountId,
-			ExtBuilder, MockWeightInfo, MockedWeightInfo, MultiPhase, Runtime, RuntimeOrigin,
-			SignedMaxSubmissions, System, TargetIndex, Targets,
+			DataProviderReady, ExtBuilder, MockWeightInfo, MockedWeightInfo, MultiPhase, Runtime,
+			RuntimeOrigin, SignedMaxSubmissions, System, TargetIndex, Targets, VotersLocked,
 		},
 		Phase,
 	};
@@ -2462,6 +2609,102 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn emergency_election_works() {
+		ExtBuilder::default().onchain_fallback(false).build_and_execute(|| {
+			roll_to_unsigned();
+			assert!(!MultiPhase::emergency_result_pending());
+
+			// not allowed outside of the emergency phase.
+			assert_noop!(
+				<MultiPhase as EmergencyElection>::set_emergency_result(vec![]),
+				Error::<Runtime>::CallNotAllowed,
+			);
+
+			assert_eq!(MultiPhase::elect().unwrap_err(), ElectionError::Fallback("NoFallback."));
+			assert_eq!(MultiPhase::current_phase(), Phase::Emergency);
+			assert!(!MultiPhase::emergency_result_pending());
+
+			let supports = vec![(30, Support { total: 30, voters: vec![(30, 30)] })];
+			assert_ok!(<MultiPhase as EmergencyElection>::set_emergency_result(supports.clone()));
+			assert!(MultiPhase::emergency_result_pending());
+
+			// the next election consumes it.
+			assert_eq!(MultiPhase::elect().unwrap().into_inner(), supports);
+			assert_eq!(MultiPhase::current_phase(), Phase::Off);
+			assert!(!MultiPhase::emergency_result_pending());
+		})
+	}
+
+	#[test]
+	fn defer_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// the data provider is not ready for the election at block 30...
+			DataProviderReady::set(false);
+			roll_to(25);
+			assert_eq!(MultiPhase::current_phase(), Phase::Off);
+
+			// ...so it defers it to block 35.
+			assert_eq!(MultiPhase::defer(10), Ok(10));
+			assert_eq!(MultiPhase::deferred_until(), Some(35));
+
+			// once ready, the phases are scheduled against the new deadline: the signed phase
+			// opens, rather than the unsigned one.
+			DataProviderReady::set(true);
+			roll_to(26);
+			assert!(MultiPhase::current_phase().is_signed());
+
+			// a shorter deferral does not bring the election forward, the granted delay is the
+			// one of the earlier deferral.
+			assert_eq!(MultiPhase::defer(1), Ok(9));
+			assert_eq!(MultiPhase::deferred_until(), Some(35));
+
+			// the deferral only lasts for this round.
+			assert_ok!(MultiPhase::elect());
+			assert_eq!(MultiPhase::deferred_until(), None);
+
+			// and elections cannot be deferred once they have failed.
+			crate::mock::OnChainFallback::set(false);
+			roll_to(27);
+			assert!(MultiPhase::current_phase().is_unsigned());
+			assert_eq!(MultiPhase::elect().unwrap_err(), ElectionError::Fallback("NoFallback."));
+			assert_eq!(MultiPhase::defer(10), Err(ElectionError::CannotDefer));
+		})
+	}
+
+	#[test]
+	fn no_snapshot_if_data_provider_not_ready() {
+		ExtBuilder::default().build_and_execute(|| {
+			DataProviderReady::set(false);
+			roll_to(15);
+			assert_eq!(MultiPhase::current_phase(), Phase::Off);
+			assert!(MultiPhase::snapshot().is_none());
+
+			DataProviderReady::set(true);
+			roll_to(16);
+			assert_eq!(MultiPhase::current_phase(), Phase::Signed);
+			assert!(MultiPhase::snapshot().is_some());
+		})
+	}
+
+	#[test]
+	fn voters_are_locked_while_the_snapshot_exists() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert!(!VotersLocked::get());
+
+			roll_to_signed();
+			assert!(MultiPhase::snapshot().is_some());
+			assert!(VotersLocked::get());
+
+			roll_to_unsigned();
+			assert!(VotersLocked::get());
+
+			assert_ok!(MultiPhase::elect());
+			assert!(MultiPhase::snapshot().is_none());
+			assert!(!VotersLocked::get());
+		})
+	}
+
 	#[test]
 	fn governance_fallback_works() {
 		ExtBuilder::default().onchain_fallback(false).build_and_execute(|| {
@@ -2607,6 +2850,46 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn election_forecast_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// without a snapshot, the data provider is read.
+			let forecast = MultiPhase::election_forecast().unwrap();
+			assert_eq!(forecast.next_election, 30);
+			assert_eq!((forecast.voters, forecast.targets), (8, 4));
+			assert_eq!(
+				(forecast.voters_bound, forecast.targets_bound),
+				(
+					<Runtime as Config>::MaxElectingVoters::get(),
+					<Runtime as Config>::MaxElectableTargets::get() as u32
+				)
+			);
+
+			// with a snapshot, its metadata is used, even if the data provider has since changed.
+			roll_to_signed();
+			Targets::set(vec![10, 20]);
+			let forecast = MultiPhase::election_forecast().unwrap();
+			assert_eq!((forecast.voters, forecast.targets), (8, 4));
+		})
+	}
+
+	#[cfg(feature = "try-runtime")]
+	#[test]
+	fn try_state_checks_the_fallbacks() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert_ok!(MultiPhase::do_try_state());
+
+			// the governance fallback cannot elect more than `MaxWinners`.
+			crate::mock::DesiredTargets::set(201);
+			assert_eq!(
+				MultiPhase::do_try_state(),
+				Err("`desired_targets` exceeds `MaxWinners`".into())
+			);
+
+			crate::mock::DesiredTargets::set(2);
+		})
+	}
+
 	#[test]
 	fn untrusted_score_verification_is_respected() {
 		ExtBuilder::default().build_and_execute(|| {
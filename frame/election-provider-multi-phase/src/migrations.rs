@@ -76,3 +76,167 @@ pub mod v1 {
 		}
 	}
 }
+
+pub mod v2 {
+	use codec::{Decode, Encode, HasCompact};
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade};
+	use sp_runtime::Saturating;
+
+	use crate::{signed::SignedSubmission, *};
+
+	/// A [`RawSolution`] from before [`RawSolution::snapshot_fingerprint`] was added.
+	#[derive(Encode, Decode)]
+	pub struct OldRawSolution<S> {
+		pub solution: S,
+		pub score: ElectionScore,
+		pub round: u32,
+	}
+
+	/// A [`SignedSubmission`] holding an [`OldRawSolution`].
+	#[derive(Encode, Decode)]
+	pub struct OldSignedSubmission<AccountId, Balance: HasCompact, Solution> {
+		pub who: AccountId,
+		pub deposit: Balance,
+		pub raw_solution: OldRawSolution<Solution>,
+		pub call_fee: Balance,
+	}
+
+	impl<AccountId, Balance: HasCompact, Solution> OldSignedSubmission<AccountId, Balance, Solution> {
+		fn migrate_to_v2(
+			self,
+			snapshot_fingerprint: [u8; 32],
+		) -> SignedSubmission<AccountId, Balance, Solution> {
+			let OldRawSolution { solution, score, round } = self.raw_solution;
+			SignedSubmission {
+				who: self.who,
+				deposit: self.deposit,
+				raw_solution: RawSolution { solution, score, round, snapshot_fingerprint },
+				call_fee: self.call_fee,
+			}
+		}
+	}
+
+	/// Backfills the [`SnapshotFingerprint`] of an existing [`Snapshot`], and the fingerprint of
+	/// the signed submissions that were computed against it.
+	pub struct MigrateToV2<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let current = Pallet::<T>::current_storage_version();
+			let onchain = Pallet::<T>::on_chain_storage_version();
+
+			log!(
+				info,
+				"Running migration with current storage version {:?} / onchain {:?}",
+				current,
+				onchain
+			);
+
+			if current == 2 && onchain == 1 {
+				// signed submissions only exist alongside the snapshot they were computed against.
+				let snapshot_fingerprint = Snapshot::<T>::get()
+					.map(|RoundSnapshot { voters, targets }| {
+						let fingerprint = frame_election_provider_support::snapshot_fingerprint(
+							&voters,
+							&targets,
+							DesiredTargets::<T>::get().unwrap_or_default(),
+						);
+						SnapshotFingerprint::<T>::put(fingerprint);
+						fingerprint
+					})
+					.unwrap_or_default();
+
+				let mut translated = 0u64;
+				SignedSubmissionsMap::<T>::translate::<
+					OldSignedSubmission<
+						T::AccountId,
+						BalanceOf<T>,
+						<T::MinerConfig as MinerConfig>::Solution,
+					>,
+					_,
+				>(|_, old| {
+					translated.saturating_inc();
+					Some(old.migrate_to_v2(snapshot_fingerprint))
+				});
+
+				log!(info, "Backfilled the fingerprint of {} signed submissions", translated);
+
+				current.put::<Pallet<T>>();
+				T::DbWeight::get().reads_writes(translated + 3, translated + 2)
+			} else {
+				log!(info, "Migration did not execute. This probably should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 2,
+				"the storage version should be 2 after the migration"
+			);
+			frame_support::ensure!(
+				Snapshot::<T>::exists() == SnapshotFingerprint::<T>::exists(),
+				"the snapshot fingerprint should exist if and only if the snapshot does"
+			);
+			Ok(())
+		}
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+		use crate::mock::*;
+		use frame_support::{storage::unhashed, traits::StorageVersion, BoundedVec};
+
+		#[test]
+		fn migrate_to_v2_backfills_fingerprints() {
+			ExtBuilder::default().build_and_execute(|| {
+				roll_to_signed();
+				assert!(MultiPhase::current_phase().is_signed());
+
+				let fingerprint = MultiPhase::snapshot_fingerprint().unwrap();
+				SnapshotFingerprint::<Runtime>::kill();
+				let old = OldSignedSubmission {
+					who: 99u64,
+					deposit: 5u64,
+					raw_solution: OldRawSolution {
+						solution: TestNposSolution::default(),
+						score: Default::default(),
+						round: 1,
+					},
+					call_fee: 1u64,
+				};
+				unhashed::put(&SignedSubmissionsMap::<Runtime>::hashed_key_for(0), &old);
+				SignedSubmissionIndices::<Runtime>::put(BoundedVec::truncate_from(vec![(
+					Default::default(),
+					System::block_number(),
+					0,
+				)]));
+				SignedSubmissionNextIndex::<Runtime>::put(1);
+				StorageVersion::new(1).put::<MultiPhase>();
+
+				MigrateToV2::<Runtime>::on_runtime_upgrade();
+
+				assert_eq!(MultiPhase::on_chain_storage_version(), 2);
+				assert_eq!(MultiPhase::snapshot_fingerprint(), Some(fingerprint));
+				let submission = SignedSubmissionsMap::<Runtime>::get(0).unwrap();
+				assert_eq!(submission.who, 99);
+				assert_eq!(submission.raw_solution.round, 1);
+				assert_eq!(submission.raw_solution.snapshot_fingerprint, fingerprint);
+			})
+		}
+
+		#[test]
+		fn migrate_to_v2_without_snapshot_works() {
+			ExtBuilder::default().build_and_execute(|| {
+				assert!(MultiPhase::snapshot().is_none());
+				StorageVersion::new(1).put::<MultiPhase>();
+
+				MigrateToV2::<Runtime>::on_runtime_upgrade();
+
+				assert_eq!(MultiPhase::on_chain_storage_version(), 2);
+				assert!(MultiPhase::snapshot_fingerprint().is_none());
+			})
+		}
+	}
+}
@@ -20,7 +20,7 @@ use crate::{self as multi_phase, unsigned::MinerConfig};
 use frame_election_provider_support::{
 	data_provider,
 	onchain::{self},
-	ElectionDataProvider, NposSolution, SequentialPhragmen,
+	ElectionDataProvider, NposSolution, SequentialPhragmen, Voter,
 };
 pub use frame_support::{assert_noop, assert_ok, pallet_prelude::GetDefault};
 use frame_support::{
@@ -49,6 +49,9 @@ use sp_runtime::{
 };
 use std::sync::Arc;
 
+#[cfg(feature = "runtime-benchmarks")]
+use sp_npos_elections::VoteWeight;
+
 pub type Block = sp_runtime::generic::Block<Header, UncheckedExtrinsic>;
 pub type UncheckedExtrinsic =
 	sp_runtime::generic::UncheckedExtrinsic<AccountId, RuntimeCall, (), ()>;
@@ -137,7 +140,7 @@ pub struct TrimHelpers {
 pub fn trim_helpers() -> TrimHelpers {
 	let RoundSnapshot { voters, targets } = MultiPhase::snapshot().unwrap();
 	let stakes: std::collections::HashMap<_, _> =
-		voters.iter().map(|(id, stake, _)| (*id, *stake)).collect();
+		voters.iter().map(|voter| (voter.who, voter.weight)).collect();
 
 	// Compute the size of a solution comprised of the selected arguments.
 	//
@@ -151,8 +154,13 @@ pub fn trim_helpers() -> TrimHelpers {
 
 	let desired_targets = MultiPhase::desired_targets().unwrap();
 
-	let ElectionResult::<_, SolutionAccuracyOf<Runtime>> { mut assignments, .. } =
-		seq_phragmen(desired_targets as usize, targets.clone(), voters.clone(), None).unwrap();
+	let ElectionResult::<_, SolutionAccuracyOf<Runtime>> { mut assignments, .. } = seq_phragmen(
+		desired_targets as usize,
+		targets.clone(),
+		voters.iter().map(|v| (v.who, v.weight, v.votes.clone())).collect(),
+		None,
+	)
+	.unwrap();
 
 	// sort by decreasing order of stake
 	assignments.sort_by_key(|assignment| {
@@ -179,7 +187,13 @@ pub fn raw_solution() -> RawSolution<SolutionOf<Runtime>> {
 	let desired_targets = MultiPhase::desired_targets().unwrap();
 
 	let ElectionResult::<_, SolutionAccuracyOf<Runtime>> { winners: _, assignments } =
-		seq_phragmen(desired_targets as usize, targets.clone(), voters.clone(), None).unwrap();
+		seq_phragmen(
+			desired_targets as usize,
+			targets.clone(),
+			voters.iter().map(|v| (v.who, v.weight, v.votes.clone())).collect(),
+			None,
+		)
+		.unwrap();
 
 	// closures
 	let cache = helpers::generate_voter_cache::<Runtime>(&voters);
@@ -195,7 +209,8 @@ pub fn raw_solution() -> RawSolution<SolutionOf<Runtime>> {
 		<SolutionOf<Runtime>>::from_assignment(&assignments, &voter_index, &target_index).unwrap();
 
 	let round = MultiPhase::round();
-	RawSolution { solution, score, round }
+	let snapshot_fingerprint = MultiPhase::snapshot_fingerprint().unwrap();
+	RawSolution { solution, score, round, snapshot_fingerprint }
 }
 
 pub fn witness() -> SolutionOrSnapshotSize {
@@ -271,15 +286,15 @@ pub enum MockedWeightInfo {
 parameter_types! {
 	pub static Targets: Vec<AccountId> = vec![10, 20, 30, 40];
 	pub static Voters: Vec<VoterOf<Runtime>> = vec![
-		(1, 10, bounded_vec![10, 20]),
-		(2, 10, bounded_vec![30, 40]),
-		(3, 10, bounded_vec![40]),
-		(4, 10, bounded_vec![10, 20, 30, 40]),
+		Voter::new(1, 10, bounded_vec![10, 20]),
+		Voter::new(2, 10, bounded_vec![30, 40]),
+		Voter::new(3, 10, bounded_vec![40]),
+		Voter::new(4, 10, bounded_vec![10, 20, 30, 40]),
 		// self votes.
-		(10, 10, bounded_vec![10]),
-		(20, 20, bounded_vec![20]),
-		(30, 30, bounded_vec![30]),
-		(40, 40, bounded_vec![40]),
+		Voter::new(10, 10, bounded_vec![10]),
+		Voter::new(20, 20, bounded_vec![20]),
+		Voter::new(30, 30, bounded_vec![30]),
+		Voter::new(40, 40, bounded_vec![40]),
 	];
 
 	pub static DesiredTargets: u32 = 2;
@@ -429,6 +444,8 @@ parameter_types! {
 	pub MaxNominations: u32 = <TestNposSolution as NposSolution>::LIMIT as u32;
 	// only used in testing to manipulate mock behaviour
 	pub static DataProviderAllowBadData: bool = false;
+	pub static DataProviderReady: bool = true;
+	pub static VotersLocked: bool = false;
 }
 
 #[derive(Default)]
@@ -473,6 +490,18 @@ impl ElectionDataProvider for StakingMock {
 		now + EpochLength::get() - now % EpochLength::get()
 	}
 
+	fn ready_for_election() -> bool {
+		DataProviderReady::get()
+	}
+
+	fn lock_voters() {
+		VotersLocked::set(true);
+	}
+
+	fn unlock_voters() {
+		VotersLocked::set(false);
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	fn put_snapshot(
 		voters: Vec<VoterOf<Runtime>>,
@@ -496,7 +525,7 @@ impl ElectionDataProvider for StakingMock {
 		targets: frame_support::BoundedVec<AccountId, Self::MaxVotesPerVoter>,
 	) {
 		let mut current = Voters::get();
-		current.push((voter, weight, targets));
+		current.push(Voter::new(voter, weight, targets));
 		Voters::set(current);
 	}
 
@@ -509,7 +538,7 @@ impl ElectionDataProvider for StakingMock {
 		// to be on-par with staking, we add a self vote as well. the stake is really not that
 		// important.
 		let mut current = Voters::get();
-		current.push((target, ExistentialDeposit::get() as u64, bounded_vec![target]));
+		current.push(Voter::new(target, ExistentialDeposit::get() as u64, bounded_vec![target]));
 		Voters::set(current);
 	}
 }
@@ -554,7 +583,7 @@ impl ExtBuilder {
 		stake: Balance,
 		targets: BoundedVec<AccountId, MaxNominations>,
 	) -> Self {
-		VOTERS.with(|v| v.borrow_mut().push((who, stake, targets)));
+		VOTERS.with(|v| v.borrow_mut().push(Voter::new(who, stake, targets)));
 		self
 	}
 	pub fn signed_max_submission(self, count: u32) -> Self {
@@ -559,6 +559,7 @@ mod tests {
 			<crate::Snapshot<Runtime>>::kill();
 			<crate::SnapshotMetadata<Runtime>>::kill();
 			<crate::DesiredTargets<Runtime>>::kill();
+			<crate::SnapshotFingerprint<Runtime>>::kill();
 		})
 	}
 
@@ -605,6 +606,9 @@ mod tests {
 				MultiPhase::create_snapshot_external(),
 				ElectionError::DataProvider("desired_targets must not be greater than MaxWinners."),
 			);
+
+			// the fallback would fail as well, which `try_state` reports.
+			DesiredTargets::set(3);
 		})
 	}
 
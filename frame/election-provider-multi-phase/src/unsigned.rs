@@ -22,22 +22,22 @@ use crate::{
 	ReadySolution, RoundSnapshot, SolutionAccuracyOf, SolutionOf, SolutionOrSnapshotSize, Weight,
 };
 use codec::Encode;
-use frame_election_provider_support::{NposSolution, NposSolver, PerThing128, VoteWeight};
+use frame_election_provider_support::{
+	miner::{Miner as BaseMiner, MinerError as BaseMinerError},
+	NposSolution, NposSolver, PerThing128,
+};
 use frame_support::{
 	dispatch::DispatchResult,
 	ensure,
 	traits::{DefensiveResult, Get},
-	BoundedVec,
 };
 use frame_system::offchain::SubmitTransaction;
-use scale_info::TypeInfo;
 use sp_npos_elections::{
-	assignment_ratio_to_staked_normalized, assignment_staked_to_ratio_normalized, ElectionResult,
-	ElectionScore, EvaluateSupport,
+	assignment_ratio_to_staked_normalized, ElectionResult, ElectionScore, EvaluateSupport,
 };
 use sp_runtime::{
 	offchain::storage::{MutateStorageError, StorageValueRef},
-	DispatchError, SaturatedConversion,
+	DispatchError,
 };
 use sp_std::prelude::*;
 
@@ -53,11 +53,7 @@ pub(crate) const OFFCHAIN_CACHED_CALL: &[u8] = b"parity/multi-phase-unsigned-ele
 /// voted.
 pub type VoterOf<T> = frame_election_provider_support::VoterOf<<T as Config>::DataProvider>;
 
-/// Same as [`VoterOf`], but parameterized by the `MinerConfig`.
-pub type MinerVoterOf<T> = frame_election_provider_support::Voter<
-	<T as MinerConfig>::AccountId,
-	<T as MinerConfig>::MaxVotesPerVoter,
->;
+pub use frame_election_provider_support::miner::{MinerConfig, MinerVoterOf};
 
 /// The relative distribution of a voter's stake among the winning targets.
 pub type Assignment<T> =
@@ -102,6 +98,17 @@ impl From<sp_npos_elections::Error> for MinerError {
 	}
 }
 
+impl From<BaseMinerError> for MinerError {
+	fn from(e: BaseMinerError) -> Self {
+		match e {
+			BaseMinerError::NposElections(e) => MinerError::NposElections(e),
+			BaseMinerError::DataProvider(_) => MinerError::SnapshotUnAvailable,
+			BaseMinerError::NoMoreVoters => MinerError::NoMoreVoters,
+			BaseMinerError::Solver => MinerError::Solver,
+		}
+	}
+}
+
 impl From<FeasibilityError> for MinerError {
 	fn from(e: FeasibilityError) -> Self {
 		MinerError::Feasibility(e)
@@ -167,11 +174,13 @@ impl<T: Config> Pallet<T> {
 		let RoundSnapshot { voters, targets } =
 			Self::snapshot().ok_or(MinerError::SnapshotUnAvailable)?;
 		let desired_targets = Self::desired_targets().ok_or(MinerError::SnapshotUnAvailable)?;
+		let snapshot_fingerprint =
+			Self::snapshot_fingerprint().ok_or(MinerError::SnapshotUnAvailable)?;
 		let (solution, score, size) = Miner::<T::MinerConfig>::mine_solution_with_snapshot::<
 			T::Solver,
 		>(voters, targets, desired_targets)?;
 		let round = Self::round();
-		Ok((RawSolution { solution, score, round }, size))
+		Ok((RawSolution { solution, score, round, snapshot_fingerprint }, size))
 	}
 
 	/// Attempt to restore a solution from cache. Otherwise, compute it fresh. Either way, submit
@@ -366,335 +375,79 @@ impl<T: Config> Pallet<T> {
 	}
 }
 
-/// Configurations for a miner that comes with this pallet.
-pub trait MinerConfig {
-	/// The account id type.
-	type AccountId: Ord + Clone + codec::Codec + sp_std::fmt::Debug;
-	/// The solution that the miner is mining.
-	type Solution: codec::Codec
-		+ Default
-		+ PartialEq
-		+ Eq
-		+ Clone
-		+ sp_std::fmt::Debug
-		+ Ord
-		+ NposSolution
-		+ TypeInfo;
-	/// Maximum number of votes per voter in the snapshots.
-	type MaxVotesPerVoter;
-	/// Maximum length of the solution that the miner is allowed to generate.
-	///
-	/// Solutions are trimmed to respect this.
-	type MaxLength: Get<u32>;
-	/// Maximum weight of the solution that the miner is allowed to generate.
-	///
-	/// Solutions are trimmed to respect this.
-	///
-	/// The weight is computed using `solution_weight`.
-	type MaxWeight: Get<Weight>;
-	/// The maximum number of winners that can be elected.
-	type MaxWinners: Get<u32>;
-	/// Something that can compute the weight of a solution.
-	///
-	/// This weight estimate is then used to trim the solution, based on [`MinerConfig::MaxWeight`].
-	fn solution_weight(voters: u32, targets: u32, active_voters: u32, degree: u32) -> Weight;
-}
-
 /// A base miner, suitable to be used for both signed and unsigned submissions.
+///
+/// This is a thin wrapper around [`BaseMiner`] that maps its errors into [`MinerError`], and
+/// additionally provides the feasibility check of this pallet.
 pub struct Miner<T: MinerConfig>(sp_std::marker::PhantomData<T>);
 impl<T: MinerConfig> Miner<T> {
 	/// Same as [`Pallet::mine_solution`], but the input snapshot data must be given.
+	///
+	/// See [`BaseMiner::mine_solution_with_snapshot`].
 	pub fn mine_solution_with_snapshot<S>(
-		voters: Vec<(T::AccountId, VoteWeight, BoundedVec<T::AccountId, T::MaxVotesPerVoter>)>,
+		voters: Vec<MinerVoterOf<T>>,
 		targets: Vec<T::AccountId>,
 		desired_targets: u32,
 	) -> Result<(SolutionOf<T>, ElectionScore, SolutionOrSnapshotSize), MinerError>
 	where
 		S: NposSolver<AccountId = T::AccountId>,
 	{
-		S::solve(desired_targets as usize, targets.clone(), voters.clone())
-			.map_err(|e| {
-				log_no_system!(error, "solver error: {:?}", e);
-				MinerError::Solver
-			})
-			.and_then(|e| {
-				Self::prepare_election_result_with_snapshot::<S::Accuracy>(
-					e,
-					voters,
-					targets,
-					desired_targets,
-				)
-			})
+		BaseMiner::<T>::mine_solution_with_snapshot::<S>(voters, targets, desired_targets)
+			.map_err(Into::into)
 	}
 
 	/// Convert a raw solution from [`sp_npos_elections::ElectionResult`] to [`RawSolution`], which
 	/// is ready to be submitted to the chain.
 	///
-	/// Will always reduce the solution as well.
+	/// See [`BaseMiner::prepare_election_result_with_snapshot`].
 	pub fn prepare_election_result_with_snapshot<Accuracy: PerThing128>(
 		election_result: ElectionResult<T::AccountId, Accuracy>,
-		voters: Vec<(T::AccountId, VoteWeight, BoundedVec<T::AccountId, T::MaxVotesPerVoter>)>,
+		voters: Vec<MinerVoterOf<T>>,
 		targets: Vec<T::AccountId>,
 		desired_targets: u32,
 	) -> Result<(SolutionOf<T>, ElectionScore, SolutionOrSnapshotSize), MinerError> {
-		// now make some helper closures.
-		let cache = helpers::generate_voter_cache::<T>(&voters);
-		let voter_index = helpers::voter_index_fn::<T>(&cache);
-		let target_index = helpers::target_index_fn::<T>(&targets);
-		let voter_at = helpers::voter_at_fn::<T>(&voters);
-		let target_at = helpers::target_at_fn::<T>(&targets);
-		let stake_of = helpers::stake_of_fn::<T>(&voters, &cache);
-
-		// Compute the size of a solution comprised of the selected arguments.
-		//
-		// This function completes in `O(edges)`; it's expensive, but linear.
-		let encoded_size_of = |assignments: &[IndexAssignmentOf<T>]| {
-			SolutionOf::<T>::try_from(assignments).map(|s| s.encoded_size())
-		};
-
-		let ElectionResult { assignments, winners: _ } = election_result;
-
-		// Reduce (requires round-trip to staked form)
-		let sorted_assignments = {
-			// convert to staked and reduce.
-			let mut staked = assignment_ratio_to_staked_normalized(assignments, &stake_of)?;
-
-			// we reduce before sorting in order to ensure that the reduction process doesn't
-			// accidentally change the sort order
-			sp_npos_elections::reduce(&mut staked);
-
-			// Sort the assignments by reversed voter stake. This ensures that we can efficiently
-			// truncate the list.
-			staked.sort_by_key(
-				|sp_npos_elections::StakedAssignment::<T::AccountId> { who, .. }| {
-					// though staked assignments are expressed in terms of absolute stake, we'd
-					// still need to iterate over all votes in order to actually compute the total
-					// stake. it should be faster to look it up from the cache.
-					let stake = cache
-						.get(who)
-						.map(|idx| {
-							let (_, stake, _) = voters[*idx];
-							stake
-						})
-						.unwrap_or_default();
-					sp_std::cmp::Reverse(stake)
-				},
-			);
-
-			// convert back.
-			assignment_staked_to_ratio_normalized(staked)?
-		};
-
-		// convert to `IndexAssignment`. This improves the runtime complexity of repeatedly
-		// converting to `Solution`.
-		let mut index_assignments = sorted_assignments
-			.into_iter()
-			.map(|assignment| IndexAssignmentOf::<T>::new(&assignment, &voter_index, &target_index))
-			.collect::<Result<Vec<_>, _>>()?;
-
-		// trim assignments list for weight and length.
-		let size =
-			SolutionOrSnapshotSize { voters: voters.len() as u32, targets: targets.len() as u32 };
-		Self::trim_assignments_weight(
+		BaseMiner::<T>::prepare_election_result_with_snapshot(
+			election_result,
+			voters,
+			targets,
 			desired_targets,
-			size,
-			T::MaxWeight::get(),
-			&mut index_assignments,
-		);
-		Self::trim_assignments_length(
-			T::MaxLength::get(),
-			&mut index_assignments,
-			&encoded_size_of,
-		)?;
-
-		// now make solution.
-		let solution = SolutionOf::<T>::try_from(&index_assignments)?;
-
-		// re-calc score.
-		let score = solution.clone().score(stake_of, voter_at, target_at)?;
-
-		Ok((solution, score, size))
+		)
+		.map_err(Into::into)
 	}
 
 	/// Greedily reduce the size of the solution to fit into the block w.r.t length.
 	///
-	/// The length of the solution is largely a function of the number of voters. The number of
-	/// winners cannot be changed. Thus, to reduce the solution size, we need to strip voters.
-	///
-	/// Note that this solution is already computed, and winners are elected based on the merit of
-	/// the total stake in the system. Nevertheless, some of the voters may be removed here.
-	///
-	/// Sometimes, removing a voter can cause a validator to also be implicitly removed, if
-	/// that voter was the only backer of that winner. In such cases, this solution is invalid,
-	/// which will be caught prior to submission.
-	///
-	/// The score must be computed **after** this step. If this step reduces the score too much,
-	/// then the solution must be discarded.
+	/// See [`BaseMiner::trim_assignments_length`].
 	pub fn trim_assignments_length(
 		max_allowed_length: u32,
 		assignments: &mut Vec<IndexAssignmentOf<T>>,
 		encoded_size_of: impl Fn(&[IndexAssignmentOf<T>]) -> Result<usize, sp_npos_elections::Error>,
 	) -> Result<(), MinerError> {
-		// Perform a binary search for the max subset of which can fit into the allowed
-		// length. Having discovered that, we can truncate efficiently.
-		let max_allowed_length: usize = max_allowed_length.saturated_into();
-		let mut high = assignments.len();
-		let mut low = 0;
-
-		// not much we can do if assignments are already empty.
-		if high == low {
-			return Ok(())
-		}
-
-		while high - low > 1 {
-			let test = (high + low) / 2;
-			if encoded_size_of(&assignments[..test])? <= max_allowed_length {
-				low = test;
-			} else {
-				high = test;
-			}
-		}
-		let maximum_allowed_voters = if low < assignments.len() &&
-			encoded_size_of(&assignments[..low + 1])? <= max_allowed_length
-		{
-			low + 1
-		} else {
-			low
-		};
-
-		// ensure our post-conditions are correct
-		debug_assert!(
-			encoded_size_of(&assignments[..maximum_allowed_voters]).unwrap() <= max_allowed_length
-		);
-		debug_assert!(if maximum_allowed_voters < assignments.len() {
-			encoded_size_of(&assignments[..maximum_allowed_voters + 1]).unwrap() >
-				max_allowed_length
-		} else {
-			true
-		});
-
-		// NOTE: before this point, every access was immutable.
-		// after this point, we never error.
-		// check before edit.
-
-		log_no_system!(
-			debug,
-			"from {} assignments, truncating to {} for length, removing {}",
-			assignments.len(),
-			maximum_allowed_voters,
-			assignments.len().saturating_sub(maximum_allowed_voters),
-		);
-		assignments.truncate(maximum_allowed_voters);
-
-		Ok(())
+		BaseMiner::<T>::trim_assignments_length(max_allowed_length, assignments, encoded_size_of)
+			.map_err(Into::into)
 	}
 
 	/// Greedily reduce the size of the solution to fit into the block w.r.t. weight.
 	///
-	/// The weight of the solution is foremost a function of the number of voters (i.e.
-	/// `assignments.len()`). Aside from this, the other components of the weight are invariant. The
-	/// number of winners shall not be changed (otherwise the solution is invalid) and the
-	/// `ElectionSize` is merely a representation of the total number of stakers.
-	///
-	/// Thus, we reside to stripping away some voters from the `assignments`.
-	///
-	/// Note that the solution is already computed, and the winners are elected based on the merit
-	/// of the entire stake in the system. Nonetheless, some of the voters will be removed further
-	/// down the line.
-	///
-	/// Indeed, the score must be computed **after** this step. If this step reduces the score too
-	/// much or remove a winner, then the solution must be discarded **after** this step.
+	/// See [`BaseMiner::trim_assignments_weight`].
 	pub fn trim_assignments_weight(
 		desired_targets: u32,
 		size: SolutionOrSnapshotSize,
 		max_weight: Weight,
 		assignments: &mut Vec<IndexAssignmentOf<T>>,
 	) {
-		let maximum_allowed_voters =
-			Self::maximum_voter_for_weight(desired_targets, size, max_weight);
-		let removing: usize =
-			assignments.len().saturating_sub(maximum_allowed_voters.saturated_into());
-		log_no_system!(
-			debug,
-			"from {} assignments, truncating to {} for weight, removing {}",
-			assignments.len(),
-			maximum_allowed_voters,
-			removing,
-		);
-		assignments.truncate(maximum_allowed_voters as usize);
+		BaseMiner::<T>::trim_assignments_weight(desired_targets, size, max_weight, assignments)
 	}
 
 	/// Find the maximum `len` that a solution can have in order to fit into the block weight.
 	///
-	/// This only returns a value between zero and `size.nominators`.
+	/// See [`BaseMiner::maximum_voter_for_weight`].
 	pub fn maximum_voter_for_weight(
 		desired_winners: u32,
 		size: SolutionOrSnapshotSize,
 		max_weight: Weight,
 	) -> u32 {
-		if size.voters < 1 {
-			return size.voters
-		}
-
-		let max_voters = size.voters.max(1);
-		let mut voters = max_voters;
-
-		// helper closures.
-		let weight_with = |active_voters: u32| -> Weight {
-			T::solution_weight(size.voters, size.targets, active_voters, desired_winners)
-		};
-
-		let next_voters = |current_weight: Weight, voters: u32, step: u32| -> Result<u32, ()> {
-			if current_weight.all_lt(max_weight) {
-				let next_voters = voters.checked_add(step);
-				match next_voters {
-					Some(voters) if voters < max_voters => Ok(voters),
-					_ => Err(()),
-				}
-			} else if current_weight.any_gt(max_weight) {
-				voters.checked_sub(step).ok_or(())
-			} else {
-				// If any of the constituent weights is equal to the max weight, we're at max
-				Ok(voters)
-			}
-		};
-
-		// First binary-search the right amount of voters
-		let mut step = voters / 2;
-		let mut current_weight = weight_with(voters);
-
-		while step > 0 {
-			match next_voters(current_weight, voters, step) {
-				// proceed with the binary search
-				Ok(next) if next != voters => {
-					voters = next;
-				},
-				// we are out of bounds, break out of the loop.
-				Err(()) => break,
-				// we found the right value - early exit the function.
-				Ok(next) => return next,
-			}
-			step /= 2;
-			current_weight = weight_with(voters);
-		}
-
-		// Time to finish. We might have reduced less than expected due to rounding error. Increase
-		// one last time if we have any room left, the reduce until we are sure we are below limit.
-		while voters < max_voters && weight_with(voters + 1).all_lt(max_weight) {
-			voters += 1;
-		}
-		while voters.checked_sub(1).is_some() && weight_with(voters).any_gt(max_weight) {
-			voters -= 1;
-		}
-
-		let final_decision = voters.min(size.voters);
-		debug_assert!(
-			weight_with(final_decision).all_lte(max_weight),
-			"weight_with({}) <= {}",
-			final_decision,
-			max_weight,
-		);
-		final_decision
+		BaseMiner::<T>::maximum_voter_for_weight(desired_winners, size, max_weight)
 	}
 
 	/// Checks the feasibility of a solution.
@@ -703,14 +456,20 @@ impl<T: MinerConfig> Miner<T> {
 		compute: ElectionCompute,
 		desired_targets: u32,
 		snapshot: RoundSnapshot<T::AccountId, MinerVoterOf<T>>,
+		current_snapshot_fingerprint: [u8; 32],
 		current_round: u32,
 		minimum_untrusted_score: Option<ElectionScore>,
 	) -> Result<ReadySolution<T::AccountId, T::MaxWinners>, FeasibilityError> {
-		let RawSolution { solution, score, round } = raw_solution;
+		let RawSolution { solution, score, round, snapshot_fingerprint } = raw_solution;
 		let RoundSnapshot { voters: snapshot_voters, targets: snapshot_targets } = snapshot;
 
 		// First, check round.
 		ensure!(current_round == round, FeasibilityError::InvalidRound);
+		// Then, check that it was computed against the current snapshot.
+		ensure!(
+			current_snapshot_fingerprint == snapshot_fingerprint,
+			FeasibilityError::StaleSnapshot
+		);
 
 		// Winners are not directly encoded in the solution.
 		let winners = solution.unique_targets();
@@ -752,8 +511,8 @@ impl<T: MinerConfig> Miner<T> {
 			let snapshot_index =
 				voter_index(&assignment.who).ok_or(FeasibilityError::InvalidVoter)?;
 			// Defensive-only: index comes from the snapshot, must exist.
-			let (_voter, _stake, targets) =
-				snapshot_voters.get(snapshot_index).ok_or(FeasibilityError::InvalidVoter)?;
+			let targets =
+				&snapshot_voters.get(snapshot_index).ok_or(FeasibilityError::InvalidVoter)?.votes;
 
 			// Check that all of the targets are valid based on the snapshot.
 			if assignment.distribution.iter().any(|(d, _)| !targets.contains(d)) {
@@ -992,7 +751,7 @@ mod tests {
 	use sp_runtime::{
 		offchain::storage_lock::{BlockAndTime, StorageLock},
 		traits::ValidateUnsigned,
-		ModuleError, PerU16, Perbill,
+		ModuleError, PerU16, Perbill, SaturatedConversion,
 	};
 
 	type Assignment = crate::unsigned::Assignment<Runtime>;
@@ -1352,7 +1111,12 @@ mod tests {
 						desired_targets,
 					)
 					.unwrap();
-				let solution = RawSolution { solution: raw, score, round: MultiPhase::round() };
+				let solution = RawSolution {
+					solution: raw,
+					score,
+					round: MultiPhase::round(),
+					snapshot_fingerprint: MultiPhase::snapshot_fingerprint().unwrap(),
+				};
 				assert_ok!(MultiPhase::unsigned_pre_dispatch_checks(&solution));
 				assert_ok!(MultiPhase::submit_unsigned(
 					RuntimeOrigin::none(),
@@ -1380,7 +1144,12 @@ mod tests {
 					desired_targets,
 				)
 				.unwrap();
-				let solution = RawSolution { solution: raw, score, round: MultiPhase::round() };
+				let solution = RawSolution {
+					solution: raw,
+					score,
+					round: MultiPhase::round(),
+					snapshot_fingerprint: MultiPhase::snapshot_fingerprint().unwrap(),
+				};
 				// 12 is not 50% more than 10
 				assert_eq!(solution.score.minimal_stake, 12);
 				assert_noop!(
@@ -1410,7 +1179,12 @@ mod tests {
 						desired_targets,
 					)
 					.unwrap();
-				let solution = RawSolution { solution: raw, score, round: MultiPhase::round() };
+				let solution = RawSolution {
+					solution: raw,
+					score,
+					round: MultiPhase::round(),
+					snapshot_fingerprint: MultiPhase::snapshot_fingerprint().unwrap(),
+				};
 				assert_eq!(solution.score.minimal_stake, 17);
 
 				// and it is fine
@@ -1823,7 +1597,7 @@ mod tests {
 			let count = assignments.len();
 			let min_stake_voter = voters
 				.iter()
-				.map(|(id, weight, _)| (weight, id))
+				.map(|voter| (voter.weight, &voter.who))
 				.min()
 				.and_then(|(_, id)| voter_index(id))
 				.unwrap();
@@ -147,17 +147,19 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	/// Proof Skipped: ElectionProviderMultiPhase DesiredTargets (max_values: Some(1), max_size: None, mode: Measured)
 	/// Storage: ElectionProviderMultiPhase Snapshot (r:0 w:1)
 	/// Proof Skipped: ElectionProviderMultiPhase Snapshot (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: ElectionProviderMultiPhase SnapshotFingerprint (r:0 w:1)
+	/// Proof Skipped: ElectionProviderMultiPhase SnapshotFingerprint (max_values: Some(1), max_size: None, mode: Measured)
 	/// The range of component `v` is `[1000, 2000]`.
 	/// The range of component `t` is `[500, 1000]`.
 	fn create_snapshot_internal(v: u32, _t: u32, ) -> Weight {
 		// Proof Size summary in bytes:
 		//  Measured:  `0`
 		//  Estimated: `0`
-		// Minimum execution time: 262_360_000 picoseconds.
-		Weight::from_parts(279_313_000, 0)
-			// Standard Error: 2_384
-			.saturating_add(Weight::from_parts(176_415, 0).saturating_mul(v.into()))
-			.saturating_add(T::DbWeight::get().writes(3_u64))
+		// Minimum execution time: 773_150_000 picoseconds.
+		Weight::from_parts(813_918_000, 0)
+			// Standard Error: 3_544
+			.saturating_add(Weight::from_parts(262_262, 0).saturating_mul(v.into()))
+			.saturating_add(T::DbWeight::get().writes(4_u64))
 	}
 	/// Storage: ElectionProviderMultiPhase SignedSubmissionIndices (r:1 w:1)
 	/// Proof Skipped: ElectionProviderMultiPhase SignedSubmissionIndices (max_values: Some(1), max_size: None, mode: Measured)
@@ -177,6 +179,8 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	/// Proof Skipped: ElectionProviderMultiPhase DesiredTargets (max_values: Some(1), max_size: None, mode: Measured)
 	/// Storage: ElectionProviderMultiPhase Snapshot (r:0 w:1)
 	/// Proof Skipped: ElectionProviderMultiPhase Snapshot (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: ElectionProviderMultiPhase SnapshotFingerprint (r:0 w:1)
+	/// Proof Skipped: ElectionProviderMultiPhase SnapshotFingerprint (max_values: Some(1), max_size: None, mode: Measured)
 	/// The range of component `a` is `[500, 800]`.
 	/// The range of component `d` is `[200, 400]`.
 	fn elect_queued(a: u32, d: u32, ) -> Weight {
@@ -188,7 +192,7 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			// Standard Error: 4_763
 			.saturating_add(Weight::from_parts(279_812, 0).saturating_mul(a.into()))
 			.saturating_add(T::DbWeight::get().reads(7_u64))
-			.saturating_add(T::DbWeight::get().writes(8_u64))
+			.saturating_add(T::DbWeight::get().writes(9_u64))
 			.saturating_add(Weight::from_parts(0, 768).saturating_mul(a.into()))
 			.saturating_add(Weight::from_parts(0, 49).saturating_mul(d.into()))
 	}
@@ -356,17 +360,19 @@ impl WeightInfo for () {
 	/// Proof Skipped: ElectionProviderMultiPhase DesiredTargets (max_values: Some(1), max_size: None, mode: Measured)
 	/// Storage: ElectionProviderMultiPhase Snapshot (r:0 w:1)
 	/// Proof Skipped: ElectionProviderMultiPhase Snapshot (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: ElectionProviderMultiPhase SnapshotFingerprint (r:0 w:1)
+	/// Proof Skipped: ElectionProviderMultiPhase SnapshotFingerprint (max_values: Some(1), max_size: None, mode: Measured)
 	/// The range of component `v` is `[1000, 2000]`.
 	/// The range of component `t` is `[500, 1000]`.
 	fn create_snapshot_internal(v: u32, _t: u32, ) -> Weight {
 		// Proof Size summary in bytes:
 		//  Measured:  `0`
 		//  Estimated: `0`
-		// Minimum execution time: 262_360_000 picoseconds.
-		Weight::from_parts(279_313_000, 0)
-			// Standard Error: 2_384
-			.saturating_add(Weight::from_parts(176_415, 0).saturating_mul(v.into()))
-			.saturating_add(RocksDbWeight::get().writes(3_u64))
+		// Minimum execution time: 773_150_000 picoseconds.
+		Weight::from_parts(813_918_000, 0)
+			// Standard Error: 3_544
+			.saturating_add(Weight::from_parts(262_262, 0).saturating_mul(v.into()))
+			.saturating_add(RocksDbWeight::get().writes(4_u64))
 	}
 	/// Storage: ElectionProviderMultiPhase SignedSubmissionIndices (r:1 w:1)
 	/// Proof Skipped: ElectionProviderMultiPhase SignedSubmissionIndices (max_values: Some(1), max_size: None, mode: Measured)
@@ -386,6 +392,8 @@ impl WeightInfo for () {
 	/// Proof Skipped: ElectionProviderMultiPhase DesiredTargets (max_values: Some(1), max_size: None, mode: Measured)
 	/// Storage: ElectionProviderMultiPhase Snapshot (r:0 w:1)
 	/// Proof Skipped: ElectionProviderMultiPhase Snapshot (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: ElectionProviderMultiPhase SnapshotFingerprint (r:0 w:1)
+	/// Proof Skipped: ElectionProviderMultiPhase SnapshotFingerprint (max_values: Some(1), max_size: None, mode: Measured)
 	/// The range of component `a` is `[500, 800]`.
 	/// The range of component `d` is `[200, 400]`.
 	fn elect_queued(a: u32, d: u32, ) -> Weight {
@@ -397,7 +405,7 @@ impl WeightInfo for () {
 			// Standard Error: 4_763
 			.saturating_add(Weight::from_parts(279_812, 0).saturating_mul(a.into()))
 			.saturating_add(RocksDbWeight::get().reads(7_u64))
-			.saturating_add(RocksDbWeight::get().writes(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(9_u64))
 			.saturating_add(Weight::from_parts(0, 768).saturating_mul(a.into()))
 			.saturating_add(Weight::from_parts(0, 49).saturating_mul(d.into()))
 	}
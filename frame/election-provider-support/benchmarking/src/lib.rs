@@ -22,8 +22,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::Decode;
-use frame_benchmarking::v1::{benchmarks, Vec};
-use frame_election_provider_support::{NposSolver, PhragMMS, SequentialPhragmen};
+use frame_benchmarking::v1::{benchmarks, sp_std::collections::btree_map::BTreeMap, Vec};
+use frame_election_provider_support::{
+	evaluation, pjr, BalancingConfig, Get, NposSolver, PhragMMS, SequentialPhragmen, WeightedRandom,
+};
+use sp_npos_elections::{assignment_ratio_to_staked_normalized, to_supports};
 
 pub struct Pallet<T: Config>(frame_system::Pallet<T>);
 pub trait Config: frame_system::Config {}
@@ -33,6 +36,23 @@ const TARGETS: [u32; 2] = [500, 1_000];
 const VOTES_PER_VOTER: [u32; 2] = [5, 16];
 
 const SEED: u32 = 999;
+
+/// Balancing of the solvers benchmarked with balancing enabled.
+pub struct Balancing;
+impl Get<Option<BalancingConfig>> for Balancing {
+	fn get() -> Option<BalancingConfig> {
+		Some(BalancingConfig { iterations: 10, tolerance: 0 })
+	}
+}
+
+/// Seed of the [`WeightedRandom`] solver benchmark.
+pub struct RandomSeed;
+impl Get<[u8; 32]> for RandomSeed {
+	fn get() -> [u8; 32] {
+		[0u8; 32]
+	}
+}
+
 fn set_up_voters_targets<AccountId: Decode + Clone>(
 	voters_len: u32,
 	targets_len: u32,
@@ -88,4 +108,107 @@ benchmarks! {
 				::solve(d as usize, targets, voters).is_ok()
 		);
 	}
+
+	weighted_random {
+		// number of votes in snapshot.
+		let v in (VOTERS[0]) .. VOTERS[1];
+		// number of targets in snapshot.
+		let t in (TARGETS[0]) .. TARGETS[1];
+		// number of votes per voter (ie the degree).
+		let d in (VOTES_PER_VOTER[0]) .. VOTES_PER_VOTER[1];
+
+		let (voters, targets) = set_up_voters_targets::<T::AccountId>(v, t, d as usize);
+	}: {
+		assert!(
+			WeightedRandom::<T::AccountId, sp_runtime::Perbill, RandomSeed>
+				::solve(d as usize, targets, voters).is_ok()
+		);
+	}
+
+	phragmms_balanced {
+		// number of votes in snapshot.
+		let v in (VOTERS[0]) .. VOTERS[1];
+		// number of targets in snapshot.
+		let t in (TARGETS[0]) .. TARGETS[1];
+		// number of votes per voter (ie the degree).
+		let d in (VOTES_PER_VOTER[0]) .. VOTES_PER_VOTER[1];
+
+		let (voters, targets) = set_up_voters_targets::<T::AccountId>(v, t, d as usize);
+	}: {
+		assert!(
+			PhragMMS::<T::AccountId, sp_runtime::Perbill, Balancing>
+				::solve(d as usize, targets, voters).is_ok()
+		);
+	}
+
+	reduce {
+		// number of votes in snapshot.
+		let v in (VOTERS[0]) .. VOTERS[1];
+		// number of targets in snapshot.
+		let t in (TARGETS[0]) .. TARGETS[1];
+		// number of votes per voter (ie the degree).
+		let d in (VOTES_PER_VOTER[0]) .. VOTES_PER_VOTER[1];
+
+		let (voters, targets) = set_up_voters_targets::<T::AccountId>(v, t, d as usize);
+		let result = SequentialPhragmen::<T::AccountId, sp_runtime::Perbill>
+			::solve(d as usize, targets, voters)
+			.unwrap();
+		let stake_of = |_: &T::AccountId| 1_000;
+		let mut staked = assignment_ratio_to_staked_normalized(result.assignments, stake_of).unwrap();
+	}: {
+		sp_npos_elections::reduce(&mut staked);
+	}
+
+	feasibility_check {
+		// number of votes in snapshot.
+		let v in (VOTERS[0]) .. VOTERS[1];
+		// number of targets in snapshot.
+		let t in (TARGETS[0]) .. TARGETS[1];
+		// number of votes per voter (ie the degree).
+		let d in (VOTES_PER_VOTER[0]) .. VOTES_PER_VOTER[1];
+
+		let (voters, targets) = set_up_voters_targets::<T::AccountId>(v, t, d as usize);
+		let result = SequentialPhragmen::<T::AccountId, sp_runtime::Perbill>
+			::solve(d as usize, targets, voters)
+			.unwrap();
+		let stake_of = |_: &T::AccountId| 1_000;
+	}: {
+		let staked = assignment_ratio_to_staked_normalized(result.assignments, stake_of).unwrap();
+		// this is what `evaluation::score_of_weight` relies on.
+		let (_, score) = evaluation::score_of(&staked);
+		assert!(score.minimal_stake > 0);
+	}
+
+	pjr_check {
+		// number of votes in snapshot.
+		let v in (VOTERS[0]) .. VOTERS[1];
+		// number of targets in snapshot.
+		let t in (TARGETS[0]) .. TARGETS[1];
+		// number of votes per voter (ie the degree).
+		let d in (VOTES_PER_VOTER[0]) .. VOTES_PER_VOTER[1];
+
+		// unlike the other benchmarks, all `t` targets are candidates, and only `d` of them are
+		// elected: the check is done for each unelected candidate.
+		let targets = (0..t)
+			.map(|i| frame_benchmarking::account::<T::AccountId>("Target", i, SEED))
+			.collect::<Vec<_>>();
+		let voters = (0..v)
+			.map(|i| {
+				let voter = frame_benchmarking::account::<T::AccountId>("Voter", i, SEED);
+				let votes = (0..d).map(|j| targets[((i + j) % t) as usize].clone()).collect::<Vec<_>>();
+				(voter, 1_000 + i as u64, votes)
+			})
+			.collect::<Vec<_>>();
+		let result = SequentialPhragmen::<T::AccountId, sp_runtime::Perbill>
+			::solve(d as usize, targets.clone(), voters.clone())
+			.unwrap();
+		let stakes = voters.iter().map(|(who, stake, _)| (who.clone(), *stake)).collect::<BTreeMap<_, _>>();
+		let stake_of = |who: &T::AccountId| stakes.get(who).copied().unwrap_or_default();
+		let staked = assignment_ratio_to_staked_normalized(result.assignments, stake_of).unwrap();
+		let supports = to_supports(&staked);
+	}: {
+		// a solution that does not satisfy PJR only makes the check return early, i.e. cheaper, so
+		// the outcome is not asserted.
+		let _ = pjr::pjr_check(&supports, targets, voters);
+	}
 }
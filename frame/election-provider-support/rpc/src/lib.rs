@@ -0,0 +1,192 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! RPC interface to preview and forecast elections, backed by the [`ElectionRuntimeApi`].
+
+use std::sync::Arc;
+
+use frame_election_provider_support::onchain::{DryRunError, ElectionForecast};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_npos_elections::ElectionScore;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+use sp_weights::Weight;
+
+pub use frame_election_provider_support_runtime_api::ElectionApi as ElectionRuntimeApi;
+
+/// Outcome of a dry-run election.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRun {
+	/// Number of winners.
+	pub winners: u32,
+	/// Score of the election.
+	pub score: ElectionScore,
+	/// Estimated weight of the election.
+	pub weight: Weight,
+}
+
+/// Forecast of the next election.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Forecast<BlockNumber> {
+	/// Block of the next election, as predicted by the data provider.
+	pub next_election: BlockNumber,
+	/// Number of voters in the snapshot, if the election was to happen now.
+	pub voters: u32,
+	/// Number of targets in the snapshot, if the election was to happen now.
+	pub targets: u32,
+	/// Maximum number of voters in the snapshot.
+	pub voters_bound: u32,
+	/// Maximum number of targets in the snapshot.
+	pub targets_bound: u32,
+}
+
+impl<BlockNumber> From<ElectionForecast<BlockNumber>> for Forecast<BlockNumber> {
+	fn from(forecast: ElectionForecast<BlockNumber>) -> Self {
+		let ElectionForecast { next_election, voters, targets, voters_bound, targets_bound } =
+			forecast;
+		Forecast { next_election, voters, targets, voters_bound, targets_bound }
+	}
+}
+
+#[rpc(client, server)]
+pub trait ElectionApi<BlockHash, BlockNumber> {
+	/// Dry-run the next election, with the given bounds and `desired_targets` instead of the
+	/// configured ones, if any.
+	#[method(name = "election_dryRun")]
+	fn dry_run(
+		&self,
+		voters_bound: Option<u32>,
+		targets_bound: Option<u32>,
+		desired_targets: Option<u32>,
+		at: Option<BlockHash>,
+	) -> RpcResult<DryRun>;
+
+	/// Forecast the next election: when it happens, and the size of its snapshot.
+	#[method(name = "election_forecast")]
+	fn forecast(&self, at: Option<BlockHash>) -> RpcResult<Forecast<BlockNumber>>;
+}
+
+/// Provides RPC methods to preview and forecast elections.
+pub struct Election<C, B> {
+	/// Shared reference to the client.
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Election<C, B> {
+	/// Creates a new instance of the Election Rpc helper.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+	/// The election failed.
+	ElectionError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+			Error::ElectionError => 2,
+		}
+	}
+}
+
+fn runtime_error(error: impl ToString) -> CallError {
+	CallError::Custom(ErrorObject::owned(
+		Error::RuntimeError.into(),
+		"Unable to call the election runtime API.",
+		Some(error.to_string()),
+	))
+}
+
+fn election_error(error: DryRunError) -> CallError {
+	CallError::Custom(ErrorObject::owned(
+		Error::ElectionError.into(),
+		"Election failed.",
+		Some(format!("{:?}", error)),
+	))
+}
+
+impl<C, Block> ElectionApiServer<<Block as BlockT>::Hash, NumberFor<Block>> for Election<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: ElectionRuntimeApi<Block>,
+{
+	fn dry_run(
+		&self,
+		voters_bound: Option<u32>,
+		targets_bound: Option<u32>,
+		desired_targets: Option<u32>,
+		at: Option<Block::Hash>,
+	) -> RpcResult<DryRun> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let (winners, score, weight) = api
+			.dry_run_elect(at_hash, voters_bound, targets_bound, desired_targets)
+			.map_err(runtime_error)?
+			.map_err(election_error)?;
+		Ok(DryRun { winners, score, weight })
+	}
+
+	fn forecast(&self, at: Option<Block::Hash>) -> RpcResult<Forecast<NumberFor<Block>>> {
+		let api = self.client.runtime_api();
+		let at_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let forecast =
+			api.election_forecast(at_hash).map_err(runtime_error)?.map_err(election_error)?;
+		Ok(forecast.into())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn forecast_serialization_works() {
+		let forecast: Forecast<u32> = ElectionForecast {
+			next_election: 42,
+			voters: 10,
+			targets: 5,
+			voters_bound: 100,
+			targets_bound: 50,
+		}
+		.into();
+		let json = serde_json::to_string(&forecast).unwrap();
+		assert_eq!(
+			json,
+			r#"{"nextElection":42,"voters":10,"targets":5,"votersBound":100,"targetsBound":50}"#
+		);
+		assert_eq!(serde_json::from_str::<Forecast<u32>>(&json).unwrap(), forecast);
+	}
+}
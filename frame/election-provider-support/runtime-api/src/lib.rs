@@ -0,0 +1,46 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for previewing elections.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_election_provider_support::onchain::{DryRunError, DryRunOutcome, ElectionForecast};
+use sp_runtime::traits::NumberFor;
+
+sp_api::decl_runtime_apis! {
+	pub trait ElectionApi {
+		/// Dry-run the next election with the configured solver, without any side effect.
+		///
+		/// The given voter and target bounds, and `desired_targets`, are used instead of the
+		/// configured ones, if any.
+		///
+		/// Returns the number of winners, the score of the election and its estimated weight.
+		fn dry_run_elect(
+			voters_bound: Option<u32>,
+			targets_bound: Option<u32>,
+			desired_targets: Option<u32>,
+		) -> Result<DryRunOutcome, DryRunError>;
+
+		/// Forecast the next election: the block it is predicted at, and the size of its snapshot,
+		/// next to the bounds of the configured election provider.
+		///
+		/// The size is the one of the current snapshot, if any, else the one it would have if it
+		/// was taken now.
+		fn election_forecast() -> Result<ElectionForecast<NumberFor<Block>>, DryRunError>;
+	}
+}
@@ -0,0 +1,122 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Common fuzzing utils.
+
+// Each function will be used based on which fuzzer binary is being used.
+#![allow(dead_code)]
+
+use frame_election_provider_solution_type::generate_solution_type;
+use frame_election_provider_support::{miner::MinerConfig, IndexAssignmentOf, Weight};
+use frame_support::traits::ConstU32;
+use rand::{seq::SliceRandom, Rng, RngCore};
+use sp_arithmetic::Percent;
+
+/// The maximum number of votes per voter of [`TestSolution`].
+pub const MAX_VOTES: usize = 16;
+
+generate_solution_type!(
+	#[compact]
+	pub struct TestSolution::<
+		VoterIndex = u32,
+		TargetIndex = u16,
+		Accuracy = Percent,
+		MaxVoters = ConstU32::<2_000>,
+	>(16)
+);
+
+/// An index assignment of [`TestSolution`].
+pub type TestIndexAssignment = IndexAssignmentOf<TestSolution>;
+
+/// The weight of a single voter in [`Runtime::solution_weight`].
+pub const WEIGHT_PER_VOTER: u64 = 1_000;
+
+/// A minimal miner configuration, only used for its trimming helpers.
+pub struct Runtime;
+impl MinerConfig for Runtime {
+	type AccountId = u64;
+	type Solution = TestSolution;
+	type MaxVotesPerVoter = ConstU32<16>;
+	type MaxLength = ConstU32<{ u32::MAX }>;
+	type MaxWeight = ();
+	type MaxWinners = ConstU32<1_000>;
+
+	fn solution_weight(voters: u32, targets: u32, active_voters: u32, degree: u32) -> Weight {
+		Weight::from_parts(
+			(active_voters as u64).saturating_mul(WEIGHT_PER_VOTER),
+			(voters as u64 + targets as u64).saturating_add(degree as u64),
+		)
+	}
+}
+
+/// converts x into the range [a, b] in a pseudo-fair way.
+pub fn to_range(x: usize, a: usize, b: usize) -> usize {
+	// does not work correctly if b < 2 * a
+	assert!(b >= 2 * a);
+	let collapsed = x % b;
+	if collapsed >= a {
+		collapsed
+	} else {
+		collapsed + a
+	}
+}
+
+/// Generate `voter_count` random index assignments over `target_count` targets.
+///
+/// Each voter index is unique, each voter votes for distinct targets, and every distribution sums
+/// up to exactly one, so that the assignments survive a round-trip through
+/// [`TestSolution`] unchanged.
+pub fn generate_random_index_assignments(
+	voter_count: usize,
+	target_count: usize,
+	mut rng: impl RngCore,
+) -> Vec<TestIndexAssignment> {
+	assert!(target_count > 0 && target_count <= u16::MAX as usize);
+	let all_targets = (0..target_count as u16).collect::<Vec<_>>();
+
+	(0..voter_count as u32)
+		.map(|who| {
+			let edges = rng.gen_range(1..=MAX_VOTES.min(target_count));
+			let mut targets =
+				all_targets.choose_multiple(&mut rng, edges).cloned().collect::<Vec<_>>();
+			let last = targets.pop().expect("at least one edge is chosen; qed");
+
+			// give each but the last target a small non-zero share, such that their sum always
+			// stays strictly below one. The last target gets the remainder.
+			let max_share = 100 / edges as u8;
+			let mut used = 0u8;
+			let mut distribution = targets
+				.into_iter()
+				.map(|target| {
+					let share = rng.gen_range(1..max_share);
+					used += share;
+					(target, Percent::from_percent(share))
+				})
+				.collect::<Vec<_>>();
+			distribution.push((last, Percent::from_percent(100 - used)));
+
+			TestIndexAssignment { who, distribution }
+		})
+		.collect()
+}
+
+/// The encoded size of the solution built from `assignments`.
+pub fn encoded_size_of(
+	assignments: &[TestIndexAssignment],
+) -> Result<usize, sp_npos_elections::Error> {
+	TestSolution::try_from(assignments).map(|s| codec::Encode::encoded_size(&s))
+}
@@ -0,0 +1,79 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fuzzing for the round-trip of index assignments through a generated solution type.
+//!
+//! It ensures that a random set of `IndexAssignment`s can be converted into a solution, encoded
+//! and decoded again, and converted back into the exact same set of assignments. It also checks
+//! that the encoded solution never exceeds its declared `MaxEncodedLen`.
+//!
+//! # Running
+//!
+//! Run with `cargo hfuzz run solution_roundtrip`. `honggfuzz`.
+//!
+//! # Debugging a panic
+//!
+//! Once a panic is found, it can be debugged with
+//! `cargo hfuzz run-debug solution_roundtrip hfuzz_workspace/solution_roundtrip/*.fuzz`.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_election_provider_support::NposSolution;
+use honggfuzz::fuzz;
+use rand::{self, SeedableRng};
+
+mod common;
+use common::{generate_random_index_assignments, to_range, TestSolution};
+
+fn main() {
+	loop {
+		fuzz!(|data: (usize, usize, u64)| {
+			let (mut voter_count, mut target_count, seed) = data;
+			let rng = rand::rngs::SmallRng::seed_from_u64(seed);
+			target_count = to_range(target_count, 1, 1000);
+			voter_count = to_range(voter_count, 1, 2000);
+
+			let assignments = generate_random_index_assignments(voter_count, target_count, rng);
+			let solution = TestSolution::try_from(assignments.as_slice()).unwrap();
+			assert_eq!(solution.voter_count(), assignments.len());
+			assert_eq!(
+				solution.edge_count(),
+				assignments.iter().map(|a| a.distribution.len()).sum::<usize>()
+			);
+
+			// the codec must be lossless and respect its declared bound.
+			let encoded = solution.encode();
+			assert!(encoded.len() <= TestSolution::max_encoded_len());
+			let decoded = TestSolution::decode(&mut encoded.as_slice()).unwrap();
+			assert_eq!(decoded, solution);
+
+			// and so must be the conversion back into assignments, modulo ordering.
+			let mut back = decoded
+				.into_assignment(|v| Some(v as u64), |t| Some(t as u64))
+				.unwrap()
+				.into_iter()
+				.map(|a| (a.who, a.distribution.into_iter().map(|(t, p)| (t as u16, p)).collect()))
+				.collect::<Vec<(u64, Vec<_>)>>();
+			let mut original = assignments
+				.into_iter()
+				.map(|a| (a.who as u64, a.distribution))
+				.collect::<Vec<(u64, Vec<_>)>>();
+			back.sort();
+			original.sort();
+			assert_eq!(back, original);
+		});
+	}
+}
@@ -0,0 +1,103 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fuzzing for the solution trimming helpers of the miner.
+//!
+//! It ensures that `trim_assignments_length` always yields the largest prefix of the assignments
+//! whose solution fits into the given length, and that `trim_assignments_weight` always yields a
+//! prefix whose solution weight fits into the given weight.
+//!
+//! # Running
+//!
+//! Run with `cargo hfuzz run trim`. `honggfuzz`.
+//!
+//! # Debugging a panic
+//!
+//! Once a panic is found, it can be debugged with
+//! `cargo hfuzz run-debug trim hfuzz_workspace/trim/*.fuzz`.
+
+use frame_election_provider_support::{
+	miner::{Miner, MinerConfig, SolutionOrSnapshotSize},
+	Weight,
+};
+use honggfuzz::fuzz;
+use rand::{self, SeedableRng};
+
+mod common;
+use common::{encoded_size_of, generate_random_index_assignments, to_range, Runtime};
+
+fn main() {
+	loop {
+		fuzz!(|data: (usize, usize, u64, u32, u64)| {
+			let (mut voter_count, mut target_count, seed, max_length, max_weight) = data;
+			let rng = rand::rngs::SmallRng::seed_from_u64(seed);
+			target_count = to_range(target_count, 1, 1000);
+			voter_count = to_range(voter_count, 1, 2000);
+
+			let assignments = generate_random_index_assignments(voter_count, target_count, rng);
+			let full_size = encoded_size_of(&assignments).unwrap();
+
+			// trimming for length.
+			let max_length = max_length % (full_size as u32 * 2).max(1);
+			let mut trimmed = assignments.clone();
+			Miner::<Runtime>::trim_assignments_length(max_length, &mut trimmed, encoded_size_of)
+				.unwrap();
+			assert_eq!(trimmed[..], assignments[..trimmed.len()]);
+			// an empty solution still has a fixed encoded size, which no trimming can undercut.
+			assert!(
+				trimmed.is_empty() || encoded_size_of(&trimmed).unwrap() <= max_length as usize
+			);
+			if trimmed.len() < assignments.len() {
+				assert!(
+					encoded_size_of(&assignments[..trimmed.len() + 1]).unwrap() >
+						max_length as usize
+				);
+			}
+
+			// trimming for weight.
+			let size =
+				SolutionOrSnapshotSize { voters: voter_count as u32, targets: target_count as u32 };
+			let desired_targets = (target_count as u32).min(16);
+			let full_weight =
+				Runtime::solution_weight(size.voters, size.targets, size.voters, desired_targets);
+			let max_weight = Weight::from_parts(
+				max_weight % full_weight.ref_time().saturating_mul(2).max(1),
+				u64::MAX,
+			);
+			let mut trimmed = assignments.clone();
+			Miner::<Runtime>::trim_assignments_weight(
+				desired_targets,
+				size,
+				max_weight,
+				&mut trimmed,
+			);
+			assert_eq!(trimmed[..], assignments[..trimmed.len()]);
+			let weight_of = |active_voters: usize| {
+				Runtime::solution_weight(
+					size.voters,
+					size.targets,
+					active_voters as u32,
+					desired_targets,
+				)
+			};
+			assert!(trimmed.is_empty() || weight_of(trimmed.len()).all_lte(max_weight));
+			if trimmed.len() < assignments.len() {
+				assert!(!weight_of(trimmed.len() + 1).all_lt(max_weight));
+			}
+		});
+	}
+}
@@ -122,6 +122,31 @@ pub(crate) fn syn_err(message: &'static str) -> syn::Error {
 ///     >(8)
 /// );
 /// ```
+///
+/// ## Serde
+///
+/// If prefixed with `#[serde]`, the generated struct also derives `serde::{Serialize,
+/// Deserialize}`, only if the `std` feature of the crate calling the macro is enabled. This is
+/// meant for off-chain tooling that inspects or replays solutions. Indices and accuracies are
+/// serialized as plain numbers, e.g. `{"votes1":[[0,1]],"votes2":[[2,[[0,500000000]],1]]}`, which
+/// is easier to work with than SCALE bytes, even for `#[compact]` solutions.
+///
+/// ```
+/// # use frame_election_provider_solution_type::generate_solution_type;
+/// # use frame_election_provider_support::NposSolution;
+/// # use sp_arithmetic::per_things::Perbill;
+/// # use frame_support::traits::ConstU32;
+/// generate_solution_type!(
+///     #[compact]
+///     #[serde]
+///     pub struct TestSolutionSerde::<
+///          VoterIndex = u16,
+///          TargetIndex = u8,
+///          Accuracy = Perbill,
+///          MaxVoters = ConstU32::<10>,
+///     >(8)
+/// );
+/// ```
 #[proc_macro]
 pub fn generate_solution_type(item: TokenStream) -> TokenStream {
 	let solution_def = syn::parse_macro_input!(item as SolutionDef);
@@ -146,32 +171,33 @@ struct SolutionDef {
 	max_voters: syn::Type,
 	count: usize,
 	compact_encoding: bool,
+	serde: bool,
 }
 
-fn check_attributes(input: ParseStream) -> syn::Result<bool> {
-	let mut attrs = input.call(syn::Attribute::parse_outer).unwrap_or_default();
-	if attrs.len() > 1 {
-		let extra_attr = attrs.pop().expect("attributes vec with len > 1 can be popped");
-		return Err(syn::Error::new_spanned(
-			extra_attr,
-			"compact solution can accept only #[compact]",
-		))
-	}
-	if attrs.is_empty() {
-		return Ok(false)
-	}
-	let attr = attrs.pop().expect("attributes vec with len 1 can be popped.");
-	if attr.path().is_ident("compact") {
-		Ok(true)
-	} else {
-		Err(syn::Error::new_spanned(attr, "compact solution can accept only #[compact]"))
+/// Parse the optional `#[compact]` and `#[serde]` attributes, in this order.
+fn check_attributes(input: ParseStream) -> syn::Result<(bool, bool)> {
+	let attrs = input.call(syn::Attribute::parse_outer).unwrap_or_default();
+	let mut expected = ["compact", "serde"].into_iter();
+	let mut compact_encoding = false;
+	let mut serde = false;
+	for attr in attrs {
+		match expected.find(|name| attr.path().is_ident(name)) {
+			Some("compact") => compact_encoding = true,
+			Some(_) => serde = true,
+			None =>
+				return Err(syn::Error::new_spanned(
+					attr,
+					"compact solution can accept only #[compact] and #[serde], in this order",
+				)),
+		}
 	}
+	Ok((compact_encoding, serde))
 }
 
 impl Parse for SolutionDef {
 	fn parse(input: ParseStream) -> syn::Result<Self> {
-		// optional #[compact]
-		let compact_encoding = check_attributes(input)?;
+		// optional #[compact] and #[serde]
+		let (compact_encoding, serde) = check_attributes(input)?;
 
 		// <vis> struct <name>
 		let vis: syn::Visibility = input.parse()?;
@@ -230,6 +256,7 @@ impl Parse for SolutionDef {
 			max_voters,
 			count,
 			compact_encoding,
+			serde,
 		})
 	}
 }
@@ -30,6 +30,7 @@ pub(crate) fn generate(def: crate::SolutionDef) -> Result<TokenStream2> {
 		weight_type,
 		max_voters,
 		compact_encoding,
+		serde,
 	} = def;
 
 	if count <= 2 {
@@ -90,6 +91,18 @@ pub(crate) fn generate(def: crate::SolutionDef) -> Result<TokenStream2> {
 		)])
 	};
 
+	let maybe_serde = if serde {
+		quote! {
+			#[cfg_attr(
+				feature = "std",
+				derive(_fepsp::serde::Serialize, _fepsp::serde::Deserialize),
+				serde(crate = "_fepsp::serde"),
+			)]
+		}
+	} else {
+		quote!()
+	};
+
 	let struct_name = syn::Ident::new("solution", proc_macro2::Span::call_site());
 	let assignment_name = syn::Ident::new("all_assignments", proc_macro2::Span::call_site());
 
@@ -100,6 +113,7 @@ pub(crate) fn generate(def: crate::SolutionDef) -> Result<TokenStream2> {
 	Ok(quote! (
 		/// A struct to encode a election assignment in a compact way.
 		#derives_and_maybe_compact_encoding
+		#maybe_serde
 		#vis struct #ident { #single #rest }
 
 		use _fepsp::__OrInvalidIndex;
@@ -0,0 +1,171 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An [`ElectionDataProvider`] adapter that aggregates many voters into a single one.
+//!
+//! This is useful for pool-like pallets, whose many small delegators would otherwise each take
+//! a spot in the snapshot. Instead, they are presented to the election as a single voter, whose
+//! backing is split back to the individual delegators once the election is over.
+
+use crate::{
+	data_provider, ElectionDataProvider, ExtendedBalance, Get, Support, Supports, VoterOf,
+};
+use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
+
+#[cfg(any(feature = "runtime-benchmarks", test))]
+use crate::{BoundedVec, VoteWeight};
+
+/// Something that knows which voters are aggregated, and how to split the backing of an aggregated
+/// voter back to its members.
+pub trait VoterAggregator<AccountId> {
+	/// The aggregated voter that represents `who`, if `who` should be aggregated.
+	fn aggregate_of(who: &AccountId) -> Option<AccountId>;
+
+	/// Split the `backing` that `who` gave to a single winner among its members.
+	///
+	/// Returns `None` if `who` is not an aggregated voter. Otherwise, the returned backings should
+	/// sum up to `backing`.
+	fn split(
+		who: &AccountId,
+		backing: ExtendedBalance,
+	) -> Option<Vec<(AccountId, ExtendedBalance)>>;
+}
+
+/// Wraps the data provider `Inner`, and aggregates its voters with `A`.
+///
+/// All voters that [`VoterAggregator::aggregate_of`] maps to the same aggregate are replaced by a
+/// single voter, at the position of the first of them. Its weight is the sum of their weights and
+/// its votes are the union of their votes, truncated to `MaxVotesPerVoter` if need be. If the
+/// aggregate is itself a voter of `Inner`, it is merged in the same way.
+///
+/// Bounds are passed to `Inner` as-is, i.e. the aggregated voters might take less space than
+/// what the bound allows. Everything but the voters is the same as `Inner`.
+///
+/// The outcome of the election can be mapped back to the individual members with
+/// [`Self::split_supports`].
+pub struct AggregatedVoters<Inner, A>(PhantomData<(Inner, A)>);
+
+impl<Inner, A> AggregatedVoters<Inner, A>
+where
+	Inner: ElectionDataProvider,
+	A: VoterAggregator<Inner::AccountId>,
+{
+	/// Replace the backing of every aggregated voter in `supports` with that of its members, as
+	/// given by [`VoterAggregator::split`].
+	pub fn split_supports(supports: Supports<Inner::AccountId>) -> Supports<Inner::AccountId> {
+		supports
+			.into_iter()
+			.map(|(winner, Support { total, voters })| {
+				let voters = voters
+					.into_iter()
+					.flat_map(|(who, backing)| {
+						A::split(&who, backing).unwrap_or_else(|| vec![(who, backing)])
+					})
+					.collect();
+				(winner, Support { total, voters })
+			})
+			.collect()
+	}
+}
+
+impl<Inner, A> ElectionDataProvider for AggregatedVoters<Inner, A>
+where
+	Inner: ElectionDataProvider,
+	Inner::AccountId: Ord + Clone,
+	A: VoterAggregator<Inner::AccountId>,
+{
+	type AccountId = Inner::AccountId;
+	type BlockNumber = Inner::BlockNumber;
+	type MaxVotesPerVoter = Inner::MaxVotesPerVoter;
+
+	fn electable_targets(
+		maybe_max_len: Option<usize>,
+	) -> data_provider::Result<Vec<Self::AccountId>> {
+		Inner::electable_targets(maybe_max_len)
+	}
+
+	fn electing_voters(maybe_max_len: Option<usize>) -> data_provider::Result<Vec<VoterOf<Self>>> {
+		let mut voters: Vec<VoterOf<Self>> = Vec::new();
+		// the index of every voter in `voters` that others might be merged into.
+		let mut index_of = BTreeMap::<Self::AccountId, usize>::new();
+		let max_votes = Self::MaxVotesPerVoter::get() as usize;
+
+		for mut voter in Inner::electing_voters(maybe_max_len)? {
+			if let Some(aggregate) = A::aggregate_of(&voter.who) {
+				voter.who = aggregate;
+			}
+
+			match index_of.get(&voter.who) {
+				Some(index) => {
+					let existing = &mut voters[*index];
+					existing.weight = existing.weight.saturating_add(voter.weight);
+					for vote in voter.votes {
+						if existing.votes.len() < max_votes && !existing.votes.contains(&vote) {
+							let _ = existing.votes.try_push(vote);
+						}
+					}
+				},
+				None => {
+					index_of.insert(voter.who.clone(), voters.len());
+					voters.push(voter);
+				},
+			}
+		}
+
+		Ok(voters)
+	}
+
+	fn desired_targets() -> data_provider::Result<u32> {
+		Inner::desired_targets()
+	}
+
+	fn next_election_prediction(now: Self::BlockNumber) -> Self::BlockNumber {
+		Inner::next_election_prediction(now)
+	}
+
+	fn ready_for_election() -> bool {
+		Inner::ready_for_election()
+	}
+
+	#[cfg(any(feature = "runtime-benchmarks", test))]
+	fn put_snapshot(
+		voters: Vec<VoterOf<Self>>,
+		targets: Vec<Self::AccountId>,
+		target_stake: Option<VoteWeight>,
+	) {
+		Inner::put_snapshot(voters, targets, target_stake)
+	}
+
+	#[cfg(any(feature = "runtime-benchmarks", test))]
+	fn add_voter(
+		voter: Self::AccountId,
+		weight: VoteWeight,
+		targets: BoundedVec<Self::AccountId, Self::MaxVotesPerVoter>,
+	) {
+		Inner::add_voter(voter, weight, targets)
+	}
+
+	#[cfg(any(feature = "runtime-benchmarks", test))]
+	fn add_target(target: Self::AccountId) {
+		Inner::add_target(target)
+	}
+
+	#[cfg(any(feature = "runtime-benchmarks", test))]
+	fn clear() {
+		Inner::clear()
+	}
+}
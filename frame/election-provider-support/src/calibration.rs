@@ -0,0 +1,265 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A native harness to calibrate the weight of an [`NposSolver`].
+//!
+//! The solver is run over a grid of randomly generated inputs, and its wall-clock time is fitted
+//! to a linear model in the number of voters, targets and the vote degree, the same model that
+//! [`crate::WeightInfo`] uses. This is meant to give custom solvers a starting point for their
+//! weights; it is no replacement for proper benchmarks on the reference hardware, in wasm.
+//!
+//! ```ignore
+//! let samples = calibration::measure::<MySolver>(&Grid::default(), |i| i as AccountId);
+//! println!("{}", calibration::fit(&samples).weight_fn("my_solver"));
+//! ```
+
+use crate::{NposSolver, VoteWeight, Weight};
+use std::time::Instant;
+
+/// The inputs to measure a solver with.
+///
+/// Every combination of `voters`, `targets` and `degrees` is measured `repeat` times, so each of
+/// them should have at least two distinct values for the fit to be meaningful.
+#[derive(Clone, Debug)]
+pub struct Grid {
+	/// The number of voters.
+	pub voters: Vec<u32>,
+	/// The number of targets.
+	pub targets: Vec<u32>,
+	/// The number of votes of each voter. Capped to the number of targets.
+	pub degrees: Vec<u32>,
+	/// The number of winners to elect. Capped to the number of targets.
+	pub to_elect: u32,
+	/// How many times each combination is measured. The median is kept.
+	pub repeat: u32,
+	/// The seed of the generated inputs.
+	pub seed: u64,
+}
+
+impl Default for Grid {
+	fn default() -> Self {
+		Self {
+			voters: vec![1_000, 2_000, 4_000],
+			targets: vec![100, 200, 400],
+			degrees: vec![4, 8, 16],
+			to_elect: 50,
+			repeat: 3,
+			seed: 42,
+		}
+	}
+}
+
+/// A single measurement of a solver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sample {
+	/// The number of voters.
+	pub voters: u32,
+	/// The number of targets.
+	pub targets: u32,
+	/// The number of votes of each voter.
+	pub degree: u32,
+	/// The median time it took, in nanoseconds.
+	pub nanos: u64,
+}
+
+/// The result of fitting a set of [`Sample`]s, as `ref_time` in picoseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FittedWeight {
+	/// The constant part.
+	pub base: u64,
+	/// The part per voter.
+	pub per_voter: u64,
+	/// The part per target.
+	pub per_target: u64,
+	/// The part per vote degree.
+	pub per_degree: u64,
+}
+
+impl FittedWeight {
+	/// The fitted weight of an election with `v` voters, `t` targets and vote degree `d`.
+	pub fn weight(&self, v: u32, t: u32, d: u32) -> Weight {
+		Weight::from_parts(self.base, 0)
+			.saturating_add(Weight::from_parts(self.per_voter, 0).saturating_mul(v as u64))
+			.saturating_add(Weight::from_parts(self.per_target, 0).saturating_mul(t as u64))
+			.saturating_add(Weight::from_parts(self.per_degree, 0).saturating_mul(d as u64))
+	}
+
+	/// A [`crate::WeightInfo`] function named `name` that returns [`Self::weight`], in the format
+	/// of the generated weight files.
+	pub fn weight_fn(&self, name: &str) -> String {
+		let term = |value: u64, param: &str| {
+			format!(
+				"\t\t\t.saturating_add(Weight::from_parts({} as u64, 0).saturating_mul({} as u64))\n",
+				separated(value),
+				param
+			)
+		};
+		format!(
+			"\tfn {}(v: u32, t: u32, d: u32, ) -> Weight {{\n\t\tWeight::from_parts({} as u64, 0)\n{}{}{}\t}}\n",
+			name,
+			separated(self.base),
+			term(self.per_voter, "v"),
+			term(self.per_target, "t"),
+			term(self.per_degree, "d"),
+		)
+	}
+}
+
+/// Format `value` with `_` as thousands separator.
+fn separated(value: u64) -> String {
+	let digits = value.to_string().into_bytes();
+	let groups = digits.rchunks(3).rev().map(|g| String::from_utf8_lossy(g)).collect::<Vec<_>>();
+	groups.join("_")
+}
+
+/// A small, deterministic pseudo-random generator (splitmix64), good enough for generating inputs.
+struct Rng(u64);
+
+impl Rng {
+	fn next(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+
+	fn below(&mut self, bound: u32) -> u32 {
+		(self.next() % bound as u64) as u32
+	}
+}
+
+/// The input of a solver: `to_elect`, targets and voters.
+pub type Input<AccountId> = (usize, Vec<AccountId>, Vec<(AccountId, VoteWeight, Vec<AccountId>)>);
+
+/// Generate a random input with `voters` voters, `targets` targets and `degree` votes per voter.
+///
+/// Targets are created with `account(0..targets)`, voters with `account(targets..)`.
+pub fn generate<AccountId: Clone>(
+	voters: u32,
+	targets: u32,
+	degree: u32,
+	to_elect: u32,
+	seed: u64,
+	account: impl Fn(u32) -> AccountId,
+) -> Input<AccountId> {
+	let mut rng = Rng(seed);
+	let degree = degree.min(targets);
+	let target_ids = (0..targets).map(&account).collect::<Vec<_>>();
+
+	let voters = (0..voters)
+		.map(|i| {
+			let mut votes: Vec<u32> = Vec::with_capacity(degree as usize);
+			while votes.len() < degree as usize {
+				let t = rng.below(targets);
+				if !votes.contains(&t) {
+					votes.push(t);
+				}
+			}
+			let stake = 1 + rng.below(1_000) as VoteWeight;
+			(
+				account(targets + i),
+				stake,
+				votes.into_iter().map(|t| target_ids[t as usize].clone()).collect(),
+			)
+		})
+		.collect();
+
+	(to_elect.min(targets) as usize, target_ids, voters)
+}
+
+/// Measure the solver `S` over every combination of `grid`.
+///
+/// Panics if the solver fails on any of the inputs.
+pub fn measure<S: NposSolver>(grid: &Grid, account: impl Fn(u32) -> S::AccountId) -> Vec<Sample> {
+	let mut samples = vec![];
+	for &voters in &grid.voters {
+		for &targets in &grid.targets {
+			for &degree in &grid.degrees {
+				let mut timings = (0..grid.repeat.max(1))
+					.map(|_| {
+						let (to_elect, targets, voters) =
+							generate(voters, targets, degree, grid.to_elect, grid.seed, &account);
+						let start = Instant::now();
+						S::solve(to_elect, targets, voters).expect("solver failed");
+						start.elapsed().as_nanos() as u64
+					})
+					.collect::<Vec<_>>();
+				timings.sort();
+				samples.push(Sample { voters, targets, degree, nanos: timings[timings.len() / 2] });
+			}
+		}
+	}
+	samples
+}
+
+/// Fit `samples` to `base + per_voter * v + per_target * t + per_degree * d` with least squares.
+///
+/// Like the benchmarking CLI, negative components are clamped to zero, and components that cannot
+/// be determined from the samples (e.g. because the grid only has one value for them) are zero.
+pub fn fit(samples: &[Sample]) -> FittedWeight {
+	// normal equations `XᵀX β = Xᵀy`, with rows `[1, v, t, d]`.
+	let mut a = [[0f64; 5]; 4];
+	for s in samples {
+		let x = [1.0, s.voters as f64, s.targets as f64, s.degree as f64];
+		for i in 0..4 {
+			for j in 0..4 {
+				a[i][j] += x[i] * x[j];
+			}
+			a[i][4] += x[i] * s.nanos as f64;
+		}
+	}
+
+	// gauss-jordan elimination with partial pivoting. Columns without a pivot are left at zero.
+	let scale = (0..4).map(|i| a[i][i].abs()).fold(1.0, f64::max);
+	let mut pivot_row = [None; 4];
+	let mut row = 0;
+	for col in 0..4 {
+		let pivot = (row..4)
+			.max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))
+			.expect("row is less than 4; qed");
+		if a[pivot][col].abs() <= scale * 1e-12 {
+			continue
+		}
+		a.swap(row, pivot);
+		for other in 0..4 {
+			if other != row {
+				let pivot = a[row];
+				let factor = a[other][col] / pivot[col];
+				a[other].iter_mut().zip(pivot).skip(col).for_each(|(x, p)| *x -= factor * p);
+			}
+		}
+		pivot_row[col] = Some(row);
+		row += 1;
+		if row == 4 {
+			break
+		}
+	}
+
+	// nanoseconds to picoseconds.
+	let coefficient = |col: usize| {
+		pivot_row[col]
+			.map(|r| (a[r][4] / a[r][col] * 1_000.0).max(0.0).round() as u64)
+			.unwrap_or_default()
+	};
+	FittedWeight {
+		base: coefficient(0),
+		per_voter: coefficient(1),
+		per_target: coefficient(2),
+		per_degree: coefficient(3),
+	}
+}
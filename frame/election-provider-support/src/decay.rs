@@ -0,0 +1,213 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An adapter that decays the scores of a [`ScoreProvider`] or [`SortedListProvider`] at read
+//! time.
+//!
+//! This allows the effective score of an account to decline over time, e.g. while it is idle,
+//! without ever rewriting the stored scores.
+
+use crate::{BatchMode, ListCursor, NodeInfo, ScoreProvider, SortedListProvider, Weight};
+use sp_runtime::traits::Bounded;
+use sp_std::{boxed::Box, marker::PhantomData, prelude::*};
+
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+/// A function that decays a stored score, used by [`DecayedScore`].
+///
+/// Implementations would typically compare the current block number with the last block in which
+/// `who` was active. The decayed score must never be greater than the stored one.
+pub trait ScoreDecay<AccountId, Score> {
+	/// Decay `score`, the stored score of `who`.
+	fn decay(who: &AccountId, score: Score) -> Score;
+}
+
+/// Wraps `Inner`, and decays all scores that are read from it with `D`.
+///
+/// Scores that are written, e.g. through [`SortedListProvider::on_update`], are passed to `Inner`
+/// as-is. When used as a [`SortedListProvider`], the order of the list is still dictated by the
+/// stored scores, i.e. it might become less accurate as scores decay, until ids are rebagged.
+pub struct DecayedScore<Inner, D>(PhantomData<(Inner, D)>);
+
+impl<AccountId, Inner, D> ScoreProvider<AccountId> for DecayedScore<Inner, D>
+where
+	Inner: ScoreProvider<AccountId>,
+	D: ScoreDecay<AccountId, Inner::Score>,
+{
+	type Score = Inner::Score;
+
+	fn score(who: &AccountId) -> Self::Score {
+		D::decay(who, Inner::score(who))
+	}
+
+	#[cfg(any(feature = "runtime-benchmarks", feature = "fuzz", feature = "std"))]
+	fn set_score_of(who: &AccountId, score: Self::Score) {
+		Inner::set_score_of(who, score)
+	}
+}
+
+impl<AccountId, Inner, D> SortedListProvider<AccountId> for DecayedScore<Inner, D>
+where
+	AccountId: 'static,
+	Inner: SortedListProvider<AccountId>,
+	Inner::Score: PartialOrd + Copy + 'static,
+	D: ScoreDecay<AccountId, Inner::Score>,
+{
+	type Error = Inner::Error;
+	type Score = Inner::Score;
+
+	// decaying can change the order of scores, while the order of the list stays.
+	const EXACT_RANK: bool = false;
+
+	fn iter() -> Box<dyn Iterator<Item = AccountId>> {
+		Inner::iter()
+	}
+
+	fn iter_from(start: &AccountId) -> Result<Box<dyn Iterator<Item = AccountId>>, Self::Error> {
+		Inner::iter_from(start)
+	}
+
+	fn cursor(last: &AccountId) -> Result<ListCursor<AccountId, Self::Score>, Self::Error>
+	where
+		AccountId: Clone,
+	{
+		Inner::cursor(last)
+	}
+
+	fn iter_resume(
+		cursor: &ListCursor<AccountId, Self::Score>,
+	) -> Box<dyn Iterator<Item = AccountId>>
+	where
+		AccountId: Clone,
+	{
+		Inner::iter_resume(cursor)
+	}
+
+	fn iter_ascending() -> Box<dyn Iterator<Item = AccountId>> {
+		Inner::iter_ascending()
+	}
+
+	fn iter_from_ascending(
+		start: &AccountId,
+	) -> Result<Box<dyn Iterator<Item = AccountId>>, Self::Error>
+	where
+		AccountId: PartialEq,
+	{
+		Inner::iter_from_ascending(start)
+	}
+
+	fn iter_in_score_range(
+		lo: Self::Score,
+		hi: Self::Score,
+	) -> Box<dyn Iterator<Item = AccountId>> {
+		// decaying never increases a score, so any id in range has a stored score of at least `lo`.
+		Box::new(Inner::iter_in_score_range(lo, Bounded::max_value()).filter(move |id| {
+			Inner::get_score(id)
+				.map(|score| D::decay(id, score))
+				.map_or(false, |score| score >= lo && score <= hi)
+		}))
+	}
+
+	fn count() -> u32 {
+		Inner::count()
+	}
+
+	fn contains(id: &AccountId) -> bool {
+		Inner::contains(id)
+	}
+
+	fn on_insert(id: AccountId, score: Self::Score) -> Result<(), Self::Error> {
+		Inner::on_insert(id, score)
+	}
+
+	fn on_update(id: &AccountId, score: Self::Score) -> Result<(), Self::Error> {
+		Inner::on_update(id, score)
+	}
+
+	fn on_insert_many(
+		items: impl IntoIterator<Item = (AccountId, Self::Score)>,
+		mode: BatchMode,
+	) -> Result<u32, Self::Error> {
+		Inner::on_insert_many(items, mode)
+	}
+
+	fn on_update_many(
+		items: impl IntoIterator<Item = (AccountId, Self::Score)>,
+		mode: BatchMode,
+	) -> Result<u32, Self::Error> {
+		Inner::on_update_many(items, mode)
+	}
+
+	fn get_score(id: &AccountId) -> Result<Self::Score, Self::Error> {
+		Inner::get_score(id).map(|score| D::decay(id, score))
+	}
+
+	fn node_info(id: &AccountId) -> Result<NodeInfo<AccountId, Self::Score>, Self::Error> {
+		Inner::node_info(id).map(|info| NodeInfo { score: D::decay(id, info.score), ..info })
+	}
+
+	fn on_increase(id: &AccountId, additional: Self::Score) -> Result<(), Self::Error> {
+		// must be applied to the stored score, not the decayed one.
+		Inner::on_increase(id, additional)
+	}
+
+	fn on_decrease(id: &AccountId, decreased: Self::Score) -> Result<(), Self::Error> {
+		Inner::on_decrease(id, decreased)
+	}
+
+	fn on_remove(id: &AccountId) -> Result<(), Self::Error> {
+		Inner::on_remove(id)
+	}
+
+	fn lock() {
+		Inner::lock()
+	}
+
+	fn unlock() {
+		Inner::unlock()
+	}
+
+	fn is_locked() -> bool {
+		Inner::is_locked()
+	}
+
+	fn unsafe_regenerate(
+		all: impl IntoIterator<Item = AccountId>,
+		score_of: Box<dyn Fn(&AccountId) -> Self::Score>,
+	) -> u32 {
+		Inner::unsafe_regenerate(all, score_of)
+	}
+
+	fn unsafe_clear() {
+		Inner::unsafe_clear()
+	}
+
+	fn on_idle_rebag(budget: Weight) -> Weight {
+		Inner::on_idle_rebag(budget)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn try_state() -> Result<(), TryRuntimeError> {
+		Inner::try_state()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn score_update_worst_case(who: &AccountId, is_increase: bool) -> Self::Score {
+		Inner::score_update_worst_case(who, is_increase)
+	}
+}
@@ -0,0 +1,98 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deposits and rewards of signed solution submissions.
+//!
+//! Any pallet that accepts solutions from arbitrary accounts must make sure that storing and
+//! checking them is paid for, and that honest submitters are compensated. [`SubmissionEconomics`]
+//! captures this, such that the same logic can be shared between pallets.
+
+use crate::Weight;
+use frame_support::traits::Get;
+use sp_arithmetic::{
+	traits::{AtLeast32BitUnsigned, Bounded, One, Saturating, Zero},
+	FixedPointNumber, FixedPointOperand, FixedU128, Percent,
+};
+use sp_runtime::SaturatedConversion;
+use sp_std::marker::PhantomData;
+
+/// Something that can compute the deposit and the reward of a signed solution submission.
+pub trait SubmissionEconomics<Balance> {
+	/// The deposit that must be reserved for a submission of `encoded_len` bytes, whose feasibility
+	/// check consumes `weight`, while `queue_len` other submissions are already queued.
+	fn deposit(queue_len: usize, encoded_len: usize, weight: Weight) -> Balance;
+
+	/// The reward for a submission that ends up being accepted.
+	fn reward() -> Balance;
+}
+
+/// Submissions are free, and not rewarded.
+impl<Balance: Zero> SubmissionEconomics<Balance> for () {
+	fn deposit(_: usize, _: usize, _: Weight) -> Balance {
+		Zero::zero()
+	}
+
+	fn reward() -> Balance {
+		Zero::zero()
+	}
+}
+
+/// A [`SubmissionEconomics`] whose base deposit increases geometrically with the length of the
+/// queue.
+///
+/// The deposit is composed of:
+///
+/// 1. a base deposit, `Base * (1 + Increase) ^ queue_len`, such that filling the queue becomes
+///    increasingly expensive.
+/// 2. a per-byte deposit, `PerByte * encoded_len`, for renting the state usage.
+/// 3. a per-weight deposit, `PerWeight * weight.ref_time()`, for the potential weight usage of
+///    checking the submission.
+///
+/// The reward is always `Reward`.
+pub struct GeometricDeposit<Balance, Base, Increase, PerByte, PerWeight, Reward>(
+	PhantomData<(Balance, Base, Increase, PerByte, PerWeight, Reward)>,
+);
+
+impl<Balance, Base, Increase, PerByte, PerWeight, Reward> SubmissionEconomics<Balance>
+	for GeometricDeposit<Balance, Base, Increase, PerByte, PerWeight, Reward>
+where
+	Balance: AtLeast32BitUnsigned + FixedPointOperand,
+	Base: Get<Balance>,
+	Increase: Get<Percent>,
+	PerByte: Get<Balance>,
+	PerWeight: Get<Balance>,
+	Reward: Get<Balance>,
+{
+	fn deposit(queue_len: usize, encoded_len: usize, weight: Weight) -> Balance {
+		let increase_factor = FixedU128::one().saturating_add(Increase::get().into());
+		let base_factor = increase_factor.saturating_pow(queue_len);
+		// the factor itself saturates way before the balance type does.
+		let base_deposit = if base_factor == FixedU128::max_value() {
+			Balance::max_value()
+		} else {
+			base_factor.saturating_mul_int(Base::get())
+		};
+		let len_deposit = PerByte::get().saturating_mul(encoded_len.saturated_into());
+		let weight_deposit = PerWeight::get().saturating_mul(weight.ref_time().saturated_into());
+
+		base_deposit.saturating_add(len_deposit).saturating_add(weight_deposit)
+	}
+
+	fn reward() -> Balance {
+		Reward::get()
+	}
+}
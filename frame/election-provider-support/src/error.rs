@@ -0,0 +1,77 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A common error type for election providers.
+//!
+//! [`ElectionProviderBase::Error`] is free to be anything, which leaves the consumers of an
+//! election provider unable to tell failures apart. Providers that use [`ElectionError`] instead
+//! let them react to, say, an ongoing election differently than to a failing data provider.
+//!
+//! [`ElectionProviderBase::Error`]: crate::ElectionProviderBase::Error
+
+use sp_runtime::{DispatchError, RuntimeDebug};
+
+/// Errors common to election providers.
+#[derive(Eq, PartialEq, RuntimeDebug)]
+pub enum ElectionError {
+	/// The data provider failed, with its message.
+	DataProvider(&'static str),
+	/// The election input exceeds its bounds.
+	BoundsExceeded,
+	/// More winners are desired or elected than `MaxWinners`.
+	TooManyWinners,
+	/// The NPoS solver failed.
+	Solver(sp_npos_elections::Error),
+	/// An election is ongoing, and its result not yet available.
+	Ongoing,
+	/// The election failed, and so did its fallback, with its message.
+	FallbackFailed(&'static str),
+	/// Any other failure, with its message.
+	Other(&'static str),
+}
+
+impl From<sp_npos_elections::Error> for ElectionError {
+	fn from(e: sp_npos_elections::Error) -> Self {
+		ElectionError::Solver(e)
+	}
+}
+
+impl From<&'static str> for ElectionError {
+	fn from(e: &'static str) -> Self {
+		ElectionError::Other(e)
+	}
+}
+
+impl From<ElectionError> for &'static str {
+	fn from(e: ElectionError) -> Self {
+		match e {
+			ElectionError::DataProvider(msg) |
+			ElectionError::FallbackFailed(msg) |
+			ElectionError::Other(msg) => msg,
+			ElectionError::BoundsExceeded => "election bounds exceeded",
+			ElectionError::TooManyWinners => "too many winners",
+			ElectionError::Solver(_) => "election solver failed",
+			ElectionError::Ongoing => "election ongoing",
+		}
+	}
+}
+
+impl From<ElectionError> for DispatchError {
+	fn from(e: ElectionError) -> Self {
+		DispatchError::Other(e.into())
+	}
+}
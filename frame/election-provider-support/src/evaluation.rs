@@ -0,0 +1,83 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities to recompute the supports and the score of an election outcome, e.g. to verify a
+//! claimed score on-chain, along with their weight.
+//!
+//! These are thin wrappers around [`sp_npos_elections`], such that users of this crate do not need
+//! to depend on it directly.
+
+use crate::{
+	BoundedSupports, ElectionScore, Get, IdentifierT, StakedAssignment, Supports, Weight,
+	WeightInfo,
+};
+use sp_npos_elections::EvaluateSupport;
+
+/// Build the supports of the given `assignments`, i.e. the backing of each winner.
+pub fn to_supports<AccountId: IdentifierT>(
+	assignments: &[StakedAssignment<AccountId>],
+) -> Supports<AccountId> {
+	sp_npos_elections::to_supports(assignments)
+}
+
+/// Same as [`to_supports`], but bounded by `MaxWinners`.
+///
+/// If there are too many winners, the unbounded supports are returned as error.
+pub fn to_bounded_supports<AccountId: IdentifierT, MaxWinners: Get<u32>>(
+	assignments: &[StakedAssignment<AccountId>],
+) -> Result<BoundedSupports<AccountId, MaxWinners>, Supports<AccountId>> {
+	to_supports(assignments).try_into()
+}
+
+/// Evaluate the score of `supports`.
+///
+/// [`BoundedSupports`] can be passed as well, as they dereference to [`Supports`].
+pub fn evaluate_support<AccountId: IdentifierT>(supports: &Supports<AccountId>) -> ElectionScore {
+	supports.evaluate()
+}
+
+/// Build the supports of `assignments` with [`to_supports`], and evaluate their score with
+/// [`evaluate_support`].
+///
+/// The weight of this is at most [`score_of_weight`].
+pub fn score_of<AccountId: IdentifierT>(
+	assignments: &[StakedAssignment<AccountId>],
+) -> (Supports<AccountId>, ElectionScore) {
+	let supports = to_supports(assignments);
+	let score = evaluate_support(&supports);
+	(supports, score)
+}
+
+/// The weight of [`score_of`] with `voters` assignments over `targets` targets, each having at most
+/// `degree` edges.
+///
+/// This is the weight of the feasibility check benchmark, which also includes converting the
+/// assignments from ratios to stakes, and is thus a safe upper bound.
+pub fn score_of_weight<T: WeightInfo>(voters: u32, targets: u32, degree: u32) -> Weight {
+	T::feasibility_check(voters, targets, degree)
+}
+
+/// Whether `score` is better than `than` by at least `threshold`.
+///
+/// See [`ElectionScore::strict_threshold_better`].
+pub fn is_score_better<P: crate::PerThing>(
+	score: ElectionScore,
+	than: ElectionScore,
+	threshold: P,
+) -> bool {
+	score.strict_threshold_better(than, threshold)
+}
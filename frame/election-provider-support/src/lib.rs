@@ -144,6 +144,7 @@
 //!
 //!     impl<T: Config> ElectionProvider for GenericElectionProvider<T> {
 //!         fn ongoing() -> bool { false }
+//!         fn defer(delay: BlockNumber) -> Result<BlockNumber, Self::Error> { Ok(delay) }
 //!         fn elect() -> Result<BoundedSupportsOf<Self>, Self::Error> {
 //!             Self::DataProvider::electable_targets(None)
 //!                 .map_err(|_| "failed to elect")
@@ -173,21 +174,48 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod aggregated;
+#[cfg(feature = "std")]
+pub mod calibration;
+pub mod decay;
+pub mod economics;
+pub mod error;
+pub mod evaluation;
+pub mod merged;
+pub mod migrations;
+pub mod min_backing;
+pub mod miner;
 pub mod onchain;
+pub mod pjr;
 pub mod traits;
-use sp_runtime::traits::{Bounded, Saturating, Zero};
-use sp_std::{fmt::Debug, prelude::*};
+pub mod weighted_random;
+use codec::Encode;
+use sp_npos_elections::{
+	assignment_ratio_to_staked_normalized, assignment_staked_to_ratio_normalized,
+};
+use sp_runtime::{
+	traits::{Bounded, Saturating, Zero},
+	DispatchResult, TransactionOutcome,
+};
+use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, prelude::*};
 
+pub use aggregated::{AggregatedVoters, VoterAggregator};
+pub use decay::{DecayedScore, ScoreDecay};
+pub use economics::{GeometricDeposit, SubmissionEconomics};
+pub use error::ElectionError;
 /// Re-export the solution generation macro.
 pub use frame_election_provider_solution_type::generate_solution_type;
 pub use frame_support::{traits::Get, weights::Weight, BoundedVec, RuntimeDebug};
+pub use merged::MergedDataProvider;
+pub use min_backing::{BelowMinBacking, MinBackedError, MinBackedWinners, OnWinnersDropped};
 /// Re-export some type as they are used in the interface.
 pub use sp_arithmetic::PerThing;
 pub use sp_npos_elections::{
-	Assignment, BalancingConfig, BoundedSupports, ElectionResult, Error, ExtendedBalance,
-	IdentifierT, PerThing128, Support, Supports, VoteWeight,
+	Assignment, BalancingConfig, BoundedSupports, ElectionResult, ElectionScore, Error,
+	ExtendedBalance, IdentifierT, PerThing128, StakedAssignment, Support, Supports, VoteWeight,
 };
 pub use traits::NposSolution;
+pub use weighted_random::WeightedRandom;
 
 #[cfg(feature = "try-runtime")]
 use sp_runtime::TryRuntimeError;
@@ -197,6 +225,8 @@ use sp_runtime::TryRuntimeError;
 pub mod private {
 	pub use codec;
 	pub use scale_info;
+	#[cfg(feature = "std")]
+	pub use serde;
 	pub use sp_arithmetic;
 	pub use sp_std;
 
@@ -326,6 +356,55 @@ pub trait ElectionDataProvider {
 	/// This is only useful for stateful election providers.
 	fn next_election_prediction(now: Self::BlockNumber) -> Self::BlockNumber;
 
+	/// Whether the data provider is willing to have an election start now.
+	///
+	/// If not, e.g. because a migration is pending, election providers should not start any new
+	/// election, e.g. by taking a snapshot. The data provider is then expected to postpone the
+	/// election through [`ElectionProvider::defer`].
+	///
+	/// By default, this is always `true`.
+	fn ready_for_election() -> bool {
+		true
+	}
+
+	/// A fingerprint of the data that an election would be computed against, see
+	/// [`snapshot_fingerprint`]: at most `voter_limit` voters and `target_limit` targets, as per
+	/// [`Self::electing_voters`] and [`Self::electable_targets`], and [`Self::desired_targets`],
+	/// capped to the number of targets.
+	///
+	/// This is the fingerprint of a snapshot taken with the same limits. Election providers can use
+	/// this to reject solutions that were computed against some other, e.g. stale, data. By
+	/// default, this reads all of the above data, which is expensive; implementors are encouraged
+	/// to provide a cheaper one if they can.
+	fn snapshot_fingerprint(
+		voter_limit: usize,
+		target_limit: usize,
+	) -> data_provider::Result<[u8; 32]>
+	where
+		Self::AccountId: codec::Encode,
+	{
+		let voters = Self::electing_voters(Some(voter_limit))?;
+		let targets = Self::electable_targets(Some(target_limit))?;
+		let desired_targets = Self::desired_targets()?.min(targets.len() as u32);
+		Ok(snapshot_fingerprint(&voters, &targets, desired_targets))
+	}
+
+	/// Keep the voters in the order in which they were just read through
+	/// [`Self::electing_voters`], until [`Self::unlock_voters`] is called.
+	///
+	/// Election providers call this once they took their snapshot, and keep it for as long as
+	/// the snapshot is in use, such that the voters, e.g. as seen through a
+	/// [`SortedListProvider`], stay consistent with the snapshot in the meantime. Changes to the
+	/// voters are still accepted, but only take effect afterwards.
+	///
+	/// By default, this does nothing.
+	fn lock_voters() {}
+
+	/// Stop keeping the voters in order, see [`Self::lock_voters`].
+	///
+	/// By default, this does nothing.
+	fn unlock_voters() {}
+
 	/// Utility function only to be used in benchmarking scenarios, to be implemented optionally,
 	/// else a noop.
 	#[cfg(any(feature = "runtime-benchmarks", test))]
@@ -360,6 +439,16 @@ pub trait ElectionDataProvider {
 	fn clear() {}
 }
 
+/// The fingerprint of an election snapshot, i.e. the hash of `voters`, `targets` and
+/// `desired_targets`, encoded as a tuple.
+pub fn snapshot_fingerprint<AccountId: codec::Encode, Bound: Get<u32>>(
+	voters: &[Voter<AccountId, Bound>],
+	targets: &[AccountId],
+	desired_targets: u32,
+) -> [u8; 32] {
+	(voters, targets, desired_targets).using_encoded(sp_core::blake2_256)
+}
+
 /// Base trait for types that can provide election
 pub trait ElectionProviderBase {
 	/// The account identifier type.
@@ -411,6 +500,13 @@ pub trait ElectionProvider: ElectionProviderBase {
 	/// Indicate if this election provider is currently ongoing an asynchronous election or not.
 	fn ongoing() -> bool;
 
+	/// Ask the election provider to postpone the next election by `requested_delay` blocks,
+	/// rather than failing it.
+	///
+	/// Returns the delay that was granted, which may be less than requested, or an error if the
+	/// election cannot be deferred at all.
+	fn defer(requested_delay: Self::BlockNumber) -> Result<Self::BlockNumber, Self::Error>;
+
 	/// Performs the election. This should be implemented as a self-weighing function. The
 	/// implementor should register its appropriate weight at the end of execution with the
 	/// system pallet directly.
@@ -428,6 +524,27 @@ pub trait InstantElectionProvider: ElectionProviderBase {
 		forced_input_voters_bound: Option<u32>,
 		forced_input_target_bound: Option<u32>,
 	) -> Result<BoundedSupportsOf<Self>, Self::Error>;
+
+	/// Check that the next instant election can succeed, as far as its input is concerned.
+	///
+	/// This is meant to be called from the `try_state` hook of whoever uses this election
+	/// provider, e.g. as a fallback. By default, this does nothing.
+	#[cfg(feature = "try-runtime")]
+	fn try_state() -> Result<(), TryRuntimeError> {
+		Ok(())
+	}
+}
+
+/// An election provider whose result can be set by a trusted origin, e.g. governance, when all
+/// other means of computing it have failed.
+pub trait EmergencyElection: ElectionProviderBase {
+	/// Set `supports` as the result of the next election, without any feasibility checks.
+	///
+	/// Implementations may only allow this while they are in an emergency state.
+	fn set_emergency_result(supports: Supports<Self::AccountId>) -> DispatchResult;
+
+	/// Whether an emergency result has been set, and not yet been consumed by an election.
+	fn emergency_result_pending() -> bool;
 }
 
 /// An election provider that does nothing whatsoever.
@@ -441,7 +558,7 @@ where
 {
 	type AccountId = AccountId;
 	type BlockNumber = BlockNumber;
-	type Error = &'static str;
+	type Error = ElectionError;
 	type MaxWinners = MaxWinners;
 	type DataProvider = DataProvider;
 }
@@ -456,8 +573,12 @@ where
 		false
 	}
 
+	fn defer(_: BlockNumber) -> Result<BlockNumber, Self::Error> {
+		Err(ElectionError::Other("`NoElection` cannot do anything."))
+	}
+
 	fn elect() -> Result<BoundedSupportsOf<Self>, Self::Error> {
-		Err("`NoElection` cannot do anything.")
+		Err(ElectionError::Other("`NoElection` cannot do anything."))
 	}
 }
 
@@ -471,7 +592,60 @@ where
 		_: Option<u32>,
 		_: Option<u32>,
 	) -> Result<BoundedSupportsOf<Self>, Self::Error> {
-		Err("`NoElection` cannot do anything.")
+		Err(ElectionError::Other("`NoElection` cannot do anything."))
+	}
+}
+
+/// An election provider that always elects nobody, successfully.
+///
+/// Unlike [`NoElection`], this does not fail. This is useful for chains that intentionally run
+/// without elections for a while, e.g. before staking is launched, and do not want the receiving
+/// pallet to go through its failure path on every election.
+pub struct EmptyElection<X>(sp_std::marker::PhantomData<X>);
+
+impl<AccountId, BlockNumber, DataProvider, MaxWinners> ElectionProviderBase
+	for EmptyElection<(AccountId, BlockNumber, DataProvider, MaxWinners)>
+where
+	DataProvider: ElectionDataProvider<AccountId = AccountId, BlockNumber = BlockNumber>,
+	MaxWinners: Get<u32>,
+{
+	type AccountId = AccountId;
+	type BlockNumber = BlockNumber;
+	type Error = ElectionError;
+	type MaxWinners = MaxWinners;
+	type DataProvider = DataProvider;
+}
+
+impl<AccountId, BlockNumber, DataProvider, MaxWinners> ElectionProvider
+	for EmptyElection<(AccountId, BlockNumber, DataProvider, MaxWinners)>
+where
+	DataProvider: ElectionDataProvider<AccountId = AccountId, BlockNumber = BlockNumber>,
+	MaxWinners: Get<u32>,
+{
+	fn ongoing() -> bool {
+		false
+	}
+
+	fn defer(requested_delay: BlockNumber) -> Result<BlockNumber, Self::Error> {
+		Ok(requested_delay)
+	}
+
+	fn elect() -> Result<BoundedSupportsOf<Self>, Self::Error> {
+		Ok(Default::default())
+	}
+}
+
+impl<AccountId, BlockNumber, DataProvider, MaxWinners> InstantElectionProvider
+	for EmptyElection<(AccountId, BlockNumber, DataProvider, MaxWinners)>
+where
+	DataProvider: ElectionDataProvider<AccountId = AccountId, BlockNumber = BlockNumber>,
+	MaxWinners: Get<u32>,
+{
+	fn instant_elect(
+		_: Option<u32>,
+		_: Option<u32>,
+	) -> Result<BoundedSupportsOf<Self>, Self::Error> {
+		Ok(Default::default())
 	}
 }
 
@@ -491,6 +665,13 @@ pub trait SortedListProvider<AccountId> {
 	/// The type used by the list to compare nodes for ordering.
 	type Score: Bounded + Saturating + Zero;
 
+	/// Whether the list is sorted by score exactly, in which case [`Self::rank`] is the number of
+	/// ids with a higher score.
+	///
+	/// Lists that only sort on a best-effort basis, e.g. into bags of scores, keep this `false`:
+	/// their ranks may be off by the ids whose relative order is not known.
+	const EXACT_RANK: bool = false;
+
 	/// An iterator over the list, which can have `take` called on it.
 	fn iter() -> Box<dyn Iterator<Item = AccountId>>;
 
@@ -499,12 +680,118 @@ pub trait SortedListProvider<AccountId> {
 	/// May return an error if `start` is invalid.
 	fn iter_from(start: &AccountId) -> Result<Box<dyn Iterator<Item = AccountId>>, Self::Error>;
 
+	/// Create a cursor pointing right after `last`, to be used with [`Self::iter_resume`].
+	///
+	/// Returns an error if `last` is not in the list. By default, the cursor holds `last`, the id
+	/// after it and its score.
+	fn cursor(last: &AccountId) -> Result<ListCursor<AccountId, Self::Score>, Self::Error>
+	where
+		AccountId: Clone,
+	{
+		Ok(ListCursor {
+			last: last.clone(),
+			next: Self::iter_from(last)?.next(),
+			bracket: Self::get_score(last)?,
+			part: 0,
+		})
+	}
+
+	/// Returns an iterator over the list, continuing from where `cursor` was created.
+	///
+	/// No item that is still in the list and was not visited before the cursor was created is
+	/// skipped, even if `cursor.last` is no longer part of the list. If the list cannot tell where
+	/// to continue exactly, it must rather return some items again than skip any.
+	///
+	/// By default, this continues after `cursor.last`, or else from `cursor.next`. If both are
+	/// gone, it starts over from the beginning of the list.
+	fn iter_resume(
+		cursor: &ListCursor<AccountId, Self::Score>,
+	) -> Box<dyn Iterator<Item = AccountId>>
+	where
+		AccountId: Clone + 'static,
+	{
+		if let Ok(iter) = Self::iter_from(&cursor.last) {
+			return iter
+		}
+		match cursor.next.as_ref().map(|next| (next, Self::iter_from(next))) {
+			Some((next, Ok(iter))) => Box::new(sp_std::iter::once(next.clone()).chain(iter)),
+			_ => Self::iter(),
+		}
+	}
+
+	/// An iterator over the list, from the lowest score to the highest.
+	///
+	/// This is the reverse of [`Self::iter`]. Implementations should avoid collecting the entire
+	/// list first, if possible. By default, this collects [`Self::iter`] and reverses it.
+	fn iter_ascending() -> Box<dyn Iterator<Item = AccountId>>
+	where
+		AccountId: 'static,
+	{
+		Box::new(Self::iter().collect::<Vec<_>>().into_iter().rev())
+	}
+
+	/// Same as [`Self::iter_from`], but from the lowest score to the highest, starting right after
+	/// the given voter.
+	///
+	/// May return an error if `start` is invalid. By default, this collects [`Self::iter`] up to
+	/// `start` and reverses it.
+	fn iter_from_ascending(
+		start: &AccountId,
+	) -> Result<Box<dyn Iterator<Item = AccountId>>, Self::Error>
+	where
+		AccountId: PartialEq + 'static,
+	{
+		// the list's own error for ids that it does not contain.
+		Self::get_score(start)?;
+		let before = Self::iter().take_while(|id| id != start).collect::<Vec<_>>();
+		Ok(Box::new(before.into_iter().rev()))
+	}
+
+	/// Returns an iterator over the ids whose score is within `lo..=hi`, in the same order as
+	/// [`Self::iter`].
+	///
+	/// Implementations should avoid walking the entire list, if possible. By default, this walks
+	/// the entire list and filters it by [`Self::get_score`].
+	fn iter_in_score_range(lo: Self::Score, hi: Self::Score) -> Box<dyn Iterator<Item = AccountId>>
+	where
+		AccountId: 'static,
+		Self::Score: PartialOrd + 'static,
+	{
+		Box::new(Self::iter().filter(
+			move |id| matches!(Self::get_score(id), Ok(score) if score >= lo && score <= hi),
+		))
+	}
+
 	/// The current count of ids in the list.
 	fn count() -> u32;
 
 	/// Return true if the list already contains `id`.
 	fn contains(id: &AccountId) -> bool;
 
+	/// The position of `id` in [`Self::iter`], the first id being at 0. See [`Self::EXACT_RANK`].
+	///
+	/// Returns an error if `id` is not in the list. By default, this walks the list up to `id`.
+	fn rank(id: &AccountId) -> Result<u32, Self::Error>
+	where
+		AccountId: PartialEq,
+	{
+		// the list's own error for ids that it does not contain.
+		Self::get_score(id)?;
+		let rank = Self::iter().position(|other| other == *id).unwrap_or(Self::count() as usize);
+		Ok(rank as u32)
+	}
+
+	/// Return true if `id` is among the first `n` ids of [`Self::iter`], e.g. the ids that fit into
+	/// the electing voters of a snapshot.
+	///
+	/// By default, this walks at most the first `n` ids.
+	fn in_top(id: &AccountId, n: u32) -> bool
+	where
+		AccountId: PartialEq,
+	{
+		Self::iter().take(n as usize).any(|other| other == *id)
+	}
+
 	/// Hook for inserting a new id.
 	///
 	/// Implementation should return an error if duplicate item is being inserted.
@@ -517,9 +804,58 @@ pub trait SortedListProvider<AccountId> {
 	/// Returns `Ok(())` iff it successfully updates an item, an `Err(_)` otherwise.
 	fn on_update(id: &AccountId, score: Self::Score) -> Result<(), Self::Error>;
 
+	/// Hook for inserting many ids at once, each with their score.
+	///
+	/// This should be preferred over repeated calls to [`Self::on_insert`] when inserting many
+	/// ids, as implementations can amortize the storage accesses.
+	///
+	/// Failures of individual ids are handled according to `mode`. Returns the number of ids that
+	/// were inserted.
+	///
+	/// By default, this calls [`Self::on_insert`] for each id, within a storage transaction for
+	/// [`BatchMode::AllOrNothing`].
+	fn on_insert_many(
+		items: impl IntoIterator<Item = (AccountId, Self::Score)>,
+		mode: BatchMode,
+	) -> Result<u32, Self::Error> {
+		apply_batch(items, mode, |(id, score)| Self::on_insert(id, score))
+	}
+
+	/// Hook for updating many ids at once, each with their new score.
+	///
+	/// This should be preferred over repeated calls to [`Self::on_update`] when updating many
+	/// ids, as implementations can amortize the storage accesses.
+	///
+	/// Failures of individual ids are handled according to `mode`. Returns the number of ids that
+	/// were updated.
+	///
+	/// By default, this calls [`Self::on_update`] for each id, within a storage transaction for
+	/// [`BatchMode::AllOrNothing`].
+	fn on_update_many(
+		items: impl IntoIterator<Item = (AccountId, Self::Score)>,
+		mode: BatchMode,
+	) -> Result<u32, Self::Error> {
+		apply_batch(items, mode, |(id, score)| Self::on_update(&id, score))
+	}
+
 	/// Get the score of `id`.
 	fn get_score(id: &AccountId) -> Result<Self::Score, Self::Error>;
 
+	/// Get information about where `id` sits in the list. Meant to be used for inspection only.
+	///
+	/// By default, the whole list is treated as a single bag, and `prev` is not known.
+	fn node_info(id: &AccountId) -> Result<NodeInfo<AccountId, Self::Score>, Self::Error> {
+		let score = Self::get_score(id)?;
+		let next = Self::iter_from(id)?.next();
+		Ok(NodeInfo {
+			score,
+			rank_hint: 0,
+			bag_bounds: (Zero::zero(), Bounded::max_value()),
+			prev: None,
+			next,
+		})
+	}
+
 	/// Same as `on_update`, but incorporate some increased score.
 	fn on_increase(id: &AccountId, additional: Self::Score) -> Result<(), Self::Error> {
 		let old_score = Self::get_score(id)?;
@@ -545,6 +881,31 @@ pub trait SortedListProvider<AccountId> {
 	/// Returns `Ok(())` iff it successfully removes an item, an `Err(_)` otherwise.
 	fn on_remove(id: &AccountId) -> Result<(), Self::Error>;
 
+	/// Lock the list, such that the order of its ids, as seen by [`Self::iter`] and the like, does
+	/// not change until [`Self::unlock`] is called.
+	///
+	/// This allows the list to be iterated over multiple blocks, e.g. to create a paged snapshot,
+	/// without ids being added, moved or dropped in the meantime. Insertions, updates and removals
+	/// must still succeed while the list is locked, since callers such as staking cannot reject
+	/// them. Implementations should instead queue them until the list is unlocked, while
+	/// [`Self::contains`], [`Self::count`] and [`Self::get_score`] already reflect them. Only
+	/// operations that merely reorder the list, if any, may fail.
+	///
+	/// By default, locking is not supported and this does nothing.
+	fn lock() {}
+
+	/// Unlock the list. See [`Self::lock`].
+	///
+	/// The changes made while the list was locked need not be applied right away, since there may
+	/// be arbitrarily many of them. Implementations may instead apply them over the next blocks,
+	/// e.g. in [`Self::on_idle_rebag`].
+	fn unlock() {}
+
+	/// Returns true if the list is currently locked. See [`Self::lock`].
+	fn is_locked() -> bool {
+		false
+	}
+
 	/// Regenerate this list from scratch. Returns the count of items inserted.
 	///
 	/// This should typically only be used at a runtime upgrade.
@@ -566,6 +927,14 @@ pub trait SortedListProvider<AccountId> {
 	/// unbounded amount of storage accesses.
 	fn unsafe_clear();
 
+	/// Lazily fix the position of misplaced ids, spending at most `budget`.
+	///
+	/// This is meant to be called with the leftover weight of a block, e.g. from `on_idle`.
+	/// Returns the weight consumed. By default, this does nothing.
+	fn on_idle_rebag(_budget: Weight) -> Weight {
+		Weight::zero()
+	}
+
 	/// Check internal state of the list. Only meant for debugging.
 	#[cfg(feature = "try-runtime")]
 	fn try_state() -> Result<(), TryRuntimeError>;
@@ -576,6 +945,87 @@ pub trait SortedListProvider<AccountId> {
 	fn score_update_worst_case(_who: &AccountId, _is_increase: bool) -> Self::Score;
 }
 
+/// How the batch operations of a [`SortedListProvider`] handle a failure of an individual item.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum BatchMode {
+	/// Return an error and leave the list untouched if any of the items fails.
+	AllOrNothing,
+	/// Skip the items that fail and proceed with the rest.
+	BestEffort,
+}
+
+/// Apply `f` to each of `items`, handling failures according to `mode`, and return the number of
+/// items for which `f` succeeded.
+///
+/// With [`BatchMode::AllOrNothing`], all changes are reverted if any item fails.
+fn apply_batch<Item, E>(
+	items: impl IntoIterator<Item = Item>,
+	mode: BatchMode,
+	mut f: impl FnMut(Item) -> Result<(), E>,
+) -> Result<u32, E> {
+	match mode {
+		BatchMode::AllOrNothing =>
+			frame_support::storage::with_transaction_unchecked(|| {
+				match items.into_iter().try_fold(0u32, |count, item| f(item).map(|_| count + 1)) {
+					Ok(count) => TransactionOutcome::Commit(Ok(count)),
+					Err(e) => TransactionOutcome::Rollback(Err(e)),
+				}
+			}),
+		BatchMode::BestEffort =>
+			Ok(items.into_iter().filter_map(|item| f(item).ok()).count() as u32),
+	}
+}
+
+/// A position within a [`SortedListProvider`], which can be stored and later on passed to
+/// [`SortedListProvider::iter_resume`] to continue iterating the list.
+///
+/// Unlike resuming with [`SortedListProvider::iter_from`], this keeps working if `last` has been
+/// removed from the list in the meantime. What a list stores in here, and how exactly it can resume
+/// from it, is up to the list.
+#[derive(
+	Clone,
+	PartialEq,
+	Eq,
+	RuntimeDebug,
+	codec::Encode,
+	codec::Decode,
+	codec::MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
+pub struct ListCursor<AccountId, Score> {
+	/// The last id that was visited.
+	pub last: AccountId,
+	/// The id that came right after `last` when the cursor was created, if any.
+	pub next: Option<AccountId>,
+	/// The score bracket that `last` belonged to when the cursor was created, e.g. the upper
+	/// threshold of its bag.
+	pub bracket: Score,
+	/// For lists that are chained from several parts, e.g. separate storage maps, the index of
+	/// the part that `last` belonged to when the cursor was created. Zero otherwise.
+	pub part: u8,
+}
+
+/// Information about where a single id sits within a [`SortedListProvider`].
+///
+/// This is meant to be exposed to tooling, e.g. through a runtime API, and is never needed by the
+/// list itself.
+#[derive(
+	Clone, PartialEq, Eq, RuntimeDebug, codec::Encode, codec::Decode, scale_info::TypeInfo,
+)]
+pub struct NodeInfo<AccountId, Score> {
+	/// The score of the id, as stored in the list.
+	pub score: Score,
+	/// The position of the id's bag, counting from the bag with the highest scores, which is 0.
+	pub rank_hint: u32,
+	/// The `(lower, upper)` bounds of the id's bag. A score belongs into the bag if it is greater
+	/// than `lower` and at most `upper`.
+	pub bag_bounds: (Score, Score),
+	/// The id right before this one in the list, if known.
+	pub prev: Option<AccountId>,
+	/// The id right after this one in the list, if any.
+	pub next: Option<AccountId>,
+}
+
 /// Something that can provide the `Score` of an account. Similar to [`ElectionProvider`] and
 /// [`ElectionDataProvider`], this should typically be implementing by whoever is supposed to *use*
 /// `SortedListProvider`.
@@ -585,6 +1035,18 @@ pub trait ScoreProvider<AccountId> {
 	/// Get the current `Score` of `who`.
 	fn score(who: &AccountId) -> Self::Score;
 
+	/// Get the current `Score` of all accounts in `of`, in the same order.
+	///
+	/// By default, this calls [`Self::score`] for each account. Implementations that can fetch
+	/// many scores with fewer storage reads should override it.
+	fn scores(of: impl Iterator<Item = AccountId>) -> Vec<(AccountId, Self::Score)> {
+		of.map(|who| {
+			let score = Self::score(&who);
+			(who, score)
+		})
+		.collect()
+	}
+
 	/// For tests, benchmarks and fuzzing, set the `score`.
 	#[cfg(any(feature = "runtime-benchmarks", feature = "fuzz", feature = "std"))]
 	fn set_score_of(_: &AccountId, _: Self::Score) {}
@@ -614,12 +1076,147 @@ pub trait NposSolver {
 	fn weight<T: WeightInfo>(voters: u32, targets: u32, vote_degree: u32) -> Weight;
 }
 
+/// Something that can post-process the [`ElectionResult`] of an [`NposSolver`].
+///
+/// A post-processor must not change the set of winners, nor the total backing of any of them.
+pub trait SolutionPostProcessor<AccountId: IdentifierT, Accuracy: PerThing128> {
+	/// Post-process the given `result`.
+	///
+	/// `stake_of` returns the stake of each voter, as it was given to the solver.
+	fn process(
+		result: ElectionResult<AccountId, Accuracy>,
+		stake_of: impl Fn(&AccountId) -> VoteWeight,
+	) -> Result<ElectionResult<AccountId, Accuracy>, Error>;
+
+	/// Measure the weight used by the post-processing, with the same parameters as
+	/// [`NposSolver::weight`].
+	fn weight<T: WeightInfo>(_voters: u32, _targets: u32, _vote_degree: u32) -> Weight {
+		Weight::zero()
+	}
+}
+
+impl<AccountId: IdentifierT, Accuracy: PerThing128> SolutionPostProcessor<AccountId, Accuracy>
+	for ()
+{
+	fn process(
+		result: ElectionResult<AccountId, Accuracy>,
+		_: impl Fn(&AccountId) -> VoteWeight,
+	) -> Result<ElectionResult<AccountId, Accuracy>, Error> {
+		Ok(result)
+	}
+}
+
+/// A [`SolutionPostProcessor`] that runs [`sp_npos_elections::reduce_chunked`] over the
+/// assignments, in chunks of `ChunkSize` voters.
+///
+/// A `ChunkSize` of zero (the default) reduces all the assignments at once.
+pub struct Reduce<ChunkSize = ()>(sp_std::marker::PhantomData<ChunkSize>);
+
+impl<AccountId: IdentifierT, Accuracy: PerThing128, ChunkSize: Get<u32>>
+	SolutionPostProcessor<AccountId, Accuracy> for Reduce<ChunkSize>
+{
+	fn process(
+		result: ElectionResult<AccountId, Accuracy>,
+		stake_of: impl Fn(&AccountId) -> VoteWeight,
+	) -> Result<ElectionResult<AccountId, Accuracy>, Error> {
+		let ElectionResult { winners, assignments } = result;
+		let mut staked = assignment_ratio_to_staked_normalized(assignments, stake_of)?;
+		sp_npos_elections::reduce_chunked(&mut staked, ChunkSize::get() as usize, || true);
+		let assignments = assignment_staked_to_ratio_normalized(staked)?;
+		Ok(ElectionResult { winners, assignments })
+	}
+
+	fn weight<T: WeightInfo>(voters: u32, targets: u32, vote_degree: u32) -> Weight {
+		T::reduce(voters, targets, vote_degree)
+	}
+}
+
+/// Same as [`Reduce`], but the chunks are reduced in parallel.
+///
+/// The outcome is exactly the same as that of [`Reduce`] with the same `ChunkSize`.
+#[cfg(feature = "std")]
+pub struct ParallelReduce<ChunkSize = ()>(sp_std::marker::PhantomData<ChunkSize>);
+
+#[cfg(feature = "std")]
+impl<AccountId: IdentifierT + Send, Accuracy: PerThing128, ChunkSize: Get<u32>>
+	SolutionPostProcessor<AccountId, Accuracy> for ParallelReduce<ChunkSize>
+{
+	fn process(
+		result: ElectionResult<AccountId, Accuracy>,
+		stake_of: impl Fn(&AccountId) -> VoteWeight,
+	) -> Result<ElectionResult<AccountId, Accuracy>, Error> {
+		let ElectionResult { winners, assignments } = result;
+		let mut staked = assignment_ratio_to_staked_normalized(assignments, stake_of)?;
+		sp_npos_elections::reduce_chunked_parallel(&mut staked, ChunkSize::get() as usize);
+		let assignments = assignment_staked_to_ratio_normalized(staked)?;
+		Ok(ElectionResult { winners, assignments })
+	}
+
+	fn weight<T: WeightInfo>(voters: u32, targets: u32, vote_degree: u32) -> Weight {
+		T::reduce(voters, targets, vote_degree)
+	}
+}
+
+/// An [`NposSolver`] that runs `Solver`, and then post-processes its outcome with
+/// `PostProcessor`.
+///
+/// Its [`NposSolver::weight`] is that of `Solver`, plus that of `PostProcessor`.
+pub struct PostProcessed<Solver, PostProcessor>(
+	sp_std::marker::PhantomData<(Solver, PostProcessor)>,
+);
+
+impl<Solver, PostProcessor> NposSolver for PostProcessed<Solver, PostProcessor>
+where
+	Solver: NposSolver,
+	Solver::Error: From<Error>,
+	PostProcessor: SolutionPostProcessor<Solver::AccountId, Solver::Accuracy>,
+{
+	type AccountId = Solver::AccountId;
+	type Accuracy = Solver::Accuracy;
+	type Error = Solver::Error;
+	fn solve(
+		to_elect: usize,
+		targets: Vec<Self::AccountId>,
+		voters: Vec<(Self::AccountId, VoteWeight, impl IntoIterator<Item = Self::AccountId>)>,
+	) -> Result<ElectionResult<Self::AccountId, Self::Accuracy>, Self::Error> {
+		let stakes = voters
+			.iter()
+			.map(|(who, stake, _)| (who.clone(), *stake))
+			.collect::<BTreeMap<_, _>>();
+		let result = Solver::solve(to_elect, targets, voters)?;
+		PostProcessor::process(result, |who| stakes.get(who).copied().unwrap_or_default())
+			.map_err(Into::into)
+	}
+
+	fn weight<T: WeightInfo>(voters: u32, targets: u32, vote_degree: u32) -> Weight {
+		Solver::weight::<T>(voters, targets, vote_degree)
+			.saturating_add(PostProcessor::weight::<T>(voters, targets, vote_degree))
+	}
+}
+
 /// A wrapper for [`sp_npos_elections::seq_phragmen`] that implements [`NposSolver`]. See the
 /// documentation of [`sp_npos_elections::seq_phragmen`] for more info.
 pub struct SequentialPhragmen<AccountId, Accuracy, Balancing = ()>(
 	sp_std::marker::PhantomData<(AccountId, Accuracy, Balancing)>,
 );
 
+#[cfg(feature = "trace")]
+impl<AccountId: IdentifierT, Accuracy: PerThing128, Balancing: Get<Option<BalancingConfig>>>
+	SequentialPhragmen<AccountId, Accuracy, Balancing>
+{
+	/// Same as [`NposSolver::solve`], but also returns a trace of all the rounds of the election.
+	pub fn solve_traced(
+		winners: usize,
+		targets: Vec<AccountId>,
+		voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+	) -> Result<
+		(ElectionResult<AccountId, Accuracy>, sp_npos_elections::trace::Trace<AccountId>),
+		sp_npos_elections::Error,
+	> {
+		sp_npos_elections::seq_phragmen_traced(winners, targets, voters, Balancing::get())
+	}
+}
+
 impl<AccountId: IdentifierT, Accuracy: PerThing128, Balancing: Get<Option<BalancingConfig>>>
 	NposSolver for SequentialPhragmen<AccountId, Accuracy, Balancing>
 {
@@ -645,6 +1242,23 @@ pub struct PhragMMS<AccountId, Accuracy, Balancing = ()>(
 	sp_std::marker::PhantomData<(AccountId, Accuracy, Balancing)>,
 );
 
+#[cfg(feature = "trace")]
+impl<AccountId: IdentifierT, Accuracy: PerThing128, Balancing: Get<Option<BalancingConfig>>>
+	PhragMMS<AccountId, Accuracy, Balancing>
+{
+	/// Same as [`NposSolver::solve`], but also returns a trace of all the rounds of the election.
+	pub fn solve_traced(
+		winners: usize,
+		targets: Vec<AccountId>,
+		voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+	) -> Result<
+		(ElectionResult<AccountId, Accuracy>, sp_npos_elections::trace::Trace<AccountId>),
+		sp_npos_elections::Error,
+	> {
+		sp_npos_elections::phragmms_traced(winners, targets, voters, Balancing::get())
+	}
+}
+
 impl<AccountId: IdentifierT, Accuracy: PerThing128, Balancing: Get<Option<BalancingConfig>>>
 	NposSolver for PhragMMS<AccountId, Accuracy, Balancing>
 {
@@ -660,12 +1274,75 @@ impl<AccountId: IdentifierT, Accuracy: PerThing128, Balancing: Get<Option<Balanc
 	}
 
 	fn weight<T: WeightInfo>(voters: u32, targets: u32, vote_degree: u32) -> Weight {
-		T::phragmms(voters, targets, vote_degree)
+		match Balancing::get() {
+			Some(_) => T::phragmms_balanced(voters, targets, vote_degree),
+			None => T::phragmms(voters, targets, vote_degree),
+		}
 	}
 }
 
 /// A voter, at the level of abstraction of this crate.
-pub type Voter<AccountId, Bound> = (AccountId, VoteWeight, BoundedVec<AccountId, Bound>);
+///
+/// Encodes exactly like the `(who, weight, votes)` tuple, which it can be converted from and into.
+#[derive(codec::Encode, codec::Decode, scale_info::TypeInfo)]
+#[scale_info(skip_type_params(Bound))]
+pub struct Voter<AccountId, Bound> {
+	/// The voter.
+	pub who: AccountId,
+	/// The stake of the voter.
+	pub weight: VoteWeight,
+	/// The targets that the voter votes for.
+	pub votes: BoundedVec<AccountId, Bound>,
+}
+
+impl<AccountId, Bound> Voter<AccountId, Bound> {
+	/// Create a new voter.
+	pub fn new(who: AccountId, weight: VoteWeight, votes: BoundedVec<AccountId, Bound>) -> Self {
+		Self { who, weight, votes }
+	}
+}
+
+// Manual implementations, such that `Bound` does not need to implement any of these itself.
+impl<AccountId: Clone, Bound> Clone for Voter<AccountId, Bound> {
+	fn clone(&self) -> Self {
+		Self { who: self.who.clone(), weight: self.weight, votes: self.votes.clone() }
+	}
+}
+
+impl<AccountId: PartialEq, Bound: Get<u32>> PartialEq for Voter<AccountId, Bound> {
+	fn eq(&self, other: &Self) -> bool {
+		self.who == other.who && self.weight == other.weight && self.votes == other.votes
+	}
+}
+
+impl<AccountId: Eq, Bound: Get<u32>> Eq for Voter<AccountId, Bound> {}
+
+impl<AccountId: Debug, Bound: Get<u32>> Debug for Voter<AccountId, Bound> {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter<'_>) -> sp_std::fmt::Result {
+		f.debug_struct("Voter")
+			.field("who", &self.who)
+			.field("weight", &self.weight)
+			.field("votes", &self.votes)
+			.finish()
+	}
+}
+
+// NOTE: kept during a deprecation window, for data providers that still use the tuple form.
+impl<AccountId, Bound> From<(AccountId, VoteWeight, BoundedVec<AccountId, Bound>)>
+	for Voter<AccountId, Bound>
+{
+	fn from((who, weight, votes): (AccountId, VoteWeight, BoundedVec<AccountId, Bound>)) -> Self {
+		Self { who, weight, votes }
+	}
+}
+
+impl<AccountId, Bound> From<Voter<AccountId, Bound>>
+	for (AccountId, VoteWeight, BoundedVec<AccountId, Bound>)
+{
+	fn from(voter: Voter<AccountId, Bound>) -> Self {
+		(voter.who, voter.weight, voter.votes)
+	}
+}
 
 /// Same as [`Voter`], but parameterized by an [`ElectionDataProvider`].
 pub type VoterOf<D> =
@@ -0,0 +1,136 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An [`ElectionDataProvider`] that merges the data of multiple providers.
+//!
+//! This allows a single election to run over, for example, the nominators of staking plus a
+//! separately managed set of candidates.
+
+use crate::{data_provider, ElectionDataProvider, VoterOf};
+use sp_std::{collections::btree_set::BTreeSet, marker::PhantomData, prelude::*};
+
+#[cfg(any(feature = "runtime-benchmarks", test))]
+use crate::{BoundedVec, VoteWeight};
+
+/// Merges the voters and targets of the data providers `A` and `B`, given as `(A, B)`.
+///
+/// Voters and targets are first taken from `A`, and then from `B`. Any voter or target of `B` that
+/// is already provided by `A` is ignored, i.e. `A` takes precedence. If a bound is given, `B` is
+/// only asked for as many items as `A` did not already provide, such that the merged result never
+/// exceeds the bound.
+///
+/// [`ElectionDataProvider::desired_targets`] and
+/// [`ElectionDataProvider::next_election_prediction`] are those of `A`, the primary provider. The
+/// merged provider is only [`ElectionDataProvider::ready_for_election`] if both are.
+///
+/// More than two providers can be merged by nesting, e.g. `MergedDataProvider<(A,
+/// MergedDataProvider<(B, C)>)>`.
+pub struct MergedDataProvider<P>(PhantomData<P>);
+
+impl<A, B> MergedDataProvider<(A, B)>
+where
+	A: ElectionDataProvider,
+	A::AccountId: Ord + Clone,
+{
+	/// Fetch items from `a` and then `b`, de-duplicated by `key` and bounded by `maybe_max_len`.
+	fn merge<T>(
+		maybe_max_len: Option<usize>,
+		a: impl FnOnce(Option<usize>) -> data_provider::Result<Vec<T>>,
+		b: impl FnOnce(Option<usize>) -> data_provider::Result<Vec<T>>,
+		key: impl Fn(&T) -> &A::AccountId,
+	) -> data_provider::Result<Vec<T>> {
+		let mut merged = a(maybe_max_len)?;
+		let remaining = maybe_max_len.map(|max| max.saturating_sub(merged.len()));
+		if remaining == Some(0) {
+			return Ok(merged)
+		}
+
+		let mut seen = merged.iter().map(|item| key(item).clone()).collect::<BTreeSet<_>>();
+		for item in b(remaining)? {
+			if seen.insert(key(&item).clone()) {
+				merged.push(item);
+			}
+		}
+		Ok(merged)
+	}
+}
+
+impl<A, B> ElectionDataProvider for MergedDataProvider<(A, B)>
+where
+	A: ElectionDataProvider,
+	A::AccountId: Ord + Clone,
+	B: ElectionDataProvider<
+		AccountId = A::AccountId,
+		BlockNumber = A::BlockNumber,
+		MaxVotesPerVoter = A::MaxVotesPerVoter,
+	>,
+{
+	type AccountId = A::AccountId;
+	type BlockNumber = A::BlockNumber;
+	type MaxVotesPerVoter = A::MaxVotesPerVoter;
+
+	fn electable_targets(
+		maybe_max_len: Option<usize>,
+	) -> data_provider::Result<Vec<Self::AccountId>> {
+		Self::merge(maybe_max_len, A::electable_targets, B::electable_targets, |t| t)
+	}
+
+	fn electing_voters(maybe_max_len: Option<usize>) -> data_provider::Result<Vec<VoterOf<Self>>> {
+		Self::merge(maybe_max_len, A::electing_voters, B::electing_voters, |v| &v.who)
+	}
+
+	fn desired_targets() -> data_provider::Result<u32> {
+		A::desired_targets()
+	}
+
+	fn next_election_prediction(now: Self::BlockNumber) -> Self::BlockNumber {
+		A::next_election_prediction(now)
+	}
+
+	fn ready_for_election() -> bool {
+		A::ready_for_election() && B::ready_for_election()
+	}
+
+	#[cfg(any(feature = "runtime-benchmarks", test))]
+	fn put_snapshot(
+		voters: Vec<VoterOf<Self>>,
+		targets: Vec<Self::AccountId>,
+		target_stake: Option<VoteWeight>,
+	) {
+		A::put_snapshot(voters, targets, target_stake)
+	}
+
+	#[cfg(any(feature = "runtime-benchmarks", test))]
+	fn add_voter(
+		voter: Self::AccountId,
+		weight: VoteWeight,
+		targets: BoundedVec<Self::AccountId, Self::MaxVotesPerVoter>,
+	) {
+		A::add_voter(voter, weight, targets)
+	}
+
+	#[cfg(any(feature = "runtime-benchmarks", test))]
+	fn add_target(target: Self::AccountId) {
+		A::add_target(target)
+	}
+
+	#[cfg(any(feature = "runtime-benchmarks", test))]
+	fn clear() {
+		A::clear();
+		B::clear();
+	}
+}
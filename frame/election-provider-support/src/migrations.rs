@@ -0,0 +1,185 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migration helpers for pallets that store election results.
+
+use crate::{BoundedSupports, Get, Support, Supports, Weight};
+use codec::FullCodec;
+use frame_support::{
+	log, storage::StorageValue, traits::OnRuntimeUpgrade, weights::RuntimeDbWeight,
+};
+use sp_runtime::traits::Zero;
+use sp_std::{marker::PhantomData, prelude::*};
+
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+const LOG_TARGET: &str = "runtime::election-provider::migrations";
+
+/// Bound `supports` to at most `MaxWinners` winners, each with at most `MaxBackersPerWinner`
+/// backers.
+///
+/// The truncation is deterministic:
+///
+/// 1. The backers of each winner are ordered by their stake, descending, and then by their account
+///    id, ascending. Only the first `MaxBackersPerWinner` of them are kept, and the stake of the
+///    removed backers is subtracted from the total of the winner.
+/// 2. The winners are then ordered by their (possibly reduced) total, descending, and then by their
+///    account id, ascending. Only the first `MaxWinners` of them are kept.
+///
+/// The kept winners retain their original relative order, and so do their kept backers.
+pub fn bound_supports<AccountId, MaxWinners, MaxBackersPerWinner>(
+	supports: Supports<AccountId>,
+) -> BoundedSupports<AccountId, MaxWinners>
+where
+	AccountId: Ord + Clone,
+	MaxWinners: Get<u32>,
+	MaxBackersPerWinner: Get<u32>,
+{
+	let max_backers = MaxBackersPerWinner::get() as usize;
+	let mut supports = supports
+		.into_iter()
+		.map(|(winner, support)| (winner, bound_support(support, max_backers)))
+		.collect::<Vec<_>>();
+
+	let max_winners = MaxWinners::get() as usize;
+	if supports.len() > max_winners {
+		let mut by_rank = (0..supports.len()).collect::<Vec<_>>();
+		by_rank.sort_by(|a, b| {
+			let (a_who, a_support) = &supports[*a];
+			let (b_who, b_support) = &supports[*b];
+			b_support.total.cmp(&a_support.total).then_with(|| a_who.cmp(b_who))
+		});
+		let mut keep = vec![false; supports.len()];
+		by_rank.into_iter().take(max_winners).for_each(|index| keep[index] = true);
+		let mut keep = keep.into_iter();
+		supports.retain(|_| keep.next().unwrap_or(false));
+	}
+
+	BoundedSupports::<AccountId, MaxWinners>::truncate_from(supports)
+}
+
+fn bound_support<AccountId: Ord>(
+	mut support: Support<AccountId>,
+	max_backers: usize,
+) -> Support<AccountId> {
+	if support.voters.len() <= max_backers {
+		return support
+	}
+
+	let mut by_rank = (0..support.voters.len()).collect::<Vec<_>>();
+	by_rank.sort_by(|a, b| {
+		let (a_who, a_stake) = &support.voters[*a];
+		let (b_who, b_stake) = &support.voters[*b];
+		b_stake.cmp(a_stake).then_with(|| a_who.cmp(b_who))
+	});
+	let mut keep = vec![false; support.voters.len()];
+	by_rank.into_iter().take(max_backers).for_each(|index| keep[index] = true);
+
+	let mut removed = Zero::zero();
+	let mut keep = keep.into_iter();
+	support.voters.retain(|(_, stake)| {
+		let kept = keep.next().unwrap_or(false);
+		if !kept {
+			removed = stake.saturating_add(removed);
+		}
+		kept
+	});
+	support.total = support.total.saturating_sub(removed);
+	support
+}
+
+/// Migrate the storage value `Item` from an unbounded [`Supports`] to [`BoundedSupports`], using
+/// [`bound_supports`].
+///
+/// `Item` is only rewritten if it exists. If it cannot be decoded, it is left untouched.
+///
+/// Pallets that store supports in other kinds of storage items can use [`bound_supports`]
+/// directly in their own migrations.
+pub struct MigrateToBoundedSupports<Item, AccountId, MaxWinners, MaxBackersPerWinner, DbWeight>(
+	PhantomData<(Item, AccountId, MaxWinners, MaxBackersPerWinner, DbWeight)>,
+);
+
+impl<Item, AccountId, MaxWinners, MaxBackersPerWinner, DbWeight> OnRuntimeUpgrade
+	for MigrateToBoundedSupports<Item, AccountId, MaxWinners, MaxBackersPerWinner, DbWeight>
+where
+	Item: StorageValue<BoundedSupports<AccountId, MaxWinners>>,
+	AccountId: FullCodec + Ord + Clone,
+	MaxWinners: Get<u32>,
+	MaxBackersPerWinner: Get<u32>,
+	DbWeight: Get<RuntimeDbWeight>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		let mut before = 0;
+		let result = Item::translate::<Supports<AccountId>, _>(|maybe_old| {
+			maybe_old.map(|old| {
+				before = old.len();
+				bound_supports::<_, MaxWinners, MaxBackersPerWinner>(old)
+			})
+		});
+
+		match result {
+			Ok(Some(new)) => {
+				log::info!(
+					target: LOG_TARGET,
+					"migrated supports of {} winners, {} kept.",
+					before,
+					new.len(),
+				);
+				DbWeight::get().reads_writes(1, 1)
+			},
+			Ok(None) => {
+				log::info!(target: LOG_TARGET, "no supports to migrate.");
+				DbWeight::get().reads(1)
+			},
+			Err(()) => {
+				log::error!(target: LOG_TARGET, "failed to decode supports, not migrated.");
+				DbWeight::get().reads(1)
+			},
+		}
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+		use codec::Encode;
+
+		let old = frame_support::storage::unhashed::get::<Supports<AccountId>>(&Item::hashed_key());
+		let expected = old.map(bound_supports::<_, MaxWinners, MaxBackersPerWinner>);
+		Ok(expected.encode())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+		use codec::Decode;
+
+		let expected =
+			Option::<BoundedSupports<AccountId, MaxWinners>>::decode(&mut state.as_slice())
+				.map_err(|_| "invalid pre-upgrade state")?;
+		let new = frame_support::storage::unhashed::get::<BoundedSupports<AccountId, MaxWinners>>(
+			&Item::hashed_key(),
+		);
+
+		frame_support::ensure!(new == expected, "migrated supports differ from the expected ones");
+		frame_support::ensure!(
+			new.iter()
+				.flatten()
+				.all(|(_, support)| support.voters.len() as u32 <= MaxBackersPerWinner::get()),
+			"a winner has too many backers"
+		);
+		Ok(())
+	}
+}
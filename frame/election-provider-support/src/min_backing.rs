@@ -0,0 +1,201 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An [`ElectionProvider`] wrapper that enforces a minimum backing on every winner.
+//!
+//! Nothing in the election algorithms themselves prevents a winner from being elected with
+//! (effectively) dust backing, if there are not enough well-backed candidates. This wrapper
+//! post-processes the outcome of another election provider, and deals with such winners according
+//! to [`BelowMinBacking`].
+
+use crate::{
+	BoundedSupportsOf, BoundedVec, ElectionProvider, ElectionProviderBase, ExtendedBalance, Get,
+	InstantElectionProvider, Support, Supports,
+};
+use sp_arithmetic::{helpers_128bit::multiply_by_rational_with_rounding, Rounding};
+use sp_runtime::RuntimeDebug;
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// What to do with the winners whose total backing is below the threshold.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum BelowMinBacking {
+	/// Drop them from the outcome.
+	Drop,
+	/// Drop them from the outcome, and move their backing to the other winners that their voters
+	/// also back, pro rata to the existing backing. Backing of voters that back no other winner is
+	/// lost.
+	Redistribute,
+	/// Fail the election altogether.
+	Fail,
+}
+
+/// Hook called with the winners that were dropped by [`MinBackedWinners`], alongside their total
+/// backing.
+pub trait OnWinnersDropped<AccountId> {
+	fn on_winners_dropped(dropped: &[(AccountId, ExtendedBalance)]);
+}
+
+impl<AccountId> OnWinnersDropped<AccountId> for () {
+	fn on_winners_dropped(_: &[(AccountId, ExtendedBalance)]) {}
+}
+
+/// The error type of [`MinBackedWinners`].
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum MinBackedError<E> {
+	/// The inner election provider failed.
+	Inner(E),
+	/// A winner's backing is below the threshold, and [`BelowMinBacking::Fail`] is configured.
+	BelowMinBacking,
+}
+
+/// Wraps the election provider `Inner`, and deals with every winner whose total backing is less
+/// than `Threshold` according to `Mode`.
+///
+/// Dropped winners are reported to `Handler`, if there are any. Note that the outcome may contain
+/// less winners than desired, or none at all, if all of them are dropped.
+pub struct MinBackedWinners<Inner, Threshold, Mode, Handler = ()>(
+	PhantomData<(Inner, Threshold, Mode, Handler)>,
+);
+
+impl<Inner, Threshold, Mode, Handler> MinBackedWinners<Inner, Threshold, Mode, Handler>
+where
+	Inner: ElectionProviderBase,
+	Inner::AccountId: Clone + PartialEq,
+	Threshold: Get<ExtendedBalance>,
+	Mode: Get<BelowMinBacking>,
+	Handler: OnWinnersDropped<Inner::AccountId>,
+{
+	fn enforce(
+		supports: BoundedSupportsOf<Inner>,
+	) -> Result<BoundedSupportsOf<Inner>, MinBackedError<Inner::Error>> {
+		let threshold = Threshold::get();
+		let mode = Mode::get();
+
+		let (mut kept, dropped): (Supports<Inner::AccountId>, Supports<Inner::AccountId>) =
+			supports.into_inner().into_iter().partition(|(_, s)| s.total >= threshold);
+
+		// NOTE: winners are only ever removed, so `truncate_from` never truncates below.
+		if dropped.is_empty() {
+			return Ok(BoundedVec::truncate_from(kept))
+		}
+		if mode == BelowMinBacking::Fail {
+			return Err(MinBackedError::BelowMinBacking)
+		}
+		if mode == BelowMinBacking::Redistribute {
+			dropped
+				.iter()
+				.flat_map(|(_, Support { voters, .. })| voters.iter())
+				.for_each(|(who, backing)| Self::redistribute(&mut kept, who, *backing));
+		}
+
+		let report = dropped.into_iter().map(|(who, s)| (who, s.total)).collect::<Vec<_>>();
+		Handler::on_winners_dropped(&report);
+
+		Ok(BoundedVec::truncate_from(kept))
+	}
+
+	/// Spread `backing` of `who` over the winners in `supports` that `who` already backs.
+	fn redistribute(
+		supports: &mut Supports<Inner::AccountId>,
+		who: &Inner::AccountId,
+		backing: ExtendedBalance,
+	) {
+		let existing = supports
+			.iter()
+			.enumerate()
+			.filter_map(|(i, (_, s))| {
+				s.voters.iter().position(|(v, _)| v == who).map(|j| (i, j, s.voters[j].1))
+			})
+			.collect::<Vec<_>>();
+		let sum = existing
+			.iter()
+			.fold(0 as ExtendedBalance, |acc, (_, _, b)| acc.saturating_add(*b));
+		if sum == 0 {
+			return
+		}
+
+		let mut remaining = backing;
+		for (k, (i, j, b)) in existing.iter().enumerate() {
+			// the last one gets whatever is left, so that no backing is lost to rounding.
+			let share = if k == existing.len() - 1 {
+				remaining
+			} else {
+				multiply_by_rational_with_rounding(backing, *b, sum, Rounding::Down)
+					.unwrap_or_default()
+					.min(remaining)
+			};
+			remaining -= share;
+
+			let support = &mut supports[*i].1;
+			support.voters[*j].1 = support.voters[*j].1.saturating_add(share);
+			support.total = support.total.saturating_add(share);
+		}
+	}
+}
+
+impl<Inner, Threshold, Mode, Handler> ElectionProviderBase
+	for MinBackedWinners<Inner, Threshold, Mode, Handler>
+where
+	Inner: ElectionProviderBase,
+{
+	type AccountId = Inner::AccountId;
+	type BlockNumber = Inner::BlockNumber;
+	type Error = MinBackedError<Inner::Error>;
+	type MaxWinners = Inner::MaxWinners;
+	type DataProvider = Inner::DataProvider;
+}
+
+impl<Inner, Threshold, Mode, Handler> ElectionProvider
+	for MinBackedWinners<Inner, Threshold, Mode, Handler>
+where
+	Inner: ElectionProvider,
+	Inner::AccountId: Clone + PartialEq,
+	Threshold: Get<ExtendedBalance>,
+	Mode: Get<BelowMinBacking>,
+	Handler: OnWinnersDropped<Inner::AccountId>,
+{
+	fn ongoing() -> bool {
+		Inner::ongoing()
+	}
+
+	fn defer(requested_delay: Self::BlockNumber) -> Result<Self::BlockNumber, Self::Error> {
+		Inner::defer(requested_delay).map_err(MinBackedError::Inner)
+	}
+
+	fn elect() -> Result<BoundedSupportsOf<Self>, Self::Error> {
+		Inner::elect().map_err(MinBackedError::Inner).and_then(Self::enforce)
+	}
+}
+
+impl<Inner, Threshold, Mode, Handler> InstantElectionProvider
+	for MinBackedWinners<Inner, Threshold, Mode, Handler>
+where
+	Inner: InstantElectionProvider,
+	Inner::AccountId: Clone + PartialEq,
+	Threshold: Get<ExtendedBalance>,
+	Mode: Get<BelowMinBacking>,
+	Handler: OnWinnersDropped<Inner::AccountId>,
+{
+	fn instant_elect(
+		forced_input_voters_bound: Option<u32>,
+		forced_input_target_bound: Option<u32>,
+	) -> Result<BoundedSupportsOf<Self>, Self::Error> {
+		Inner::instant_elect(forced_input_voters_bound, forced_input_target_bound)
+			.map_err(MinBackedError::Inner)
+			.and_then(Self::enforce)
+	}
+}
@@ -0,0 +1,679 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reusable utilities to mine an NPoS solution, typically in an offchain worker.
+//!
+//! The main entry point is [`Miner`], which is parameterized by a [`MinerConfig`]. It can:
+//!
+//! 1. fetch a snapshot from any [`ElectionDataProvider`] ([`Miner::fetch_snapshot`]),
+//! 2. solve it with any [`NposSolver`] ([`Miner::mine_solution_with_snapshot`]),
+//! 3. reduce and trim the result to fit the length and weight bounds of [`MinerConfig`]
+//!    ([`Miner::trim_assignments_length`], [`Miner::trim_assignments_weight`]),
+//! 4. and finally compute the score of the outcome.
+//!
+//! The [`helpers`] module contains functions to build the index getter closures that are needed to
+//! convert between a snapshot and the index-based [`NposSolution`].
+
+use crate::{
+	ElectionDataProvider, Get, IndexAssignmentOf, NposSolution, NposSolver, PerThing128, Weight,
+};
+use codec::{Decode, Encode};
+use frame_support::log;
+use scale_info::TypeInfo;
+use sp_npos_elections::{
+	assignment_ratio_to_staked_normalized, assignment_staked_to_ratio_normalized, ElectionResult,
+	ElectionScore,
+};
+use sp_runtime::{RuntimeDebug, SaturatedConversion};
+use sp_std::prelude::*;
+
+const LOG_TARGET: &str = "runtime::election-provider";
+
+macro_rules! log_miner {
+	($level:tt, $pattern:expr $(, $values:expr)* $(,)?) => {
+		log::$level!(
+			target: LOG_TARGET,
+			concat!("🗳 ", $pattern) $(, $values)*
+		)
+	};
+}
+
+/// Configurations for a [`Miner`].
+pub trait MinerConfig {
+	/// The account id type.
+	type AccountId: Ord + Clone + codec::Codec + sp_std::fmt::Debug;
+	/// The solution that the miner is mining.
+	type Solution: codec::Codec
+		+ Default
+		+ PartialEq
+		+ Eq
+		+ Clone
+		+ sp_std::fmt::Debug
+		+ Ord
+		+ NposSolution
+		+ TypeInfo;
+	/// Maximum number of votes per voter in the snapshots.
+	type MaxVotesPerVoter;
+	/// Maximum length of the solution that the miner is allowed to generate.
+	///
+	/// Solutions are trimmed to respect this.
+	type MaxLength: Get<u32>;
+	/// Maximum weight of the solution that the miner is allowed to generate.
+	///
+	/// Solutions are trimmed to respect this.
+	///
+	/// The weight is computed using `solution_weight`.
+	type MaxWeight: Get<Weight>;
+	/// The maximum number of winners that can be elected.
+	type MaxWinners: Get<u32>;
+	/// Something that can compute the weight of a solution.
+	///
+	/// This weight estimate is then used to trim the solution, based on [`MinerConfig::MaxWeight`].
+	fn solution_weight(voters: u32, targets: u32, active_voters: u32, degree: u32) -> Weight;
+}
+
+/// The solution type of a [`MinerConfig`].
+pub type SolutionOf<T> = <T as MinerConfig>::Solution;
+
+/// The voter index type of the solution of a [`MinerConfig`].
+pub type SolutionVoterIndexOf<T> = <SolutionOf<T> as NposSolution>::VoterIndex;
+
+/// The target index type of the solution of a [`MinerConfig`].
+pub type SolutionTargetIndexOf<T> = <SolutionOf<T> as NposSolution>::TargetIndex;
+
+/// The accuracy of the solution of a [`MinerConfig`].
+pub type SolutionAccuracyOf<T> = <SolutionOf<T> as NposSolution>::Accuracy;
+
+/// A voter's fundamental data, parameterized by a [`MinerConfig`]: their ID, their stake, and the
+/// list of candidates for whom they voted.
+pub type MinerVoterOf<T> =
+	crate::Voter<<T as MinerConfig>::AccountId, <T as MinerConfig>::MaxVotesPerVoter>;
+
+/// The [`crate::IndexAssignment`] type specialized for a particular [`MinerConfig`].
+pub type MinerIndexAssignmentOf<T> = IndexAssignmentOf<SolutionOf<T>>;
+
+/// The snapshot that a [`Miner`] works on: voters, targets and the number of desired targets.
+pub type SnapshotOf<T> = (Vec<MinerVoterOf<T>>, Vec<<T as MinerConfig>::AccountId>, u32);
+
+/// Encodes the length of a solution or a snapshot.
+///
+/// This can be stored on-chain, in which case it should contain the **size of the entire
+/// snapshot**. It can also be used in dispatchables as weight witness data, in which case it
+/// should **only contain the size of the presented solution**, not the entire snapshot.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, Debug, Default, TypeInfo)]
+pub struct SolutionOrSnapshotSize {
+	/// The length of voters.
+	#[codec(compact)]
+	pub voters: u32,
+	/// The length of targets.
+	#[codec(compact)]
+	pub targets: u32,
+}
+
+/// Errors of the [`Miner`].
+#[derive(Eq, PartialEq, RuntimeDebug)]
+pub enum MinerError {
+	/// An internal error in the NPoS elections crate.
+	NposElections(sp_npos_elections::Error),
+	/// The data provider failed to provide the snapshot.
+	DataProvider(&'static str),
+	/// There are no more voters to remove to trim the solution.
+	NoMoreVoters,
+	/// An error from the solver.
+	Solver,
+}
+
+impl From<sp_npos_elections::Error> for MinerError {
+	fn from(e: sp_npos_elections::Error) -> Self {
+		MinerError::NposElections(e)
+	}
+}
+
+/// A base miner, suitable to be used for both signed and unsigned submissions.
+pub struct Miner<T: MinerConfig>(sp_std::marker::PhantomData<T>);
+
+impl<T: MinerConfig> Miner<T> {
+	/// Fetch a snapshot from the data provider `D`.
+	///
+	/// The bounds are passed as-is to [`ElectionDataProvider::electing_voters`] and
+	/// [`ElectionDataProvider::electable_targets`].
+	pub fn fetch_snapshot<D>(
+		voters_bound: Option<usize>,
+		targets_bound: Option<usize>,
+	) -> Result<SnapshotOf<T>, MinerError>
+	where
+		D: ElectionDataProvider<AccountId = T::AccountId, MaxVotesPerVoter = T::MaxVotesPerVoter>,
+	{
+		let targets = D::electable_targets(targets_bound).map_err(MinerError::DataProvider)?;
+		let voters = D::electing_voters(voters_bound).map_err(MinerError::DataProvider)?;
+		let desired_targets = D::desired_targets().map_err(MinerError::DataProvider)?;
+		Ok((voters, targets, desired_targets))
+	}
+
+	/// Fetch a snapshot from the data provider `D`, and mine a solution for it with `S`.
+	///
+	/// See [`Self::fetch_snapshot`] and [`Self::mine_solution_with_snapshot`].
+	pub fn mine_solution<S, D>(
+		voters_bound: Option<usize>,
+		targets_bound: Option<usize>,
+	) -> Result<(SolutionOf<T>, ElectionScore, SolutionOrSnapshotSize), MinerError>
+	where
+		S: NposSolver<AccountId = T::AccountId>,
+		D: ElectionDataProvider<AccountId = T::AccountId, MaxVotesPerVoter = T::MaxVotesPerVoter>,
+	{
+		let (voters, targets, desired_targets) =
+			Self::fetch_snapshot::<D>(voters_bound, targets_bound)?;
+		Self::mine_solution_with_snapshot::<S>(voters, targets, desired_targets)
+	}
+
+	/// Mine a solution for the given snapshot with `S`.
+	///
+	/// The outcome is reduced, and trimmed to fit into [`MinerConfig::MaxWeight`] and
+	/// [`MinerConfig::MaxLength`]. The returned score is that of the final, trimmed solution.
+	pub fn mine_solution_with_snapshot<S>(
+		voters: Vec<MinerVoterOf<T>>,
+		targets: Vec<T::AccountId>,
+		desired_targets: u32,
+	) -> Result<(SolutionOf<T>, ElectionScore, SolutionOrSnapshotSize), MinerError>
+	where
+		S: NposSolver<AccountId = T::AccountId>,
+	{
+		S::solve(
+			desired_targets as usize,
+			targets.clone(),
+			voters.iter().cloned().map(Into::into).collect(),
+		)
+		.map_err(|e| {
+			log_miner!(error, "solver error: {:?}", e);
+			MinerError::Solver
+		})
+		.and_then(|e| {
+			Self::prepare_election_result_with_snapshot::<S::Accuracy>(
+				e,
+				voters,
+				targets,
+				desired_targets,
+			)
+		})
+	}
+
+	/// Convert a raw solution from [`sp_npos_elections::ElectionResult`] to a solution of type
+	/// [`MinerConfig::Solution`], which is ready to be submitted to the chain.
+	///
+	/// Will always reduce the solution as well.
+	pub fn prepare_election_result_with_snapshot<Accuracy: PerThing128>(
+		election_result: ElectionResult<T::AccountId, Accuracy>,
+		voters: Vec<MinerVoterOf<T>>,
+		targets: Vec<T::AccountId>,
+		desired_targets: u32,
+	) -> Result<(SolutionOf<T>, ElectionScore, SolutionOrSnapshotSize), MinerError> {
+		// now make some helper closures.
+		let cache = helpers::generate_voter_cache::<T>(&voters);
+		let voter_index = helpers::voter_index_fn::<T>(&cache);
+		let target_index = helpers::target_index_fn::<T>(&targets);
+		let voter_at = helpers::voter_at_fn::<T>(&voters);
+		let target_at = helpers::target_at_fn::<T>(&targets);
+		let stake_of = helpers::stake_of_fn::<T>(&voters, &cache);
+
+		// Compute the size of a solution comprised of the selected arguments.
+		//
+		// This function completes in `O(edges)`; it's expensive, but linear.
+		let encoded_size_of = |assignments: &[MinerIndexAssignmentOf<T>]| {
+			SolutionOf::<T>::try_from(assignments).map(|s| s.encoded_size())
+		};
+
+		let ElectionResult { assignments, winners: _ } = election_result;
+
+		// Reduce (requires round-trip to staked form)
+		let sorted_assignments = {
+			// convert to staked and reduce.
+			let mut staked = assignment_ratio_to_staked_normalized(assignments, &stake_of)?;
+
+			// we reduce before sorting in order to ensure that the reduction process doesn't
+			// accidentally change the sort order
+			sp_npos_elections::reduce(&mut staked);
+
+			// Sort the assignments by reversed voter stake. This ensures that we can efficiently
+			// truncate the list.
+			staked.sort_by_key(
+				|sp_npos_elections::StakedAssignment::<T::AccountId> { who, .. }| {
+					// though staked assignments are expressed in terms of absolute stake, we'd
+					// still need to iterate over all votes in order to actually compute the total
+					// stake. it should be faster to look it up from the cache.
+					let stake = cache.get(who).map(|idx| voters[*idx].weight).unwrap_or_default();
+					sp_std::cmp::Reverse(stake)
+				},
+			);
+
+			// convert back.
+			assignment_staked_to_ratio_normalized(staked)?
+		};
+
+		// convert to `IndexAssignment`. This improves the runtime complexity of repeatedly
+		// converting to `Solution`.
+		let mut index_assignments = sorted_assignments
+			.into_iter()
+			.map(|assignment| {
+				MinerIndexAssignmentOf::<T>::new(&assignment, &voter_index, &target_index)
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		// trim assignments list for weight and length.
+		let size =
+			SolutionOrSnapshotSize { voters: voters.len() as u32, targets: targets.len() as u32 };
+		Self::trim_assignments_weight(
+			desired_targets,
+			size,
+			T::MaxWeight::get(),
+			&mut index_assignments,
+		);
+		Self::trim_assignments_length(
+			T::MaxLength::get(),
+			&mut index_assignments,
+			&encoded_size_of,
+		)?;
+
+		// now make solution.
+		let solution = SolutionOf::<T>::try_from(&index_assignments)?;
+
+		// re-calc score.
+		let score = solution.clone().score(stake_of, voter_at, target_at)?;
+
+		Ok((solution, score, size))
+	}
+
+	/// Greedily reduce the size of the solution to fit into the block w.r.t length.
+	///
+	/// The length of the solution is largely a function of the number of voters. The number of
+	/// winners cannot be changed. Thus, to reduce the solution size, we need to strip voters.
+	///
+	/// Note that this solution is already computed, and winners are elected based on the merit of
+	/// the total stake in the system. Nevertheless, some of the voters may be removed here.
+	///
+	/// Sometimes, removing a voter can cause a validator to also be implicitly removed, if
+	/// that voter was the only backer of that winner. In such cases, this solution is invalid,
+	/// which will be caught prior to submission.
+	///
+	/// The score must be computed **after** this step. If this step reduces the score too much,
+	/// then the solution must be discarded.
+	pub fn trim_assignments_length(
+		max_allowed_length: u32,
+		assignments: &mut Vec<MinerIndexAssignmentOf<T>>,
+		encoded_size_of: impl Fn(
+			&[MinerIndexAssignmentOf<T>],
+		) -> Result<usize, sp_npos_elections::Error>,
+	) -> Result<(), MinerError> {
+		// Perform a binary search for the max subset of which can fit into the allowed
+		// length. Having discovered that, we can truncate efficiently.
+		let max_allowed_length: usize = max_allowed_length.saturated_into();
+		let mut high = assignments.len();
+		let mut low = 0;
+
+		// not much we can do if assignments are already empty.
+		if high == low {
+			return Ok(())
+		}
+
+		while high - low > 1 {
+			let test = (high + low) / 2;
+			if encoded_size_of(&assignments[..test])? <= max_allowed_length {
+				low = test;
+			} else {
+				high = test;
+			}
+		}
+		let maximum_allowed_voters = if low < assignments.len() &&
+			encoded_size_of(&assignments[..low + 1])? <= max_allowed_length
+		{
+			low + 1
+		} else {
+			low
+		};
+
+		// ensure our post-conditions are correct
+		debug_assert!(
+			encoded_size_of(&assignments[..maximum_allowed_voters]).unwrap() <= max_allowed_length
+		);
+		debug_assert!(if maximum_allowed_voters < assignments.len() {
+			encoded_size_of(&assignments[..maximum_allowed_voters + 1]).unwrap() >
+				max_allowed_length
+		} else {
+			true
+		});
+
+		// NOTE: before this point, every access was immutable.
+		// after this point, we never error.
+		// check before edit.
+
+		log_miner!(
+			debug,
+			"from {} assignments, truncating to {} for length, removing {}",
+			assignments.len(),
+			maximum_allowed_voters,
+			assignments.len().saturating_sub(maximum_allowed_voters),
+		);
+		assignments.truncate(maximum_allowed_voters);
+
+		Ok(())
+	}
+
+	/// Greedily reduce the size of the solution to fit into the block w.r.t. weight.
+	///
+	/// The weight of the solution is foremost a function of the number of voters (i.e.
+	/// `assignments.len()`). Aside from this, the other components of the weight are invariant. The
+	/// number of winners shall not be changed (otherwise the solution is invalid) and the
+	/// `ElectionSize` is merely a representation of the total number of stakers.
+	///
+	/// Thus, we reside to stripping away some voters from the `assignments`.
+	///
+	/// Note that the solution is already computed, and the winners are elected based on the merit
+	/// of the entire stake in the system. Nonetheless, some of the voters will be removed further
+	/// down the line.
+	///
+	/// Indeed, the score must be computed **after** this step. If this step reduces the score too
+	/// much or remove a winner, then the solution must be discarded **after** this step.
+	pub fn trim_assignments_weight(
+		desired_targets: u32,
+		size: SolutionOrSnapshotSize,
+		max_weight: Weight,
+		assignments: &mut Vec<MinerIndexAssignmentOf<T>>,
+	) {
+		let maximum_allowed_voters =
+			Self::maximum_voter_for_weight(desired_targets, size, max_weight);
+		let removing: usize =
+			assignments.len().saturating_sub(maximum_allowed_voters.saturated_into());
+		log_miner!(
+			debug,
+			"from {} assignments, truncating to {} for weight, removing {}",
+			assignments.len(),
+			maximum_allowed_voters,
+			removing,
+		);
+		assignments.truncate(maximum_allowed_voters as usize);
+	}
+
+	/// Find the maximum `len` that a solution can have in order to fit into the block weight.
+	///
+	/// This only returns a value between zero and `size.nominators`.
+	pub fn maximum_voter_for_weight(
+		desired_winners: u32,
+		size: SolutionOrSnapshotSize,
+		max_weight: Weight,
+	) -> u32 {
+		if size.voters < 1 {
+			return size.voters
+		}
+
+		let max_voters = size.voters.max(1);
+		let mut voters = max_voters;
+
+		// helper closures.
+		let weight_with = |active_voters: u32| -> Weight {
+			T::solution_weight(size.voters, size.targets, active_voters, desired_winners)
+		};
+
+		let next_voters = |current_weight: Weight, voters: u32, step: u32| -> Result<u32, ()> {
+			if current_weight.all_lt(max_weight) {
+				let next_voters = voters.checked_add(step);
+				match next_voters {
+					Some(voters) if voters < max_voters => Ok(voters),
+					_ => Err(()),
+				}
+			} else if current_weight.any_gt(max_weight) {
+				voters.checked_sub(step).ok_or(())
+			} else {
+				// If any of the constituent weights is equal to the max weight, we're at max
+				Ok(voters)
+			}
+		};
+
+		// First binary-search the right amount of voters
+		let mut step = voters / 2;
+		let mut current_weight = weight_with(voters);
+
+		while step > 0 {
+			match next_voters(current_weight, voters, step) {
+				// proceed with the binary search
+				Ok(next) if next != voters => {
+					voters = next;
+				},
+				// we are out of bounds, break out of the loop.
+				Err(()) => break,
+				// we found the right value - early exit the function.
+				Ok(next) => return next,
+			}
+			step /= 2;
+			current_weight = weight_with(voters);
+		}
+
+		// Time to finish. We might have reduced less than expected due to rounding error. Increase
+		// one last time if we have any room left, the reduce until we are sure we are below limit.
+		while voters < max_voters && weight_with(voters + 1).all_lt(max_weight) {
+			voters += 1;
+		}
+		while voters.checked_sub(1).is_some() && weight_with(voters).any_gt(max_weight) {
+			voters -= 1;
+		}
+
+		let final_decision = voters.min(size.voters);
+		debug_assert!(
+			weight_with(final_decision).all_lte(max_weight),
+			"weight_with({}) <= {}",
+			final_decision,
+			max_weight,
+		);
+		final_decision
+	}
+}
+
+/// Helper functions to build the index getter closures of a [`Miner`].
+pub mod helpers {
+	use super::{MinerConfig, MinerVoterOf, SolutionTargetIndexOf, SolutionVoterIndexOf};
+	#[cfg(feature = "std")]
+	use super::{MinerIndexAssignmentOf, SolutionAccuracyOf};
+	use crate::VoteWeight;
+	#[cfg(feature = "std")]
+	use sp_npos_elections::Assignment;
+	use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+	/// Generate a btree-map cache of the voters and their indices.
+	///
+	/// This can be used to efficiently build index getter closures.
+	pub fn generate_voter_cache<T: MinerConfig>(
+		snapshot: &Vec<MinerVoterOf<T>>,
+	) -> BTreeMap<T::AccountId, usize> {
+		let mut cache: BTreeMap<T::AccountId, usize> = BTreeMap::new();
+		snapshot.iter().enumerate().for_each(|(i, voter)| {
+			let _existed = cache.insert(voter.who.clone(), i);
+			// if a duplicate exists, we only consider the last one. Defensive only, should never
+			// happen.
+			debug_assert!(_existed.is_none());
+		});
+
+		cache
+	}
+
+	/// Create a function that returns the index of a voter in the snapshot.
+	///
+	/// The returning index type is the same as the one defined in `T::Solution::Voter`.
+	///
+	/// ## Warning
+	///
+	/// Note that this will represent the snapshot data from which the `cache` is generated.
+	pub fn voter_index_fn<T: MinerConfig>(
+		cache: &BTreeMap<T::AccountId, usize>,
+	) -> impl Fn(&T::AccountId) -> Option<SolutionVoterIndexOf<T>> + '_ {
+		move |who| {
+			cache
+				.get(who)
+				.and_then(|i| <usize as TryInto<SolutionVoterIndexOf<T>>>::try_into(*i).ok())
+		}
+	}
+
+	/// Create a function that returns the index of a voter in the snapshot.
+	///
+	/// Same as [`voter_index_fn`] but the returned function owns all its necessary data; nothing
+	/// is borrowed.
+	pub fn voter_index_fn_owned<T: MinerConfig>(
+		cache: BTreeMap<T::AccountId, usize>,
+	) -> impl Fn(&T::AccountId) -> Option<SolutionVoterIndexOf<T>> {
+		move |who| {
+			cache
+				.get(who)
+				.and_then(|i| <usize as TryInto<SolutionVoterIndexOf<T>>>::try_into(*i).ok())
+		}
+	}
+
+	/// Same as [`voter_index_fn`], but the returning index is converted into usize, if possible.
+	///
+	/// ## Warning
+	///
+	/// Note that this will represent the snapshot data from which the `cache` is generated.
+	pub fn voter_index_fn_usize<T: MinerConfig>(
+		cache: &BTreeMap<T::AccountId, usize>,
+	) -> impl Fn(&T::AccountId) -> Option<usize> + '_ {
+		move |who| cache.get(who).cloned()
+	}
+
+	/// Create a function that returns the index of a target in the snapshot.
+	///
+	/// The returned index type is the same as the one defined in `T::Solution::Target`.
+	///
+	/// Note: to the extent possible, the returned function should be cached and reused. Producing
+	/// that function requires a `O(n log n)` data transform. Each invocation of that function
+	/// completes in `O(log n)`.
+	pub fn target_index_fn<T: MinerConfig>(
+		snapshot: &Vec<T::AccountId>,
+	) -> impl Fn(&T::AccountId) -> Option<SolutionTargetIndexOf<T>> + '_ {
+		let cache: BTreeMap<_, _> =
+			snapshot.iter().enumerate().map(|(idx, account_id)| (account_id, idx)).collect();
+		move |who| {
+			cache
+				.get(who)
+				.and_then(|i| <usize as TryInto<SolutionTargetIndexOf<T>>>::try_into(*i).ok())
+		}
+	}
+
+	/// Create a function that can map a voter index ([`SolutionVoterIndexOf`]) to the actual voter
+	/// account using a linearly indexible snapshot.
+	pub fn voter_at_fn<T: MinerConfig>(
+		snapshot: &Vec<MinerVoterOf<T>>,
+	) -> impl Fn(SolutionVoterIndexOf<T>) -> Option<T::AccountId> + '_ {
+		move |i| {
+			<SolutionVoterIndexOf<T> as TryInto<usize>>::try_into(i)
+				.ok()
+				.and_then(|i| snapshot.get(i).map(|voter| voter.who.clone()))
+		}
+	}
+
+	/// Create a function that can map a target index ([`SolutionTargetIndexOf`]) to the actual
+	/// target account using a linearly indexible snapshot.
+	pub fn target_at_fn<T: MinerConfig>(
+		snapshot: &Vec<T::AccountId>,
+	) -> impl Fn(SolutionTargetIndexOf<T>) -> Option<T::AccountId> + '_ {
+		move |i| {
+			<SolutionTargetIndexOf<T> as TryInto<usize>>::try_into(i)
+				.ok()
+				.and_then(|i| snapshot.get(i).cloned())
+		}
+	}
+
+	/// Create a function to get the stake of a voter.
+	///
+	/// ## Warning
+	///
+	/// The cache need must be derived from the same snapshot. Zero is returned if a voter is
+	/// non-existent.
+	pub fn stake_of_fn<'a, T: MinerConfig>(
+		snapshot: &'a Vec<MinerVoterOf<T>>,
+		cache: &'a BTreeMap<T::AccountId, usize>,
+	) -> impl Fn(&T::AccountId) -> VoteWeight + 'a {
+		move |who| {
+			if let Some(index) = cache.get(who) {
+				snapshot.get(*index).map(|voter| voter.weight).unwrap_or_default()
+			} else {
+				0
+			}
+		}
+	}
+
+	/// Create a function that returns the index of a voter in the snapshot, backed by a hashmap.
+	///
+	/// Same as [`voter_index_fn_owned`], but each invocation completes in `O(1)`, and the returned
+	/// function can be shared between threads, see [`index_assignments_from`].
+	#[cfg(feature = "std")]
+	pub fn voter_index_fn_hashed<T: MinerConfig>(
+		snapshot: &[MinerVoterOf<T>],
+	) -> impl Fn(&T::AccountId) -> Option<SolutionVoterIndexOf<T>> + Sync
+	where
+		T::AccountId: std::hash::Hash + Sync,
+	{
+		let cache: std::collections::HashMap<_, _> = snapshot
+			.iter()
+			.enumerate()
+			.map(|(idx, voter)| (voter.who.clone(), idx))
+			.collect();
+		move |who| {
+			cache
+				.get(who)
+				.and_then(|i| <usize as TryInto<SolutionVoterIndexOf<T>>>::try_into(*i).ok())
+		}
+	}
+
+	/// Create a function that returns the index of a target in the snapshot, backed by a hashmap.
+	///
+	/// Same as [`target_index_fn`], but each invocation completes in `O(1)`, and the returned
+	/// function can be shared between threads, see [`index_assignments_from`].
+	#[cfg(feature = "std")]
+	pub fn target_index_fn_hashed<T: MinerConfig>(
+		snapshot: &[T::AccountId],
+	) -> impl Fn(&T::AccountId) -> Option<SolutionTargetIndexOf<T>> + Sync
+	where
+		T::AccountId: std::hash::Hash + Sync,
+	{
+		let cache: std::collections::HashMap<_, _> =
+			snapshot.iter().enumerate().map(|(idx, who)| (who.clone(), idx)).collect();
+		move |who| {
+			cache
+				.get(who)
+				.and_then(|i| <usize as TryInto<SolutionTargetIndexOf<T>>>::try_into(*i).ok())
+		}
+	}
+
+	/// Convert `assignments` into [`MinerIndexAssignmentOf`], in parallel.
+	///
+	/// The order of `assignments` is preserved. Fails if any voter or target is not in the
+	/// snapshot that `voter_index` and `target_index` are built from.
+	#[cfg(feature = "std")]
+	pub fn index_assignments_from<T: MinerConfig>(
+		assignments: &[Assignment<T::AccountId, SolutionAccuracyOf<T>>],
+		voter_index: impl Fn(&T::AccountId) -> Option<SolutionVoterIndexOf<T>> + Sync,
+		target_index: impl Fn(&T::AccountId) -> Option<SolutionTargetIndexOf<T>> + Sync,
+	) -> Result<Vec<MinerIndexAssignmentOf<T>>, crate::Error>
+	where
+		Assignment<T::AccountId, SolutionAccuracyOf<T>>: Sync,
+		MinerIndexAssignmentOf<T>: Send,
+	{
+		use rayon::prelude::*;
+		assignments
+			.par_iter()
+			.map(|assignment| {
+				MinerIndexAssignmentOf::<T>::new(assignment, &voter_index, &target_index)
+			})
+			.collect()
+	}
+}
@@ -20,33 +20,75 @@
 //! careful when using it onchain.
 
 use crate::{
-	BoundedSupportsOf, Debug, ElectionDataProvider, ElectionProvider, ElectionProviderBase,
-	InstantElectionProvider, NposSolver, WeightInfo,
+	BoundedSupportsOf, ElectionDataProvider, ElectionError, ElectionProvider, ElectionProviderBase,
+	InstantElectionProvider, NposSolver, Weight, WeightInfo,
 };
+use codec::{Decode, Encode};
 use frame_support::{dispatch::DispatchClass, traits::Get};
+use scale_info::TypeInfo;
 use sp_npos_elections::{
-	assignment_ratio_to_staked_normalized, to_supports, BoundedSupports, ElectionResult, VoteWeight,
+	assignment_ratio_to_staked_normalized, to_supports, BoundedSupports, ElectionResult,
+	ElectionScore, EvaluateSupport, Supports, VoteWeight,
 };
+use sp_runtime::RuntimeDebug;
 use sp_std::{collections::btree_map::BTreeMap, marker::PhantomData, prelude::*};
 
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+#[cfg(feature = "try-runtime")]
+use sp_std::collections::btree_set::BTreeSet;
+
 /// Errors of the on-chain election.
-#[derive(Eq, PartialEq, Debug)]
-pub enum Error {
+///
+/// Only [`ElectionError::Solver`], [`ElectionError::DataProvider`] and
+/// [`ElectionError::TooManyWinners`], the latter when `desired_targets` requested by the data
+/// provider exceeds `MaxWinners`, are ever returned.
+pub type Error = ElectionError;
+
+/// Encodable counterpart of [`Error`], returned by [`OnChainExecution::dry_run`].
+#[derive(Encode, Decode, TypeInfo, Clone, Eq, PartialEq, RuntimeDebug)]
+pub enum DryRunError {
 	/// An internal error in the NPoS elections crate.
-	NposElections(sp_npos_elections::Error),
-	/// Errors from the data provider.
-	DataProvider(&'static str),
-	/// Configurational error caused by `desired_targets` requested by data provider exceeding
-	/// `MaxWinners`.
+	NposElections,
+	/// Errors from the data provider, with their message.
+	DataProvider(Vec<u8>),
+	/// `desired_targets` exceeds `MaxWinners`.
 	TooManyWinners,
+	/// Any other error, with its message.
+	Other(Vec<u8>),
 }
 
-impl From<sp_npos_elections::Error> for Error {
-	fn from(e: sp_npos_elections::Error) -> Self {
-		Error::NposElections(e)
+impl From<Error> for DryRunError {
+	fn from(e: Error) -> Self {
+		match e {
+			Error::Solver(_) => DryRunError::NposElections,
+			Error::DataProvider(msg) => DryRunError::DataProvider(msg.as_bytes().to_vec()),
+			Error::TooManyWinners => DryRunError::TooManyWinners,
+			other => DryRunError::Other(<&'static str>::from(other).as_bytes().to_vec()),
+		}
 	}
 }
 
+/// The outcome of [`OnChainExecution::dry_run`]: the number of winners, the score of the
+/// election, and the weight it would consume.
+pub type DryRunOutcome = (u32, ElectionScore, Weight);
+
+/// Forecast of the next election, as returned by [`OnChainExecution::forecast`], or by any other
+/// election provider.
+#[derive(Encode, Decode, TypeInfo, Clone, Eq, PartialEq, RuntimeDebug)]
+pub struct ElectionForecast<BlockNumber> {
+	/// Block of the next election, as predicted by the data provider.
+	pub next_election: BlockNumber,
+	/// Number of voters in the snapshot, if the election was to happen now.
+	pub voters: u32,
+	/// Number of targets in the snapshot, if the election was to happen now.
+	pub targets: u32,
+	/// Maximum number of voters in the snapshot, e.g. [`Config::VotersBound`].
+	pub voters_bound: u32,
+	/// Maximum number of targets in the snapshot, e.g. [`Config::TargetsBound`].
+	pub targets_bound: u32,
+}
+
 /// A simple on-chain implementation of the election provider trait.
 ///
 /// This implements both `ElectionProvider` and `InstantElectionProvider`.
@@ -104,10 +146,37 @@ fn elect_with_input_bounds<T: Config>(
 	maybe_max_voters: Option<usize>,
 	maybe_max_targets: Option<usize>,
 ) -> Result<OnChainBoundedSupportsOf<T>, Error> {
+	let (supports, weight) =
+		solve_with_input_bounds::<T>(maybe_max_voters, maybe_max_targets, None)?;
+	frame_system::Pallet::<T::System>::register_extra_weight_unchecked(
+		weight,
+		DispatchClass::Mandatory,
+	);
+
+	// defensive: Since npos solver returns a result always bounded by `desired_targets`, this is
+	// never expected to happen as long as npos solver does what is expected for it to do.
+	let supports: OnChainBoundedSupportsOf<T> =
+		supports.try_into().map_err(|_| Error::TooManyWinners)?;
+
+	Ok(supports)
+}
+
+/// Solve the election with the given input bounds, and `desired_targets` if given instead of that
+/// of the data provider.
+///
+/// Return the supports of the winners, and the weight of the election.
+fn solve_with_input_bounds<T: Config>(
+	maybe_max_voters: Option<usize>,
+	maybe_max_targets: Option<usize>,
+	maybe_desired_targets: Option<u32>,
+) -> Result<(Supports<<T::System as frame_system::Config>::AccountId>, Weight), Error> {
 	let voters = T::DataProvider::electing_voters(maybe_max_voters).map_err(Error::DataProvider)?;
 	let targets =
 		T::DataProvider::electable_targets(maybe_max_targets).map_err(Error::DataProvider)?;
-	let desired_targets = T::DataProvider::desired_targets().map_err(Error::DataProvider)?;
+	let desired_targets = match maybe_desired_targets {
+		Some(desired_targets) => desired_targets,
+		None => T::DataProvider::desired_targets().map_err(Error::DataProvider)?,
+	};
 
 	if desired_targets > T::MaxWinners::get() {
 		// early exit
@@ -117,17 +186,19 @@ fn elect_with_input_bounds<T: Config>(
 	let voters_len = voters.len() as u32;
 	let targets_len = targets.len() as u32;
 
-	let stake_map: BTreeMap<_, _> = voters
-		.iter()
-		.map(|(validator, vote_weight, _)| (validator.clone(), *vote_weight))
-		.collect();
+	let stake_map: BTreeMap<_, _> =
+		voters.iter().map(|voter| (voter.who.clone(), voter.weight)).collect();
 
 	let stake_of = |w: &<T::System as frame_system::Config>::AccountId| -> VoteWeight {
 		stake_map.get(w).cloned().unwrap_or_default()
 	};
 
-	let ElectionResult { winners: _, assignments } =
-		T::Solver::solve(desired_targets as usize, targets, voters).map_err(Error::from)?;
+	let ElectionResult { winners: _, assignments } = T::Solver::solve(
+		desired_targets as usize,
+		targets,
+		voters.into_iter().map(Into::into).collect(),
+	)
+	.map_err(Error::from)?;
 
 	let staked = assignment_ratio_to_staked_normalized(assignments, &stake_of)?;
 
@@ -136,17 +207,53 @@ fn elect_with_input_bounds<T: Config>(
 		targets_len,
 		<T::DataProvider as ElectionDataProvider>::MaxVotesPerVoter::get(),
 	);
-	frame_system::Pallet::<T::System>::register_extra_weight_unchecked(
-		weight,
-		DispatchClass::Mandatory,
-	);
 
-	// defensive: Since npos solver returns a result always bounded by `desired_targets`, this is
-	// never expected to happen as long as npos solver does what is expected for it to do.
-	let supports: OnChainBoundedSupportsOf<T> =
-		to_supports(&staked).try_into().map_err(|_| Error::TooManyWinners)?;
+	Ok((to_supports(&staked), weight))
+}
 
-	Ok(supports)
+impl<T: Config> OnChainExecution<T> {
+	/// Run the election as [`ElectionProvider::elect`] would, without registering its weight, to
+	/// preview its outcome.
+	///
+	/// The given bounds and `desired_targets` are used instead of the configured ones, if any.
+	pub fn dry_run(
+		maybe_max_voters: Option<u32>,
+		maybe_max_targets: Option<u32>,
+		maybe_desired_targets: Option<u32>,
+	) -> Result<DryRunOutcome, Error> {
+		let (supports, weight) = solve_with_input_bounds::<T>(
+			Some(maybe_max_voters.unwrap_or_else(T::VotersBound::get) as usize),
+			Some(maybe_max_targets.unwrap_or_else(T::TargetsBound::get) as usize),
+			maybe_desired_targets,
+		)?;
+		if supports.len() > T::MaxWinners::get() as usize {
+			return Err(Error::TooManyWinners)
+		}
+
+		Ok((supports.len() as u32, supports.evaluate(), weight))
+	}
+
+	/// Forecast the next election: when it will happen, and the size of its snapshot if it was to
+	/// happen now.
+	///
+	/// Note that this reads the whole snapshot from the data provider.
+	pub fn forecast(
+	) -> Result<ElectionForecast<<T::System as frame_system::Config>::BlockNumber>, Error> {
+		let (voters_bound, targets_bound) = (T::VotersBound::get(), T::TargetsBound::get());
+		let now = frame_system::Pallet::<T::System>::block_number();
+		let voters = T::DataProvider::electing_voters(Some(voters_bound as usize))
+			.map_err(Error::DataProvider)?;
+		let targets = T::DataProvider::electable_targets(Some(targets_bound as usize))
+			.map_err(Error::DataProvider)?;
+
+		Ok(ElectionForecast {
+			next_election: T::DataProvider::next_election_prediction(now),
+			voters: voters.len() as u32,
+			targets: targets.len() as u32,
+			voters_bound,
+			targets_bound,
+		})
+	}
 }
 
 impl<T: Config> ElectionProviderBase for OnChainExecution<T> {
@@ -167,6 +274,36 @@ impl<T: Config> InstantElectionProvider for OnChainExecution<T> {
 			Some(T::TargetsBound::get().min(forced_input_target_bound.unwrap_or(u32::MAX)) as usize),
 		)
 	}
+
+	/// Check that the input of the next election, as provided by [`Config::DataProvider`], is
+	/// consistent:
+	///
+	/// - the data provider can provide it within [`Config::VotersBound`] and
+	///   [`Config::TargetsBound`], without duplicates.
+	/// - `desired_targets` is at most [`Config::MaxWinners`].
+	///
+	/// The onchain election keeps no state of its own, so these are the only invariants it can
+	/// break at the next election.
+	#[cfg(feature = "try-runtime")]
+	fn try_state() -> Result<(), TryRuntimeError> {
+		let voters = T::DataProvider::electing_voters(Some(T::VotersBound::get() as usize))
+			.map_err(TryRuntimeError::Other)?;
+		let targets = T::DataProvider::electable_targets(Some(T::TargetsBound::get() as usize))
+			.map_err(TryRuntimeError::Other)?;
+		let desired_targets = T::DataProvider::desired_targets().map_err(TryRuntimeError::Other)?;
+
+		frame_support::ensure!(
+			desired_targets <= T::MaxWinners::get(),
+			"`desired_targets` exceeds `MaxWinners`"
+		);
+
+		let unique_voters = voters.iter().map(|voter| &voter.who).collect::<BTreeSet<_>>();
+		frame_support::ensure!(unique_voters.len() == voters.len(), "duplicate voters");
+		let unique_targets = targets.iter().collect::<BTreeSet<_>>();
+		frame_support::ensure!(unique_targets.len() == targets.len(), "duplicate targets");
+
+		Ok(())
+	}
 }
 
 impl<T: Config> ElectionProvider for OnChainExecution<T> {
@@ -174,6 +311,11 @@ impl<T: Config> ElectionProvider for OnChainExecution<T> {
 		false
 	}
 
+	fn defer(requested_delay: Self::BlockNumber) -> Result<Self::BlockNumber, Self::Error> {
+		// elections only ever happen when requested, there is nothing to postpone here.
+		Ok(requested_delay)
+	}
+
 	fn elect() -> Result<BoundedSupportsOf<Self>, Self::Error> {
 		elect_with_input_bounds::<T>(
 			Some(T::VotersBound::get() as usize),
@@ -239,6 +381,7 @@ mod tests {
 	parameter_types! {
 		pub static MaxWinners: u32 = 10;
 		pub static DesiredTargets: u32 = 2;
+		pub static Targets: Vec<AccountId> = vec![10, 20, 30];
 	}
 
 	impl Config for PhragmenParams {
@@ -265,7 +408,7 @@ mod tests {
 		use frame_support::{bounded_vec, traits::ConstU32};
 
 		use super::*;
-		use crate::{data_provider, VoterOf};
+		use crate::{data_provider, Voter, VoterOf};
 
 		pub struct DataProvider;
 		impl ElectionDataProvider for DataProvider {
@@ -274,22 +417,28 @@ mod tests {
 			type MaxVotesPerVoter = ConstU32<2>;
 			fn electing_voters(_: Option<usize>) -> data_provider::Result<Vec<VoterOf<Self>>> {
 				Ok(vec![
-					(1, 10, bounded_vec![10, 20]),
-					(2, 20, bounded_vec![30, 20]),
-					(3, 30, bounded_vec![10, 30]),
+					Voter::new(1, 10, bounded_vec![10, 20]),
+					Voter::new(2, 20, bounded_vec![30, 20]),
+					Voter::new(3, 30, bounded_vec![10, 30]),
 				])
 			}
 
-			fn electable_targets(_: Option<usize>) -> data_provider::Result<Vec<AccountId>> {
-				Ok(vec![10, 20, 30])
+			fn electable_targets(
+				maybe_max_len: Option<usize>,
+			) -> data_provider::Result<Vec<AccountId>> {
+				let targets = Targets::get();
+				if maybe_max_len.map_or(false, |max_len| targets.len() > max_len) {
+					return Err("Targets too big")
+				}
+				Ok(targets)
 			}
 
 			fn desired_targets() -> data_provider::Result<u32> {
 				Ok(DesiredTargets::get())
 			}
 
-			fn next_election_prediction(_: BlockNumber) -> BlockNumber {
-				0
+			fn next_election_prediction(now: BlockNumber) -> BlockNumber {
+				now + 10
 			}
 		}
 	}
@@ -321,6 +470,75 @@ mod tests {
 		})
 	}
 
+	#[cfg(feature = "try-runtime")]
+	#[test]
+	fn try_state_works() {
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			assert_eq!(OnChainExecution::<PhragmenParams>::try_state(), Ok(()));
+
+			DesiredTargets::set(10);
+			MaxWinners::set(9);
+			assert_eq!(
+				OnChainExecution::<PhragmenParams>::try_state(),
+				Err("`desired_targets` exceeds `MaxWinners`".into())
+			);
+
+			MaxWinners::set(10);
+			Targets::set(vec![10, 20, 20]);
+			assert_eq!(
+				OnChainExecution::<PhragmenParams>::try_state(),
+				Err("duplicate targets".into())
+			);
+
+			// more targets than `TargetsBound`.
+			Targets::set((0..401).collect());
+			assert_eq!(
+				OnChainExecution::<PhragmenParams>::try_state(),
+				Err("Targets too big".into())
+			);
+		})
+	}
+
+	#[test]
+	fn dry_run_works() {
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			let (winners, score, weight) =
+				OnChainExecution::<PhragmenParams>::dry_run(None, None, None).unwrap();
+			assert_eq!(winners, 2);
+			assert_eq!(score.minimal_stake, 25);
+			assert_eq!(weight, <() as WeightInfo>::phragmen(3, 3, 2));
+			// no weight registered.
+			assert_eq!(frame_system::Pallet::<Runtime>::block_weight().total(), Weight::zero());
+
+			// with other desired targets.
+			let (winners, _, _) =
+				OnChainExecution::<PhragmenParams>::dry_run(None, None, Some(3)).unwrap();
+			assert_eq!(winners, 3);
+			assert_eq!(
+				OnChainExecution::<PhragmenParams>::dry_run(None, None, Some(11)),
+				Err(Error::TooManyWinners)
+			);
+			assert_eq!(DryRunError::from(Error::TooManyWinners), DryRunError::TooManyWinners);
+		})
+	}
+
+	#[test]
+	fn forecast_works() {
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			frame_system::Pallet::<Runtime>::set_block_number(5);
+			assert_eq!(
+				OnChainExecution::<PhragmenParams>::forecast(),
+				Ok(ElectionForecast {
+					next_election: 15,
+					voters: 3,
+					targets: 3,
+					voters_bound: 600,
+					targets_bound: 400,
+				})
+			);
+		})
+	}
+
 	#[test]
 	fn onchain_phragmms_works() {
 		sp_io::TestExternalities::new_empty().execute_with(|| {
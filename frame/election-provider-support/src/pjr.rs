@@ -0,0 +1,132 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Weighed wrappers around the PJR checks of [`sp_npos_elections::pjr`], suitable to be used
+//! onchain.
+//!
+//! Checking a solution for PJR needs the entire voter set, not only the edges that end up in the
+//! [`Supports`]. The weight of such a check is therefore a function of the snapshot size, and the
+//! caller must make sure that it cannot be abused, e.g. by requiring a deposit for a challenge.
+
+use crate::{IdentifierT, Supports, VoteWeight, Weight, WeightInfo};
+use sp_std::prelude::*;
+
+/// Convert voters, as they are typically stored in a snapshot, into the format expected by
+/// [`sp_npos_elections::pjr`].
+fn into_pjr_voters<AccountId: IdentifierT>(
+	all_voters: impl IntoIterator<Item = (AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+) -> Vec<(AccountId, VoteWeight, Vec<AccountId>)> {
+	all_voters
+		.into_iter()
+		.map(|(who, stake, votes)| (who, stake, votes.into_iter().collect()))
+		.collect()
+}
+
+/// Check `supports` to satisfy PJR, with the standard threshold.
+///
+/// `all_candidates` and `all_voters` must be the entire set of targets and voters that the
+/// election was computed upon.
+///
+/// Returns `Err(counter_example)` if the solution does not satisfy PJR, where `counter_example` is
+/// an unelected candidate that should have been elected. See [`sp_npos_elections::pjr_check`].
+///
+/// The weight of this function is [`pjr_check_weight`].
+pub fn pjr_check<AccountId: IdentifierT>(
+	supports: &Supports<AccountId>,
+	all_candidates: Vec<AccountId>,
+	all_voters: impl IntoIterator<Item = (AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+) -> Result<(), AccountId> {
+	sp_npos_elections::pjr_check(supports, all_candidates, into_pjr_voters(all_voters))
+}
+
+/// Validate a challenge to `supports`, claiming that `counter_example` should have been elected.
+///
+/// Returns `true` if the challenge is valid, i.e. `supports` does not satisfy PJR. This is cheaper
+/// than [`pjr_check`], and is bounded by [`pjr_check_weight`] as well. See
+/// [`sp_npos_elections::validate_pjr_challenge`].
+pub fn validate_pjr_challenge<AccountId: IdentifierT>(
+	counter_example: AccountId,
+	supports: &Supports<AccountId>,
+	all_candidates: Vec<AccountId>,
+	all_voters: impl IntoIterator<Item = (AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+) -> bool {
+	sp_npos_elections::validate_pjr_challenge(
+		counter_example,
+		supports,
+		all_candidates,
+		into_pjr_voters(all_voters),
+	)
+}
+
+/// The weight of [`pjr_check`].
+///
+/// - `voters` is the number of voters.
+/// - `targets` is the number of targets.
+/// - `vote_degree` is the degree ie the maximum numbers of votes per voter.
+pub fn pjr_check_weight<T: WeightInfo>(voters: u32, targets: u32, vote_degree: u32) -> Weight {
+	T::pjr_check(voters, targets, vote_degree)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_npos_elections::Support;
+
+	fn voters() -> Vec<(u64, VoteWeight, Vec<u64>)> {
+		vec![(1, 20, vec![10]), (2, 20, vec![10]), (3, 30, vec![30, 40])]
+	}
+
+	fn fair() -> Supports<u64> {
+		vec![
+			(10, Support { total: 40, voters: vec![(1, 20), (2, 20)] }),
+			(30, Support { total: 30, voters: vec![(3, 30)] }),
+		]
+	}
+
+	fn unfair() -> Supports<u64> {
+		// the voters of 10 have more than `70 / 2` stake, yet are not represented.
+		vec![
+			(30, Support { total: 15, voters: vec![(3, 15)] }),
+			(40, Support { total: 15, voters: vec![(3, 15)] }),
+		]
+	}
+
+	#[test]
+	fn pjr_check_works() {
+		assert_eq!(pjr_check(&fair(), vec![10, 20, 30, 40], voters()), Ok(()));
+		assert_eq!(pjr_check(&unfair(), vec![10, 20, 30, 40], voters()), Err(10));
+	}
+
+	#[test]
+	fn validate_pjr_challenge_works() {
+		assert!(!validate_pjr_challenge(40, &fair(), vec![10, 20, 30, 40], voters()));
+		assert!(validate_pjr_challenge(10, &unfair(), vec![10, 20, 30, 40], voters()));
+		// not a candidate at all.
+		assert!(!validate_pjr_challenge(50, &unfair(), vec![10, 20, 30, 40], voters()));
+	}
+
+	#[test]
+	fn works_with_bounded_votes() {
+		use frame_support::{bounded_vec, traits::ConstU32, BoundedVec};
+		let voters: Vec<(u64, VoteWeight, BoundedVec<u64, ConstU32<2>>)> = vec![
+			(1, 20, bounded_vec![10]),
+			(2, 20, bounded_vec![10]),
+			(3, 30, bounded_vec![30, 40]),
+		];
+		assert_eq!(pjr_check(&unfair(), vec![10, 20, 30, 40], voters), Err(10));
+	}
+}
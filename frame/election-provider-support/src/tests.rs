@@ -47,6 +47,29 @@ mod solution_type {
 		);
 	}
 
+	#[test]
+	fn solution_struct_serde_works() {
+		generate_solution_type!(
+			#[compact]
+			#[serde]
+			pub struct InnerTestSolutionSerde::<
+				VoterIndex = u32,
+				TargetIndex = u16,
+				Accuracy = TestAccuracy,
+				MaxVoters = ConstU32::<20>,
+			>(3)
+		);
+		let solution = InnerTestSolutionSerde {
+			votes1: vec![(2, 20)],
+			votes2: vec![(1, [(10, p(80))], 11)],
+			..Default::default()
+		};
+
+		let json = serde_json::to_string(&solution).unwrap();
+		assert_eq!(json, r#"{"votes1":[[2,20]],"votes2":[[1,[[10,800000000]],11]],"votes3":[]}"#);
+		assert_eq!(serde_json::from_str::<InnerTestSolutionSerde>(&json).unwrap(), solution);
+	}
+
 	#[test]
 	fn solution_struct_works_with_and_without_compact() {
 		// we use u32 size to make sure compact is smaller.
@@ -452,3 +475,1338 @@ fn index_assignments_generate_same_solution_as_plain_assignments() {
 
 	assert_eq!(solution, index_compact);
 }
+
+mod post_processing {
+	use crate::{
+		NposSolver, PostProcessed, Reduce, SequentialPhragmen, SolutionPostProcessor, VoteWeight,
+	};
+	use frame_support::traits::ConstU32;
+	use sp_npos_elections::{assignment_ratio_to_staked_normalized, to_supports, ElectionResult};
+	use sp_runtime::Perbill;
+
+	type Plain = SequentialPhragmen<u64, Perbill>;
+
+	fn voters() -> Vec<(u64, VoteWeight, Vec<u64>)> {
+		vec![
+			(1, 10, vec![10, 20, 30]),
+			(2, 20, vec![10, 20, 30]),
+			(3, 30, vec![10, 30]),
+			(4, 40, vec![20, 30]),
+			(5, 50, vec![10, 20]),
+		]
+	}
+
+	fn stake_of(who: &u64) -> VoteWeight {
+		voters().into_iter().find(|(v, _, _)| v == who).map(|(_, s, _)| s).unwrap()
+	}
+
+	fn edges(result: &ElectionResult<u64, Perbill>) -> usize {
+		result.assignments.iter().map(|a| a.distribution.len()).sum()
+	}
+
+	fn supports(result: &ElectionResult<u64, Perbill>) -> Vec<(u64, u128)> {
+		let staked =
+			assignment_ratio_to_staked_normalized(result.assignments.clone(), stake_of).unwrap();
+		to_supports(&staked).into_iter().map(|(w, s)| (w, s.total)).collect()
+	}
+
+	#[test]
+	fn post_processing_keeps_winners_and_backing() {
+		let plain = Plain::solve(2, vec![10, 20, 30], voters()).unwrap();
+		let reduced = PostProcessed::<Plain, Reduce>::solve(2, vec![10, 20, 30], voters()).unwrap();
+		let chunked =
+			PostProcessed::<Plain, Reduce<ConstU32<2>>>::solve(2, vec![10, 20, 30], voters())
+				.unwrap();
+
+		for result in [&reduced, &chunked] {
+			assert_eq!(result.winners, plain.winners);
+			assert_eq!(supports(result), supports(&plain));
+			assert!(edges(result) <= edges(&plain));
+		}
+		assert!(edges(&reduced) < edges(&plain));
+	}
+
+	#[test]
+	fn post_processing_weight_is_accounted_for() {
+		use crate::{BalancingConfig, Get, PhragMMS, WeightInfo};
+
+		struct Balancing;
+		impl Get<Option<BalancingConfig>> for Balancing {
+			fn get() -> Option<BalancingConfig> {
+				Some(BalancingConfig { iterations: 10, tolerance: 0 })
+			}
+		}
+
+		assert_eq!(
+			PostProcessed::<Plain, Reduce>::weight::<()>(1_000, 500, 16),
+			<() as WeightInfo>::phragmen(1_000, 500, 16) +
+				<() as WeightInfo>::reduce(1_000, 500, 16),
+		);
+		assert_eq!(
+			PhragMMS::<u64, Perbill>::weight::<()>(1_000, 500, 16),
+			<() as WeightInfo>::phragmms(1_000, 500, 16),
+		);
+		assert_eq!(
+			PhragMMS::<u64, Perbill, Balancing>::weight::<()>(1_000, 500, 16),
+			<() as WeightInfo>::phragmms_balanced(1_000, 500, 16),
+		);
+	}
+
+	#[test]
+	fn parallel_reduce_same_as_sequential() {
+		let plain = Plain::solve(2, vec![10, 20, 30], voters()).unwrap();
+		let sequential = Reduce::<ConstU32<2>>::process(
+			ElectionResult {
+				winners: plain.winners.clone(),
+				assignments: plain.assignments.clone(),
+			},
+			stake_of,
+		)
+		.unwrap();
+		let parallel = crate::ParallelReduce::<ConstU32<2>>::process(plain, stake_of).unwrap();
+
+		assert_eq!(sequential.winners, parallel.winners);
+		assert_eq!(sequential.assignments, parallel.assignments);
+	}
+}
+
+mod miner {
+	use crate::{
+		data_provider,
+		miner::{Miner, MinerConfig, MinerError, MinerVoterOf, SolutionOrSnapshotSize},
+		mock::TestAccuracy,
+		ElectionDataProvider, NposSolution, SequentialPhragmen, Voter, VoterOf, Weight,
+	};
+	use frame_support::{bounded_vec, parameter_types, traits::ConstU32};
+
+	crate::generate_solution_type!(
+		#[compact]
+		pub struct MinerSolution::<
+			VoterIndex = u32,
+			TargetIndex = u16,
+			Accuracy = TestAccuracy,
+			MaxVoters = ConstU32::<2_500>,
+		>(16)
+	);
+
+	parameter_types! {
+		pub static MaxLength: u32 = u32::MAX;
+		pub static MaxWeight: Weight = Weight::MAX;
+	}
+
+	struct Runtime;
+	impl MinerConfig for Runtime {
+		type AccountId = u64;
+		type Solution = MinerSolution;
+		type MaxVotesPerVoter = ConstU32<16>;
+		type MaxLength = MaxLength;
+		type MaxWeight = MaxWeight;
+		type MaxWinners = ConstU32<16>;
+
+		fn solution_weight(_v: u32, _t: u32, active_voters: u32, _d: u32) -> Weight {
+			Weight::from_parts(active_voters as u64 * 10, 0)
+		}
+	}
+
+	struct DataProvider;
+	impl ElectionDataProvider for DataProvider {
+		type AccountId = u64;
+		type BlockNumber = u64;
+		type MaxVotesPerVoter = ConstU32<16>;
+
+		fn electing_voters(
+			maybe_max_len: Option<usize>,
+		) -> data_provider::Result<Vec<VoterOf<Self>>> {
+			Ok(voters().into_iter().take(maybe_max_len.unwrap_or(usize::MAX)).collect())
+		}
+
+		fn electable_targets(maybe_max_len: Option<usize>) -> data_provider::Result<Vec<u64>> {
+			if maybe_max_len.map_or(false, |max| max < 3) {
+				return Err("too many targets")
+			}
+			Ok(vec![10, 20, 30])
+		}
+
+		fn desired_targets() -> data_provider::Result<u32> {
+			Ok(2)
+		}
+
+		fn next_election_prediction(_: u64) -> u64 {
+			0
+		}
+	}
+
+	type Solver = SequentialPhragmen<u64, TestAccuracy>;
+
+	fn voters() -> Vec<MinerVoterOf<Runtime>> {
+		vec![
+			Voter::new(1, 10, bounded_vec![10, 20]),
+			Voter::new(2, 20, bounded_vec![30, 20]),
+			Voter::new(3, 30, bounded_vec![10, 30]),
+			Voter::new(4, 40, bounded_vec![20]),
+		]
+	}
+
+	#[test]
+	fn fetch_snapshot_works() {
+		let (voters, targets, desired_targets) =
+			Miner::<Runtime>::fetch_snapshot::<DataProvider>(Some(2), None).unwrap();
+		assert_eq!(voters.len(), 2);
+		assert_eq!(targets, vec![10, 20, 30]);
+		assert_eq!(desired_targets, 2);
+
+		assert_eq!(
+			Miner::<Runtime>::fetch_snapshot::<DataProvider>(None, Some(1)).unwrap_err(),
+			MinerError::DataProvider("too many targets"),
+		);
+	}
+
+	#[test]
+	fn mine_solution_works() {
+		let (solution, score, size) =
+			Miner::<Runtime>::mine_solution::<Solver, DataProvider>(None, None).unwrap();
+
+		assert_eq!(size, SolutionOrSnapshotSize { voters: 4, targets: 3 });
+		assert_eq!(solution.unique_targets().len(), 2);
+		assert_eq!(solution.voter_count(), 4);
+		assert_eq!(score.sum_stake, 100);
+
+		// same as mining with a snapshot that is given explicitly.
+		assert_eq!(
+			Miner::<Runtime>::mine_solution_with_snapshot::<Solver>(voters(), vec![10, 20, 30], 2)
+				.unwrap(),
+			(solution, score, size),
+		);
+	}
+
+	#[test]
+	fn mine_solution_trims_weight() {
+		// only 2 voters fit into the weight limit.
+		MaxWeight::set(Weight::from_parts(25, 0));
+		let (solution, score, _) =
+			Miner::<Runtime>::mine_solution::<Solver, DataProvider>(None, None).unwrap();
+		MaxWeight::set(Weight::MAX);
+
+		assert_eq!(solution.voter_count(), 2);
+		// the voters with the most stake are kept.
+		assert_eq!(score.sum_stake, 70);
+	}
+
+	#[test]
+	fn mine_solution_trims_length() {
+		let (solution, _, _) =
+			Miner::<Runtime>::mine_solution::<Solver, DataProvider>(None, None).unwrap();
+		let full_length = codec::Encode::encoded_size(&solution) as u32;
+
+		MaxLength::set(full_length - 1);
+		let (trimmed, _, _) =
+			Miner::<Runtime>::mine_solution::<Solver, DataProvider>(None, None).unwrap();
+		MaxLength::set(u32::MAX);
+
+		assert!(trimmed.voter_count() < solution.voter_count());
+		assert!((codec::Encode::encoded_size(&trimmed) as u32) < full_length);
+	}
+
+	#[test]
+	fn maximum_voter_for_weight_works() {
+		let size = SolutionOrSnapshotSize { voters: 10, targets: 0 };
+		let max_voters = |w: u64| {
+			Miner::<Runtime>::maximum_voter_for_weight(0, size, Weight::from_parts(w, u64::MAX))
+		};
+
+		assert_eq!(max_voters(0), 0);
+		assert_eq!(max_voters(9), 0);
+		assert_eq!(max_voters(10), 1);
+		assert_eq!(max_voters(55), 5);
+		assert_eq!(max_voters(100), 10);
+		assert_eq!(max_voters(1000), 10);
+	}
+	#[test]
+	fn index_assignments_from_works() {
+		use crate::{miner::helpers, Assignment, Error};
+		use sp_runtime::Perbill;
+
+		let voters = voters();
+		let targets = vec![10, 20, 30];
+		let voter_index = helpers::voter_index_fn_hashed::<Runtime>(&voters);
+		let target_index = helpers::target_index_fn_hashed::<Runtime>(&targets);
+
+		let assignments = (1..=4)
+			.map(|who| Assignment { who, distribution: vec![(who % 3 * 10 + 10, Perbill::one())] })
+			.collect::<Vec<_>>();
+		let index_assignments =
+			helpers::index_assignments_from::<Runtime>(&assignments, &voter_index, &target_index)
+				.unwrap();
+
+		// same as converting them one by one, in the same order.
+		let cache = helpers::generate_voter_cache::<Runtime>(&voters);
+		let expected = assignments
+			.iter()
+			.map(|a| {
+				crate::IndexAssignmentOf::<MinerSolution>::new(
+					a,
+					helpers::voter_index_fn::<Runtime>(&cache),
+					helpers::target_index_fn::<Runtime>(&targets),
+				)
+			})
+			.collect::<Result<Vec<_>, _>>()
+			.unwrap();
+		assert_eq!(index_assignments, expected);
+
+		// unknown accounts are rejected.
+		let unknown = vec![Assignment { who: 5, distribution: vec![(10, Perbill::one())] }];
+		assert_eq!(
+			helpers::index_assignments_from::<Runtime>(&unknown, &voter_index, &target_index),
+			Err(Error::SolutionInvalidIndex),
+		);
+	}
+}
+
+mod decay {
+	use crate::{DecayedScore, ScoreDecay, ScoreProvider};
+
+	struct Stored;
+	impl ScoreProvider<u64> for Stored {
+		type Score = u64;
+		fn score(who: &u64) -> u64 {
+			who * 100
+		}
+	}
+
+	/// Accounts with an odd id are idle, and their score is halved.
+	struct HalveIdle;
+	impl ScoreDecay<u64, u64> for HalveIdle {
+		fn decay(who: &u64, score: u64) -> u64 {
+			if who % 2 == 1 {
+				score / 2
+			} else {
+				score
+			}
+		}
+	}
+
+	#[test]
+	fn decayed_score_works() {
+		type Decayed = DecayedScore<Stored, HalveIdle>;
+		assert_eq!(Decayed::score(&2), 200);
+		assert_eq!(Decayed::score(&3), 150);
+		assert_eq!(Decayed::scores(vec![1, 4].into_iter()), vec![(1, 50), (4, 400)]);
+	}
+}
+
+mod economics {
+	use crate::{GeometricDeposit, SubmissionEconomics, Weight};
+	use frame_support::{parameter_types, traits::ConstU128};
+	use sp_arithmetic::Percent;
+
+	parameter_types! {
+		pub static Increase: Percent = Percent::from_percent(10);
+	}
+
+	type Economics =
+		GeometricDeposit<u128, ConstU128<1000>, Increase, ConstU128<2>, ConstU128<3>, ConstU128<7>>;
+
+	#[test]
+	fn deposit_is_base_per_byte_and_per_weight() {
+		assert_eq!(Economics::deposit(0, 0, Weight::zero()), 1000);
+		assert_eq!(Economics::deposit(0, 10, Weight::zero()), 1000 + 2 * 10);
+		assert_eq!(Economics::deposit(0, 0, Weight::from_parts(5, 1_000)), 1000 + 3 * 5);
+		assert_eq!(Economics::deposit(0, 10, Weight::from_parts(5, 0)), 1000 + 20 + 15);
+		assert_eq!(Economics::reward(), 7);
+	}
+
+	#[test]
+	fn base_deposit_increases_geometrically() {
+		assert_eq!(Economics::deposit(1, 0, Weight::zero()), 1100);
+		assert_eq!(Economics::deposit(2, 0, Weight::zero()), 1210);
+		assert_eq!(Economics::deposit(3, 0, Weight::zero()), 1331);
+		// only the base deposit increases.
+		assert_eq!(Economics::deposit(2, 10, Weight::from_parts(5, 0)), 1210 + 20 + 15);
+
+		// without an increase, the base deposit is fixed.
+		Increase::set(Percent::zero());
+		assert_eq!(Economics::deposit(10, 0, Weight::zero()), 1000);
+	}
+
+	#[test]
+	fn deposit_saturates() {
+		Increase::set(Percent::from_percent(100));
+		assert_eq!(Economics::deposit(200, 0, Weight::zero()), u128::MAX);
+		assert_eq!(Economics::deposit(200, 10, Weight::from_parts(5, 0)), u128::MAX);
+	}
+
+	#[test]
+	fn unit_is_free() {
+		assert_eq!(<() as SubmissionEconomics<u128>>::deposit(5, 10, Weight::MAX), 0);
+		assert_eq!(<() as SubmissionEconomics<u128>>::reward(), 0);
+	}
+}
+
+mod migrations {
+	use crate::{
+		migrations::{bound_supports, MigrateToBoundedSupports},
+		BoundedSupports, Support, Supports,
+	};
+	use frame_support::{
+		storage::unhashed,
+		traits::{ConstU32, OnRuntimeUpgrade},
+		weights::constants::RocksDbWeight,
+	};
+
+	type MaxWinners = ConstU32<2>;
+	type MaxBackers = ConstU32<2>;
+
+	#[frame_support::storage_alias]
+	type Stored = StorageValue<Test, BoundedSupports<u64, MaxWinners>>;
+
+	type Migration = MigrateToBoundedSupports<Stored, u64, MaxWinners, MaxBackers, RocksDbWeight>;
+
+	fn support(voters: Vec<(u64, u128)>) -> Support<u64> {
+		Support { total: voters.iter().map(|(_, s)| s).sum(), voters }
+	}
+
+	fn unbounded() -> Supports<u64> {
+		vec![
+			(10, support(vec![(1, 10), (2, 30), (3, 20)])),
+			(20, support(vec![(4, 50)])),
+			(30, support(vec![(5, 30), (6, 25), (7, 25)])),
+		]
+	}
+
+	#[test]
+	fn bound_supports_truncates_deterministically() {
+		let bounded = bound_supports::<_, MaxWinners, MaxBackers>(unbounded());
+		// 10 keeps its two largest backers, 30 its largest one and the one with the lowest id on
+		// a tie. 10 and 20 then tie on their total, and 20 is removed.
+		assert_eq!(
+			bounded.into_inner(),
+			vec![(10, support(vec![(2, 30), (3, 20)])), (30, support(vec![(5, 30), (6, 25)])),],
+		);
+
+		// already bounded supports are untouched.
+		let supports = vec![(10, support(vec![(1, 10), (2, 30)])), (20, support(vec![(4, 50)]))];
+		assert_eq!(
+			bound_supports::<_, MaxWinners, MaxBackers>(supports.clone()).into_inner(),
+			supports,
+		);
+	}
+
+	#[test]
+	fn total_only_loses_the_removed_stake() {
+		let supports = vec![(10, Support { total: 100, voters: vec![(1, 10), (2, 30), (3, 20)] })];
+		assert_eq!(
+			bound_supports::<_, MaxWinners, MaxBackers>(supports).into_inner(),
+			vec![(10, Support { total: 90, voters: vec![(2, 30), (3, 20)] })],
+		);
+	}
+
+	#[test]
+	fn migration_works() {
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			// nothing to migrate.
+			assert_eq!(Migration::on_runtime_upgrade(), RocksDbWeight::get().reads(1));
+			assert!(!Stored::exists());
+
+			unhashed::put(&Stored::hashed_key(), &unbounded());
+			assert_eq!(Migration::on_runtime_upgrade(), RocksDbWeight::get().reads_writes(1, 1));
+			assert_eq!(
+				Stored::get().unwrap(),
+				bound_supports::<_, MaxWinners, MaxBackers>(unbounded()),
+			);
+
+			// undecodable values are left untouched.
+			unhashed::put_raw(&Stored::hashed_key(), &[1, 2, 3]);
+			assert_eq!(Migration::on_runtime_upgrade(), RocksDbWeight::get().reads(1));
+			assert_eq!(unhashed::get_raw(&Stored::hashed_key()), Some(vec![1, 2, 3]));
+		})
+	}
+
+	#[cfg(feature = "try-runtime")]
+	#[test]
+	fn migration_try_runtime_checks_work() {
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			unhashed::put(&Stored::hashed_key(), &unbounded());
+			let state = Migration::pre_upgrade().unwrap();
+			Migration::on_runtime_upgrade();
+			assert!(Migration::post_upgrade(state.clone()).is_ok());
+
+			// a different outcome is detected.
+			Stored::kill();
+			assert!(Migration::post_upgrade(state).is_err());
+		})
+	}
+}
+
+mod merged {
+	use crate::{data_provider, ElectionDataProvider, MergedDataProvider, Voter, VoterOf};
+	use frame_support::{bounded_vec, traits::ConstU32};
+
+	struct Staking;
+	impl ElectionDataProvider for Staking {
+		type AccountId = u64;
+		type BlockNumber = u64;
+		type MaxVotesPerVoter = ConstU32<2>;
+
+		fn electing_voters(
+			maybe_max_len: Option<usize>,
+		) -> data_provider::Result<Vec<VoterOf<Self>>> {
+			Ok(vec![
+				Voter::new(1, 10, bounded_vec![10, 20]),
+				Voter::new(2, 20, bounded_vec![20]),
+				Voter::new(10, 5, bounded_vec![10]),
+			]
+			.into_iter()
+			.take(maybe_max_len.unwrap_or(usize::MAX))
+			.collect())
+		}
+
+		fn electable_targets(maybe_max_len: Option<usize>) -> data_provider::Result<Vec<u64>> {
+			Ok(vec![10, 20].into_iter().take(maybe_max_len.unwrap_or(usize::MAX)).collect())
+		}
+
+		fn desired_targets() -> data_provider::Result<u32> {
+			Ok(2)
+		}
+
+		fn next_election_prediction(now: u64) -> u64 {
+			now + 10
+		}
+	}
+
+	struct Whitelist;
+	impl ElectionDataProvider for Whitelist {
+		type AccountId = u64;
+		type BlockNumber = u64;
+		type MaxVotesPerVoter = ConstU32<2>;
+
+		fn electing_voters(
+			maybe_max_len: Option<usize>,
+		) -> data_provider::Result<Vec<VoterOf<Self>>> {
+			if maybe_max_len.map_or(false, |max| max < 2) {
+				return Err("too many voters")
+			}
+			// voter 2 is also a staker, and its vote is ignored.
+			Ok(vec![Voter::new(2, 100, bounded_vec![30]), Voter::new(3, 30, bounded_vec![30])])
+		}
+
+		fn electable_targets(_: Option<usize>) -> data_provider::Result<Vec<u64>> {
+			Ok(vec![20, 30])
+		}
+
+		fn desired_targets() -> data_provider::Result<u32> {
+			Ok(5)
+		}
+
+		fn next_election_prediction(now: u64) -> u64 {
+			now + 100
+		}
+	}
+
+	type Merged = MergedDataProvider<(Staking, Whitelist)>;
+
+	#[test]
+	fn merges_and_deduplicates() {
+		assert_eq!(
+			Merged::electing_voters(None).unwrap(),
+			vec![
+				Voter::new(1, 10, bounded_vec![10, 20]),
+				Voter::new(2, 20, bounded_vec![20]),
+				Voter::new(10, 5, bounded_vec![10]),
+				Voter::new(3, 30, bounded_vec![30]),
+			]
+		);
+		assert_eq!(Merged::electable_targets(None).unwrap(), vec![10, 20, 30]);
+
+		// the primary provider dictates the rest.
+		assert_eq!(Merged::desired_targets().unwrap(), 2);
+		assert_eq!(Merged::next_election_prediction(5), 15);
+	}
+
+	#[test]
+	fn respects_bounds() {
+		// the primary provider exhausts the bound, the secondary one is not asked.
+		assert_eq!(Merged::electing_voters(Some(3)).unwrap().len(), 3);
+		assert_eq!(Merged::electable_targets(Some(1)).unwrap(), vec![10]);
+
+		// the secondary provider only gets the remainder, with which it fails.
+		assert_eq!(Merged::electing_voters(Some(4)).unwrap_err(), "too many voters");
+
+		// duplicates are not filling up the bound, so there might be less than what fits.
+		assert_eq!(Merged::electing_voters(Some(5)).unwrap().len(), 4);
+		assert_eq!(Merged::electable_targets(Some(3)).unwrap(), vec![10, 20, 30]);
+	}
+}
+
+mod aggregated {
+	use crate::{
+		data_provider, AggregatedVoters, ElectionDataProvider, ExtendedBalance, Support, Voter,
+		VoterAggregator, VoterOf,
+	};
+	use frame_support::{bounded_vec, traits::ConstU32};
+
+	struct Staking;
+	impl ElectionDataProvider for Staking {
+		type AccountId = u64;
+		type BlockNumber = u64;
+		type MaxVotesPerVoter = ConstU32<2>;
+
+		fn electing_voters(_: Option<usize>) -> data_provider::Result<Vec<VoterOf<Self>>> {
+			Ok(vec![
+				Voter::new(1, 10, bounded_vec![10]),
+				Voter::new(100, 30, bounded_vec![10]),
+				Voter::new(2, 20, bounded_vec![20]),
+				Voter::new(101, 10, bounded_vec![20, 10]),
+				Voter::new(102, 20, bounded_vec![30]),
+			])
+		}
+
+		fn electable_targets(_: Option<usize>) -> data_provider::Result<Vec<u64>> {
+			Ok(vec![10, 20, 30])
+		}
+
+		fn desired_targets() -> data_provider::Result<u32> {
+			Ok(2)
+		}
+
+		fn next_election_prediction(now: u64) -> u64 {
+			now + 10
+		}
+	}
+
+	/// Accounts 100 and up are members of pool 1000, 100 with a quarter and 101 with the rest.
+	struct Pool;
+	impl VoterAggregator<u64> for Pool {
+		fn aggregate_of(who: &u64) -> Option<u64> {
+			(*who >= 100).then_some(1000)
+		}
+
+		fn split(who: &u64, backing: ExtendedBalance) -> Option<Vec<(u64, ExtendedBalance)>> {
+			(*who == 1000).then(|| vec![(100, backing / 4), (101, backing - backing / 4)])
+		}
+	}
+
+	type Aggregated = AggregatedVoters<Staking, Pool>;
+
+	#[test]
+	fn aggregates_voters() {
+		assert_eq!(
+			Aggregated::electing_voters(None).unwrap(),
+			vec![
+				Voter::new(1, 10, bounded_vec![10]),
+				// votes are merged, up to the bound.
+				Voter::new(1000, 60, bounded_vec![10, 20]),
+				Voter::new(2, 20, bounded_vec![20]),
+			]
+		);
+		assert_eq!(Aggregated::electable_targets(None).unwrap(), vec![10, 20, 30]);
+		assert_eq!(Aggregated::desired_targets().unwrap(), 2);
+	}
+
+	#[test]
+	fn split_supports_works() {
+		let supports = vec![
+			(10, Support { total: 50, voters: vec![(1, 10), (1000, 40)] }),
+			(20, Support { total: 40, voters: vec![(2, 20), (1000, 20)] }),
+		];
+		assert_eq!(
+			Aggregated::split_supports(supports),
+			vec![
+				(10, Support { total: 50, voters: vec![(1, 10), (100, 10), (101, 30)] }),
+				(20, Support { total: 40, voters: vec![(2, 20), (100, 5), (101, 15)] }),
+			]
+		);
+	}
+}
+
+mod min_backing {
+	use crate::{
+		data_provider, BelowMinBacking, BoundedSupportsOf, ElectionDataProvider, ElectionProvider,
+		ElectionProviderBase, ExtendedBalance, MinBackedError, MinBackedWinners, OnWinnersDropped,
+		Support, VoterOf,
+	};
+	use frame_support::{parameter_types, traits::ConstU32};
+
+	parameter_types! {
+		static Mode: BelowMinBacking = BelowMinBacking::Drop;
+		static Dropped: Vec<(u64, ExtendedBalance)> = vec![];
+		pub const Threshold: ExtendedBalance = 10;
+	}
+
+	struct Staking;
+	impl ElectionDataProvider for Staking {
+		type AccountId = u64;
+		type BlockNumber = u64;
+		type MaxVotesPerVoter = ConstU32<2>;
+
+		fn electing_voters(_: Option<usize>) -> data_provider::Result<Vec<VoterOf<Self>>> {
+			Ok(vec![])
+		}
+
+		fn electable_targets(_: Option<usize>) -> data_provider::Result<Vec<u64>> {
+			Ok(vec![])
+		}
+
+		fn desired_targets() -> data_provider::Result<u32> {
+			Ok(3)
+		}
+
+		fn next_election_prediction(now: u64) -> u64 {
+			now
+		}
+	}
+
+	struct Inner;
+	impl ElectionProviderBase for Inner {
+		type AccountId = u64;
+		type BlockNumber = u64;
+		type Error = &'static str;
+		type MaxWinners = ConstU32<4>;
+		type DataProvider = Staking;
+	}
+
+	impl ElectionProvider for Inner {
+		fn ongoing() -> bool {
+			false
+		}
+
+		fn defer(requested_delay: u64) -> Result<u64, Self::Error> {
+			Ok(requested_delay)
+		}
+
+		fn elect() -> Result<BoundedSupportsOf<Self>, Self::Error> {
+			Ok(vec![
+				(10, Support { total: 100, voters: vec![(1, 60), (2, 40)] }),
+				(20, Support { total: 50, voters: vec![(2, 20), (3, 30)] }),
+				(30, Support { total: 5, voters: vec![(1, 2), (2, 3)] }),
+			]
+			.try_into()
+			.unwrap())
+		}
+	}
+
+	struct RecordDropped;
+	impl OnWinnersDropped<u64> for RecordDropped {
+		fn on_winners_dropped(dropped: &[(u64, ExtendedBalance)]) {
+			Dropped::set(dropped.to_vec());
+		}
+	}
+
+	type MinBacked = MinBackedWinners<Inner, Threshold, Mode, RecordDropped>;
+
+	#[test]
+	fn drops_winners_below_threshold() {
+		Mode::set(BelowMinBacking::Drop);
+		assert_eq!(
+			MinBacked::elect().unwrap().into_inner(),
+			vec![
+				(10, Support { total: 100, voters: vec![(1, 60), (2, 40)] }),
+				(20, Support { total: 50, voters: vec![(2, 20), (3, 30)] }),
+			]
+		);
+		assert_eq!(Dropped::get(), vec![(30, 5)]);
+	}
+
+	#[test]
+	fn redistributes_backing_of_dropped_winners() {
+		Mode::set(BelowMinBacking::Redistribute);
+		assert_eq!(
+			MinBacked::elect().unwrap().into_inner(),
+			vec![
+				// 1 only backs 10, so it gets all of its 2. 2 splits its 3 as 40:20.
+				(10, Support { total: 104, voters: vec![(1, 62), (2, 42)] }),
+				(20, Support { total: 51, voters: vec![(2, 21), (3, 30)] }),
+			]
+		);
+		assert_eq!(Dropped::get(), vec![(30, 5)]);
+	}
+
+	#[test]
+	fn fails_on_winners_below_threshold() {
+		Mode::set(BelowMinBacking::Fail);
+		assert_eq!(MinBacked::elect(), Err(MinBackedError::BelowMinBacking));
+		assert!(Dropped::get().is_empty());
+	}
+
+	#[test]
+	fn noop_if_all_winners_are_backed_enough() {
+		Mode::set(BelowMinBacking::Fail);
+		assert_eq!(
+			MinBackedWinners::<Inner, (), Mode, RecordDropped>::elect(),
+			Inner::elect().map_err(MinBackedError::Inner)
+		);
+		assert!(Dropped::get().is_empty());
+	}
+}
+
+mod weighted_random {
+	use crate::{NposSolver, WeightedRandom};
+	use frame_support::parameter_types;
+	use sp_arithmetic::{PerThing, Perbill};
+
+	parameter_types! {
+		static Seed: [u8; 32] = [0u8; 32];
+	}
+
+	type Random = WeightedRandom<u64, Perbill, Seed>;
+
+	fn voters() -> Vec<(u64, u64, Vec<u64>)> {
+		vec![(1, 10, vec![10, 20]), (2, 90, vec![20, 30]), (3, 50, vec![30, 10, 30])]
+	}
+
+	#[test]
+	fn elects_winners_with_their_approval_stake() {
+		let result = Random::solve(2, vec![10, 20, 30, 40], voters()).unwrap();
+		assert_eq!(result.winners.len(), 2);
+		result.winners.iter().for_each(|(w, stake)| {
+			let expected = match w {
+				10 => 60,
+				20 => 100,
+				30 => 140,
+				_ => unreachable!("40 has no approval stake"),
+			};
+			assert_eq!(*stake, expected);
+		});
+
+		// every voter splits evenly among its elected targets.
+		result.assignments.iter().for_each(|a| {
+			assert!(a.distribution.iter().all(|(t, _)| result.winners.iter().any(|(w, _)| w == t)));
+			assert_eq!(
+				a.distribution.iter().map(|(_, p)| p.deconstruct()).sum::<u32>(),
+				Perbill::ACCURACY
+			);
+		});
+	}
+
+	#[test]
+	fn is_deterministic_for_a_seed() {
+		Seed::set([7u8; 32]);
+		let first = Random::solve(2, vec![10, 20, 30, 40], voters()).unwrap();
+		let second = Random::solve(2, vec![10, 20, 30, 40], voters()).unwrap();
+		assert_eq!(first.winners, second.winners);
+		assert_eq!(first.assignments, second.assignments);
+	}
+
+	#[test]
+	fn never_elects_targets_without_approval_stake() {
+		let result = Random::solve(4, vec![10, 20, 30, 40], voters()).unwrap();
+		let mut winners = result.winners.into_iter().map(|(w, _)| w).collect::<Vec<_>>();
+		winners.sort();
+		assert_eq!(winners, vec![10, 20, 30]);
+	}
+
+	#[test]
+	fn duplicate_targets_are_elected_once() {
+		let result = Random::solve(4, vec![10, 10, 20, 30, 30, 40], voters()).unwrap();
+		let mut winners = result.winners.into_iter().map(|(w, _)| w).collect::<Vec<_>>();
+		winners.sort();
+		assert_eq!(winners, vec![10, 20, 30]);
+	}
+
+	#[test]
+	fn selection_is_proportional_to_stake() {
+		let voters = vec![(1, 10, vec![10]), (2, 90, vec![20])];
+		let elected_20 = (0u8..=255)
+			.filter(|i| {
+				Seed::set([*i; 32]);
+				Random::solve(1, vec![10, 20], voters.clone()).unwrap().winners[0].0 == 20
+			})
+			.count();
+		// 90% expected, leave plenty of room for the small sample.
+		assert!((200..=250).contains(&elected_20), "{}", elected_20);
+	}
+}
+
+mod snapshot_fingerprint {
+	use crate::{data_provider, snapshot_fingerprint, ElectionDataProvider, Voter, VoterOf};
+	use frame_support::{bounded_vec, parameter_types, traits::ConstU32};
+
+	parameter_types! {
+		static DesiredTargets: u32 = 2;
+	}
+
+	struct Staking;
+	impl ElectionDataProvider for Staking {
+		type AccountId = u64;
+		type BlockNumber = u64;
+		type MaxVotesPerVoter = ConstU32<2>;
+
+		fn electing_voters(
+			maybe_max_len: Option<usize>,
+		) -> data_provider::Result<Vec<VoterOf<Self>>> {
+			let mut voters =
+				vec![Voter::new(1, 10, bounded_vec![10, 20]), Voter::new(2, 20, bounded_vec![20])];
+			voters.truncate(maybe_max_len.unwrap_or(usize::MAX));
+			Ok(voters)
+		}
+
+		fn electable_targets(maybe_max_len: Option<usize>) -> data_provider::Result<Vec<u64>> {
+			let mut targets = vec![10, 20];
+			targets.truncate(maybe_max_len.unwrap_or(usize::MAX));
+			Ok(targets)
+		}
+
+		fn desired_targets() -> data_provider::Result<u32> {
+			match DesiredTargets::get() {
+				0 => Err("no desired targets"),
+				desired => Ok(desired),
+			}
+		}
+
+		fn next_election_prediction(now: u64) -> u64 {
+			now
+		}
+	}
+
+	#[test]
+	fn default_fingerprint_covers_all_data() {
+		let voters = Staking::electing_voters(None).unwrap();
+		let fingerprint = Staking::snapshot_fingerprint(10, 10).unwrap();
+		assert_eq!(fingerprint, snapshot_fingerprint(&voters, &[10, 20], 2));
+
+		// any change to the data changes the fingerprint.
+		assert_ne!(fingerprint, snapshot_fingerprint(&voters, &[10, 20, 30], 2));
+		assert_ne!(fingerprint, snapshot_fingerprint(&voters[..1], &[10, 20], 2));
+
+		DesiredTargets::set(1);
+		assert_ne!(fingerprint, Staking::snapshot_fingerprint(10, 10).unwrap());
+	}
+
+	#[test]
+	fn default_fingerprint_respects_the_limits() {
+		let voters = Staking::electing_voters(None).unwrap();
+
+		// only the data within the limits is read, and `desired_targets` is capped to the targets.
+		assert_eq!(
+			Staking::snapshot_fingerprint(1, 1),
+			Ok(snapshot_fingerprint(&voters[..1], &[10], 1))
+		);
+		assert_eq!(
+			Staking::snapshot_fingerprint(2, 2),
+			Staking::snapshot_fingerprint(usize::MAX, usize::MAX)
+		);
+	}
+
+	#[test]
+	fn default_fingerprint_propagates_errors() {
+		DesiredTargets::set(0);
+		assert_eq!(Staking::snapshot_fingerprint(10, 10), Err("no desired targets"));
+	}
+}
+
+mod calibration {
+	use crate::{
+		calibration::{fit, generate, measure, FittedWeight, Grid, Sample},
+		SequentialPhragmen, Weight,
+	};
+	use sp_runtime::Perbill;
+
+	fn samples(degrees: &[u32]) -> Vec<Sample> {
+		let mut samples = vec![];
+		for voters in [10, 20, 40] {
+			for targets in [5, 10] {
+				for &degree in degrees {
+					let nanos = 1_000 + 2 * voters as u64 + 3 * targets as u64 + 50 * degree as u64;
+					samples.push(Sample { voters, targets, degree, nanos });
+				}
+			}
+		}
+		samples
+	}
+
+	#[test]
+	fn fit_recovers_linear_model() {
+		assert_eq!(
+			fit(&samples(&[1, 2, 4])),
+			FittedWeight {
+				base: 1_000_000,
+				per_voter: 2_000,
+				per_target: 3_000,
+				per_degree: 50_000
+			}
+		);
+	}
+
+	#[test]
+	fn fit_ignores_undetermined_components() {
+		// with a single degree, its cost is part of the base.
+		assert_eq!(
+			fit(&samples(&[2])),
+			FittedWeight { base: 1_100_000, per_voter: 2_000, per_target: 3_000, per_degree: 0 }
+		);
+		assert_eq!(fit(&[]), FittedWeight::default());
+	}
+
+	#[test]
+	fn generate_works() {
+		let (to_elect, targets, voters) = generate(20, 5, 8, 10, 42, |i| i as u64);
+		assert_eq!(to_elect, 5);
+		assert_eq!(targets, vec![0, 1, 2, 3, 4]);
+		assert_eq!(voters.len(), 20);
+		voters.iter().enumerate().for_each(|(i, (who, stake, votes))| {
+			assert_eq!(*who, 5 + i as u64);
+			assert!(*stake > 0);
+			let mut distinct = votes.clone();
+			distinct.sort();
+			distinct.dedup();
+			assert_eq!(distinct.len(), 5);
+		});
+
+		// deterministic.
+		assert_eq!(generate(20, 5, 8, 10, 42, |i| i as u64).2, voters);
+	}
+
+	#[test]
+	fn weight_fn_works() {
+		let fitted =
+			FittedWeight { base: 0, per_voter: 32_973_000, per_target: 1_000, per_degree: 12 };
+		assert_eq!(fitted.weight(1, 2, 3), Weight::from_parts(32_975_036, 0));
+		assert_eq!(
+			fitted.weight_fn("phragmen"),
+			"\tfn phragmen(v: u32, t: u32, d: u32, ) -> Weight {\n\
+			\t\tWeight::from_parts(0 as u64, 0)\n\
+			\t\t\t.saturating_add(Weight::from_parts(32_973_000 as u64, 0).saturating_mul(v as u64))\n\
+			\t\t\t.saturating_add(Weight::from_parts(1_000 as u64, 0).saturating_mul(t as u64))\n\
+			\t\t\t.saturating_add(Weight::from_parts(12 as u64, 0).saturating_mul(d as u64))\n\
+			\t}\n"
+		);
+	}
+
+	#[test]
+	fn measure_works() {
+		let grid = Grid {
+			voters: vec![10, 20],
+			targets: vec![5, 10],
+			degrees: vec![2, 3],
+			to_elect: 3,
+			repeat: 1,
+			seed: 0,
+		};
+		let samples = measure::<SequentialPhragmen<u64, Perbill>>(&grid, |i| i as u64);
+		assert_eq!(samples.len(), 8);
+		assert_eq!((samples[7].voters, samples[7].targets, samples[7].degree), (20, 10, 3));
+	}
+}
+
+mod evaluation {
+	use crate::{
+		evaluation::{
+			evaluate_support, is_score_better, score_of, score_of_weight, to_bounded_supports,
+			to_supports,
+		},
+		BoundedSupports, ElectionScore, StakedAssignment, Support, WeightInfo,
+	};
+	use frame_support::traits::ConstU32;
+	use sp_runtime::Perbill;
+
+	fn assignments() -> Vec<StakedAssignment<u64>> {
+		vec![
+			StakedAssignment { who: 1, distribution: vec![(10, 10), (20, 5)] },
+			StakedAssignment { who: 2, distribution: vec![(20, 20)] },
+		]
+	}
+
+	#[test]
+	fn supports_and_score_work() {
+		let supports = to_supports(&assignments());
+		assert_eq!(
+			supports,
+			vec![
+				(10, Support { total: 10, voters: vec![(1, 10)] }),
+				(20, Support { total: 25, voters: vec![(1, 5), (2, 20)] }),
+			]
+		);
+
+		let score = evaluate_support(&supports);
+		assert_eq!(
+			score,
+			ElectionScore { minimal_stake: 10, sum_stake: 35, sum_stake_squared: 725 }
+		);
+		assert_eq!(score_of(&assignments()), (supports.clone(), score));
+
+		// bounded supports evaluate the same.
+		let bounded: BoundedSupports<u64, ConstU32<2>> =
+			to_bounded_supports(&assignments()).unwrap();
+		assert_eq!(evaluate_support(&bounded), score);
+		assert_eq!(to_bounded_supports::<_, ConstU32<1>>(&assignments()), Err(supports));
+	}
+
+	#[test]
+	fn score_comparison_works() {
+		let score = ElectionScore { minimal_stake: 10, sum_stake: 35, sum_stake_squared: 725 };
+		let better = ElectionScore { minimal_stake: 11, ..score };
+		assert!(is_score_better(better, score, Perbill::zero()));
+		assert!(!is_score_better(score, better, Perbill::zero()));
+		assert!(!is_score_better(better, score, Perbill::from_percent(20)));
+	}
+
+	#[test]
+	fn score_of_weight_is_feasibility_check() {
+		assert_eq!(
+			score_of_weight::<()>(10, 20, 3),
+			<() as WeightInfo>::feasibility_check(10, 20, 3)
+		);
+	}
+}
+
+mod empty_election {
+	use crate::{
+		data_provider, ElectionDataProvider, ElectionError, ElectionProvider, EmptyElection,
+		InstantElectionProvider, NoElection, VoterOf,
+	};
+	use frame_support::traits::ConstU32;
+
+	struct Staking;
+	impl ElectionDataProvider for Staking {
+		type AccountId = u64;
+		type BlockNumber = u64;
+		type MaxVotesPerVoter = ConstU32<2>;
+
+		fn electing_voters(_: Option<usize>) -> data_provider::Result<Vec<VoterOf<Self>>> {
+			unreachable!("elections do not read any data")
+		}
+
+		fn electable_targets(_: Option<usize>) -> data_provider::Result<Vec<u64>> {
+			unreachable!("elections do not read any data")
+		}
+
+		fn desired_targets() -> data_provider::Result<u32> {
+			unreachable!("elections do not read any data")
+		}
+
+		fn next_election_prediction(now: u64) -> u64 {
+			now
+		}
+	}
+
+	type Empty = EmptyElection<(u64, u64, Staking, ConstU32<4>)>;
+	type Nothing = NoElection<(u64, u64, Staking, ConstU32<4>)>;
+
+	#[test]
+	fn empty_election_elects_nobody() {
+		assert!(Empty::elect().unwrap().is_empty());
+		assert!(Empty::instant_elect(None, None).unwrap().is_empty());
+		assert_eq!(Empty::defer(5), Ok(5));
+
+		// as opposed to `NoElection`.
+		let err = || ElectionError::Other("`NoElection` cannot do anything.");
+		assert_eq!(Nothing::elect(), Err(err()));
+		assert_eq!(Nothing::instant_elect(None, None), Err(err()));
+		assert_eq!(Nothing::defer(5), Err(err()));
+	}
+}
+
+mod election_error {
+	use crate::{onchain::DryRunError, ElectionError};
+	use sp_runtime::DispatchError;
+
+	#[test]
+	fn conversions_work() {
+		assert_eq!(
+			ElectionError::from(sp_npos_elections::Error::SolutionTargetOverflow),
+			ElectionError::Solver(sp_npos_elections::Error::SolutionTargetOverflow)
+		);
+		assert_eq!(ElectionError::from("oops"), ElectionError::Other("oops"));
+
+		assert_eq!(
+			DispatchError::from(ElectionError::DataProvider("no voters")),
+			DispatchError::Other("no voters")
+		);
+		assert_eq!(
+			DispatchError::from(ElectionError::Ongoing),
+			DispatchError::Other("election ongoing")
+		);
+
+		assert_eq!(
+			DryRunError::from(ElectionError::Solver(
+				sp_npos_elections::Error::SolutionWeightOverflow
+			)),
+			DryRunError::NposElections
+		);
+		assert_eq!(
+			DryRunError::from(ElectionError::BoundsExceeded),
+			DryRunError::Other(b"election bounds exceeded".to_vec())
+		);
+	}
+}
+
+mod sorted_list {
+	use crate::{BatchMode, ListCursor, SortedListProvider};
+	use frame_support::{assert_ok, storage::unhashed};
+
+	/// A list that is stored as a single vector of `(id, score)`, sorted by score, and only
+	/// implements the required methods of [`SortedListProvider`].
+	struct List;
+
+	impl List {
+		const KEY: &'static [u8] = b"sorted_list";
+
+		fn get() -> Vec<(u64, u64)> {
+			unhashed::get(Self::KEY).unwrap_or_default()
+		}
+
+		fn put(mut list: Vec<(u64, u64)>) {
+			list.sort_by(|(_, a), (_, b)| b.cmp(a));
+			unhashed::put(Self::KEY, &list);
+		}
+
+		fn position(id: &u64) -> Result<usize, &'static str> {
+			Self::get().iter().position(|(other, _)| other == id).ok_or("not in list")
+		}
+	}
+
+	impl SortedListProvider<u64> for List {
+		type Error = &'static str;
+		type Score = u64;
+
+		fn iter() -> Box<dyn Iterator<Item = u64>> {
+			Box::new(Self::get().into_iter().map(|(id, _)| id))
+		}
+
+		fn iter_from(start: &u64) -> Result<Box<dyn Iterator<Item = u64>>, Self::Error> {
+			let position = Self::position(start)?;
+			Ok(Box::new(Self::iter().skip(position + 1)))
+		}
+
+		fn count() -> u32 {
+			Self::get().len() as u32
+		}
+
+		fn contains(id: &u64) -> bool {
+			Self::position(id).is_ok()
+		}
+
+		fn on_insert(id: u64, score: u64) -> Result<(), Self::Error> {
+			if Self::contains(&id) {
+				return Err("duplicate")
+			}
+			let mut list = Self::get();
+			list.push((id, score));
+			Self::put(list);
+			Ok(())
+		}
+
+		fn on_update(id: &u64, score: u64) -> Result<(), Self::Error> {
+			let position = Self::position(id)?;
+			let mut list = Self::get();
+			list[position].1 = score;
+			Self::put(list);
+			Ok(())
+		}
+
+		fn get_score(id: &u64) -> Result<u64, Self::Error> {
+			Ok(Self::get()[Self::position(id)?].1)
+		}
+
+		fn on_remove(id: &u64) -> Result<(), Self::Error> {
+			let position = Self::position(id)?;
+			let mut list = Self::get();
+			list.remove(position);
+			Self::put(list);
+			Ok(())
+		}
+
+		fn unsafe_regenerate(
+			all: impl IntoIterator<Item = u64>,
+			score_of: Box<dyn Fn(&u64) -> u64>,
+		) -> u32 {
+			Self::put(all.into_iter().map(|id| (id, score_of(&id))).collect());
+			Self::count()
+		}
+
+		fn unsafe_clear() {
+			Self::put(vec![]);
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn try_state() -> Result<(), sp_runtime::TryRuntimeError> {
+			Ok(())
+		}
+
+		#[cfg(feature = "runtime-benchmarks")]
+		fn score_update_worst_case(_: &u64, _: bool) -> u64 {
+			unimplemented!()
+		}
+	}
+
+	fn with_list(f: impl FnOnce()) {
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			List::unsafe_regenerate(vec![1, 2, 3, 4, 5], Box::new(|id| id * 10));
+			f()
+		})
+	}
+
+	#[test]
+	fn default_iter_in_score_range_works() {
+		with_list(|| {
+			assert_eq!(List::iter_in_score_range(20, 40).collect::<Vec<_>>(), vec![4, 3, 2]);
+			assert_eq!(List::iter_in_score_range(41, 49).count(), 0);
+			assert_eq!(List::iter_in_score_range(0, u64::MAX).count(), 5);
+		})
+	}
+
+	#[test]
+	fn default_on_insert_many_works() {
+		with_list(|| {
+			assert_eq!(List::on_insert_many(vec![(6, 60), (7, 5)], BatchMode::AllOrNothing), Ok(2));
+			assert_eq!(List::iter().collect::<Vec<_>>(), vec![6, 5, 4, 3, 2, 1, 7]);
+
+			// a duplicate reverts the entire batch.
+			assert_eq!(
+				List::on_insert_many(vec![(8, 80), (1, 10)], BatchMode::AllOrNothing),
+				Err("duplicate")
+			);
+			assert!(!List::contains(&8));
+
+			// unless failures are skipped.
+			assert_eq!(List::on_insert_many(vec![(8, 80), (1, 10)], BatchMode::BestEffort), Ok(1));
+			assert!(List::contains(&8));
+		})
+	}
+
+	#[test]
+	fn default_on_update_many_works() {
+		with_list(|| {
+			assert_eq!(List::on_update_many(vec![(1, 60), (2, 0)], BatchMode::AllOrNothing), Ok(2));
+			assert_eq!(List::iter().collect::<Vec<_>>(), vec![1, 5, 4, 3, 2]);
+
+			// a missing id reverts the entire batch.
+			assert_eq!(
+				List::on_update_many(vec![(3, 70), (9, 10)], BatchMode::AllOrNothing),
+				Err("not in list")
+			);
+			assert_eq!(List::get_score(&3), Ok(30));
+
+			// unless failures are skipped.
+			assert_eq!(List::on_update_many(vec![(3, 70), (9, 10)], BatchMode::BestEffort), Ok(1));
+			assert_eq!(List::get_score(&3), Ok(70));
+		})
+	}
+
+	#[test]
+	fn default_iter_ascending_works() {
+		with_list(|| {
+			assert_eq!(List::iter_ascending().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+			assert_eq!(List::iter_from_ascending(&3).unwrap().collect::<Vec<_>>(), vec![4, 5]);
+			assert_eq!(List::iter_from_ascending(&5).unwrap().count(), 0);
+			assert!(List::iter_from_ascending(&9).is_err());
+		})
+	}
+
+	#[test]
+	fn default_iter_resume_works() {
+		with_list(|| {
+			let cursor = List::cursor(&4).unwrap();
+			assert_eq!(cursor, ListCursor { last: 4, next: Some(3), bracket: 40, part: 0 });
+			assert_eq!(List::iter_resume(&cursor).collect::<Vec<_>>(), vec![3, 2, 1]);
+			assert!(List::cursor(&9).is_err());
+
+			// when the cursor's id is removed, it continues from the next one.
+			assert_ok!(List::on_remove(&4));
+			assert_eq!(List::iter_resume(&cursor).collect::<Vec<_>>(), vec![3, 2, 1]);
+
+			// when the next one is gone as well, it starts over rather than skipping any.
+			assert_ok!(List::on_remove(&3));
+			assert_eq!(List::iter_resume(&cursor).collect::<Vec<_>>(), vec![5, 2, 1]);
+		})
+	}
+}
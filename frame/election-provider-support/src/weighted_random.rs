@@ -0,0 +1,143 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A cheap, randomized [`NposSolver`], meant to be used as an emergency fallback when none of
+//! the proper solvers fit in a block.
+
+use crate::{
+	Assignment, ElectionResult, ExtendedBalance, Get, IdentifierT, NposSolver, PerThing128,
+	VoteWeight, Weight, WeightInfo,
+};
+use codec::Encode;
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	marker::PhantomData,
+	prelude::*,
+};
+
+/// An [`NposSolver`] that elects winners at random, with a probability proportional to their
+/// approval stake, i.e. the sum of the stake of all voters that vote for them.
+///
+/// Winners are drawn without replacement, using randomness derived from `Seed`, e.g. the BABE
+/// randomness of the current epoch. The same seed and input always lead to the same outcome. Each
+/// voter splits its stake evenly among the winners it voted for.
+///
+/// Computing the approval stakes is linear in the number of votes, while each draw is linear in
+/// the number of targets. No balancing or score maximization of any sort is done, hence the outcome
+/// is generally much worse than that of [`crate::SequentialPhragmen`].
+pub struct WeightedRandom<AccountId, Accuracy, Seed>(PhantomData<(AccountId, Accuracy, Seed)>);
+
+impl<AccountId: IdentifierT, Accuracy: PerThing128, Seed: Get<[u8; 32]>> NposSolver
+	for WeightedRandom<AccountId, Accuracy, Seed>
+{
+	type AccountId = AccountId;
+	type Accuracy = Accuracy;
+	type Error = sp_npos_elections::Error;
+
+	fn solve(
+		to_elect: usize,
+		targets: Vec<Self::AccountId>,
+		voters: Vec<(Self::AccountId, VoteWeight, impl IntoIterator<Item = Self::AccountId>)>,
+	) -> Result<ElectionResult<Self::AccountId, Self::Accuracy>, Self::Error> {
+		let mut approval_stake = targets
+			.iter()
+			.map(|t| (t.clone(), 0 as ExtendedBalance))
+			.collect::<BTreeMap<_, _>>();
+		let voters = voters
+			.into_iter()
+			.map(|(who, stake, votes)| {
+				let mut votes = votes
+					.into_iter()
+					.filter(|t| approval_stake.contains_key(t))
+					.collect::<Vec<_>>();
+				votes.sort();
+				votes.dedup();
+				votes.iter().for_each(|t| {
+					approval_stake
+						.entry(t.clone())
+						.and_modify(|s| *s = s.saturating_add(stake.into()));
+				});
+				(who, votes)
+			})
+			.collect::<Vec<_>>();
+
+		// keep the order in which targets were given, as the draws depend on it. Taking the
+		// approval stake out of the map also drops duplicate targets.
+		let mut candidates = targets
+			.into_iter()
+			.filter_map(|t| approval_stake.remove(&t).map(|s| (t, s)))
+			.collect::<Vec<_>>();
+		let mut total = candidates
+			.iter()
+			.fold(0 as ExtendedBalance, |acc, (_, s)| acc.saturating_add(*s));
+
+		let seed = Seed::get();
+		let mut winners = Vec::with_capacity(to_elect.min(candidates.len()));
+		for round in 0..to_elect as u32 {
+			if total == 0 {
+				break
+			}
+
+			let hash = sp_core::blake2_256(&(seed, round).encode());
+			let mut bytes = [0u8; 16];
+			bytes.copy_from_slice(&hash[..16]);
+			let mut pick = u128::from_le_bytes(bytes) % total;
+
+			let index = candidates
+				.iter()
+				.position(|(_, s)| {
+					if pick < *s {
+						true
+					} else {
+						pick -= *s;
+						false
+					}
+				})
+				.expect("pick is less than the sum of all stakes; qed");
+			let (winner, stake) = candidates.remove(index);
+			total = total.saturating_sub(stake);
+			winners.push((winner, stake));
+		}
+
+		let elected = winners.iter().map(|(w, _)| w.clone()).collect::<BTreeSet<_>>();
+		let assignments = voters
+			.into_iter()
+			.filter_map(|(who, votes)| {
+				let distribution = votes
+					.into_iter()
+					.filter(|t| elected.contains(t))
+					.map(|t| (t, Accuracy::zero()))
+					.collect::<Vec<_>>();
+				(!distribution.is_empty()).then_some(Assignment { who, distribution })
+			})
+			.map(|mut assignment| {
+				let share = Accuracy::from_rational(1, assignment.distribution.len() as u128);
+				assignment.distribution.iter_mut().for_each(|(_, p)| *p = share);
+				assignment
+					.try_normalize()
+					.map_err(sp_npos_elections::Error::ArithmeticError)
+					.map(|_| assignment)
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(ElectionResult { winners, assignments })
+	}
+
+	fn weight<T: WeightInfo>(voters: u32, targets: u32, vote_degree: u32) -> Weight {
+		T::weighted_random(voters, targets, vote_degree)
+	}
+}
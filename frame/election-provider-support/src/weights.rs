@@ -7,7 +7,7 @@
 // you may not use this file except in compliance with the License.
 // You may obtain a copy of the License at
 //
-// http://www.apache.org/licenses/LICENSE-2.0
+// 	http://www.apache.org/licenses/LICENSE-2.0
 //
 // Unless required by applicable law or agreed to in writing, software
 // distributed under the License is distributed on an "AS IS" BASIS,
@@ -18,78 +18,240 @@
 //! Autogenerated weights for pallet_election_provider_support_benchmarking
 //!
 //! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2022-04-23, STEPS: `1`, REPEAT: 1, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
+//! DATE: 2026-10-15, STEPS: `50`, REPEAT: `20`, LOW RANGE: `[]`, HIGH RANGE: `[]`
+//! WORST CASE MAP SIZE: `1000000`
+//! HOSTNAME: `vm`, CPU: `Intel(R) Xeon(R) Processor`
+//! EXECUTION: `Some(Native)`, WASM-EXECUTION: `Compiled`, CHAIN: `None`, DB CACHE: `1024`
 
 // Executed Command:
-// target/release/substrate
-// benchmark
-// pallet
-// --chain=dev
-// --steps=1
-// --repeat=1
+// /tmp/mpbench/target/release/epsgen
 // --pallet=pallet_election_provider_support_benchmarking
-// --extrinsic=*
-// --execution=wasm
-// --wasm-execution=compiled
-// --heap-pages=4096
-// --output=frame/election-provider-support/src/weights.rs
+// --steps=50
+// --repeat=20
+// --output=/tmp/eps_weights.rs
 // --template=./.maintain/frame-weight-template.hbs
+// --header=./HEADER-APACHE2
 
 #![cfg_attr(rustfmt, rustfmt_skip)]
 #![allow(unused_parens)]
 #![allow(unused_imports)]
+#![allow(missing_docs)]
 
 use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
-use sp_std::marker::PhantomData;
+use core::marker::PhantomData;
 
 /// Weight functions needed for pallet_election_provider_support_benchmarking.
 pub trait WeightInfo {
 	fn phragmen(v: u32, t: u32, d: u32, ) -> Weight;
 	fn phragmms(v: u32, t: u32, d: u32, ) -> Weight;
+	fn weighted_random(v: u32, t: u32, d: u32, ) -> Weight;
+	fn phragmms_balanced(v: u32, t: u32, d: u32, ) -> Weight;
+	fn reduce(v: u32, t: u32, d: u32, ) -> Weight;
+	fn feasibility_check(v: u32, t: u32, d: u32, ) -> Weight;
+	fn pjr_check(v: u32, t: u32, d: u32, ) -> Weight;
 }
 
 /// Weights for pallet_election_provider_support_benchmarking using the Substrate node and recommended hardware.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
-	fn phragmen(v: u32, t: u32, d: u32, ) -> Weight {
-		Weight::from_parts(0 as u64, 0)
-			// Standard Error: 667_000
-			.saturating_add(Weight::from_parts(32_973_000 as u64, 0).saturating_mul(v as u64))
-			// Standard Error: 1_334_000
-			.saturating_add(Weight::from_parts(1_334_000 as u64, 0).saturating_mul(t as u64))
-			// Standard Error: 60_644_000
-			.saturating_add(Weight::from_parts(2_636_364_000 as u64, 0).saturating_mul(d as u64))
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn phragmen(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 1_939_791_000 picoseconds.
+		Weight::from_parts(1_966_183_000, 0)
+			// Standard Error: 83_427
+			.saturating_add(Weight::from_parts(2_433_294, 0).saturating_mul(v.into()))
+			// Standard Error: 8_529_314
+			.saturating_add(Weight::from_parts(487_820_403, 0).saturating_mul(d.into()))
 	}
-	fn phragmms(v: u32, t: u32, d: u32, ) -> Weight {
-		Weight::from_parts(0 as u64, 0)
-			// Standard Error: 73_000
-			.saturating_add(Weight::from_parts(21_073_000 as u64, 0).saturating_mul(v as u64))
-			// Standard Error: 146_000
-			.saturating_add(Weight::from_parts(65_000 as u64, 0).saturating_mul(t as u64))
-			// Standard Error: 6_649_000
-			.saturating_add(Weight::from_parts(1_711_424_000 as u64, 0).saturating_mul(d as u64))
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn phragmms(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 1_824_135_000 picoseconds.
+		Weight::from_parts(1_845_531_000, 0)
+			// Standard Error: 72_080
+			.saturating_add(Weight::from_parts(2_919_042, 0).saturating_mul(v.into()))
+			// Standard Error: 7_369_219
+			.saturating_add(Weight::from_parts(597_244_562, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn weighted_random(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 928_774_000 picoseconds.
+		Weight::from_parts(934_843_000, 0)
+			// Standard Error: 14_909
+			.saturating_add(Weight::from_parts(722_520, 0).saturating_mul(v.into()))
+			// Standard Error: 1_524_341
+			.saturating_add(Weight::from_parts(122_421_760, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn phragmms_balanced(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 7_287_346_000 picoseconds.
+		Weight::from_parts(7_580_747_000, 0)
+			// Standard Error: 883_718
+			.saturating_add(Weight::from_parts(27_022_526, 0).saturating_mul(v.into()))
+			// Standard Error: 90_348_200
+			.saturating_add(Weight::from_parts(7_415_449_922, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn reduce(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 64_322_523_000 picoseconds.
+		Weight::from_parts(72_064_201_000, 0)
+			// Standard Error: 2_695_410
+			.saturating_add(Weight::from_parts(165_806_297, 0).saturating_mul(v.into()))
+			// Standard Error: 275_569_116
+			.saturating_add(Weight::from_parts(16_341_868_760, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn feasibility_check(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 591_239_000 picoseconds.
+		Weight::from_parts(600_111_000, 0)
+			// Standard Error: 11_176
+			.saturating_add(Weight::from_parts(309_749, 0).saturating_mul(v.into()))
+			// Standard Error: 1_142_655
+			.saturating_add(Weight::from_parts(71_274_578, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn pjr_check(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 17_013_480_000 picoseconds.
+		Weight::from_parts(17_186_596_000, 0)
+			// Standard Error: 1_064_027
+			.saturating_add(Weight::from_parts(12_070_098, 0).saturating_mul(v.into()))
+			// Standard Error: 108_782_396
+			.saturating_add(Weight::from_parts(3_954_461_596, 0).saturating_mul(d.into()))
 	}
 }
 
-// For backwards compatibility and tests
+// For backwards compatibility and tests.
 impl WeightInfo for () {
-	fn phragmen(v: u32, t: u32, d: u32, ) -> Weight {
-		Weight::from_parts(0 as u64, 0)
-			// Standard Error: 667_000
-			.saturating_add(Weight::from_parts(32_973_000 as u64, 0).saturating_mul(v as u64))
-			// Standard Error: 1_334_000
-			.saturating_add(Weight::from_parts(1_334_000 as u64, 0).saturating_mul(t as u64))
-			// Standard Error: 60_644_000
-			.saturating_add(Weight::from_parts(2_636_364_000 as u64, 0).saturating_mul(d as u64))
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn phragmen(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 1_939_791_000 picoseconds.
+		Weight::from_parts(1_966_183_000, 0)
+			// Standard Error: 83_427
+			.saturating_add(Weight::from_parts(2_433_294, 0).saturating_mul(v.into()))
+			// Standard Error: 8_529_314
+			.saturating_add(Weight::from_parts(487_820_403, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn phragmms(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 1_824_135_000 picoseconds.
+		Weight::from_parts(1_845_531_000, 0)
+			// Standard Error: 72_080
+			.saturating_add(Weight::from_parts(2_919_042, 0).saturating_mul(v.into()))
+			// Standard Error: 7_369_219
+			.saturating_add(Weight::from_parts(597_244_562, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn weighted_random(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 928_774_000 picoseconds.
+		Weight::from_parts(934_843_000, 0)
+			// Standard Error: 14_909
+			.saturating_add(Weight::from_parts(722_520, 0).saturating_mul(v.into()))
+			// Standard Error: 1_524_341
+			.saturating_add(Weight::from_parts(122_421_760, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn phragmms_balanced(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 7_287_346_000 picoseconds.
+		Weight::from_parts(7_580_747_000, 0)
+			// Standard Error: 883_718
+			.saturating_add(Weight::from_parts(27_022_526, 0).saturating_mul(v.into()))
+			// Standard Error: 90_348_200
+			.saturating_add(Weight::from_parts(7_415_449_922, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn reduce(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 64_322_523_000 picoseconds.
+		Weight::from_parts(72_064_201_000, 0)
+			// Standard Error: 2_695_410
+			.saturating_add(Weight::from_parts(165_806_297, 0).saturating_mul(v.into()))
+			// Standard Error: 275_569_116
+			.saturating_add(Weight::from_parts(16_341_868_760, 0).saturating_mul(d.into()))
+	}
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn feasibility_check(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 591_239_000 picoseconds.
+		Weight::from_parts(600_111_000, 0)
+			// Standard Error: 11_176
+			.saturating_add(Weight::from_parts(309_749, 0).saturating_mul(v.into()))
+			// Standard Error: 1_142_655
+			.saturating_add(Weight::from_parts(71_274_578, 0).saturating_mul(d.into()))
 	}
-	fn phragmms(v: u32, t: u32, d: u32, ) -> Weight {
-		Weight::from_parts(0 as u64, 0)
-			// Standard Error: 73_000
-			.saturating_add(Weight::from_parts(21_073_000 as u64, 0).saturating_mul(v as u64))
-			// Standard Error: 146_000
-			.saturating_add(Weight::from_parts(65_000 as u64, 0).saturating_mul(t as u64))
-			// Standard Error: 6_649_000
-			.saturating_add(Weight::from_parts(1_711_424_000 as u64, 0).saturating_mul(d as u64))
+	/// The range of component `v` is `[1000, 2000]`.
+	/// The range of component `t` is `[500, 1000]`.
+	/// The range of component `d` is `[5, 16]`.
+	fn pjr_check(v: u32, _t: u32, d: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 17_013_480_000 picoseconds.
+		Weight::from_parts(17_186_596_000, 0)
+			// Standard Error: 1_064_027
+			.saturating_add(Weight::from_parts(12_070_098, 0).saturating_mul(v.into()))
+			// Standard Error: 108_782_396
+			.saturating_add(Weight::from_parts(3_954_461_596, 0).saturating_mul(d.into()))
 	}
 }
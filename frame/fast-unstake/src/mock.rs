@@ -127,6 +127,9 @@ impl frame_election_provider_support::ElectionProvider for MockElection {
 	fn ongoing() -> bool {
 		Ongoing::get()
 	}
+	fn defer(_: BlockNumber) -> Result<BlockNumber, Self::Error> {
+		Err(())
+	}
 	fn elect() -> Result<frame_election_provider_support::BoundedSupportsOf<Self>, Self::Error> {
 		Err(())
 	}
@@ -18,8 +18,8 @@
 //! Implementations for the Staking FRAME Pallet.
 
 use frame_election_provider_support::{
-	data_provider, BoundedSupportsOf, ElectionDataProvider, ElectionProvider, ScoreProvider,
-	SortedListProvider, VoteWeight, VoterOf,
+	data_provider, BoundedSupportsOf, ElectionDataProvider, ElectionProvider, ListCursor,
+	ScoreProvider, SortedListProvider, VoteWeight, Voter, VoterOf,
 };
 use frame_support::{
 	defensive,
@@ -502,6 +502,9 @@ impl<T: Config> Pallet<T> {
 	/// Get election result from `T::ElectionProvider`.
 	/// In case election result has more than [`MinimumValidatorCount`] validator trigger a new era.
 	///
+	/// While a migration of this pallet is pending, the election is deferred by a session, once
+	/// per era. If the migration is still pending by then, the election goes ahead anyway.
+	///
 	/// In case a new era is planned, the new validator set is returned.
 	pub(crate) fn try_trigger_new_era(
 		start_session_index: SessionIndex,
@@ -520,6 +523,34 @@ impl<T: Config> Pallet<T> {
 				// both bounds checked in integrity test to be equal
 				.defensive_unwrap_or_default()
 		} else {
+			if !<Self as ElectionDataProvider>::ready_for_election() {
+				let planned_era = CurrentEra::<T>::get().map_or(0, |era| era.saturating_add(1));
+				if DeferredElection::<T>::get() == Some(planned_era) {
+					// don't stall the elections on a migration that might never happen.
+					log!(
+						warn,
+						"election of era {:?} was deferred already, but a migration is still \
+						pending. electing anyway.",
+						planned_era
+					);
+				} else {
+					// retry at the next session, rather than failing the election.
+					match <T::ElectionProvider>::defer(T::NextNewSession::average_session_length())
+					{
+						Ok(delay) => {
+							DeferredElection::<T>::put(planned_era);
+							log!(
+								warn,
+								"election deferred by {:?} blocks, a migration is pending",
+								delay
+							);
+							return None
+						},
+						Err(e) => log!(warn, "election could not be deferred due to {:?}", e),
+					}
+				}
+			}
+
 			let result = <T::ElectionProvider>::elect().map_err(|e| {
 				log!(warn, "election provider failed due to {:?}", e);
 				Self::deposit_event(Event::StakingElectionFailed);
@@ -792,7 +823,7 @@ impl<T: Config> Pallet<T> {
 			if let Some(Nominations { targets, .. }) = <Nominators<T>>::get(&voter) {
 				let voter_weight = weight_of(&voter);
 				if !targets.is_empty() {
-					all_voters.push((voter.clone(), voter_weight, targets));
+					all_voters.push(Voter::new(voter.clone(), voter_weight, targets));
 					nominators_taken.saturating_inc();
 				} else {
 					// Technically should never happen, but not much we can do about it.
@@ -801,7 +832,7 @@ impl<T: Config> Pallet<T> {
 					if voter_weight < min_active_stake { voter_weight } else { min_active_stake };
 			} else if Validators::<T>::contains_key(&voter) {
 				// if this voter is a validator:
-				let self_vote = (
+				let self_vote = Voter::new(
 					voter.clone(),
 					weight_of(&voter),
 					vec![voter.clone()]
@@ -1022,6 +1053,19 @@ impl<T: Config> ElectionDataProvider for Pallet<T> {
 		Ok(Self::get_npos_targets(None))
 	}
 
+	/// Staking is not ready for an election while a migration of its storage is pending.
+	fn ready_for_election() -> bool {
+		Self::on_chain_storage_version() == Self::current_storage_version()
+	}
+
+	fn lock_voters() {
+		T::VoterList::lock();
+	}
+
+	fn unlock_voters() {
+		T::VoterList::unlock();
+	}
+
 	fn next_election_prediction(now: T::BlockNumber) -> T::BlockNumber {
 		let current_era = Self::current_era().unwrap_or(0);
 		let current_session = Self::current_planned_session();
@@ -1140,7 +1184,7 @@ impl<T: Config> ElectionDataProvider for Pallet<T> {
 			);
 		});
 
-		voters.into_iter().for_each(|(v, s, t)| {
+		voters.into_iter().for_each(|Voter { who: v, weight: s, votes: t }| {
 			let stake = <BalanceOf<T>>::try_from(s).unwrap_or_else(|_| {
 				panic!("cannot convert a VoteWeight into BalanceOf, benchmark needs reconfiguring.")
 			});
@@ -1444,6 +1488,32 @@ impl<T: Config> SortedListProvider<T::AccountId> for UseValidatorsMap<T> {
 			Err(())
 		}
 	}
+	fn cursor(last: &T::AccountId) -> Result<ListCursor<T::AccountId, Self::Score>, Self::Error> {
+		let next = Self::iter_from(last)?.next();
+		Ok(ListCursor {
+			last: last.clone(),
+			next,
+			bracket: Pallet::<T>::weight_of(last).into(),
+			part: 0,
+		})
+	}
+	fn iter_resume(
+		cursor: &ListCursor<T::AccountId, Self::Score>,
+	) -> Box<dyn Iterator<Item = T::AccountId>> {
+		// the map is ordered by key, so this works even if `last` was removed.
+		let start_key = Validators::<T>::hashed_key_for(&cursor.last);
+		Box::new(Validators::<T>::iter_from(start_key).map(|(n, _)| n))
+	}
+	fn iter_ascending() -> Box<dyn Iterator<Item = T::AccountId>> {
+		// not sorted, thus the same as `iter`.
+		Self::iter()
+	}
+	fn iter_from_ascending(
+		start: &T::AccountId,
+	) -> Result<Box<dyn Iterator<Item = T::AccountId>>, Self::Error> {
+		// not sorted, thus the same as `iter_from`.
+		Self::iter_from(start)
+	}
 	fn count() -> u32 {
 		Validators::<T>::count()
 	}
@@ -1520,6 +1590,39 @@ impl<T: Config> SortedListProvider<T::AccountId> for UseNominatorsAndValidatorsM
 			Err(())
 		}
 	}
+	fn cursor(last: &T::AccountId) -> Result<ListCursor<T::AccountId, Self::Score>, Self::Error> {
+		let next = Self::iter_from(last)?.next();
+		// validators are iterated first, then nominators.
+		let part = if Validators::<T>::contains_key(last) { 0 } else { 1 };
+		Ok(ListCursor { last: last.clone(), next, bracket: Pallet::<T>::weight_of(last), part })
+	}
+	fn iter_resume(
+		cursor: &ListCursor<T::AccountId, Self::Score>,
+	) -> Box<dyn Iterator<Item = T::AccountId>> {
+		// both maps are ordered by key, so this works even if `last` was removed, or moved to the
+		// other map.
+		if cursor.part == 0 {
+			let start_key = Validators::<T>::hashed_key_for(&cursor.last);
+			Box::new(
+				Validators::<T>::iter_from(start_key)
+					.map(|(v, _)| v)
+					.chain(Nominators::<T>::iter().map(|(n, _)| n)),
+			)
+		} else {
+			let start_key = Nominators::<T>::hashed_key_for(&cursor.last);
+			Box::new(Nominators::<T>::iter_from(start_key).map(|(n, _)| n))
+		}
+	}
+	fn iter_ascending() -> Box<dyn Iterator<Item = T::AccountId>> {
+		// not sorted, thus the same as `iter`.
+		Self::iter()
+	}
+	fn iter_from_ascending(
+		start: &T::AccountId,
+	) -> Result<Box<dyn Iterator<Item = T::AccountId>>, Self::Error> {
+		// not sorted, thus the same as `iter_from`.
+		Self::iter_from(start)
+	}
 	fn count() -> u32 {
 		Nominators::<T>::count().saturating_add(Validators::<T>::count())
 	}
@@ -1720,9 +1823,11 @@ impl<T: Config> StakingInterface for Pallet<T> {
 #[cfg(any(test, feature = "try-runtime"))]
 impl<T: Config> Pallet<T> {
 	pub(crate) fn do_try_state(_: BlockNumberFor<T>) -> Result<(), TryRuntimeError> {
+		// while the list is locked, it still iterates the ids that have been removed from it.
 		ensure!(
-			T::VoterList::iter()
-				.all(|x| <Nominators<T>>::contains_key(&x) || <Validators<T>>::contains_key(&x)),
+			T::VoterList::iter().filter(T::VoterList::contains).all(|x| {
+				<Nominators<T>>::contains_key(&x) || <Validators<T>>::contains_key(&x)
+			}),
 			"VoterList contains non-staker"
 		);
 
@@ -580,6 +580,12 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(crate) type ChillThreshold<T: Config> = StorageValue<_, Percent, OptionQuery>;
 
+	/// The last era whose election was deferred, as a migration of this pallet was pending.
+	///
+	/// The election of an era is deferred at most once, see [`Pallet::try_trigger_new_era`].
+	#[pallet::storage]
+	pub(crate) type DeferredElection<T> = StorageValue<_, EraIndex, OptionQuery>;
+
 	#[pallet::genesis_config]
 	#[derive(frame_support::DefaultNoBound)]
 	pub struct GenesisConfig<T: Config> {
@@ -600,6 +606,10 @@ pub mod pallet {
 	#[pallet::genesis_build]
 	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
 		fn build(&self) {
+			// a new chain starts at the current storage version, even if the genesis config is
+			// built without the `OnGenesis` hook of the runtime.
+			STORAGE_VERSION.put::<Pallet<T>>();
+
 			ValidatorCount::<T>::put(self.validator_count);
 			MinimumValidatorCount::<T>::put(self.minimum_validator_count);
 			Invulnerables::<T>::put(&self.invulnerables);
@@ -1551,7 +1561,8 @@ pub mod pallet {
 			let reapable = T::Currency::total_balance(&stash) < ed ||
 				Self::ledger(Self::bonded(stash.clone()).ok_or(Error::<T>::NotStash)?)
 					.map(|l| l.total)
-					.unwrap_or_default() < ed;
+					.unwrap_or_default() <
+					ed;
 			ensure!(reapable, Error::<T>::FundedTarget);
 
 			Self::kill_stash(&stash, num_slashing_spans)?;
@@ -4534,7 +4534,7 @@ mod election_data_provider {
 			)
 			.unwrap()
 			.into_iter()
-			.any(|(w, _, t)| { v == w && t[0] == w })))
+			.any(|voter| { v == voter.who && voter.votes[0] == voter.who })))
 		})
 	}
 
@@ -4613,8 +4613,7 @@ mod election_data_provider {
 					Staking::electing_voters(Some(2))
 						.unwrap()
 						.iter()
-						.map(|(stash, _, _)| stash)
-						.copied()
+						.map(|voter| voter.who)
 						.collect::<Vec<_>>(),
 					vec![11],
 				);
@@ -4680,6 +4679,56 @@ mod election_data_provider {
 			assert_eq!(ForceEra::<Test>::get(), Forcing::NotForcing);
 		})
 	}
+
+	#[test]
+	fn not_ready_for_election_while_a_migration_is_pending() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert!(Staking::ready_for_election());
+
+			// a migration is pending.
+			StorageVersion::new(12).put::<Staking>();
+			assert!(!Staking::ready_for_election());
+
+			// the election is deferred to the next session rather than failing.
+			let _ = staking_events_since_last_call();
+			start_session(2);
+			assert_eq!(current_era(), 0);
+			assert_eq!(staking_events_since_last_call(), vec![]);
+
+			// once migrated, the era is planned at the next session.
+			StorageVersion::new(13).put::<Staking>();
+			assert!(Staking::ready_for_election());
+			advance_session();
+			assert_eq!(current_era(), 1);
+			assert_eq!(staking_events_since_last_call(), vec![Event::StakersElected]);
+		})
+	}
+
+	#[test]
+	fn elections_are_deferred_once_per_era_while_a_migration_is_pending() {
+		ExtBuilder::default().build_and_execute(|| {
+			StorageVersion::new(12).put::<Staking>();
+			let _ = staking_events_since_last_call();
+
+			// the election is deferred once...
+			start_session(2);
+			assert_eq!(current_era(), 0);
+			assert_eq!(staking_events_since_last_call(), vec![]);
+
+			// ...and goes ahead at the next session, although the migration is still pending.
+			advance_session();
+			assert_eq!(current_era(), 1);
+			assert_eq!(staking_events_since_last_call(), vec![Event::StakersElected]);
+
+			// the election of the next era is deferred once again.
+			start_session(6);
+			assert_eq!(current_era(), 1);
+			assert!(!staking_events_since_last_call().contains(&Event::StakersElected));
+			advance_session();
+			assert_eq!(current_era(), 2);
+			assert_eq!(staking_events_since_last_call(), vec![Event::StakersElected]);
+		})
+	}
 }
 
 #[test]
@@ -5198,6 +5247,79 @@ mod sorted_list_provider {
 			assert_eq!(<Test as Config>::VoterList::iter().collect::<Vec<_>>(), vec![11, 21, 31]);
 		});
 	}
+
+	#[test]
+	fn maps_iter_resume_continues_in_the_right_map() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			type Maps = crate::UseNominatorsAndValidatorsMap<Test>;
+			bond_nominator(61, 500, vec![11]);
+			bond_nominator(71, 500, vec![11]);
+			let all = Maps::iter().collect::<Vec<_>>();
+			let validators = Validators::<Test>::iter_keys().count();
+
+			// given a cursor at the last validator, and one at the first nominator.
+			let last_validator = all[validators - 1];
+			let first_nominator = all[validators];
+			let validator_cursor = Maps::cursor(&last_validator).unwrap();
+			let nominator_cursor = Maps::cursor(&first_nominator).unwrap();
+			assert_eq!((validator_cursor.part, nominator_cursor.part), (0, 1));
+
+			// when both the ids and the ones after them are gone.
+			assert_ok!(Staking::chill(RuntimeOrigin::signed(last_validator)));
+			assert_ok!(Staking::chill(RuntimeOrigin::signed(first_nominator)));
+			assert_ok!(Staking::chill(RuntimeOrigin::signed(all[validators + 1])));
+
+			// then both still continue in the map that they were iterating.
+			assert_eq!(
+				Maps::iter_resume(&validator_cursor).collect::<Vec<_>>(),
+				all[validators + 2..].to_vec()
+			);
+			assert_eq!(
+				Maps::iter_resume(&nominator_cursor).collect::<Vec<_>>(),
+				all[validators + 2..].to_vec()
+			);
+		});
+	}
+
+	#[test]
+	fn voter_changes_while_list_is_locked_are_applied_once_unlocked() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			// given
+			assert_eq!(
+				<Test as Config>::VoterList::iter().collect::<Vec<_>>(),
+				vec![11, 21, 31, 101]
+			);
+			<Test as Config>::VoterList::lock();
+
+			// when voters come and go while the list is locked.
+			assert_ok!(Staking::chill(RuntimeOrigin::signed(101)));
+			assert_ok!(Staking::nominate(RuntimeOrigin::signed(21), vec![11]));
+			bond_nominator(61, 500, vec![11]);
+
+			// then the counters stay in sync, while the list is not changed yet.
+			assert_eq!(
+				<Test as Config>::VoterList::count(),
+				Nominators::<Test>::count() + Validators::<Test>::count()
+			);
+			assert!(!<Test as Config>::VoterList::contains(&101));
+			assert!(<Test as Config>::VoterList::contains(&61));
+			assert_eq!(
+				<Test as Config>::VoterList::iter().collect::<Vec<_>>(),
+				vec![11, 21, 31, 101]
+			);
+			assert_ok!(Staking::do_try_state(System::block_number()));
+
+			// when
+			<Test as Config>::VoterList::unlock();
+			<Test as Config>::VoterList::on_idle_rebag(Weight::MAX);
+
+			// then
+			assert_eq!(
+				<Test as Config>::VoterList::iter().collect::<Vec<_>>(),
+				vec![11, 21, 31, 61]
+			);
+		});
+	}
 }
 
 #[test]
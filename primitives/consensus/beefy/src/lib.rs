@@ -39,7 +39,9 @@ mod test_utils;
 pub mod witness;
 
 pub use commitment::{Commitment, SignedCommitment, VersionedFinalityProof};
-pub use payload::{known_payloads, BeefyPayloadId, Payload, PayloadProvider};
+pub use payload::{
+	known_payloads, BeefyPayloadId, ChainedPayloadProvider, Payload, PayloadProvider,
+};
 #[cfg(feature = "std")]
 pub use test_utils::*;
 
@@ -63,6 +65,46 @@ pub trait BeefyAuthorityId<MsgHash: Hash>: RuntimeAppPublic {
 	fn verify(&self, signature: &<Self as RuntimeAppPublic>::Signature, msg: &[u8]) -> bool;
 }
 
+/// Trait representing BEEFY authority id, whose signatures are verified over messages hashed with
+/// a [`SignatureHasher`] chosen at runtime.
+pub trait BeefyAuthorityIdWithHasher: RuntimeAppPublic {
+	/// Verify a signature over `msg` hashed with `hasher`.
+	///
+	/// Return `true` if signature over `msg` is valid for this id.
+	fn verify_with_hasher(
+		&self,
+		signature: &<Self as RuntimeAppPublic>::Signature,
+		msg: &[u8],
+		hasher: SignatureHasher,
+	) -> bool;
+}
+
+/// The hash function BEEFY messages are hashed with before being signed.
+///
+/// It is declared by the runtime through [`BeefyApi::signature_hasher`], so that bridges to chains
+/// without cheap Keccak can use a different one.
+#[derive(Clone, Copy, Debug, Default, Decode, Encode, PartialEq, Eq, TypeInfo)]
+pub enum SignatureHasher {
+	/// Keccak-256, cheap on EVM chains.
+	#[default]
+	Keccak256,
+	/// Blake2b-256.
+	Blake2_256,
+	/// SHA2-256.
+	Sha2_256,
+}
+
+impl SignatureHasher {
+	/// Hash `msg` with this hash function.
+	pub fn hash(&self, msg: &[u8]) -> [u8; 32] {
+		match self {
+			Self::Keccak256 => sp_io::hashing::keccak_256(msg),
+			Self::Blake2_256 => sp_io::hashing::blake2_256(msg),
+			Self::Sha2_256 => sp_io::hashing::sha2_256(msg),
+		}
+	}
+}
+
 /// BEEFY cryptographic types
 ///
 /// This module basically introduces three crypto types:
@@ -76,7 +118,9 @@ pub trait BeefyAuthorityId<MsgHash: Hash>: RuntimeAppPublic {
 /// The current underlying crypto scheme used is ECDSA. This can be changed,
 /// without affecting code restricted against the above listed crypto types.
 pub mod crypto {
-	use super::{BeefyAuthorityId, Hash, RuntimeAppPublic};
+	use super::{
+		BeefyAuthorityId, BeefyAuthorityIdWithHasher, Hash, RuntimeAppPublic, SignatureHasher,
+	};
 	use sp_application_crypto::{app_crypto, ecdsa};
 	use sp_core::crypto::Wraps;
 	app_crypto!(ecdsa, crate::KEY_TYPE);
@@ -93,9 +137,26 @@ pub mod crypto {
 	{
 		fn verify(&self, signature: &<Self as RuntimeAppPublic>::Signature, msg: &[u8]) -> bool {
 			let msg_hash = <MsgHash as Hash>::hash(msg).into();
+			self.verify_prehashed(signature, &msg_hash)
+		}
+	}
+
+	impl BeefyAuthorityIdWithHasher for AuthorityId {
+		fn verify_with_hasher(
+			&self,
+			signature: &Signature,
+			msg: &[u8],
+			hasher: SignatureHasher,
+		) -> bool {
+			self.verify_prehashed(signature, &hasher.hash(msg))
+		}
+	}
+
+	impl AuthorityId {
+		fn verify_prehashed(&self, signature: &Signature, msg_hash: &[u8; 32]) -> bool {
 			match sp_io::crypto::secp256k1_ecdsa_recover_compressed(
 				signature.as_inner_ref().as_ref(),
-				&msg_hash,
+				msg_hash,
 			) {
 				Ok(raw_pubkey) => raw_pubkey.as_ref() == AsRef::<[u8]>::as_ref(self),
 				_ => false,
@@ -104,9 +165,37 @@ pub mod crypto {
 	}
 }
 
+/// BEEFY cryptographic types for BLS crypto
+///
+/// This module basically introduces three crypto types:
+/// - `bls_crypto::Pair`
+/// - `bls_crypto::Public`
+/// - `bls_crypto::Signature`
+///
+/// These are the types of the secondary crypto scheme while migrating BEEFY from ECDSA to BLS,
+/// see [`BeefyApi::secondary_validator_set`].
+#[cfg(feature = "bls-experimental")]
+pub mod bls_crypto {
+	use sp_application_crypto::{app_crypto, bls377};
+	app_crypto!(bls377, crate::KEY_TYPE);
+
+	/// Identity of a BEEFY authority using BLS as its crypto.
+	pub type AuthorityId = Public;
+
+	/// Signature for a BEEFY authority using BLS as its crypto.
+	pub type AuthoritySignature = Signature;
+}
+
 /// The `ConsensusEngineId` of BEEFY.
 pub const BEEFY_ENGINE_ID: sp_runtime::ConsensusEngineId = *b"BEEF";
 
+/// The `ConsensusEngineId` of the BEEFY justifications signed with the secondary crypto scheme,
+/// while migrating between crypto schemes.
+///
+/// These justifications are encoded as [`VersionedFinalityProof`]s of the secondary signature
+/// type, and stored next to the regular [`BEEFY_ENGINE_ID`] ones.
+pub const BEEFY_SECONDARY_ENGINE_ID: sp_runtime::ConsensusEngineId = *b"BEFS";
+
 /// Authority set id starts with zero at BEEFY pallet genesis.
 pub const GENESIS_AUTHORITY_SET_ID: u64 = 0;
 
@@ -173,6 +262,10 @@ pub enum ConsensusLog<AuthorityId: Codec> {
 	/// MMR root hash.
 	#[codec(index = 3)]
 	MmrRoot(MmrRootHash),
+	/// The hash function of the signatures of the authorities announced along with this log, if
+	/// it is not [`SignatureHasher::Keccak256`].
+	#[codec(index = 4)]
+	SignatureHasher(SignatureHasher),
 }
 
 /// BEEFY vote message.
@@ -240,6 +333,33 @@ where
 	Id: BeefyAuthorityId<MsgHash> + PartialEq,
 	Number: Clone + Encode + PartialEq,
 	MsgHash: Hash,
+{
+	let check = |vote: &VoteMessage<Number, Id, <Id as RuntimeAppPublic>::Signature>| {
+		check_commitment_signature::<_, _, MsgHash>(&vote.commitment, &vote.id, &vote.signature)
+	};
+	is_equivocation(report) && check(&report.first) && check(&report.second)
+}
+
+/// Like [`check_equivocation_proof`], for votes signed over commitments hashed with `hasher`.
+pub fn check_equivocation_proof_with_hasher<Number, Id>(
+	report: &EquivocationProof<Number, Id, <Id as RuntimeAppPublic>::Signature>,
+	hasher: SignatureHasher,
+) -> bool
+where
+	Id: BeefyAuthorityIdWithHasher + PartialEq,
+	Number: Clone + Encode + PartialEq,
+{
+	let check = |vote: &VoteMessage<Number, Id, <Id as RuntimeAppPublic>::Signature>| {
+		vote.id.verify_with_hasher(&vote.signature, &vote.commitment.encode(), hasher)
+	};
+	is_equivocation(report) && check(&report.first) && check(&report.second)
+}
+
+/// Check that both votes of `report` come from the same authority and conflict in the same round.
+fn is_equivocation<Number, Id, Signature>(report: &EquivocationProof<Number, Id, Signature>) -> bool
+where
+	Id: PartialEq,
+	Number: PartialEq,
 {
 	let first = &report.first;
 	let second = &report.second;
@@ -258,12 +378,7 @@ where
 		return false
 	}
 
-	// check signatures on both votes are valid
-	let valid_first = check_commitment_signature(&first.commitment, &first.id, &first.signature);
-	let valid_second =
-		check_commitment_signature(&second.commitment, &second.id, &second.signature);
-
-	return valid_first && valid_second
+	true
 }
 
 /// New BEEFY validator set notification hook.
@@ -342,6 +457,19 @@ sp_api::decl_runtime_apis! {
 			set_id: ValidatorSetId,
 			authority_id: crypto::AuthorityId,
 		) -> Option<OpaqueKeyOwnershipProof>;
+
+		/// Return the hash function BEEFY messages are hashed with before being signed.
+		#[api_version(3)]
+		fn signature_hasher() -> SignatureHasher;
+
+		/// Return the current active BEEFY validator set with the keys of the crypto scheme
+		/// BEEFY is migrating to, if a migration is in progress.
+		///
+		/// The public keys are SCALE-encoded and in the same order as the ones of
+		/// [`Self::validator_set`]. Voters sign with both keys during the migration, so that
+		/// light clients of either crypto scheme can follow BEEFY.
+		#[api_version(4)]
+		fn secondary_validator_set() -> Option<ValidatorSet<Vec<u8>>>;
 	}
 }
 
@@ -349,7 +477,7 @@ sp_api::decl_runtime_apis! {
 mod tests {
 	use super::*;
 	use sp_application_crypto::ecdsa::{self, Public};
-	use sp_core::{blake2_256, crypto::Wraps, keccak_256, Pair};
+	use sp_core::{blake2_256, crypto::Wraps, hashing::sha2_256, keccak_256, Pair};
 	use sp_runtime::traits::{BlakeTwo256, Keccak256};
 
 	#[test]
@@ -404,4 +532,32 @@ mod tests {
 			msg,
 		));
 	}
+
+	#[test]
+	fn verify_with_hasher_works() {
+		let msg = &b"test-message"[..];
+		let (pair, _) = crypto::Pair::generate();
+		let sign = |msg_hash: [u8; 32]| -> crypto::Signature {
+			pair.as_inner_ref().sign_prehashed(&msg_hash).into()
+		};
+
+		let hashers = [
+			(SignatureHasher::Keccak256, sign(keccak_256(msg))),
+			(SignatureHasher::Blake2_256, sign(blake2_256(msg))),
+			(SignatureHasher::Sha2_256, sign(sha2_256(msg))),
+		];
+		for (hasher, signature) in &hashers {
+			for (other, other_signature) in &hashers {
+				assert_eq!(
+					pair.public().verify_with_hasher(other_signature, msg, *hasher),
+					hasher == other
+				);
+			}
+			assert!(!pair.public().verify_with_hasher(signature, b"other message", *hasher));
+		}
+
+		// Keccak256 is the default, matching `BeefyAuthorityId<Keccak256>`.
+		assert_eq!(SignatureHasher::default(), SignatureHasher::Keccak256);
+		assert!(BeefyAuthorityId::<Keccak256>::verify(&pair.public(), &hashers[0].1, msg));
+	}
 }
@@ -72,12 +72,81 @@ impl Payload {
 		self.0.sort_by_key(|(id, _)| *id);
 		self
 	}
+
+	/// Merge entries of `other` into this payload.
+	///
+	/// Entries whose [`BeefyPayloadId`] is already present in `self` are ignored, so the
+	/// existing value always takes precedence. The resulting entries stay sorted by id.
+	///
+	/// Returns self to allow for daisy chaining.
+	pub fn merge(mut self, other: Payload) -> Self {
+		for (id, value) in other.0 {
+			if let Err(index) = self.0.binary_search_by(|probe| probe.0.cmp(&id)) {
+				self.0.insert(index, (id, value));
+			}
+		}
+		self
+	}
 }
 
 /// Trait for custom BEEFY payload providers.
 pub trait PayloadProvider<B: Block> {
 	/// Provide BEEFY payload if available for `header`.
 	fn payload(&self, header: &B::Header) -> Option<Payload>;
+
+	/// Extend the payload of this provider with the entries contributed by `extra`.
+	///
+	/// See [`ChainedPayloadProvider`] for details.
+	fn chain<E>(self, extra: E) -> ChainedPayloadProvider<Self, E>
+	where
+		Self: Sized,
+		E: PayloadProvider<B>,
+	{
+		ChainedPayloadProvider::new(self, extra)
+	}
+}
+
+/// A [`PayloadProvider`] extending the payload of a `primary` provider with the entries of an
+/// `extra` one.
+///
+/// No payload is provided unless the `primary` provider has one for the given header, while a
+/// missing `extra` payload leaves the `primary` one untouched. On conflicting
+/// [`BeefyPayloadId`]s the `primary` entry wins. Entries are always ordered by payload id,
+/// regardless of the order in which providers are chained.
+///
+/// Providers can be chained repeatedly to contribute any number of payload entries:
+///
+/// ```ignore
+/// let payload_provider = MmrRootProvider::new(client.clone())
+/// 	.chain(StateRootProvider::new(client.clone()))
+/// 	.chain(MyBridgeRootProvider::new(client));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChainedPayloadProvider<P, E> {
+	primary: P,
+	extra: E,
+}
+
+impl<P, E> ChainedPayloadProvider<P, E> {
+	/// Create a new provider extending the payload of `primary` with the one of `extra`.
+	pub fn new(primary: P, extra: E) -> Self {
+		Self { primary, extra }
+	}
+}
+
+impl<B, P, E> PayloadProvider<B> for ChainedPayloadProvider<P, E>
+where
+	B: Block,
+	P: PayloadProvider<B>,
+	E: PayloadProvider<B>,
+{
+	fn payload(&self, header: &B::Header) -> Option<Payload> {
+		let payload = self.primary.payload(header)?;
+		match self.extra.payload(header) {
+			Some(extra) => Some(payload.merge(extra)),
+			None => Some(payload),
+		}
+	}
 }
 
 #[cfg(test)]
@@ -102,4 +171,48 @@ mod tests {
 		assert_eq!(payload.get_raw(&id3), Some(&msg3.encode()));
 		assert_eq!(payload.get_raw(&known_payloads::MMR_ROOT_ID), None);
 	}
+
+	#[test]
+	fn payload_merge_keeps_existing_entries_and_order() {
+		let payload = Payload::from_single_entry(*b"mh", vec![1]).push_raw(*b"xy", vec![2]);
+		let other = Payload::from_single_entry(*b"ab", vec![3]).push_raw(*b"mh", vec![4]);
+
+		let merged = payload.merge(other);
+		assert_eq!(merged, Payload(vec![(*b"ab", vec![3]), (*b"mh", vec![1]), (*b"xy", vec![2])]));
+	}
+
+	#[test]
+	fn chained_payload_providers_work() {
+		use sp_runtime::testing::{Block as TestBlock, Header, TestXt};
+
+		type Block = TestBlock<TestXt<(), ()>>;
+
+		struct Fixed(Option<Payload>);
+		impl PayloadProvider<Block> for Fixed {
+			fn payload(&self, _header: &Header) -> Option<Payload> {
+				self.0.clone()
+			}
+		}
+
+		let header = Header::new_from_number(1);
+		let mmr = || Fixed(Some(Payload::from_single_entry(known_payloads::MMR_ROOT_ID, vec![1])));
+		let extra = || Fixed(Some(Payload::from_single_entry(*b"ab", vec![2])));
+		let other = || Fixed(Some(Payload::from_single_entry(*b"zz", vec![3])));
+
+		// entries are sorted by id regardless of chaining order
+		let expected = Payload::from_single_entry(*b"ab", vec![2])
+			.push_raw(known_payloads::MMR_ROOT_ID, vec![1])
+			.push_raw(*b"zz", vec![3]);
+		assert_eq!(mmr().chain(other()).chain(extra()).payload(&header), Some(expected.clone()));
+		assert_eq!(mmr().chain(extra().chain(other())).payload(&header), Some(expected));
+
+		// missing extra payload leaves primary payload untouched
+		assert_eq!(
+			mmr().chain(Fixed(None)).payload(&header),
+			Some(Payload::from_single_entry(known_payloads::MMR_ROOT_ID, vec![1]))
+		);
+
+		// missing primary payload means no payload at all
+		assert_eq!(Fixed(None).chain(extra()).payload(&header), None);
+	}
 }
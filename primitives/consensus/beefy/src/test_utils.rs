@@ -17,9 +17,13 @@
 
 #![cfg(feature = "std")]
 
-use crate::{crypto, Commitment, EquivocationProof, Payload, ValidatorSetId, VoteMessage};
+use crate::{
+	crypto, Commitment, EquivocationProof, Payload, SignatureHasher, ValidatorSet, ValidatorSetId,
+	VoteMessage, KEY_TYPE,
+};
 use codec::Encode;
 use sp_core::{ecdsa, keccak_256, Pair};
+use sp_keystore::{Error as KeystoreError, Keystore};
 use std::collections::HashMap;
 use strum::IntoEnumIterator;
 
@@ -40,9 +44,12 @@ pub enum Keyring {
 impl Keyring {
 	/// Sign `msg`.
 	pub fn sign(self, msg: &[u8]) -> crypto::Signature {
-		// todo: use custom signature hashing type
-		let msg = keccak_256(msg);
-		ecdsa::Pair::from(self).sign_prehashed(&msg).into()
+		self.sign_with_hasher(msg, SignatureHasher::Keccak256)
+	}
+
+	/// Sign `msg` hashed with `hasher`.
+	pub fn sign_with_hasher(self, msg: &[u8], hasher: SignatureHasher) -> crypto::Signature {
+		ecdsa::Pair::from(self).sign_prehashed(&hasher.hash(msg)).into()
 	}
 
 	/// Return key pair.
@@ -91,20 +98,161 @@ impl From<Keyring> for crypto::Public {
 	}
 }
 
+/// Any number of deterministic test authorities using [`crate::crypto`] types, for validator sets
+/// larger than [`Keyring`].
+///
+/// Authority `index` is derived from the seed `{path}//{index}`, e.g. `//Validator//42` for the
+/// default path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestAuthorities {
+	path: String,
+}
+
+impl Default for TestAuthorities {
+	fn default() -> Self {
+		Self::new("//Validator")
+	}
+}
+
+impl TestAuthorities {
+	/// Create test authorities derived from `path`.
+	pub fn new(path: impl Into<String>) -> Self {
+		Self { path: path.into() }
+	}
+
+	/// Return seed string of authority `index`.
+	pub fn to_seed(&self, index: u32) -> String {
+		format!("{}//{}", self.path, index)
+	}
+
+	/// Return key pair of authority `index`.
+	pub fn pair(&self, index: u32) -> crypto::Pair {
+		ecdsa::Pair::from_string(&self.to_seed(index), None).unwrap().into()
+	}
+
+	/// Return public key of authority `index`.
+	pub fn public(&self, index: u32) -> crypto::Public {
+		self.pair(index).public()
+	}
+
+	/// Sign `msg` with authority `index`, the same way as [`Keyring::sign`].
+	pub fn sign(&self, index: u32, msg: &[u8]) -> crypto::Signature {
+		let msg = keccak_256(msg);
+		ecdsa::Pair::from(self.pair(index)).sign_prehashed(&msg).into()
+	}
+
+	/// Return public keys of the first `count` authorities.
+	pub fn publics(&self, count: u32) -> Vec<crypto::Public> {
+		(0..count).map(|index| self.public(index)).collect()
+	}
+
+	/// Return a validator set with id `id` of the first `count` authorities.
+	///
+	/// Panics if `count` is zero.
+	pub fn validator_set(&self, count: u32, id: ValidatorSetId) -> ValidatorSet<crypto::Public> {
+		ValidatorSet::new(self.publics(count), id).expect("validator set must not be empty")
+	}
+
+	/// Insert the BEEFY keys of authorities `indices` into `keystore`, e.g. a
+	/// `sp_keystore::testing::MemoryKeystore`, and return their public keys.
+	pub fn populate_keystore(
+		&self,
+		keystore: &dyn Keystore,
+		indices: impl IntoIterator<Item = u32>,
+	) -> Result<Vec<crypto::Public>, KeystoreError> {
+		indices
+			.into_iter()
+			.map(|index| {
+				keystore
+					.ecdsa_generate_new(KEY_TYPE, Some(&self.to_seed(index)))
+					.map(Into::into)
+			})
+			.collect()
+	}
+}
+
 /// Create a new `EquivocationProof` based on given arguments.
 pub fn generate_equivocation_proof(
 	vote1: (u64, Payload, ValidatorSetId, &Keyring),
 	vote2: (u64, Payload, ValidatorSetId, &Keyring),
+) -> EquivocationProof<u64, crypto::Public, crypto::Signature> {
+	generate_equivocation_proof_with_hasher(vote1, vote2, SignatureHasher::Keccak256)
+}
+
+/// Create a new `EquivocationProof` based on given arguments, with votes signed over commitments
+/// hashed with `hasher`.
+pub fn generate_equivocation_proof_with_hasher(
+	vote1: (u64, Payload, ValidatorSetId, &Keyring),
+	vote2: (u64, Payload, ValidatorSetId, &Keyring),
+	hasher: SignatureHasher,
 ) -> EquivocationProof<u64, crypto::Public, crypto::Signature> {
 	let signed_vote = |block_number: u64,
 	                   payload: Payload,
 	                   validator_set_id: ValidatorSetId,
 	                   keyring: &Keyring| {
 		let commitment = Commitment { validator_set_id, block_number, payload };
-		let signature = keyring.sign(&commitment.encode());
+		let signature = keyring.sign_with_hasher(&commitment.encode(), hasher);
 		VoteMessage { commitment, id: keyring.public(), signature }
 	};
 	let first = signed_vote(vote1.0, vote1.1, vote1.2, vote1.3);
 	let second = signed_vote(vote2.0, vote2.1, vote2.2, vote2.3);
 	EquivocationProof { first, second }
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::BeefyAuthorityId;
+	use sp_keystore::testing::MemoryKeystore;
+	use sp_runtime::traits::Keccak256;
+
+	#[test]
+	fn test_authorities_are_deterministic() {
+		let authorities = TestAuthorities::default();
+		assert_eq!(authorities.to_seed(42), "//Validator//42");
+		assert_eq!(authorities.public(42), authorities.public(42));
+		assert_eq!(
+			authorities.public(42),
+			crypto::Pair::from_string("//Validator//42", None).unwrap().public()
+		);
+
+		let publics = authorities.publics(100);
+		assert_eq!(publics.iter().collect::<std::collections::HashSet<_>>().len(), 100);
+		assert_eq!(authorities.validator_set(100, 7), ValidatorSet::new(publics, 7).unwrap());
+
+		// other paths give other keys.
+		let other = TestAuthorities::new("//Other");
+		assert_ne!(other.public(0), authorities.public(0));
+		assert!(!authorities.publics(10).contains(&other.public(0)));
+
+		let msg = b"I am validator 7";
+		let signature = authorities.sign(7, msg);
+		assert!(BeefyAuthorityId::<Keccak256>::verify(&authorities.public(7), &signature, msg));
+		assert!(!BeefyAuthorityId::<Keccak256>::verify(&authorities.public(8), &signature, msg));
+	}
+
+	#[test]
+	fn test_authorities_populate_keystore() {
+		let authorities = TestAuthorities::default();
+		let keystore = MemoryKeystore::new();
+
+		let publics = authorities.populate_keystore(&keystore, 3..6).unwrap();
+		assert_eq!(publics, (3..6).map(|index| authorities.public(index)).collect::<Vec<_>>());
+
+		let mut stored: Vec<crypto::Public> =
+			keystore.ecdsa_public_keys(KEY_TYPE).into_iter().map(Into::into).collect();
+		stored.sort();
+		let mut publics = publics;
+		publics.sort();
+		assert_eq!(stored, publics);
+
+		// signatures of keystore keys match the ones of the test authorities.
+		let msg = keccak_256(b"I am validator 4");
+		let signature: crypto::Signature = keystore
+			.ecdsa_sign_prehashed(KEY_TYPE, authorities.public(4).as_ref(), &msg)
+			.unwrap()
+			.unwrap()
+			.into();
+		assert_eq!(signature, authorities.sign(4, b"I am validator 4"));
+	}
+}
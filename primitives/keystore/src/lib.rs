@@ -174,6 +174,26 @@ pub trait Keystore: Send + Sync {
 		msg: &[u8; 32],
 	) -> Result<Option<ecdsa::Signature>, Error>;
 
+	/// Generate ecdsa signatures for a batch of pre-hashed messages, all signed with the same key.
+	///
+	/// Returns the signatures in the order of `msgs`, or `None` in case the given `key_type`
+	/// and `public` combination doesn't exist in the keystore.
+	/// An `Err` will be returned if generating any of the signatures failed.
+	///
+	/// The default implementation calls [`Self::ecdsa_sign_prehashed`] for every message.
+	/// Implementations for which every call is costly, e.g. because of a network round-trip,
+	/// should overwrite it.
+	fn ecdsa_sign_prehashed_batch(
+		&self,
+		key_type: KeyTypeId,
+		public: &ecdsa::Public,
+		msgs: &[[u8; 32]],
+	) -> Result<Option<Vec<ecdsa::Signature>>, Error> {
+		msgs.iter()
+			.map(|msg| self.ecdsa_sign_prehashed(key_type, public, msg))
+			.collect::<Result<Option<Vec<_>>, _>>()
+	}
+
 	#[cfg(feature = "bls-experimental")]
 	/// Returns all bls12-381 public keys for the given key type.
 	fn bls381_public_keys(&self, id: KeyTypeId) -> Vec<bls381::Public>;
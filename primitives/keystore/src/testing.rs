@@ -406,4 +406,27 @@ mod tests {
 		let res = store.ecdsa_sign_prehashed(ECDSA, &pair.public(), &msg).unwrap();
 		assert!(res.is_some());
 	}
+
+	#[test]
+	fn ecdsa_sign_prehashed_batch_works() {
+		let store = MemoryKeystore::new();
+
+		let suri = "//Alice";
+		let pair = ecdsa::Pair::from_string(suri, None).unwrap();
+
+		let msgs = [sp_core::keccak_256(b"first message"), sp_core::keccak_256(b"second message")];
+
+		// no key in key store
+		let res = store.ecdsa_sign_prehashed_batch(ECDSA, &pair.public(), &msgs).unwrap();
+		assert!(res.is_none());
+
+		// insert key, sign again
+		store.insert(ECDSA, suri, pair.public().as_ref()).unwrap();
+
+		let res = store.ecdsa_sign_prehashed_batch(ECDSA, &pair.public(), &msgs).unwrap().unwrap();
+		assert_eq!(res.len(), 2);
+		for (sig, msg) in res.iter().zip(msgs.iter()) {
+			assert!(ecdsa::Pair::verify_prehashed(sig, msg, &pair.public()));
+		}
+	}
 }
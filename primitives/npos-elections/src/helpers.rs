@@ -17,8 +17,15 @@
 
 //! Helper methods for npos-elections.
 
-use crate::{Assignment, Error, IdentifierT, PerThing128, StakedAssignment, VoteWeight};
-use sp_arithmetic::PerThing;
+use crate::{
+	Assignment, Error, ExtendedBalance, IdentifierT, PerThing128, StakedAssignment, Support,
+	VoteWeight,
+};
+use sp_arithmetic::{
+	helpers_128bit::multiply_by_rational_with_rounding,
+	traits::{BaseArithmetic, Zero},
+	PerThing, Rounding,
+};
 use sp_std::prelude::*;
 
 /// Converts a vector of ratio assignments into ones with absolute budget value.
@@ -75,6 +82,119 @@ pub fn assignment_staked_to_ratio_normalized<A: IdentifierT, P: PerThing128>(
 	Ok(ratio)
 }
 
+/// Sum up the given stakes, returning `None` if the sum overflows `T`.
+pub fn checked_sum_stakes<T: BaseArithmetic + Copy>(
+	stakes: impl IntoIterator<Item = T>,
+) -> Option<T> {
+	stakes.into_iter().try_fold(T::zero(), |acc, stake| acc.checked_add(&stake))
+}
+
+/// Sum up the given stakes, saturating at the maximum value of `T`.
+pub fn saturating_sum_stakes<T: BaseArithmetic + Copy>(stakes: impl IntoIterator<Item = T>) -> T {
+	stakes.into_iter().fold(T::zero(), |acc, stake| acc.saturating_add(stake))
+}
+
+/// The total backing stake of all the given supports, or `None` if it overflows.
+pub fn checked_total_support<A>(supports: &[(A, Support<A>)]) -> Option<ExtendedBalance> {
+	checked_sum_stakes(supports.iter().map(|(_, support)| support.total))
+}
+
+/// The total backing stake of all the given supports, saturating at [`ExtendedBalance::MAX`].
+pub fn saturating_total_support<A>(supports: &[(A, Support<A>)]) -> ExtendedBalance {
+	saturating_sum_stakes(supports.iter().map(|(_, support)| support.total))
+}
+
+/// Hand out `dust` over the entries of `distribution`, in a deterministic way.
+///
+/// Every entry receives an equal share of `dust`. The remainder of that division is then handed
+/// out one unit at a time, to the entries with the largest values first. Ties are broken by the
+/// position of the entry. Entries saturate at [`ExtendedBalance::MAX`].
+pub fn distribute_dust<A>(distribution: &mut [(A, ExtendedBalance)], dust: ExtendedBalance) {
+	if distribution.is_empty() || dust.is_zero() {
+		return
+	}
+
+	let count = distribution.len() as ExtendedBalance;
+	let per_entry = dust / count;
+	let mut leftover = dust % count;
+
+	// stable sort, so that ties keep their original order.
+	let mut order = (0..distribution.len()).collect::<Vec<_>>();
+	order.sort_by_key(|i| sp_std::cmp::Reverse(distribution[*i].1));
+
+	for i in order {
+		let mut bump = per_entry;
+		if !leftover.is_zero() {
+			bump += 1;
+			leftover -= 1;
+		}
+		distribution[i].1 = distribution[i].1.saturating_add(bump);
+	}
+}
+
+/// Rescale `distribution` such that it sums up to exactly `target`.
+///
+/// The proportions of the entries are preserved as far as the precision allows. Each entry is
+/// rounded down, and the rounding dust is handed out via [`distribute_dust`]. If all entries are
+/// zero, `target` is split evenly among them.
+///
+/// Returns an error if `distribution` is empty while `target` is not zero, or if the sum of
+/// `distribution` overflows.
+pub fn normalize_distribution<A>(
+	distribution: &mut [(A, ExtendedBalance)],
+	target: ExtendedBalance,
+) -> Result<(), Error> {
+	if distribution.is_empty() {
+		return if target.is_zero() {
+			Ok(())
+		} else {
+			Err(Error::ArithmeticError("cannot normalize an empty distribution"))
+		}
+	}
+
+	let sum = checked_sum_stakes(distribution.iter().map(|(_, weight)| *weight))
+		.ok_or(Error::ArithmeticError("sum of distribution cannot fit in `ExtendedBalance`"))?;
+	if sum == target {
+		return Ok(())
+	}
+
+	if !sum.is_zero() {
+		for (_, weight) in distribution.iter_mut() {
+			// `weight <= sum`, thus this can never exceed `target`.
+			*weight = multiply_by_rational_with_rounding(*weight, target, sum, Rounding::Down)
+				.ok_or(Error::ArithmeticError("failed to rescale distribution"))?;
+		}
+	}
+
+	// every entry was rounded down, thus this is at most `distribution.len() - 1`, or `target` if
+	// all entries were zero.
+	let distributed = saturating_sum_stakes(distribution.iter().map(|(_, weight)| *weight));
+	distribute_dust(distribution, target.saturating_sub(distributed));
+	Ok(())
+}
+
+/// Same as [`normalize_distribution`], but for a distribution of ratios that should sum up to
+/// exactly [`PerThing::one`].
+pub fn normalize_ratio_distribution<A, P: PerThing>(
+	distribution: &mut [(A, P)],
+) -> Result<(), Error> {
+	let mut parts = distribution
+		.iter()
+		.map(|(_, p)| ((), p.deconstruct().into()))
+		.collect::<Vec<((), ExtendedBalance)>>();
+	normalize_distribution(&mut parts, P::ACCURACY.into())?;
+
+	for ((_, p), (_, normalized)) in distribution.iter_mut().zip(parts) {
+		// `normalized <= P::ACCURACY`, thus this can never fail.
+		*p = P::from_parts(
+			normalized
+				.try_into()
+				.map_err(|_| Error::ArithmeticError("normalized ratio cannot fit in `P`"))?,
+		);
+	}
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -110,4 +230,133 @@ mod tests {
 			]
 		);
 	}
+
+	#[test]
+	fn sum_stakes_works() {
+		assert_eq!(checked_sum_stakes(vec![1u64, 2, 3]), Some(6));
+		assert_eq!(checked_sum_stakes(Vec::<u64>::new()), Some(0));
+		assert_eq!(checked_sum_stakes(vec![u64::MAX, 1]), None);
+
+		assert_eq!(saturating_sum_stakes(vec![1u64, 2, 3]), 6);
+		assert_eq!(saturating_sum_stakes(vec![u64::MAX, 1]), u64::MAX);
+
+		let supports = vec![
+			(10u32, Support { total: 5, voters: vec![(1, 5)] }),
+			(20u32, Support { total: ExtendedBalance::MAX, voters: vec![] }),
+		];
+		assert_eq!(checked_total_support(&supports[..1]), Some(5));
+		assert_eq!(checked_total_support(&supports), None);
+		assert_eq!(saturating_total_support(&supports), ExtendedBalance::MAX);
+	}
+
+	#[test]
+	fn distribute_dust_is_deterministic() {
+		let mut distribution = vec![(1u32, 10), (2, 30), (3, 30), (4, 0)];
+		distribute_dust(&mut distribution, 6);
+		// everyone gets one, then the two largest (in order) get one more each.
+		assert_eq!(distribution, vec![(1, 11), (2, 32), (3, 32), (4, 1)]);
+
+		let mut distribution = vec![(1u32, 0), (2, 0), (3, 0)];
+		distribute_dust(&mut distribution, 2);
+		assert_eq!(distribution, vec![(1, 1), (2, 1), (3, 0)]);
+
+		let mut distribution = vec![(1u32, ExtendedBalance::MAX)];
+		distribute_dust(&mut distribution, 1);
+		assert_eq!(distribution, vec![(1, ExtendedBalance::MAX)]);
+	}
+
+	#[test]
+	fn normalize_distribution_works() {
+		// rounding up.
+		let mut distribution = vec![(1u32, 33), (2, 33), (3, 33)];
+		normalize_distribution(&mut distribution, 100).unwrap();
+		assert_eq!(distribution, vec![(1, 34), (2, 33), (3, 33)]);
+
+		// rounding down.
+		let mut distribution = vec![(1u32, 50), (2, 30), (3, 21)];
+		normalize_distribution(&mut distribution, 100).unwrap();
+		assert_eq!(distribution, vec![(1, 50), (2, 30), (3, 20)]);
+
+		// rescaling.
+		let mut distribution = vec![(1u32, 1), (2, 3)];
+		normalize_distribution(&mut distribution, 1000).unwrap();
+		assert_eq!(distribution, vec![(1, 250), (2, 750)]);
+
+		// values close to the limit don't overflow.
+		let max = ExtendedBalance::MAX;
+		let mut distribution = vec![(1u32, max / 2), (2, max / 2)];
+		normalize_distribution(&mut distribution, max).unwrap();
+		assert_eq!(distribution, vec![(1, max / 2 + 1), (2, max / 2)]);
+
+		// sum overflows.
+		let mut distribution = vec![(1u32, max), (2, 1)];
+		assert!(normalize_distribution(&mut distribution, max).is_err());
+
+		// empty.
+		assert!(normalize_distribution::<u32>(&mut [], 0).is_ok());
+		assert!(normalize_distribution::<u32>(&mut [], 1).is_err());
+	}
+
+	#[test]
+	fn normalize_distribution_less_than_one_unit() {
+		// the corner case of a distribution whose entries all sum up to less than one unit.
+		let mut distribution = vec![(1u32, 0), (2, 0), (3, 0)];
+		normalize_distribution(&mut distribution, 100).unwrap();
+		assert_eq!(distribution, vec![(1, 34), (2, 33), (3, 33)]);
+
+		let mut distribution = vec![(1u32, 0), (2, 0), (3, 0)];
+		normalize_distribution(&mut distribution, 1).unwrap();
+		assert_eq!(distribution, vec![(1, 1), (2, 0), (3, 0)]);
+
+		let mut distribution = vec![(1u32, Perbill::zero()), (2, Perbill::zero())];
+		normalize_ratio_distribution(&mut distribution).unwrap();
+		assert_eq!(
+			distribution,
+			vec![(1, Perbill::from_percent(50)), (2, Perbill::from_percent(50))]
+		);
+	}
+
+	#[test]
+	fn normalize_ratio_distribution_works() {
+		let mut distribution = vec![(1u32, Perbill::from_parts(333_333_333)); 3];
+		normalize_ratio_distribution(&mut distribution).unwrap();
+		assert_eq!(
+			distribution.iter().map(|(_, p)| p.deconstruct()).collect::<Vec<_>>(),
+			vec![333_333_334, 333_333_333, 333_333_333],
+		);
+	}
+
+	#[test]
+	fn normalize_distribution_fuzz() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(42);
+
+		for _ in 0..1_000 {
+			let count = rng.gen_range(1..32);
+			let mut distribution = (0..count)
+				.map(|i| (i, rng.gen_range(0..ExtendedBalance::MAX / 32)))
+				.collect::<Vec<(u32, ExtendedBalance)>>();
+			let target = rng.gen::<ExtendedBalance>();
+			let original = distribution.clone();
+
+			normalize_distribution(&mut distribution, target).unwrap();
+
+			// the sum is exactly the target.
+			assert_eq!(checked_sum_stakes(distribution.iter().map(|(_, w)| *w)), Some(target));
+			// the order of entries is kept.
+			assert!(distribution.iter().zip(original.iter()).all(|((a, _), (b, _))| a == b));
+			// larger entries never end up smaller than smaller ones.
+			for (i, (_, a)) in original.iter().enumerate() {
+				for (j, (_, b)) in original.iter().enumerate() {
+					if a > b {
+						assert!(distribution[i].1 >= distribution[j].1);
+					}
+				}
+			}
+			// normalizing is idempotent.
+			let normalized = distribution.clone();
+			normalize_distribution(&mut distribution, target).unwrap();
+			assert_eq!(distribution, normalized);
+		}
+	}
 }
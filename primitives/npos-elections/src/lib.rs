@@ -98,6 +98,7 @@ pub mod phragmen;
 pub mod phragmms;
 pub mod pjr;
 pub mod reduce;
+pub mod trace;
 pub mod traits;
 
 pub use assignments::{Assignment, StakedAssignment};
@@ -106,7 +107,9 @@ pub use helpers::*;
 pub use phragmen::*;
 pub use phragmms::*;
 pub use pjr::*;
-pub use reduce::reduce;
+#[cfg(feature = "std")]
+pub use reduce::reduce_chunked_parallel;
+pub use reduce::{reduce, reduce_chunked};
 pub use traits::{IdentifierT, PerThing128};
 
 /// The errors that might occur in this crate and `frame-election-provider-solution-type`.
@@ -21,8 +21,8 @@
 //! to the Maximin problem.
 
 use crate::{
-	balancing, setup_inputs, BalancingConfig, CandidatePtr, ElectionResult, ExtendedBalance,
-	IdentifierT, PerThing128, VoteWeight, Voter,
+	balancing, setup_inputs, trace::Tracer, BalancingConfig, CandidatePtr, ElectionResult,
+	ExtendedBalance, IdentifierT, PerThing128, VoteWeight, Voter,
 };
 use sp_arithmetic::{
 	helpers_128bit::multiply_by_rational_with_rounding,
@@ -72,10 +72,34 @@ pub fn seq_phragmen<AccountId: IdentifierT, P: PerThing128>(
 	candidates: Vec<AccountId>,
 	voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
 	balancing: Option<BalancingConfig>,
+) -> Result<ElectionResult<AccountId, P>, crate::Error> {
+	seq_phragmen_with(to_elect, candidates, voters, balancing, &mut ())
+}
+
+/// Same as [`seq_phragmen`], but also returns a [`crate::trace::Trace`] of all the rounds.
+#[cfg(feature = "trace")]
+pub fn seq_phragmen_traced<AccountId: IdentifierT, P: PerThing128>(
+	to_elect: usize,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+	balancing: Option<BalancingConfig>,
+) -> Result<(ElectionResult<AccountId, P>, crate::trace::Trace<AccountId>), crate::Error> {
+	let mut trace = Default::default();
+	seq_phragmen_with(to_elect, candidates, voters, balancing, &mut trace)
+		.map(|result| (result, trace))
+}
+
+fn seq_phragmen_with<AccountId: IdentifierT, P: PerThing128, T: Tracer<AccountId>>(
+	to_elect: usize,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+	balancing: Option<BalancingConfig>,
+	tracer: &mut T,
 ) -> Result<ElectionResult<AccountId, P>, crate::Error> {
 	let (candidates, voters) = setup_inputs(candidates, voters);
 
-	let (candidates, mut voters) = seq_phragmen_core::<AccountId>(to_elect, candidates, voters)?;
+	let (candidates, mut voters) =
+		seq_phragmen_core_with::<AccountId, _>(to_elect, candidates, voters, tracer)?;
 
 	if let Some(ref config) = balancing {
 		// NOTE: might create zero-edges, but we will strip them again when we convert voter into
@@ -115,9 +139,18 @@ pub fn seq_phragmen<AccountId: IdentifierT, P: PerThing128>(
 /// This can only fail if the normalization fails.
 // To create the inputs needed for this function, see [`crate::setup_inputs`].
 pub fn seq_phragmen_core<AccountId: IdentifierT>(
+	to_elect: usize,
+	candidates: Vec<CandidatePtr<AccountId>>,
+	voters: Vec<Voter<AccountId>>,
+) -> Result<(Vec<CandidatePtr<AccountId>>, Vec<Voter<AccountId>>), crate::Error> {
+	seq_phragmen_core_with(to_elect, candidates, voters, &mut ())
+}
+
+fn seq_phragmen_core_with<AccountId: IdentifierT, T: Tracer<AccountId>>(
 	to_elect: usize,
 	candidates: Vec<CandidatePtr<AccountId>>,
 	mut voters: Vec<Voter<AccountId>>,
+	tracer: &mut T,
 ) -> Result<(Vec<CandidatePtr<AccountId>>, Vec<Voter<AccountId>>), crate::Error> {
 	// we have already checked that we have more candidates than minimum_candidate_count.
 	let to_elect = to_elect.min(candidates.len());
@@ -157,6 +190,16 @@ pub fn seq_phragmen_core<AccountId: IdentifierT>(
 			}
 		}
 
+		if T::ENABLED {
+			let scores = candidates
+				.iter()
+				.map(|c| c.borrow())
+				.filter(|c| !c.elected)
+				.map(|c| (c.who.clone(), c.score))
+				.collect();
+			tracer.scores(round, scores);
+		}
+
 		// loop 3: find the best
 		if let Some(winner_ptr) = candidates
 			.iter()
@@ -175,6 +218,23 @@ pub fn seq_phragmen_core<AccountId: IdentifierT>(
 					}
 				}
 			}
+
+			if T::ENABLED {
+				tracer.elected(round, &winner.who, winner.score);
+				// the stake each voter would assign to the winner, were this the last round.
+				for voter in &voters {
+					if let Some(edge) = voter.edges.iter().find(|e| e.who == winner.who) {
+						let stake = multiply_by_rational_with_rounding(
+							voter.budget,
+							edge.load.n(),
+							voter.load.n(),
+							Rounding::Down,
+						)
+						.unwrap_or(Bounded::max_value());
+						tracer.redistributed(round, &voter.who, stake);
+					}
+				}
+			}
 		} else {
 			break
 		}
@@ -22,8 +22,8 @@
 //! MMS algorithm.
 
 use crate::{
-	balance, setup_inputs, BalancingConfig, CandidatePtr, ElectionResult, ExtendedBalance,
-	IdentifierT, PerThing128, VoteWeight, Voter,
+	balance, setup_inputs, trace::Tracer, BalancingConfig, CandidatePtr, ElectionResult,
+	ExtendedBalance, IdentifierT, PerThing128, VoteWeight, Voter,
 };
 use sp_arithmetic::{traits::Bounded, PerThing, Rational128};
 use sp_std::{prelude::*, rc::Rc};
@@ -46,14 +46,57 @@ pub fn phragmms<AccountId: IdentifierT, P: PerThing128>(
 	candidates: Vec<AccountId>,
 	voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
 	balancing: Option<BalancingConfig>,
+) -> Result<ElectionResult<AccountId, P>, crate::Error> {
+	phragmms_with(to_elect, candidates, voters, balancing, &mut ())
+}
+
+/// Same as [`phragmms`], but also returns a [`crate::trace::Trace`] of all the rounds.
+#[cfg(feature = "trace")]
+pub fn phragmms_traced<AccountId: IdentifierT, P: PerThing128>(
+	to_elect: usize,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+	balancing: Option<BalancingConfig>,
+) -> Result<(ElectionResult<AccountId, P>, crate::trace::Trace<AccountId>), crate::Error> {
+	let mut trace = Default::default();
+	phragmms_with(to_elect, candidates, voters, balancing, &mut trace).map(|result| (result, trace))
+}
+
+fn phragmms_with<AccountId: IdentifierT, P: PerThing128, T: Tracer<AccountId>>(
+	to_elect: usize,
+	candidates: Vec<AccountId>,
+	voters: Vec<(AccountId, VoteWeight, impl IntoIterator<Item = AccountId>)>,
+	balancing: Option<BalancingConfig>,
+	tracer: &mut T,
 ) -> Result<ElectionResult<AccountId, P>, crate::Error> {
 	let (candidates, mut voters) = setup_inputs(candidates, voters);
 
 	let mut winners = vec![];
 	for round in 0..to_elect {
 		if let Some(round_winner) = calculate_max_score::<AccountId, P>(&candidates, &voters) {
+			if T::ENABLED {
+				let scores = candidates
+					.iter()
+					.map(|c| c.borrow())
+					.filter(|c| !c.elected)
+					.map(|c| (c.who.clone(), c.score))
+					.collect();
+				tracer.scores(round, scores);
+				let winner = round_winner.borrow();
+				tracer.elected(round, &winner.who, winner.score);
+			}
+
 			apply_elected::<AccountId>(&mut voters, Rc::clone(&round_winner));
 
+			if T::ENABLED {
+				let winner = round_winner.borrow();
+				for voter in &voters {
+					if let Some(edge) = voter.edges.iter().find(|e| e.who == winner.who) {
+						tracer.redistributed(round, &voter.who, edge.weight);
+					}
+				}
+			}
+
 			round_winner.borrow_mut().round = round;
 			round_winner.borrow_mut().elected = true;
 			winners.push(round_winner);
@@ -106,7 +106,7 @@ fn merge<A: IdentifierT>(voter_root_path: Vec<NodeRef<A>>, target_root_path: Vec
 /// result will most likely be corrupt otherwise.
 ///
 /// O(|E_w| ⋅ k).
-fn reduce_4<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32 {
+fn reduce_4<A: IdentifierT>(assignments: &mut [StakedAssignment<A>]) -> u32 {
 	let mut combination_map: Map<A> = Map::new();
 	let mut num_changed: u32 = Zero::zero();
 
@@ -313,7 +313,7 @@ fn reduce_4<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32 {
 /// result will most likely be corrupt otherwise.
 ///
 /// O(|Ew| ⋅ m)
-fn reduce_all<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32 {
+fn reduce_all<A: IdentifierT>(assignments: &mut [StakedAssignment<A>]) -> u32 {
 	let mut num_changed: u32 = Zero::zero();
 	let mut tree: BTreeMap<NodeId<A>, NodeRef<A>> = BTreeMap::new();
 
@@ -658,11 +658,60 @@ fn reduce_all<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32
 ///
 /// O(min{ |Ew| ⋅ k + m3 , |Ew| ⋅ m })
 pub fn reduce<A: IdentifierT>(assignments: &mut Vec<StakedAssignment<A>>) -> u32 where {
+	reduce_slice(assignments)
+}
+
+/// Same as [`reduce`], but operates on a slice of assignments.
+fn reduce_slice<A: IdentifierT>(assignments: &mut [StakedAssignment<A>]) -> u32 {
 	let mut num_changed = reduce_4(assignments);
 	num_changed += reduce_all(assignments);
 	num_changed
 }
 
+/// Same as [`reduce`], but the assignments are split into consecutive chunks of `chunk_size`
+/// voters, each of which is reduced independently.
+///
+/// Since each chunk only contains a subset of the voters, cycles that span multiple chunks are
+/// not removed, thus the outcome is possibly less reduced than that of [`reduce`]. Yet, the backing
+/// of all targets is still preserved. A `chunk_size` of zero is treated as a single chunk.
+///
+/// After each chunk is reduced, `should_continue` is called and if it returns `false`, the
+/// remaining chunks are left untouched. This allows the caller to interleave the reduction with,
+/// for example, deadline checks.
+///
+/// Returns the number of edges removed.
+pub fn reduce_chunked<A: IdentifierT>(
+	assignments: &mut [StakedAssignment<A>],
+	chunk_size: usize,
+	mut should_continue: impl FnMut() -> bool,
+) -> u32 {
+	let chunk_size = if chunk_size.is_zero() { assignments.len().max(1) } else { chunk_size };
+	let mut num_changed: u32 = Zero::zero();
+	for chunk in assignments.chunks_mut(chunk_size) {
+		num_changed = num_changed.saturating_add(reduce_slice(chunk));
+		if !should_continue() {
+			break
+		}
+	}
+	num_changed
+}
+
+/// Same as [`reduce_chunked`], but all the chunks are reduced in parallel.
+///
+/// The outcome is exactly the same as that of [`reduce_chunked`] with the same `chunk_size`.
+#[cfg(feature = "std")]
+pub fn reduce_chunked_parallel<A: IdentifierT + Send>(
+	assignments: &mut [StakedAssignment<A>],
+	chunk_size: usize,
+) -> u32 {
+	use rayon::prelude::*;
+	let chunk_size = if chunk_size.is_zero() { assignments.len().max(1) } else { chunk_size };
+	assignments
+		.par_chunks_mut(chunk_size)
+		.map(reduce_slice)
+		.reduce(Zero::zero, |a, b| a.saturating_add(b))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -920,4 +969,91 @@ mod tests {
 		let num_reduced = reduce_all(&mut assignments);
 		assert!(16 - num_reduced <= n + m);
 	}
+
+	#[test]
+	fn reduce_chunked_preserves_backing() {
+		let assignments = vec![
+			StakedAssignment { who: 1, distribution: vec![(10, 10)] },
+			StakedAssignment { who: 2, distribution: vec![(10, 15), (20, 5)] },
+			StakedAssignment { who: 3, distribution: vec![(20, 15), (40, 15)] },
+			StakedAssignment { who: 4, distribution: vec![(20, 10), (30, 10), (40, 20)] },
+			StakedAssignment { who: 5, distribution: vec![(20, 20), (30, 10), (40, 20)] },
+		];
+		let backing = |assignments: &[StakedAssignment<u32>]| {
+			crate::to_support_map(assignments)
+				.into_iter()
+				.map(|(t, s)| (t, s.total))
+				.collect::<Vec<_>>()
+		};
+
+		// a zero chunk size is the same as a monolithic reduce.
+		let mut chunked = assignments.clone();
+		let mut monolithic = assignments.clone();
+		assert_eq!(reduce_chunked(&mut chunked, 0, || true), reduce(&mut monolithic));
+		assert_eq!(chunked, monolithic);
+
+		// with smaller chunks, less is reduced, but the backing is the same.
+		let mut chunked = assignments.clone();
+		assert_eq!(reduce_chunked(&mut chunked, 3, || true), 2);
+		assert_eq!(backing(&chunked), backing(&assignments));
+		assert_eq!(
+			chunked,
+			vec![
+				StakedAssignment { who: 1, distribution: vec![(10, 10)] },
+				StakedAssignment { who: 2, distribution: vec![(10, 15), (20, 5)] },
+				StakedAssignment { who: 3, distribution: vec![(20, 15), (40, 15)] },
+				StakedAssignment { who: 4, distribution: vec![(20, 30), (40, 10)] },
+				StakedAssignment { who: 5, distribution: vec![(30, 20), (40, 30)] },
+			],
+		);
+	}
+
+	#[test]
+	fn reduce_chunked_can_stop_early() {
+		let assignments = vec![
+			StakedAssignment { who: 1, distribution: vec![(10, 25), (20, 75)] },
+			StakedAssignment { who: 2, distribution: vec![(10, 50), (20, 50)] },
+			StakedAssignment { who: 3, distribution: vec![(10, 25), (20, 75)] },
+			StakedAssignment { who: 4, distribution: vec![(10, 50), (20, 50)] },
+		];
+
+		let mut chunked = assignments.clone();
+		let mut calls = 0;
+		let num_reduced = reduce_chunked(&mut chunked, 2, || {
+			calls += 1;
+			false
+		});
+
+		assert_eq!(calls, 1);
+		assert_eq!(num_reduced, 1);
+		// the second chunk is untouched.
+		assert_eq!(chunked[2..], assignments[2..]);
+		assert_ne!(chunked[..2], assignments[..2]);
+	}
+
+	#[test]
+	fn reduce_chunked_parallel_same_as_sequential() {
+		use rand::{seq::SliceRandom, Rng, SeedableRng};
+		let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+		let targets = (1000..1050).collect::<Vec<u32>>();
+		let assignments = (0..500u32)
+			.map(|who| StakedAssignment {
+				who,
+				distribution: targets
+					.choose_multiple(&mut rng, 8)
+					.map(|t| (*t, rng.gen_range(1..1000)))
+					.collect(),
+			})
+			.collect::<Vec<_>>();
+
+		for chunk_size in [0, 1, 7, 64, 1000] {
+			let mut sequential = assignments.clone();
+			let mut parallel = assignments.clone();
+			assert_eq!(
+				reduce_chunked(&mut sequential, chunk_size, || true),
+				reduce_chunked_parallel(&mut parallel, chunk_size),
+			);
+			assert_eq!(sequential, parallel);
+		}
+	}
 }
@@ -0,0 +1,196 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracing of the rounds of [`crate::seq_phragmen`] and [`crate::phragmms`], for debugging.
+//!
+//! The algorithms report what happens in each round to a [`Tracer`], which is a no-op unless
+//! [`Tracer::ENABLED`]. With the `trace` feature, [`Trace`] records everything into a structured
+//! object, see [`crate::seq_phragmen_traced`] and [`crate::phragmms_traced`].
+
+use crate::ExtendedBalance;
+use sp_arithmetic::Rational128;
+use sp_std::prelude::*;
+
+/// Something that observes the rounds of an election algorithm.
+///
+/// In each round, [`Tracer::scores`] is called first, then [`Tracer::elected`], and then
+/// [`Tracer::redistributed`] for each voter of the elected candidate.
+pub(crate) trait Tracer<AccountId> {
+	/// Whether this tracer does anything. If not, the algorithms skip computing what they report.
+	const ENABLED: bool = false;
+
+	/// The scores of all candidates that were not elected before `round`.
+	fn scores(&mut self, _round: usize, _scores: Vec<(AccountId, Rational128)>) {}
+
+	/// `who` got elected in `round`, with `score`.
+	fn elected(&mut self, _round: usize, _who: &AccountId, _score: Rational128) {}
+
+	/// `voter` assigned `stake` to the candidate elected in `round`.
+	fn redistributed(&mut self, _round: usize, _voter: &AccountId, _stake: ExtendedBalance) {}
+}
+
+impl<AccountId> Tracer<AccountId> for () {}
+
+/// What happened in a single round of an election.
+#[cfg(feature = "trace")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundTrace<AccountId> {
+	/// The index of the round, starting at zero.
+	pub round: usize,
+	/// The score of every candidate that was not elected before this round, including the one that
+	/// got elected. For seq-phragmen lower is better, for phragmms higher is better.
+	pub scores: Vec<(AccountId, Rational128)>,
+	/// The candidate elected in this round.
+	pub elected: AccountId,
+	/// The score of [`Self::elected`].
+	pub elected_score: Rational128,
+	/// The stake that each voter assigned to [`Self::elected`] in this round.
+	///
+	/// For seq-phragmen, this is provisional: it is the stake each voter would assign if the
+	/// election ended at this round. For phragmms, this is before balancing, if any.
+	pub redistribution: Vec<(AccountId, ExtendedBalance)>,
+}
+
+/// A structured trace of all the rounds of an election.
+#[cfg(feature = "trace")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace<AccountId> {
+	/// All rounds, in order.
+	pub rounds: Vec<RoundTrace<AccountId>>,
+	pending_scores: Vec<(AccountId, Rational128)>,
+}
+
+#[cfg(feature = "trace")]
+impl<AccountId> Default for Trace<AccountId> {
+	fn default() -> Self {
+		Self { rounds: vec![], pending_scores: vec![] }
+	}
+}
+
+#[cfg(feature = "trace")]
+impl<AccountId: PartialEq> Trace<AccountId> {
+	/// The round in which `who` got elected, if any.
+	pub fn elected_in(&self, who: &AccountId) -> Option<&RoundTrace<AccountId>> {
+		self.rounds.iter().find(|r| &r.elected == who)
+	}
+
+	/// The score of `who` in each round where it was a candidate, alongside the elected score.
+	///
+	/// This is most often what explains why `who` did not get elected.
+	pub fn scores_of(&self, who: &AccountId) -> Vec<(usize, Rational128, Rational128)> {
+		self.rounds
+			.iter()
+			.filter_map(|r| {
+				r.scores
+					.iter()
+					.find(|(c, _)| c == who)
+					.map(|(_, score)| (r.round, *score, r.elected_score))
+			})
+			.collect()
+	}
+}
+
+#[cfg(feature = "trace")]
+impl<AccountId: Clone> Tracer<AccountId> for Trace<AccountId> {
+	const ENABLED: bool = true;
+
+	fn scores(&mut self, _round: usize, scores: Vec<(AccountId, Rational128)>) {
+		self.pending_scores = scores;
+	}
+
+	fn elected(&mut self, round: usize, who: &AccountId, score: Rational128) {
+		self.rounds.push(RoundTrace {
+			round,
+			scores: sp_std::mem::take(&mut self.pending_scores),
+			elected: who.clone(),
+			elected_score: score,
+			redistribution: vec![],
+		});
+	}
+
+	fn redistributed(&mut self, round: usize, voter: &AccountId, stake: ExtendedBalance) {
+		if let Some(last) = self.rounds.last_mut().filter(|r| r.round == round) {
+			last.redistribution.push((voter.clone(), stake));
+		}
+	}
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+	use super::*;
+	use crate::{phragmms, phragmms_traced, seq_phragmen, seq_phragmen_traced};
+	use sp_arithmetic::Perbill;
+
+	type Voters = Vec<(u64, u64, Vec<u64>)>;
+
+	fn input() -> (Vec<u64>, Voters) {
+		(vec![1, 2, 3], vec![(10, 10, vec![1, 2]), (20, 20, vec![1, 3]), (30, 30, vec![2, 3])])
+	}
+
+	#[test]
+	fn seq_phragmen_trace_works() {
+		let (candidates, voters) = input();
+		let (result, trace) =
+			seq_phragmen_traced::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+
+		// tracing does not change the outcome.
+		let untraced = seq_phragmen::<_, Perbill>(2, candidates, voters, None).unwrap();
+		assert_eq!(result.winners, untraced.winners);
+		assert_eq!(result.assignments, untraced.assignments);
+
+		assert_eq!(trace.rounds.len(), 2);
+		assert_eq!(trace.rounds.iter().map(|r| r.elected).collect::<Vec<_>>(), vec![3, 2]);
+
+		// everyone is a candidate in the first round, and the lowest score wins.
+		let first = &trace.rounds[0];
+		assert_eq!(first.scores.iter().map(|(c, _)| *c).collect::<Vec<_>>(), vec![1, 2, 3]);
+		assert!(first.scores.iter().all(|(_, s)| *s >= first.elected_score));
+		assert_eq!(first.redistribution, vec![(20, 20), (30, 30)]);
+
+		// the winner of the first round is no longer a candidate.
+		let second = &trace.rounds[1];
+		assert_eq!(second.scores.iter().map(|(c, _)| *c).collect::<Vec<_>>(), vec![1, 2]);
+		assert_eq!(second.redistribution.iter().map(|(v, _)| *v).collect::<Vec<_>>(), vec![10, 30]);
+
+		assert_eq!(trace.elected_in(&2).map(|r| r.round), Some(1));
+		assert!(trace.elected_in(&1).is_none());
+		assert_eq!(trace.scores_of(&1).len(), 2);
+		assert!(trace.scores_of(&1).iter().all(|(_, score, elected)| score >= elected));
+	}
+
+	#[test]
+	fn phragmms_trace_works() {
+		let (candidates, voters) = input();
+		let (result, trace) =
+			phragmms_traced::<_, Perbill>(2, candidates.clone(), voters.clone(), None).unwrap();
+
+		let untraced = phragmms::<_, Perbill>(2, candidates, voters, None).unwrap();
+		assert_eq!(result.winners, untraced.winners);
+		assert_eq!(result.assignments, untraced.assignments);
+
+		assert_eq!(
+			trace.rounds.iter().map(|r| r.elected).collect::<Vec<_>>(),
+			result.winners.iter().map(|(w, _)| *w).collect::<Vec<_>>(),
+		);
+
+		// the highest score wins.
+		for round in &trace.rounds {
+			assert!(round.scores.iter().all(|(_, s)| *s <= round.elected_score));
+		}
+		assert_eq!(trace.rounds[0].redistribution, vec![(20, 20), (30, 30)]);
+	}
+}